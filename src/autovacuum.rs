@@ -0,0 +1,95 @@
+//! A background thread that keeps a long-running database tidy: it
+//! collects the MVCC versions no transaction can see anymore and
+//! checkpoints the WAL, on an interval, without being asked.
+
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::error::ReefDBError;
+use crate::mvcc::MVCCManager;
+use crate::wal::{checkpoint, WriteAheadLog};
+
+/// Runs the autovacuum thread until dropped. Dropping it wakes the thread
+/// and waits for the pass in progress, if any, to finish.
+pub struct Autovacuum {
+    /// Set to ask the thread to stop, with the condvar it sleeps on
+    shutdown: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Autovacuum {
+    /// Starts a thread that every `interval` collects old MVCC versions and,
+    /// for a WAL kept on disk, checkpoints it when it has grown since the
+    /// last pass, calling `flush_storage` first. An in-memory database only
+    /// gets a pass while versions are accumulating.
+    pub(crate) fn spawn(
+        interval: Duration,
+        mvcc_manager: Arc<Mutex<MVCCManager>>,
+        wal: Arc<Mutex<WriteAheadLog>>,
+        flush_storage: impl Fn() -> Result<(), ReefDBError> + Send + 'static,
+    ) -> Self {
+        let shutdown = Arc::new((Mutex::new(false), Condvar::new()));
+        let signal = shutdown.clone();
+        let thread = thread::spawn(move || {
+            let (stopped, wake) = &*signal;
+            let mut checkpointed_size = None;
+            loop {
+                let guard = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+                let (guard, _) = wake.wait_timeout_while(guard, interval, |stopped| !*stopped)
+                    .unwrap_or_else(PoisonError::into_inner);
+                if *guard {
+                    return;
+                }
+                drop(guard);
+                // A failed pass is retried on the next tick
+                let _ = vacuum(&mvcc_manager, &wal, &flush_storage, &mut checkpointed_size);
+            }
+        });
+        Autovacuum { shutdown, thread: Some(thread) }
+    }
+}
+
+impl Drop for Autovacuum {
+    fn drop(&mut self) {
+        let (stopped, wake) = &*self.shutdown;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        wake.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// One autovacuum pass. `checkpointed_size` is the size the WAL was left at
+/// by the last checkpoint this thread made.
+fn vacuum(
+    mvcc_manager: &Mutex<MVCCManager>,
+    wal: &Mutex<WriteAheadLog>,
+    flush_storage: &impl Fn() -> Result<(), ReefDBError>,
+    checkpointed_size: &mut Option<u64>,
+) -> Result<(), ReefDBError> {
+    {
+        let mut mvcc_manager = mvcc_manager.lock().unwrap_or_else(PoisonError::into_inner);
+        if mvcc_manager.version_count() > 0 {
+            let oldest_active = mvcc_manager.oldest_active_transaction();
+            mvcc_manager.gc(oldest_active);
+        }
+    }
+
+    {
+        let wal = wal.lock().unwrap_or_else(PoisonError::into_inner);
+        if !wal.is_persistent() || *checkpointed_size == Some(wal.size()) {
+            return Ok(());
+        }
+    }
+    // Only entries the storage holds can go
+    flush_storage()?;
+    let mut wal = wal.lock().unwrap_or_else(PoisonError::into_inner);
+    // Commit records reach the WAL before the MVCC commit, so a transaction
+    // without one is still running for as long as MVCC says so
+    let mvcc_manager = mvcc_manager.lock().unwrap_or_else(PoisonError::into_inner);
+    checkpoint::compact(&mut wal, |transaction_id| mvcc_manager.is_active(transaction_id))?;
+    *checkpointed_size = Some(wal.size());
+    Ok(())
+}
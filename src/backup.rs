@@ -0,0 +1,133 @@
+use std::any::Any;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::{IndexManager, IndexType};
+use crate::storage::{Storage, TableStorage};
+use crate::wal::WALOperation;
+use crate::{InMemoryReefDB, OnDiskReefDB, ReefDB};
+
+/// Version written at the start of every backup file, ahead of the
+/// `DatabaseBackup` it describes
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// A complete snapshot of a database: schemas and rows, secondary indexes
+/// and the full-text index.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseBackup<FTS> {
+    pub tables: TableStorage,
+    /// Indexes keyed by table and column
+    pub indexes: Vec<(String, String, IndexType)>,
+    pub fts_index: FTS,
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+    FTS: Serialize + DeserializeOwned,
+{
+    /// Writes a snapshot of the whole database to `path`.
+    pub fn backup_to<P: AsRef<Path>>(&self, path: P) -> Result<(), ReefDBError> {
+        let mut tables = TableStorage::new();
+        let mut indexes = Vec::new();
+        for (table_name, (columns, rows)) in self.storage.get_all_tables() {
+            tables.insert_table(table_name.clone(), columns.clone(), rows.clone());
+            for column in columns {
+                if let Ok(index) = self.storage.get_index(table_name, &column.name) {
+                    indexes.push((table_name.clone(), column.name.clone(), index.clone()));
+                }
+            }
+        }
+
+        let backup = DatabaseBackup {
+            tables,
+            indexes,
+            fts_index: self.inverted_index.clone(),
+        };
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(&mut writer, &BACKUP_FORMAT_VERSION)?;
+        bincode::serialize_into(&mut writer, &backup)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Replaces the contents of this database with the snapshot at `path`.
+    pub fn restore_backup<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ReefDBError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        // Another version may lay the rest out differently, so it is
+        // checked before anything else is decoded
+        let version: u32 = bincode::deserialize_from(&mut reader)?;
+        if version != BACKUP_FORMAT_VERSION {
+            return Err(ReefDBError::Other(format!(
+                "Unsupported backup format version {} (expected {})",
+                version, BACKUP_FORMAT_VERSION
+            )));
+        }
+        let backup: DatabaseBackup<FTS> = bincode::deserialize_from(reader)?;
+
+        // Drop the current contents, including their indexes
+        let existing: Vec<(String, Vec<String>)> = self.storage.get_all_tables().iter()
+            .map(|(name, (columns, _))| (name.clone(), columns.iter().map(|c| c.name.clone()).collect()))
+            .collect();
+        for (table_name, columns) in &existing {
+            for column_name in columns {
+                self.storage.drop_index(table_name, column_name);
+            }
+        }
+        self.storage.clear();
+        self.tables = TableStorage::new();
+
+        for (table_name, (columns, rows)) in backup.tables.tables.iter() {
+            self.storage.insert_table(table_name.clone(), columns.clone(), rows.clone());
+            self.tables.insert_table(table_name.clone(), columns.clone(), rows.clone());
+        }
+        for (table_name, column_name, index) in backup.indexes {
+            self.storage.create_index(&table_name, &column_name, index)?;
+        }
+        self.inverted_index = backup.fts_index;
+
+        self.log_restored_tables(&existing, &backup.tables)
+    }
+
+    /// Records the restored tables as a committed transaction so WAL recovery
+    /// rebuilds them instead of replaying the history they replaced.
+    fn log_restored_tables(&self, previous: &[(String, Vec<String>)], restored: &TableStorage) -> Result<(), ReefDBError> {
+        let tm = match &self.transaction_manager {
+            Some(tm) => tm,
+            None => return Ok(()),
+        };
+
+        let tx_id = rand::random::<u64>();
+        for (table_name, _) in previous {
+            tm.log_operation(tx_id, WALOperation::DropTable, table_name, bincode::serialize(&())?)?;
+        }
+        for (table_name, table) in restored.tables.iter() {
+            tm.log_operation(tx_id, WALOperation::CreateTable, table_name, bincode::serialize(table)?)?;
+        }
+        tm.log_operation(tx_id, WALOperation::Commit, "", vec![])
+    }
+}
+
+impl InMemoryReefDB {
+    /// Creates an in-memory database from a backup file.
+    pub fn restore_from<P: AsRef<Path>>(path: P) -> Result<Self, ReefDBError> {
+        let mut db = Self::create_in_memory()?;
+        db.restore_backup(path)?;
+        Ok(db)
+    }
+}
+
+impl OnDiskReefDB {
+    /// Creates an on-disk database at `kv_path` from a backup file.
+    pub fn restore_from<P: AsRef<Path>>(path: P, kv_path: String, index_path: String) -> Result<Self, ReefDBError> {
+        let mut db = Self::create_on_disk(kv_path, index_path)?;
+        db.restore_backup(path)?;
+        Ok(db)
+    }
+}
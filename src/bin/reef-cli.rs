@@ -0,0 +1,140 @@
+//! Interactive SQL shell.
+//!
+//! `reef-cli` opens an in-memory database, `reef-cli DATA_DIR` an on-disk
+//! one. Statements end with `;` and may span several lines; lines starting
+//! with `.` are meta-commands, see `.help`.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use reefdb::builder::{ReefDBBuilder, StorageBackend};
+use reefdb::error::ReefDBError;
+use reefdb::fts::default::DefaultSearchIdx;
+use reefdb::ReefDB;
+
+const HELP: &str = "\
+.tables          List tables
+.schema [TABLE]  Describe one table, or all of them
+.help            Show this help
+.quit            Exit";
+
+/// Splits complete `;`-terminated statements off the front of `buffer`,
+/// leaving any unfinished statement behind. Semicolons inside quoted
+/// strings don't end a statement.
+fn take_statements(buffer: &mut String) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in buffer.char_indices() {
+        match c {
+            // A doubled quote inside a string toggles twice, so it stays quoted
+            '\'' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                statements.push(buffer[start..i].trim().to_string());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    buffer.drain(..start);
+    statements.retain(|s| !s.is_empty());
+    statements
+}
+
+fn run_statement<S: StorageBackend>(db: &mut ReefDB<S, DefaultSearchIdx>, sql: &str) {
+    match db.query(sql) {
+        Ok(result) => println!("{}", result),
+        Err(e) => eprintln!("Error: {}", e),
+    }
+}
+
+/// Runs a meta-command, returning false when the shell should exit
+fn run_meta_command<S: StorageBackend>(db: &mut ReefDB<S, DefaultSearchIdx>, line: &str) -> bool {
+    let mut words = line.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some(".quit" | ".exit"), _) => return false,
+        (Some(".help"), _) => println!("{}", HELP),
+        (Some(".tables"), _) => {
+            for table in db.list_tables() {
+                println!("{}", table);
+            }
+        }
+        (Some(".schema"), Some(table)) => run_statement(db, &format!("DESCRIBE {}", table)),
+        (Some(".schema"), None) => {
+            for table in db.list_tables() {
+                println!("{}", table);
+                run_statement(db, &format!("DESCRIBE {}", table));
+            }
+        }
+        (Some(command), _) => eprintln!("Unknown command {}, see .help", command),
+        (None, _) => {}
+    }
+    true
+}
+
+fn repl<S: StorageBackend>(db: &mut ReefDB<S, DefaultSearchIdx>) -> io::Result<()> {
+    let interactive = io::stdin().is_terminal();
+    let mut buffer = String::new();
+    let mut lines = io::stdin().lock().lines();
+
+    loop {
+        if interactive {
+            print!("{}", if buffer.trim().is_empty() { "reefdb> " } else { "   ...> " });
+            io::stdout().flush()?;
+        }
+        let Some(line) = lines.next().transpose()? else {
+            break;
+        };
+
+        if buffer.trim().is_empty() && line.trim_start().starts_with('.') {
+            if !run_meta_command(db, line.trim()) {
+                return Ok(());
+            }
+            continue;
+        }
+
+        buffer.push_str(&line);
+        buffer.push('\n');
+        for statement in take_statements(&mut buffer) {
+            run_statement(db, &statement);
+        }
+    }
+
+    // Input ended in the middle of a statement without its `;`
+    if !buffer.trim().is_empty() {
+        run_statement(db, buffer.trim());
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), ReefDBError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.as_slice() {
+        [] => repl(&mut ReefDBBuilder::in_memory().build()?)?,
+        [flag] if flag == "-h" || flag == "--help" => println!("Usage: reef-cli [DATA_DIR]\n\n{}", HELP),
+        [data_dir] => repl(&mut ReefDBBuilder::on_disk(data_dir).build()?)?,
+        _ => {
+            eprintln!("Usage: reef-cli [DATA_DIR]");
+            std::process::exit(2);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::take_statements;
+
+    #[test]
+    fn test_take_statements() {
+        let mut buffer = "SELECT 1;\nINSERT INTO t VALUES ('a;b', 'it''s');  ;\nSELECT".to_string();
+        assert_eq!(take_statements(&mut buffer), vec![
+            "SELECT 1".to_string(),
+            "INSERT INTO t VALUES ('a;b', 'it''s')".to_string(),
+        ]);
+        assert_eq!(buffer, "\nSELECT");
+
+        buffer.push_str(" *\nFROM t;");
+        assert_eq!(take_statements(&mut buffer), vec!["SELECT *\nFROM t".to_string()]);
+        assert!(buffer.is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::error::ReefDBError;
+use crate::fts::default::DefaultSearchIdx;
+use crate::fts::search::Search;
+use crate::fts::tokenizers::registry::{SharedTokenizer, TokenizerRegistry};
+use crate::fts::tokenizers::tokenizer::Tokenizer;
+use crate::functions::{register_builtins, Function, FunctionRegistry};
+use crate::indexes::index_manager::IndexManager;
+use crate::locks::LockConfig;
+use crate::mvcc::MVCCManager;
+use crate::storage::{disk::OnDiskStorage, memory::InMemoryStorage, mmap::MmapStorage, Storage, TableStorage};
+use crate::transaction::{IsolationLevel, QueryLimits};
+use crate::transaction_manager::TransactionManager;
+use crate::wal::WriteAheadLog;
+use crate::ReefDB;
+
+/// A storage engine the builder knows how to open.
+pub trait StorageBackend: Storage + IndexManager + Clone + Any + Sized {
+    /// File name of the data file inside the data directory, `None` for
+    /// backends that keep nothing on disk.
+    const DATA_FILE: Option<&'static str>;
+
+    fn open(data_file: Option<&Path>) -> Result<Self, ReefDBError>;
+
+    /// Opens the data file growing it `block_size` bytes at a time, for
+    /// backends that write in blocks
+    fn open_with_block_size(data_file: Option<&Path>, _block_size: usize) -> Result<Self, ReefDBError> {
+        Self::open(data_file)
+    }
+}
+
+impl StorageBackend for InMemoryStorage {
+    const DATA_FILE: Option<&'static str> = None;
+
+    fn open(_data_file: Option<&Path>) -> Result<Self, ReefDBError> {
+        Ok(InMemoryStorage::new())
+    }
+}
+
+impl StorageBackend for OnDiskStorage {
+    const DATA_FILE: Option<&'static str> = Some("reefdb.db");
+
+    fn open(data_file: Option<&Path>) -> Result<Self, ReefDBError> {
+        OnDiskStorage::open(data_file.expect("on-disk storage needs a data file").display().to_string(), None)
+    }
+
+    fn open_with_block_size(data_file: Option<&Path>, block_size: usize) -> Result<Self, ReefDBError> {
+        let data_file = data_file.expect("on-disk storage needs a data file").display().to_string();
+        OnDiskStorage::open(data_file, Some(block_size.max(1)))
+    }
+}
+
+impl StorageBackend for MmapStorage {
+    const DATA_FILE: Option<&'static str> = Some("reefdb.mmap");
+
+    fn open(data_file: Option<&Path>) -> Result<Self, ReefDBError> {
+        Ok(MmapStorage::new(data_file.expect("mmap storage needs a data file").display().to_string()))
+    }
+}
+
+/// Configures and opens a [`ReefDB`]. The storage backend is part of the
+/// builder's type, so `build` returns the matching concrete database type,
+/// e.g. `ReefDBBuilder::on_disk(dir).build()` yields an `OnDiskReefDB`.
+pub struct ReefDBBuilder<S: StorageBackend, FTS: Search + Clone = DefaultSearchIdx>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    data_dir: Option<PathBuf>,
+    autocommit: bool,
+    isolation_level: IsolationLevel,
+    lock_config: LockConfig,
+    query_limits: QueryLimits,
+    autovacuum_interval: Option<Duration>,
+    block_size: Option<usize>,
+    functions: Vec<Function>,
+    tokenizers: Vec<(String, SharedTokenizer)>,
+    fts_args: FTS::NewArgs,
+    _storage: PhantomData<S>,
+}
+
+impl ReefDBBuilder<InMemoryStorage> {
+    pub fn in_memory() -> Self {
+        Self::new()
+    }
+}
+
+impl ReefDBBuilder<OnDiskStorage> {
+    pub fn on_disk<P: Into<PathBuf>>(data_dir: P) -> Self {
+        Self::new().with_data_dir(data_dir)
+    }
+
+    /// Grows the data file `block_size` bytes at a time instead of
+    /// `storage::disk::DEFAULT_BLOCK_SIZE`
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = Some(block_size);
+        self
+    }
+}
+
+impl ReefDBBuilder<MmapStorage> {
+    pub fn mmap<P: Into<PathBuf>>(data_dir: P) -> Self {
+        Self::new().with_data_dir(data_dir)
+    }
+}
+
+impl<S: StorageBackend, FTS: Search + Clone> Default for ReefDBBuilder<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: StorageBackend, FTS: Search + Clone> ReefDBBuilder<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    pub fn new() -> Self {
+        ReefDBBuilder {
+            data_dir: None,
+            autocommit: true,
+            isolation_level: IsolationLevel::ReadCommitted,
+            lock_config: LockConfig::default(),
+            query_limits: QueryLimits::default(),
+            autovacuum_interval: None,
+            block_size: None,
+            functions: Vec::new(),
+            tokenizers: Vec::new(),
+            fts_args: Default::default(),
+            _storage: PhantomData,
+        }
+    }
+
+    /// Directory holding the data file and the WAL. Required by persistent
+    /// backends and ignored by the in-memory one.
+    pub fn with_data_dir<P: Into<PathBuf>>(mut self, data_dir: P) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    pub fn with_autocommit(mut self, autocommit: bool) -> Self {
+        self.autocommit = autocommit;
+        self
+    }
+
+    /// Isolation level of the transactions autocommit wraps statements in
+    pub fn with_isolation_level(mut self, isolation_level: IsolationLevel) -> Self {
+        self.isolation_level = isolation_level;
+        self
+    }
+
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_config = self.lock_config.with_lock_timeout(lock_timeout);
+        self
+    }
+
+    pub fn with_lock_config(mut self, lock_config: LockConfig) -> Self {
+        self.lock_config = lock_config;
+        self
+    }
+
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.query_limits = query_limits;
+        self
+    }
+
+    /// Collects old MVCC versions and checkpoints the WAL on a background
+    /// thread every `interval`, which stops when the database is dropped.
+    /// Off by default.
+    pub fn with_autovacuum(mut self, interval: Duration) -> Self {
+        self.autovacuum_interval = Some(interval);
+        self
+    }
+
+    /// Registers a user-defined function alongside the builtins
+    pub fn with_function(mut self, function: Function) -> Self {
+        self.functions.push(function);
+        self
+    }
+
+    /// Registers a tokenizer TSVECTOR columns can name with
+    /// `TOKENIZER 'name'`, in place before tables reopened from disk are
+    /// indexed
+    pub fn with_tokenizer<T: Tokenizer + Send + Sync + 'static>(mut self, name: &str, tokenizer: T) -> Self {
+        self.tokenizers.push((name.to_string(), Arc::new(tokenizer)));
+        self
+    }
+
+    pub fn with_fts_args(mut self, fts_args: FTS::NewArgs) -> Self {
+        self.fts_args = fts_args;
+        self
+    }
+
+    /// Opens the database, replaying any committed transactions left in the
+    /// WAL of a persistent backend.
+    pub fn build(self) -> Result<ReefDB<S, FTS>, ReefDBError>
+    where
+        S: Send,
+        FTS: Send + 'static,
+    {
+        let mut function_registry = FunctionRegistry::new();
+        register_builtins(&mut function_registry)?;
+        for function in self.functions {
+            function_registry.register(function)?;
+        }
+        let mut tokenizers = TokenizerRegistry::new();
+        for (name, tokenizer) in &self.tokenizers {
+            tokenizers.register_shared(name, tokenizer.clone())?;
+        }
+
+        let data_file = match (S::DATA_FILE, &self.data_dir) {
+            (Some(file_name), Some(data_dir)) => {
+                std::fs::create_dir_all(data_dir)?;
+                Some(data_dir.join(file_name))
+            }
+            (Some(_), None) => {
+                return Err(ReefDBError::Other("This storage backend requires a data directory".to_string()));
+            }
+            (None, _) => None,
+        };
+        let wal = match &data_file {
+            Some(data_file) => WriteAheadLog::new(PathBuf::from(format!("{}.wal", data_file.display())))?,
+            None => WriteAheadLog::new_in_memory()?,
+        };
+
+        let mut db = ReefDB {
+            tables: TableStorage::new(),
+            inverted_index: FTS::new(self.fts_args),
+            storage: match self.block_size {
+                Some(block_size) => S::open_with_block_size(data_file.as_deref(), block_size)?,
+                None => S::open(data_file.as_deref())?,
+            },
+            transaction_manager: None,
+            data_dir: self.data_dir,
+            autocommit: self.autocommit,
+            autocommit_isolation_level: self.isolation_level,
+            mvcc_manager: Arc::new(Mutex::new(MVCCManager::new())),
+            current_transaction_id: None,
+            transaction_snapshot: None,
+            function_registry,
+            tokenizers,
+            query_stats: RefCell::new(None),
+            warn_on_full_scan: false,
+            full_scan_warning_rows: crate::DEFAULT_FULL_SCAN_WARNING_ROWS,
+            query_deadline: Cell::new(None),
+        };
+
+        let mut transaction_manager = TransactionManager::create_with_config(db.clone(), wal, self.lock_config);
+        transaction_manager.set_query_limits(self.query_limits);
+        if transaction_manager.recover()? > 0 {
+            db = transaction_manager.database()?;
+        }
+        db.load_tables_from_storage();
+        if let Some(interval) = self.autovacuum_interval {
+            transaction_manager.start_autovacuum(interval);
+        }
+        for (name, _) in &self.tokenizers {
+            db.attach_tokenizer(name)?;
+        }
+        db.transaction_manager = Some(transaction_manager);
+        Ok(db)
+    }
+}
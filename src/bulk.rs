@@ -0,0 +1,102 @@
+use std::any::Any;
+use std::collections::BTreeSet;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::sql::constraints::constraint::Constraint;
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+use crate::storage::Storage;
+use crate::wal::WALOperation;
+use crate::{fts_document_id, ReefDB};
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Appends `rows` to `table_name` in a single transaction, for loading
+    /// large amounts of data. The rows are checked against the schema and
+    /// constraints up front, so either all of them are inserted or none are.
+    /// They are then written to storage and the WAL as one batch and the
+    /// full-text index is updated. Returns the number of rows inserted.
+    ///
+    /// Runs in the current transaction if there is one; otherwise it needs
+    /// autocommit, like any other INSERT.
+    pub fn bulk_insert(&mut self, table_name: &str, rows: Vec<Vec<DataValue>>) -> Result<usize, ReefDBError> {
+        if self.current_transaction_id.is_some() {
+            return self.bulk_insert_in_transaction(table_name, rows);
+        }
+        if !self.autocommit {
+            return Err(ReefDBError::TransactionNotActive);
+        }
+        self.with_implicit_transaction(|db| db.bulk_insert_in_transaction(table_name, rows))
+    }
+
+    fn bulk_insert_in_transaction(&mut self, table_name: &str, rows: Vec<Vec<DataValue>>) -> Result<usize, ReefDBError> {
+        let rows: Vec<_> = rows.into_iter().map(|row| self.coerce_insert(table_name, row)).collect();
+        let (schema, existing) = self.get_table_schema(table_name)?;
+        let checks = self.row_checks(table_name, schema)?;
+
+        for (n, row) in rows.iter().enumerate() {
+            if row.len() != schema.len() {
+                return Err(ReefDBError::ArityMismatch(format!(
+                    "row {}: {} values for {} columns",
+                    n + 1, row.len(), schema.len()
+                )));
+            }
+            for (value, column) in row.iter().zip(schema.iter()) {
+                if !value.matches_type(&column.data_type) {
+                    return Err(ReefDBError::TypeMismatch(format!(
+                        "row {}: column {} expects {:?}, got {:?}",
+                        n + 1, column.name, column.data_type, value
+                    )));
+                }
+                if column.constraints.contains(&Constraint::NotNull)
+                    && matches!(value, DataValue::Text(text) if text.is_empty())
+                {
+                    return Err(ReefDBError::Other(format!(
+                        "Row {}: NOT NULL constraint violation for column {}", n + 1, column.name
+                    )));
+                }
+            }
+            self.check_row(table_name, &checks, existing.len() + n, row)?;
+        }
+
+        // Unique values are checked against the table and the rest of the batch
+        for (i, column) in schema.iter().enumerate() {
+            if !column.constraints.iter().any(|c| matches!(c, Constraint::Unique | Constraint::PrimaryKey)) {
+                continue;
+            }
+            let mut seen: BTreeSet<&DataValue> = existing.iter().map(|row| &row[i]).collect();
+            for (n, row) in rows.iter().enumerate() {
+                if !seen.insert(&row[i]) {
+                    return Err(ReefDBError::Other(format!(
+                        "Row {}: unique constraint violation for column {} with value {:?}",
+                        n + 1, column.name, row[i]
+                    )));
+                }
+            }
+        }
+
+        let first_row_index = existing.len();
+        let fts_columns: Vec<(usize, String)> = schema.iter().enumerate()
+            .filter(|(_, c)| c.data_type == DataType::TSVector)
+            .map(|(i, c)| (i, c.name.clone()))
+            .collect();
+
+        self.log_wal(WALOperation::BulkInsert, table_name, &rows)?;
+        for (offset, row) in rows.iter().enumerate() {
+            self.index_row(table_name, fts_document_id(first_row_index + offset), row)?;
+            for (i, column_name) in &fts_columns {
+                if let DataValue::Text(text) = &row[*i] {
+                    self.inverted_index.add_document(table_name, column_name, fts_document_id(first_row_index + offset), text);
+                    self.add_gin_document(table_name, column_name, fts_document_id(first_row_index + offset), text)?;
+                }
+            }
+        }
+
+        self.tables.append_rows(table_name, rows.clone())?;
+        self.storage.append_rows(table_name, rows)
+    }
+}
@@ -0,0 +1,254 @@
+use std::any::Any;
+use std::io::{Read, Write};
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::sql::column_def::ColumnDef;
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::{DataValue, normalize_timestamp};
+use crate::sql::statements::{insert::InsertStatement, Statement};
+use crate::storage::Storage;
+use crate::ReefDB;
+
+/// A parsed CSV record. Empty unquoted fields are `None` and map to NULL.
+struct CsvRecord {
+    line: usize,
+    fields: Vec<Option<String>>,
+}
+
+fn escape_field(field: &str) -> String {
+    if field.is_empty() || field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn render_value(value: &DataValue) -> String {
+    match value {
+        DataValue::Null => String::new(),
+        other => escape_field(&other.to_string()),
+    }
+}
+
+fn write_record<W: Write>(writer: &mut W, fields: &[String]) -> Result<(), ReefDBError> {
+    writer.write_all(fields.join(",").as_bytes())?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Splits CSV input into records, following RFC 4180 quoting. Quoted fields
+/// may span lines; `line` is where each record starts.
+fn parse_records(input: &str) -> Result<Vec<CsvRecord>, ReefDBError> {
+    let mut records = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start_line = line;
+        let mut fields = Vec::new();
+        loop {
+            let mut field = String::new();
+            let mut quoted = false;
+            if chars.peek() == Some(&'"') {
+                quoted = true;
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => {
+                            return Err(ReefDBError::Other(format!(
+                                "CSV line {}: unterminated quoted field", start_line
+                            )));
+                        }
+                    }
+                }
+            }
+
+            // Collect the rest of an unquoted field, or check what follows a quote
+            while let Some(&c) = chars.peek() {
+                if c == ',' || c == '\n' || c == '\r' {
+                    break;
+                }
+                if quoted {
+                    return Err(ReefDBError::Other(format!(
+                        "CSV line {}: unexpected character '{}' after closing quote", line, c
+                    )));
+                }
+                field.push(c);
+                chars.next();
+            }
+
+            fields.push(if field.is_empty() && !quoted { None } else { Some(field) });
+
+            match chars.next() {
+                Some(',') => continue,
+                Some('\r') => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    line += 1;
+                    break;
+                }
+                Some('\n') => {
+                    line += 1;
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        // Skip blank lines
+        if fields.len() == 1 && fields[0].is_none() {
+            continue;
+        }
+        records.push(CsvRecord { line: start_line, fields });
+    }
+
+    Ok(records)
+}
+
+fn coerce_field(field: Option<String>, column: &ColumnDef, line: usize) -> Result<DataValue, ReefDBError> {
+    let text = match field {
+        Some(text) => text,
+        None => return Ok(DataValue::Null),
+    };
+    let invalid = || ReefDBError::Other(format!(
+        "CSV line {}: invalid {:?} value '{}' for column {}", line, column.data_type, text, column.name
+    ));
+
+    Ok(match column.data_type {
+        DataType::Integer => DataValue::Integer(text.trim().parse().map_err(|_| invalid())?),
+        DataType::Float => DataValue::Float(text.trim().parse().map_err(|_| invalid())?),
+        DataType::Boolean => match text.trim().to_lowercase().as_str() {
+            "true" => DataValue::Boolean(true),
+            "false" => DataValue::Boolean(false),
+            _ => return Err(invalid()),
+        },
+        DataType::Text | DataType::TSVector => DataValue::Text(text),
+        DataType::Date => DataValue::Date(text),
+        DataType::Timestamp => DataValue::Timestamp(normalize_timestamp(text.trim()).ok_or_else(invalid)?),
+        DataType::Null => DataValue::Null,
+    })
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Writes `table_name` as CSV: a header row of column names followed by
+    /// one record per row. NULL is written as an empty field. Returns the
+    /// number of rows written.
+    pub fn export_table_csv<W: Write>(&self, table_name: &str, mut writer: W) -> Result<usize, ReefDBError> {
+        let (columns, rows) = self.storage.get_table_ref(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+
+        let header: Vec<String> = columns.iter().map(|c| escape_field(&c.name)).collect();
+        write_record(&mut writer, &header)?;
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(render_value).collect();
+            write_record(&mut writer, &fields)?;
+        }
+        writer.flush()?;
+        Ok(rows.len())
+    }
+
+    /// Inserts the CSV records from `reader` into `table_name`. The header row
+    /// names the columns; columns it leaves out are set to NULL. Every record
+    /// is parsed before anything is inserted, and outside a transaction the
+    /// rows are inserted in one of their own, so a record that fails leaves
+    /// the table as it was. Returns the number of rows inserted.
+    pub fn import_table_csv<R: Read>(&mut self, table_name: &str, mut reader: R) -> Result<usize, ReefDBError> {
+        let columns = self.storage.get_table_ref(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?
+            .0
+            .clone();
+
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+        let mut records = parse_records(&input)?.into_iter();
+
+        let header = records.next()
+            .ok_or_else(|| ReefDBError::Other("CSV input has no header row".to_string()))?;
+        let mut positions = Vec::with_capacity(header.fields.len());
+        for name in header.fields {
+            let name = name.unwrap_or_default();
+            let position = columns.iter().position(|c| c.name == name)
+                .ok_or_else(|| ReefDBError::Other(format!(
+                    "CSV line {}: column {} does not exist in table {}", header.line, name, table_name
+                )))?;
+            positions.push(position);
+        }
+
+        let mut rows = Vec::new();
+        for record in records {
+            if record.fields.len() != positions.len() {
+                return Err(ReefDBError::Other(format!(
+                    "CSV line {}: expected {} fields, found {}",
+                    record.line, positions.len(), record.fields.len()
+                )));
+            }
+            let mut values = vec![DataValue::Null; columns.len()];
+            for (field, &position) in record.fields.into_iter().zip(positions.iter()) {
+                values[position] = coerce_field(field, &columns[position], record.line)?;
+            }
+            rows.push((record.line, values));
+        }
+
+        let count = rows.len();
+        let at_line = |line: usize| move |e: ReefDBError| ReefDBError::Other(format!("CSV line {}: {}", line, e));
+        let inserts = rows.into_iter()
+            .map(|(line, values)| (line, Statement::Insert(InsertStatement::IntoTable(table_name.to_string(), values))));
+        if self.current_transaction_id.is_some() {
+            for (line, insert) in inserts {
+                self.execute_statement(insert).map_err(at_line(line))?;
+            }
+        } else {
+            self.transaction(|tx| {
+                for (line, insert) in inserts {
+                    tx.execute(insert).map_err(at_line(line))?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_field() {
+        assert_eq!(escape_field("plain"), "plain");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(escape_field(""), "\"\"");
+    }
+
+    #[test]
+    fn test_parse_records() {
+        let records = parse_records("a,b,c\r\n1,\"x, \"\"y\"\"\",\n2,\"multi\nline\",\"\"\n").unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1].fields, vec![Some("1".to_string()), Some("x, \"y\"".to_string()), None]);
+        assert_eq!(records[2].line, 3);
+        assert_eq!(records[2].fields[1], Some("multi\nline".to_string()));
+        assert_eq!(records[2].fields[2], Some(String::new()));
+
+        assert!(parse_records("a\n\"open").is_err());
+        assert!(parse_records("\"a\"b").is_err());
+    }
+}
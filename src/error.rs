@@ -11,6 +11,7 @@ pub enum ReefDBError {
     TransactionNotFound(u64),
     DuplicateKey(String),
     LockAcquisitionFailed(String),
+    LockPoisoned(&'static str),
     WALError(String),
     MVCCError(String),
     IoError(String),
@@ -20,6 +21,28 @@ pub enum ReefDBError {
     InvalidIsolationLevel(String),
     Other(String),
     WriteConflict(String),
+    LockTimeout(String),
+    ResourceLimitExceeded(String),
+    /// A query that ran past its timeout and was stopped
+    QueryTimeout(String),
+    SerializationFailure(String),
+    ReadOnlyTransaction,
+    ReadOnlyReplica,
+    CheckViolation(String),
+    /// A value of the wrong type for the column or operation it was given to
+    TypeMismatch(String),
+    /// A row, function call or UNION side with more or fewer values than
+    /// expected
+    ArityMismatch(String),
+    TableAlreadyExists(String),
+    DuplicateColumn(String),
+    /// A second index on a column, as `table.column`
+    IndexAlreadyExists(String),
+    /// An unqualified column name more than one table of a query has
+    AmbiguousColumn(String),
+    /// SQL that does not parse. `position` is the byte offset in the
+    /// statement where parsing stopped.
+    SyntaxError { message: String, position: usize },
 }
 
 impl fmt::Display for ReefDBError {
@@ -33,6 +56,7 @@ impl fmt::Display for ReefDBError {
             ReefDBError::TransactionNotFound(id) => write!(f, "Transaction not found: {}", id),
             ReefDBError::DuplicateKey(key) => write!(f, "Duplicate key violation: {}", key),
             ReefDBError::LockAcquisitionFailed(msg) => write!(f, "Failed to acquire lock: {}", msg),
+            ReefDBError::LockPoisoned(lock) => write!(f, "A thread panicked while holding the {} lock", lock),
             ReefDBError::WALError(msg) => write!(f, "WAL error: {}", msg),
             ReefDBError::MVCCError(msg) => write!(f, "MVCC error: {}", msg),
             ReefDBError::IoError(msg) => write!(f, "IO error: {}", msg),
@@ -42,10 +66,97 @@ impl fmt::Display for ReefDBError {
             ReefDBError::InvalidIsolationLevel(level) => write!(f, "Invalid isolation level: {}", level),
             ReefDBError::Other(msg) => write!(f, "{}", msg),
             ReefDBError::WriteConflict(msg) => write!(f, "Write conflict: {}", msg),
+            ReefDBError::LockTimeout(msg) => write!(f, "Lock wait timeout: {}", msg),
+            ReefDBError::ResourceLimitExceeded(msg) => write!(f, "Resource limit exceeded: {}", msg),
+            ReefDBError::QueryTimeout(msg) => write!(f, "Query timed out: {}", msg),
+            ReefDBError::SerializationFailure(msg) => write!(f, "Could not serialize access: {}", msg),
+            ReefDBError::ReadOnlyTransaction => write!(f, "Cannot modify the database in a read-only transaction"),
+            ReefDBError::ReadOnlyReplica => write!(f, "Cannot modify a read replica"),
+            ReefDBError::CheckViolation(msg) => write!(f, "Check constraint violated: {}", msg),
+            ReefDBError::TypeMismatch(msg) => write!(f, "Type mismatch: {}", msg),
+            ReefDBError::ArityMismatch(msg) => write!(f, "Arity mismatch: {}", msg),
+            ReefDBError::TableAlreadyExists(table) => write!(f, "Table already exists: {}", table),
+            ReefDBError::DuplicateColumn(column) => write!(f, "Column already exists: {}", column),
+            ReefDBError::IndexAlreadyExists(index) => write!(f, "Index already exists on {}", index),
+            ReefDBError::AmbiguousColumn(column) => write!(f, "Column reference {} is ambiguous", column),
+            ReefDBError::SyntaxError { message, position } => {
+                write!(f, "Syntax error at position {}: {}", position, message)
+            }
         }
     }
 }
 
+impl ReefDBError {
+    /// A number identifying the kind of error, stable across releases so
+    /// clients can match on it. Codes are grouped by hundreds: 1xxx for
+    /// parsing and schema, 2xxx for data, 3xxx for transactions, 4xxx for
+    /// locks, 5xxx for storage and 6xxx for limits; 9000 is anything else.
+    pub fn code(&self) -> u16 {
+        match self {
+            ReefDBError::SyntaxError { .. } => 1000,
+            ReefDBError::TableNotFound(_) => 1001,
+            ReefDBError::ColumnNotFound(_) => 1002,
+            ReefDBError::TableAlreadyExists(_) => 1003,
+            ReefDBError::DuplicateColumn(_) => 1004,
+            ReefDBError::IndexAlreadyExists(_) => 1005,
+            ReefDBError::AmbiguousColumn(_) => 1006,
+            ReefDBError::TypeMismatch(_) => 2001,
+            ReefDBError::ArityMismatch(_) => 2002,
+            ReefDBError::DuplicateKey(_) => 2003,
+            ReefDBError::CheckViolation(_) => 2004,
+            ReefDBError::TransactionNotActive => 3001,
+            ReefDBError::TransactionNotFound(_) => 3002,
+            ReefDBError::SavepointNotFound(_) => 3003,
+            ReefDBError::SavepointNotActive(_) => 3004,
+            ReefDBError::InvalidIsolationLevel(_) => 3005,
+            ReefDBError::WriteConflict(_) => 3006,
+            ReefDBError::SerializationFailure(_) => 3007,
+            ReefDBError::ReadOnlyTransaction => 3008,
+            ReefDBError::ReadOnlyReplica => 3009,
+            ReefDBError::LockAcquisitionFailed(_) => 4001,
+            ReefDBError::LockPoisoned(_) => 4002,
+            ReefDBError::LockConflict(_) => 4003,
+            ReefDBError::LockTimeout(_) => 4004,
+            ReefDBError::DeadlockDetected(_) => 4005,
+            ReefDBError::Deadlock => 4006,
+            ReefDBError::IoError(_) => 5001,
+            ReefDBError::WALError(_) => 5002,
+            ReefDBError::MVCCError(_) => 5003,
+            ReefDBError::ResourceLimitExceeded(_) => 6001,
+            ReefDBError::QueryTimeout(_) => 6002,
+            ReefDBError::Other(_) => 9000,
+        }
+    }
+
+    /// For a syntax error, the line of `sql` it is on with a caret under
+    /// the position, as in
+    ///
+    /// ```text
+    /// SELCT * FROM t
+    /// ^
+    /// ```
+    pub fn caret(&self, sql: &str) -> Option<String> {
+        let ReefDBError::SyntaxError { position, .. } = self else {
+            return None;
+        };
+        let position = (*position).min(sql.len());
+        let line_start = sql[..position].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = sql[position..].find('\n').map_or(sql.len(), |i| position + i);
+        let column = sql[line_start..position].chars().count();
+        Some(format!("{}\n{}^", &sql[line_start..line_end], " ".repeat(column)))
+    }
+
+    /// Whether the transaction failed because of concurrent transactions
+    /// and may succeed if run again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self,
+            ReefDBError::SerializationFailure(_)
+            | ReefDBError::Deadlock
+            | ReefDBError::DeadlockDetected(_)
+            | ReefDBError::LockTimeout(_))
+    }
+}
+
 impl std::error::Error for ReefDBError {}
 
 impl From<io::Error> for ReefDBError {
@@ -0,0 +1,298 @@
+//! `EXPLAIN` lists the steps a query would go through with the rows each is
+//! estimated to read and pass on; `EXPLAIN ANALYZE` runs it and reports the
+//! actual rows and the time each step took.
+
+use std::any::Any;
+use std::time::Instant;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::functions::{aggregate_argument, Aggregate};
+use crate::indexes::index_manager::{IndexKind, IndexManager};
+use crate::introspection::column_info;
+use crate::result::{QueryResult, ReefDBResult};
+use crate::sql::clauses::join_clause::JoinClause;
+use crate::sql::clauses::wheres::where_type::WhereType;
+use crate::sql::column::{Column, ColumnType};
+use crate::sql::column_value_pair::ColumnValuePair;
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+use crate::sql::operators::op::Op;
+use crate::sql::statements::{select::SelectStatement, Statement};
+use crate::stats::QueryStats;
+use crate::storage::Storage;
+use crate::ReefDB;
+
+/// Share of rows assumed to pass a predicate the statistics say nothing
+/// about
+const DEFAULT_SELECTIVITY: f64 = 1.0 / 3.0;
+
+/// An index is only read instead of the table when it leaves at most this
+/// share of the rows to look at
+const MAX_INDEX_SELECTIVITY: f64 = 0.5;
+
+/// Whether looking up `matches` rows through an index beats scanning all
+/// `rows` of the table
+pub(crate) fn index_pays_off(matches: usize, rows: usize) -> bool {
+    matches as f64 <= rows as f64 * MAX_INDEX_SELECTIVITY
+}
+
+fn estimate(rows: f64) -> usize {
+    rows.round() as usize
+}
+
+/// A scan of `table_name`, reading only the rows an index found. A GIN
+/// index stands for the full-text search of its column.
+pub(crate) fn scan_step(table_name: &str, index: Option<(&str, IndexKind)>) -> String {
+    match index {
+        Some((index, IndexKind::GIN)) => format!("Full-Text Index Scan on {} using {}", table_name, index),
+        Some((index, kind)) => format!("{} Index Scan on {} using {}", kind, table_name, index),
+        None => format!("Seq Scan on {}", table_name),
+    }
+}
+
+pub(crate) fn aggregate_step(group_by: &[Column]) -> String {
+    if group_by.is_empty() {
+        return "Aggregate".to_string();
+    }
+    let keys: Vec<String> = group_by.iter()
+        .map(|column| match &column.table {
+            Some(table) => format!("{}.{}", table, column.name),
+            None => column.name.clone(),
+        })
+        .collect();
+    format!("Group Aggregate by {}", keys.join(", "))
+}
+
+pub(crate) fn count_step(table_name: &str) -> String {
+    format!("Count Fast Path on {}", table_name)
+}
+
+/// Whether a query is a lone `COUNT(*)` over a whole table, which is the
+/// table's row count without reading any row
+pub(crate) fn counts_all_rows(
+    columns: &[Column],
+    where_clause: Option<&WhereType>,
+    joins: &[JoinClause],
+    group_by: &[Column],
+) -> bool {
+    let [column] = columns else {
+        return false;
+    };
+    let ColumnType::Function(_, args) = &column.column_type else {
+        return false;
+    };
+    where_clause.is_none() && joins.is_empty() && group_by.is_empty()
+        && Aggregate::of_column(column) == Some(Aggregate::Count)
+        && matches!(aggregate_argument(args), Some((DataValue::Text(arg), false)) if arg == "*")
+}
+
+pub(crate) fn join_step(join: &JoinClause) -> String {
+    match &join.on {
+        Some((left, right)) => format!(
+            "Nested Loop {:?} Join {} on {}.{} = {}.{}",
+            join.join_type, join.table_ref.name, left.table_name, left.column_name, right.table_name, right.column_name
+        ),
+        None => format!("Nested Loop {:?} Join {}", join.join_type, join.table_ref.name),
+    }
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Distinct values of a column of `table`, or when unqualified of the
+    /// first of `tables` that has it
+    fn estimated_cardinality(&self, tables: &[&str], table: Option<&str>, column: &str) -> Option<usize> {
+        match table {
+            Some(table) => self.storage.column_cardinality(table, column),
+            None => tables.iter().find_map(|table| self.storage.column_cardinality(table, column)),
+        }
+    }
+
+    /// Estimated share of rows `where_clause` keeps. `tables` are the main
+    /// table followed by the joined ones.
+    pub(crate) fn estimate_selectivity(&self, where_clause: &WhereType, tables: &[&str]) -> f64 {
+        let equality = |table: Option<&str>, column: &str| {
+            self.estimated_cardinality(tables, table, column)
+                .filter(|&distinct| distinct > 0)
+                .map_or(DEFAULT_SELECTIVITY, |distinct| 1.0 / distinct as f64)
+        };
+        match where_clause {
+            WhereType::Regular(clause) if clause.operator == Op::Equal => {
+                equality(clause.table.as_deref(), &clause.col_name)
+            }
+            WhereType::In(clause) => {
+                (equality(clause.column.table.as_deref(), &clause.column.name) * clause.values.len() as f64).min(1.0)
+            }
+            // The inverted index knows exactly how many rows match
+            WhereType::FTS(clause) => {
+                let table = clause.column.table.as_deref().unwrap_or(tables[0]);
+                match self.storage.row_count(table) {
+                    Some(rows) if rows > 0 => {
+                        let matches = self.inverted_index.search(table, &clause.column.name, &clause.query.text);
+                        matches.len() as f64 / rows as f64
+                    }
+                    _ => DEFAULT_SELECTIVITY,
+                }
+            }
+            WhereType::And(left, right) => {
+                self.estimate_selectivity(left, tables) * self.estimate_selectivity(right, tables)
+            }
+            WhereType::Or(left, right) => {
+                let (left, right) = (self.estimate_selectivity(left, tables), self.estimate_selectivity(right, tables));
+                left + right - left * right
+            }
+            _ => DEFAULT_SELECTIVITY,
+        }
+    }
+
+    /// The steps `handle_select` goes through for `select`, in the order it
+    /// finishes them, with the rows each is estimated to read and pass on
+    fn plan_select(&self, select: &SelectStatement) -> Vec<(String, usize, usize)> {
+        let SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by) = select;
+        let tables: Vec<&str> = std::iter::once(table_ref.name.as_str())
+            .chain(joins.iter().map(|join| join.table_ref.name.as_str()))
+            .collect();
+        let row_count = |table: &str| self.storage.row_count(table).unwrap_or(0);
+        let selectivity = where_clause.as_ref()
+            .map_or(1.0, |where_clause| self.estimate_selectivity(where_clause, &tables));
+
+        let mut steps = Vec::new();
+        let scanned = row_count(&table_ref.name);
+        if counts_all_rows(columns, where_clause.as_ref(), joins, group_by) {
+            steps.push((count_step(&table_ref.name), 0, 1));
+            if !order_by.is_empty() {
+                steps.push(("Sort".to_string(), 1, 1));
+            }
+            return steps;
+        }
+        let mut rows = 0;
+        if joins.is_empty() {
+            let index = where_clause.as_ref()
+                .and_then(|where_clause| Self::required_fts_clause(where_clause, &table_ref.name))
+                .filter(|clause| {
+                    let matches = self.inverted_index.search(&table_ref.name, &clause.column.name, &clause.query.text);
+                    index_pays_off(matches.len(), scanned)
+                })
+                .map(|clause| (format!("{}.{}", table_ref.name, clause.column.name), IndexKind::GIN))
+                .or_else(|| where_clause.as_ref()
+                    .and_then(|where_clause| self.equality_index_matches(where_clause, &table_ref.name))
+                    .filter(|(_, _, row_indexes)| index_pays_off(row_indexes.len(), scanned))
+                    .map(|(column_name, kind, _)| (format!("{}.{}", table_ref.name, column_name), kind)));
+            rows = estimate(scanned as f64 * selectivity);
+            steps.push((scan_step(&table_ref.name, index.as_ref().map(|(index, kind)| (index.as_str(), *kind))), scanned, rows));
+        } else {
+            steps.push((scan_step(&table_ref.name, None), scanned, scanned));
+            // Each join pairs the main table's rows with its own
+            for join in joins {
+                let join_rows = row_count(&join.table_ref.name);
+                // An equi-join matches each row with the rows sharing its
+                // value, of which the side with more distinct values has fewer
+                let distinct = join.on.as_ref().map_or(1, |(left, right)| {
+                    let cardinality = |side: &ColumnValuePair| self.estimated_cardinality(
+                        &tables,
+                        Some(side.table_name.as_str()).filter(|table| !table.is_empty()),
+                        &side.column_name,
+                    );
+                    cardinality(left).max(cardinality(right)).unwrap_or(1).max(1)
+                });
+                let pairs = estimate((scanned * join_rows) as f64 / distinct as f64);
+                steps.push((join_step(join), scanned * (1 + join_rows), pairs));
+                let kept = estimate(pairs as f64 * selectivity);
+                if where_clause.is_some() {
+                    steps.push(("Filter".to_string(), pairs, kept));
+                }
+                rows += kept;
+            }
+        }
+        if !group_by.is_empty() || columns.iter().any(|c| Aggregate::of_column(c).is_some()) {
+            let groups = match group_by.as_slice() {
+                [] => 1,
+                _ if rows == 0 => 0,
+                keys => keys.iter()
+                    .map(|key| self.estimated_cardinality(&tables, key.table.as_deref(), &key.name).unwrap_or(rows).max(1))
+                    .fold(1usize, |groups, distinct| groups.saturating_mul(distinct))
+                    .min(rows),
+            };
+            steps.push((aggregate_step(group_by), rows, groups));
+            rows = groups;
+        }
+        if !order_by.is_empty() {
+            steps.push(("Sort".to_string(), rows, rows));
+        }
+        steps
+    }
+
+    /// `EXPLAIN [ANALYZE] SELECT ...`: one row per step, plus a total when
+    /// analyzing
+    pub(crate) fn handle_explain(&self, analyze: bool, statement: Statement) -> Result<ReefDBResult, ReefDBError> {
+        let (Statement::Select(select) | Statement::SelectForUpdate(select)) = statement else {
+            return Err(ReefDBError::Other("EXPLAIN only supports SELECT statements".to_string()));
+        };
+
+        let rows: Vec<Vec<DataValue>> = if analyze {
+            let stats = self.analyze_select(select)?;
+            let millis = |duration: std::time::Duration| DataValue::Float(duration.as_secs_f64() * 1000.0);
+            stats.steps.iter()
+                .map(|step| vec![
+                    DataValue::Text(step.description.clone()),
+                    DataValue::Integer(step.rows_in as i64),
+                    DataValue::Integer(step.rows_out as i64),
+                    millis(step.duration),
+                ])
+                .chain(std::iter::once(vec![
+                    DataValue::Text("Total".to_string()),
+                    DataValue::Integer(stats.rows_scanned as i64),
+                    DataValue::Integer(stats.rows_returned as i64),
+                    millis(stats.duration),
+                ]))
+                .collect()
+        } else {
+            self.plan_select(&select).into_iter()
+                .map(|(step, rows_in, rows_out)| vec![
+                    DataValue::Text(step),
+                    DataValue::Integer(rows_in as i64),
+                    DataValue::Integer(rows_out as i64),
+                    DataValue::Null,
+                ])
+                .collect()
+        };
+
+        let columns = vec![
+            column_info("step", DataType::Text, false),
+            column_info("rows_in", DataType::Integer, true),
+            column_info("rows_out", DataType::Integer, true),
+            column_info("time_ms", DataType::Float, true),
+        ];
+        Ok(ReefDBResult::Select(QueryResult::with_columns(rows.into_iter().enumerate().collect(), columns)))
+    }
+
+    /// Runs `select`, collecting its stats apart from those of any
+    /// `query_with_stats` it runs within, which then get them added in
+    fn analyze_select(&self, select: SelectStatement) -> Result<QueryStats, ReefDBError> {
+        let outer = self.query_stats.replace(Some(QueryStats::default()));
+        let started = Instant::now();
+        let result = self.resolve_subqueries(Statement::Select(select)).and_then(|stmt| match stmt {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by)) => {
+                self.handle_select(table_ref, columns, where_clause, joins, order_by, group_by)
+            }
+            _ => unreachable!("resolving subqueries keeps the statement a SELECT"),
+        });
+        let mut stats = self.query_stats.replace(outer).unwrap_or_default();
+        stats.duration = started.elapsed();
+        if let ReefDBResult::Select(rows) = result? {
+            stats.rows_returned = rows.len();
+        }
+
+        self.with_stats(|outer| {
+            outer.rows_scanned += stats.rows_scanned;
+            outer.join_rows += stats.join_rows;
+            if outer.index_used.is_none() {
+                outer.index_used = stats.index_used.clone();
+            }
+            outer.steps.extend(stats.steps.iter().cloned());
+        });
+        Ok(stats)
+    }
+}
@@ -11,6 +11,9 @@ use crate::indexes::gin::GinIndex;
 use super::search::Search;
 use super::tokenizers::tokenizer::Tokenizer;
 use super::tokenizers::default::DefaultTokenizer;
+use super::language::LanguageConfig;
+use super::ranking::CollectionStats;
+use super::tokenizers::registry::SharedTokenizer;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OnDiskInvertedIndex<T: Tokenizer> {
@@ -74,6 +77,30 @@ impl<T: Tokenizer + Serialize + for<'de> Deserialize<'de>> Search for OnDiskInve
         self.index.update_document(table, column, row_id, text);
         self.save_to_file(&self.file_path).unwrap();
     }
+
+    fn set_column_config(&mut self, table: &str, column: &str, config: LanguageConfig) {
+        self.index.set_column_config(table, column, config);
+        self.save_to_file(&self.file_path).unwrap();
+    }
+
+    fn set_column_tokenizer(&mut self, table: &str, column: &str, tokenizer: SharedTokenizer) {
+        // Not saved, so there is nothing to write
+        self.index.set_column_tokenizer(table, column, tokenizer);
+    }
+
+    fn clear_column(&mut self, table: &str, column: &str) {
+        self.index.clear_column(table, column);
+        self.save_to_file(&self.file_path).unwrap();
+    }
+
+    fn refresh_stats(&mut self) {
+        // Not saved either, so a loaded index has none until refreshed
+        self.index.refresh_stats();
+    }
+
+    fn collection_stats(&self, table: &str, column: &str) -> Option<&CollectionStats> {
+        self.index.collection_stats(table, column)
+    }
 }
 
 #[cfg(test)]
@@ -41,6 +41,11 @@ impl EnglishProcessor {
             stemmer: Stemmer::create(Algorithm::English),
         }
     }
+
+    /// The built-in English stop-word list used when no custom list is configured
+    pub fn default_stop_words() -> HashSet<String> {
+        ENGLISH_STOP_WORDS.clone()
+    }
 }
 
 impl LanguageProcessor for EnglishProcessor {
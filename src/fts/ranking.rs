@@ -153,17 +153,24 @@ impl BM25Ranking {
         term_doc_frequencies: HashMap<String, usize>,
         avg_doc_length: f64,
     ) -> Self {
-        BM25Ranking {
-            collection_stats: Some(CollectionStats {
-                total_docs,
-                term_doc_frequencies,
-            }),
+        Self::from_stats(CollectionStats {
+            total_docs,
+            term_doc_frequencies,
             avg_doc_length,
+        })
+    }
+
+    pub fn from_stats(stats: CollectionStats) -> Self {
+        BM25Ranking {
+            avg_doc_length: stats.avg_doc_length,
+            collection_stats: Some(stats),
         }
     }
 
     fn calculate_idf(&self, term: &str) -> f64 {
-        if let Some(stats) = &self.collection_stats {
+        // Without a collection to compare against every term is as rare
+        // as any other, rather than rarer than impossible
+        if let Some(stats) = self.collection_stats.as_ref().filter(|stats| stats.total_docs > 0) {
             let doc_freq = stats.term_doc_frequencies.get(term).copied().unwrap_or(1);
             // Ensure IDF is always positive by using ln(1 + (N - n + 0.5)/(n + 0.5))
             (1.0 + (stats.total_docs as f64 - doc_freq as f64 + 0.5) / (doc_freq as f64 + 0.5)).ln()
@@ -267,10 +274,15 @@ impl RankingSystem for BM25Ranking {
     }
 }
 
-#[derive(Debug, Default)]
-struct CollectionStats {
-    total_docs: usize,
-    term_doc_frequencies: HashMap<String, usize>,
+/// Statistics of all the documents of a full-text column, which weigh a
+/// term by how rare it is and a document by how long it is
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollectionStats {
+    pub total_docs: usize,
+    /// Number of documents each term appears in
+    pub term_doc_frequencies: HashMap<String, usize>,
+    /// Average number of tokens per document
+    pub avg_doc_length: f64,
 }
 
 impl BM25Ranking {
@@ -412,12 +424,17 @@ impl TfIdfRanking {
         term_doc_frequencies: HashMap<String, usize>,
         avg_doc_length: f64,
     ) -> Self {
-        TfIdfRanking {
-            collection_stats: Some(CollectionStats {
-                total_docs,
-                term_doc_frequencies,
-            }),
+        Self::from_stats(CollectionStats {
+            total_docs,
+            term_doc_frequencies,
             avg_doc_length,
+        })
+    }
+
+    pub fn from_stats(stats: CollectionStats) -> Self {
+        TfIdfRanking {
+            avg_doc_length: stats.avg_doc_length,
+            collection_stats: Some(stats),
         }
     }
 
@@ -433,7 +450,9 @@ impl TfIdfRanking {
     }
 
     fn calculate_smoothed_idf(&self, term: &str) -> f64 {
-        if let Some(stats) = &self.collection_stats {
+        // Without a collection to compare against every term is as rare
+        // as any other, rather than rarer than impossible
+        if let Some(stats) = self.collection_stats.as_ref().filter(|stats| stats.total_docs > 0) {
             let doc_freq = stats.term_doc_frequencies.get(term).copied().unwrap_or(1);
             ((stats.total_docs as f64 + 1.0) / (doc_freq as f64 + 1.0)).ln() + 1.0
         } else {
@@ -442,7 +461,9 @@ impl TfIdfRanking {
     }
 
     fn calculate_idf(&self, term: &str) -> f64 {
-        if let Some(stats) = &self.collection_stats {
+        // Without a collection to compare against every term is as rare
+        // as any other, rather than rarer than impossible
+        if let Some(stats) = self.collection_stats.as_ref().filter(|stats| stats.total_docs > 0) {
             let doc_freq = stats.term_doc_frequencies.get(term).copied().unwrap_or(1);
             ((stats.total_docs as f64) / (doc_freq as f64)).ln()
         } else {
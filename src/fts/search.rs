@@ -1,5 +1,9 @@
 use std::collections::HashSet;
 
+use super::language::LanguageConfig;
+use super::ranking::CollectionStats;
+use super::tokenizers::registry::SharedTokenizer;
+
 pub trait Search {
     type NewArgs: Default;
     fn new(args: Self::NewArgs) -> Self;
@@ -9,4 +13,11 @@ pub trait Search {
     fn add_document(&mut self, table: &str, column: &str, row_id: usize, text: &str);
     fn remove_document(&mut self, table: &str, column: &str, row_id: usize);
     fn update_document(&mut self, table: &str, column: &str, row_id: usize, text: &str);
+    fn set_column_config(&mut self, table: &str, column: &str, config: LanguageConfig);
+    fn set_column_tokenizer(&mut self, table: &str, column: &str, tokenizer: SharedTokenizer);
+    fn clear_column(&mut self, table: &str, column: &str);
+    /// Recomputes the statistics `collection_stats` returns from the
+    /// documents indexed now
+    fn refresh_stats(&mut self);
+    fn collection_stats(&self, table: &str, column: &str) -> Option<&CollectionStats>;
 }
\ No newline at end of file
@@ -24,6 +24,8 @@ pub struct Token {
 pub enum TokenType {
     Word,
     NotWord,
+    /// A query term matching tokens up to this many edits away
+    Fuzzy(usize),
     Number,
     Email,
     URL,
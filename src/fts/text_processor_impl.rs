@@ -74,6 +74,17 @@ impl DefaultTextProcessor {
         }
     }
 
+    /// Creates a processor whose default language uses the given configuration,
+    /// e.g. to supply a custom stop-word list for a single column.
+    pub fn with_config(config: LanguageConfig) -> Self {
+        let mut language_processors: HashMap<String, Box<dyn LanguageProcessor>> = HashMap::new();
+        language_processors.insert("english".to_string(), Box::new(EnglishProcessor::new(Some(config))) as Box<dyn LanguageProcessor>);
+        DefaultTextProcessor {
+            language_processors,
+            default_language: "english".to_string(),
+        }
+    }
+
     pub fn get_language_processor(&self, language: Option<&str>) -> &dyn LanguageProcessor {
         let language = language.unwrap_or(&self.default_language);
         self.language_processors
@@ -143,43 +154,44 @@ impl DefaultTextProcessor {
                 }
             }
         } else {
-            for term in parsed.terms {
+            for (i, term) in parsed.terms.iter().enumerate() {
                 let word = term.text.to_lowercase();
-                if !processor.is_stop_word(&word) {
-                    position += 1;
-                    tokens.push(Token {
-                        text: processor.stem(&word),
-                        position,
-                        weight: 1.0,
-                        type_: if term.is_negated { TokenType::NotWord } else { TokenType::Word },
+                if processor.is_stop_word(&word) {
+                    continue;
+                }
+                position += 1;
+                let token = Token {
+                    text: processor.stem(&word),
+                    position,
+                    weight: 1.0,
+                    // A negated term is matched exactly
+                    type_: match (term.is_negated, term.max_edits) {
+                        (true, _) => TokenType::NotWord,
+                        (false, Some(edits)) => TokenType::Fuzzy(edits),
+                        (false, None) => TokenType::Word,
+                    },
+                };
+                // The operator before a term joins it to the previous kept
+                // one; NOT is carried by the term itself as a NotWord
+                if let Some(previous) = tokens.last() {
+                    operators.push(match parsed.operators.get(i - 1) {
+                        Some(SqlQueryOperator::Or) => QueryOperator::Or,
+                        Some(SqlQueryOperator::Proximity(distance)) => {
+                            QueryOperator::Proximity(vec![previous.clone(), token.clone()], *distance)
+                        }
+                        _ => QueryOperator::And,
                     });
                 }
+                tokens.push(token);
             }
         }
 
         // If it's a phrase query, add a single Phrase operator
         if matches!(query.query_type, QueryType::Phrase) {
             operators = vec![QueryOperator::Phrase(tokens.clone())];
-        } else {
-            // Convert SQL operators to FTS operators
-            for op in parsed.operators {
-                match op {
-                    SqlQueryOperator::And => operators.push(QueryOperator::And),
-                    SqlQueryOperator::Or => operators.push(QueryOperator::Or),
-                    SqlQueryOperator::Not => {
-                        // NOT is handled via TokenType::NotWord
-                        // If there are tokens before this NOT, add an AND operator
-                        if !tokens.is_empty() {
-                            operators.push(QueryOperator::And);
-                        }
-                    }
-                }
-            }
-
+        } else if operators.is_empty() && tokens.len() > 1 {
             // If no operators were found between tokens, default to AND
-            if operators.is_empty() && tokens.len() > 1 {
-                operators = vec![QueryOperator::And; tokens.len() - 1];
-            }
+            operators = vec![QueryOperator::And; tokens.len() - 1];
         }
 
         ProcessedQuery { tokens, operators }
@@ -189,7 +201,10 @@ impl DefaultTextProcessor {
 impl Clone for DefaultTextProcessor {
     fn clone(&self) -> Self {
         let mut language_processors: HashMap<String, Box<dyn LanguageProcessor>> = HashMap::new();
-        language_processors.insert("english".to_string(), Box::new(EnglishProcessor::new(None)) as Box<dyn LanguageProcessor>);
+        for (lang, processor) in &self.language_processors {
+            let config = processor.get_config().clone();
+            language_processors.insert(lang.clone(), Box::new(EnglishProcessor::new(Some(config))) as Box<dyn LanguageProcessor>);
+        }
         DefaultTextProcessor {
             language_processors,
             default_language: self.default_language.clone(),
@@ -249,4 +264,19 @@ mod tests {
             .collect();
         assert_eq!(positions, vec![1, 2, 3, 4, 5]);
     }
+
+    #[test]
+    fn test_custom_stop_words_survive_clone() {
+        let config = LanguageConfig {
+            stop_words: ["inc", "ltd"].iter().map(|w| w.to_string()).collect(),
+            ..Default::default()
+        };
+        let processor = DefaultTextProcessor::with_config(config).clone();
+
+        let doc = processor.process_document("Acme Inc and Widgets Ltd", None);
+        assert!(!doc.tokens.iter().any(|t| t.text == "inc"));
+        assert!(!doc.tokens.iter().any(|t| t.text == "ltd"));
+        // Custom list replaces the defaults, so "and" is kept
+        assert!(doc.tokens.iter().any(|t| t.text == "and"));
+    }
 } 
\ No newline at end of file
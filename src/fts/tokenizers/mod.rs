@@ -1,2 +1,4 @@
 pub mod default;
+pub mod ngram;
+pub mod registry;
 pub mod tokenizer;
@@ -0,0 +1,61 @@
+use std::iter;
+
+use serde::{Deserialize, Serialize};
+
+use super::tokenizer::Tokenizer;
+
+/// Splits each word into its overlapping runs of `n` characters, so that a
+/// search for part of a word finds it. Suits languages written without
+/// spaces, such as Chinese or Japanese, where the default tokenizer keeps a
+/// whole sentence as one token. Words shorter than `n` are kept whole.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NgramTokenizer {
+    n: usize,
+}
+
+impl NgramTokenizer {
+    /// A tokenizer emitting runs of `n` characters, at least one
+    pub fn with_size(n: usize) -> Self {
+        NgramTokenizer { n: n.max(1) }
+    }
+}
+
+impl Tokenizer for NgramTokenizer {
+    fn tokenize<'a>(&self, text: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+        let n = self.n;
+        Box::new(text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .flat_map(move |word| {
+                // Byte offsets of each character, and of the end of the word
+                let bounds: Vec<usize> = word.char_indices()
+                    .map(|(i, _)| i)
+                    .chain(iter::once(word.len()))
+                    .collect();
+                let chars = bounds.len() - 1;
+                let grams = chars.saturating_sub(n) + 1;
+                (0..grams).map(move |i| &word[bounds[i]..bounds[(i + n).min(chars)]])
+            }))
+    }
+
+    /// Trigrams
+    fn new() -> Self {
+        NgramTokenizer::with_size(3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ngram_tokenizer() {
+        let tokenizer = NgramTokenizer::new();
+        let tokens: Vec<&str> = tokenizer.tokenize("Rust, go").collect();
+        assert_eq!(tokens, vec!["Rus", "ust", "go"]);
+
+        let tokenizer = NgramTokenizer::with_size(2);
+        let tokens: Vec<&str> = tokenizer.tokenize("東京タワー").collect();
+        assert_eq!(tokens, vec!["東京", "京タ", "タワ", "ワー"]);
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::ReefDBError;
+
+use super::tokenizer::Tokenizer;
+
+/// A tokenizer shared between the registry and the columns using it
+pub type SharedTokenizer = Arc<dyn Tokenizer + Send + Sync>;
+
+/// Tokenizers TSVECTOR columns can name in a `TOKENIZER 'name'` clause
+#[derive(Clone, Default)]
+pub struct TokenizerRegistry {
+    tokenizers: HashMap<String, SharedTokenizer>,
+}
+
+impl TokenizerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: Tokenizer + Send + Sync + 'static>(&mut self, name: &str, tokenizer: T) -> Result<(), ReefDBError> {
+        self.register_shared(name, Arc::new(tokenizer))
+    }
+
+    pub(crate) fn register_shared(&mut self, name: &str, tokenizer: SharedTokenizer) -> Result<(), ReefDBError> {
+        if self.tokenizers.contains_key(name) {
+            return Err(ReefDBError::Other(format!("Tokenizer {} already registered", name)));
+        }
+        self.tokenizers.insert(name.to_string(), tokenizer);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<SharedTokenizer> {
+        self.tokenizers.get(name).cloned()
+    }
+}
+
+impl fmt::Debug for TokenizerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.tokenizers.keys()).finish()
+    }
+}
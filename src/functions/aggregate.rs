@@ -0,0 +1,172 @@
+use std::cmp::Ordering;
+
+use crate::error::ReefDBError;
+use crate::sql::column::{Column, ColumnType};
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+
+/// Functions that fold all the rows matched by a query into one value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "count" => Some(Aggregate::Count),
+            "sum" => Some(Aggregate::Sum),
+            "avg" => Some(Aggregate::Avg),
+            "min" => Some(Aggregate::Min),
+            "max" => Some(Aggregate::Max),
+            _ => None,
+        }
+    }
+
+    /// The aggregate computed by a selected column, if any
+    pub fn of_column(column: &Column) -> Option<Self> {
+        match &column.column_type {
+            ColumnType::Function(name, _) => Self::from_name(name),
+            _ => None,
+        }
+    }
+
+    /// The type of the aggregate's result given the type of its argument,
+    /// which is `None` for `COUNT(*)` or when it is not a column
+    pub fn result_type(&self, arg_type: Option<&DataType>) -> DataType {
+        match (self, arg_type) {
+            (Aggregate::Count, _) => DataType::Integer,
+            (Aggregate::Avg, _) => DataType::Float,
+            (_, Some(data_type)) => data_type.clone(),
+            (_, None) => DataType::Float,
+        }
+    }
+
+    /// COUNT is never NULL; the others are NULL when no row has a value
+    pub fn is_nullable(&self) -> bool {
+        !matches!(self, Aggregate::Count)
+    }
+}
+
+/// Name of the pseudo-function the parser wraps around the argument of
+/// `COUNT(DISTINCT x)` and the like
+pub(crate) const DISTINCT: &str = "DISTINCT";
+
+/// The single argument of an aggregate and whether it is DISTINCT
+pub(crate) fn aggregate_argument(args: &[DataValue]) -> Option<(&DataValue, bool)> {
+    match args {
+        [DataValue::Function { name, args }] if name == DISTINCT => match args.as_slice() {
+            [arg] => Some((arg, true)),
+            _ => None,
+        },
+        [arg] => Some((arg, false)),
+        _ => None,
+    }
+}
+
+/// Running state of one aggregate over the rows of a query
+pub(crate) struct Accumulator {
+    aggregate: Aggregate,
+    count: i64,
+    sum: Option<DataValue>,
+    best: Option<DataValue>,
+}
+
+impl Accumulator {
+    pub(crate) fn new(aggregate: Aggregate) -> Self {
+        Self { aggregate, count: 0, sum: None, best: None }
+    }
+
+    /// Adds a row's value. `None` stands for a `COUNT(*)` row, which counts
+    /// whatever its contents; NULL values are skipped by every aggregate.
+    pub(crate) fn update(&mut self, value: Option<&DataValue>) -> Result<(), ReefDBError> {
+        let value = match value {
+            None => {
+                self.count += 1;
+                return Ok(());
+            }
+            Some(DataValue::Null) => return Ok(()),
+            Some(value) => value,
+        };
+        self.count += 1;
+
+        match self.aggregate {
+            Aggregate::Count => {}
+            Aggregate::Sum | Aggregate::Avg => {
+                self.sum = Some(match (self.sum.take(), value) {
+                    (None, DataValue::Integer(_) | DataValue::Float(_)) => value.clone(),
+                    (Some(DataValue::Integer(a)), DataValue::Integer(b)) => DataValue::Integer(a.checked_add(*b)
+                        .ok_or_else(|| ReefDBError::Other("Integer overflow in SUM".to_string()))?),
+                    (Some(DataValue::Integer(a)), DataValue::Float(b)) => DataValue::Float(a as f64 + b),
+                    (Some(DataValue::Float(a)), DataValue::Integer(b)) => DataValue::Float(a + *b as f64),
+                    (Some(DataValue::Float(a)), DataValue::Float(b)) => DataValue::Float(a + b),
+                    _ => return Err(ReefDBError::Other(format!(
+                        "Cannot compute {:?} of non-numeric value {:?}", self.aggregate, value
+                    ))),
+                });
+            }
+            Aggregate::Min | Aggregate::Max => {
+                let wanted = if self.aggregate == Aggregate::Min { Ordering::Less } else { Ordering::Greater };
+                let replace = match &self.best {
+                    None => true,
+                    Some(best) => value.sort_cmp(best)? == wanted,
+                };
+                if replace {
+                    self.best = Some(value.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> DataValue {
+        match self.aggregate {
+            Aggregate::Count => DataValue::Integer(self.count),
+            Aggregate::Sum => self.sum.unwrap_or(DataValue::Null),
+            Aggregate::Avg => match self.sum {
+                Some(DataValue::Integer(sum)) => DataValue::Float(sum as f64 / self.count as f64),
+                Some(DataValue::Float(sum)) => DataValue::Float(sum / self.count as f64),
+                _ => DataValue::Null,
+            },
+            Aggregate::Min | Aggregate::Max => self.best.unwrap_or(DataValue::Null),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fold(aggregate: Aggregate, values: &[DataValue]) -> DataValue {
+        let mut acc = Accumulator::new(aggregate);
+        for value in values {
+            acc.update(Some(value)).unwrap();
+        }
+        acc.finish()
+    }
+
+    #[test]
+    fn test_aggregates() {
+        let values = [DataValue::Integer(3), DataValue::Null, DataValue::Integer(1), DataValue::Integer(5)];
+        assert_eq!(fold(Aggregate::Count, &values), DataValue::Integer(3));
+        assert_eq!(fold(Aggregate::Sum, &values), DataValue::Integer(9));
+        assert_eq!(fold(Aggregate::Avg, &values), DataValue::Float(3.0));
+        assert_eq!(fold(Aggregate::Min, &values), DataValue::Integer(1));
+        assert_eq!(fold(Aggregate::Max, &values), DataValue::Integer(5));
+        assert!(Accumulator::new(Aggregate::Sum).update(Some(&DataValue::Text("a".to_string()))).is_err());
+    }
+
+    #[test]
+    fn test_aggregates_over_no_values() {
+        assert_eq!(fold(Aggregate::Count, &[]), DataValue::Integer(0));
+        assert_eq!(fold(Aggregate::Count, &[DataValue::Null]), DataValue::Integer(0));
+        for aggregate in [Aggregate::Sum, Aggregate::Avg, Aggregate::Min, Aggregate::Max] {
+            assert_eq!(fold(aggregate, &[]), DataValue::Null);
+            assert_eq!(fold(aggregate, &[DataValue::Null]), DataValue::Null);
+        }
+    }
+}
@@ -1,11 +1,13 @@
-use crate::sql::data_value::DataValue;
+use crate::sql::data_value::{DataValue, TIMESTAMP_FORMAT, parse_timestamp_text};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
 use crate::error::ReefDBError;
-use crate::functions::{Function, FunctionArg, FunctionArgType, FunctionReturnType, FunctionRegistry};
+use crate::functions::{Function, FunctionArg, FunctionArgType, FunctionHandler, FunctionReturnType, FunctionRegistry};
 use std::fmt;
 use crate::sql::clauses::full_text_search::ranking::{TSRanking, NORM_LENGTH};
+use crate::sql::clauses::full_text_search::weight::TextWeight;
 use crate::fts::text_processor::{TextProcessor, TsVector, ProcessedQuery, TSQuery};
 use crate::fts::text_processor_impl::DefaultTextProcessor;
-use crate::fts::ranking::{RankingSystem, BM25Ranking, RankingConfig};
+use crate::fts::ranking::{RankingSystem, BM25Ranking, CollectionStats, RankingConfig};
 
 impl fmt::Display for DataValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -24,29 +26,58 @@ impl fmt::Display for DataValue {
     }
 }
 
+/// `ts_rank` weighing terms by how rare they are in `stats`, the
+/// statistics of the ranked column. Without them every term counts the same.
+pub(crate) fn ts_rank(args: &[DataValue], stats: Option<&CollectionStats>) -> Result<DataValue, ReefDBError> {
+    if let [DataValue::TSVector(ref vector), DataValue::TSQuery(query)] = args {
+        // Create a ranking system with default configuration
+        let ranking_system = match stats {
+            Some(stats) => BM25Ranking::from_stats(stats.clone()),
+            None => BM25Ranking::new(),
+        };
+        let config = RankingConfig::default();
+
+        // Convert TSQuery to ProcessedQuery
+        let processed_query: ProcessedQuery = query.clone().into();
+
+        // Calculate the rank using the ProcessedQuery
+        let rank = ranking_system.rank(vector, &processed_query, &config);
+
+        Ok(DataValue::Float(rank))
+    } else {
+        Err(ReefDBError::TypeMismatch("invalid argument types for ts_rank".to_string()))
+    }
+}
+
 pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBError> {
     // String functions
     registry.register(Function {
         name: "concat".to_string(),
+        args: vec![],
+        return_type: FunctionReturnType::String,
+        variadic: Some(FunctionArgType::Any),
+        handler: |args| {
+            // NULL arguments are skipped rather than making the result NULL
+            Ok(DataValue::Text(args.iter()
+                .filter(|value| **value != DataValue::Null)
+                .map(|value| value.to_string())
+                .collect()))
+        },
+    })?;
+
+    // `a || b`, which unlike CONCAT is NULL when either side is
+    registry.register(Function {
+        name: "textcat".to_string(),
         args: vec![
-            FunctionArg {
-                name: "str1".to_string(),
-                arg_type: FunctionArgType::String,
-                is_optional: false,
-            },
-            FunctionArg {
-                name: "str2".to_string(),
-                arg_type: FunctionArgType::String,
-                is_optional: false,
-            },
+            FunctionArg::new("left".to_string(), FunctionArgType::Any),
+            FunctionArg::new("right".to_string(), FunctionArgType::Any),
         ],
         return_type: FunctionReturnType::String,
-        handler: |args| {
-            if let [DataValue::Text(s1), DataValue::Text(s2)] = args.as_slice() {
-                Ok(DataValue::Text(format!("{}{}", s1, s2)))
-            } else {
-                Err(ReefDBError::Other("Invalid argument types for concat".to_string()))
-            }
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::Null, _] | [_, DataValue::Null] => Ok(DataValue::Null),
+            [left, right] => Ok(DataValue::Text(format!("{}{}", left, right))),
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for textcat".to_string())),
         },
     })?;
 
@@ -66,11 +97,12 @@ pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBEr
             },
         ],
         return_type: FunctionReturnType::Integer,
+        variadic: None,
         handler: |args| {
             if let [DataValue::Integer(a), DataValue::Integer(b)] = args.as_slice() {
                 Ok(DataValue::Integer(a + b))
             } else {
-                Err(ReefDBError::Other("Invalid argument types for add".to_string()))
+                Err(ReefDBError::TypeMismatch("invalid argument types for add".to_string()))
             }
         },
     })?;
@@ -90,15 +122,149 @@ pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBEr
             },
         ],
         return_type: FunctionReturnType::Integer,
+        variadic: None,
         handler: |args| {
             if let [DataValue::Integer(a), DataValue::Integer(b)] = args.as_slice() {
                 Ok(DataValue::Integer(a * b))
             } else {
-                Err(ReefDBError::Other("Invalid argument types for multiply".to_string()))
+                Err(ReefDBError::TypeMismatch("invalid argument types for multiply".to_string()))
+            }
+        },
+    })?;
+
+    registry.register(Function {
+        name: "abs".to_string(),
+        args: vec![FunctionArg::new("x".to_string(), FunctionArgType::Any)],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::Null] => Ok(DataValue::Null),
+            [DataValue::Integer(x)] => x.checked_abs().map(DataValue::Integer)
+                .ok_or_else(|| ReefDBError::Other("abs: integer overflow".to_string())),
+            [x] => Ok(DataValue::Float(float_arg("abs", x)?.abs())),
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for abs".to_string())),
+        },
+    })?;
+
+    registry.register(Function {
+        name: "round".to_string(),
+        args: vec![
+            FunctionArg::new("x".to_string(), FunctionArgType::Any),
+            FunctionArg::new("digits".to_string(), FunctionArgType::Integer).optional(),
+        ],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| {
+            let (x, digits) = match args.as_slice() {
+                [DataValue::Null] | [DataValue::Null, _] => return Ok(DataValue::Null),
+                [x] => (x, 0),
+                [x, DataValue::Integer(digits)] => (x, *digits),
+                _ => return Err(ReefDBError::TypeMismatch("invalid argument types for round".to_string())),
+            };
+            let digits = i32::try_from(digits)
+                .map_err(|_| ReefDBError::Other(format!("round: invalid number of digits {}", digits)))?;
+            match x {
+                DataValue::Integer(i) if digits >= 0 => Ok(DataValue::Integer(*i)),
+                DataValue::Integer(i) => {
+                    let scale = 10f64.powi(-digits);
+                    Ok(DataValue::Integer(((*i as f64 / scale).round() * scale) as i64))
+                }
+                x => {
+                    let scale = 10f64.powi(digits);
+                    Ok(DataValue::Float((float_arg("round", x)? * scale).round() / scale))
+                }
             }
         },
     })?;
 
+    registry.register(Function {
+        name: "ceil".to_string(),
+        args: vec![FunctionArg::new("x".to_string(), FunctionArgType::Any)],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::Null] => Ok(DataValue::Null),
+            [DataValue::Integer(x)] => Ok(DataValue::Integer(*x)),
+            [x] => Ok(DataValue::Float(float_arg("ceil", x)?.ceil())),
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for ceil".to_string())),
+        },
+    })?;
+
+    registry.register(Function {
+        name: "floor".to_string(),
+        args: vec![FunctionArg::new("x".to_string(), FunctionArgType::Any)],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::Null] => Ok(DataValue::Null),
+            [DataValue::Integer(x)] => Ok(DataValue::Integer(*x)),
+            [x] => Ok(DataValue::Float(float_arg("floor", x)?.floor())),
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for floor".to_string())),
+        },
+    })?;
+
+    registry.register(Function {
+        name: "mod".to_string(),
+        args: vec![
+            FunctionArg::new("a".to_string(), FunctionArgType::Any),
+            FunctionArg::new("b".to_string(), FunctionArgType::Any),
+        ],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::Null, _] | [_, DataValue::Null] => Ok(DataValue::Null),
+            [DataValue::Integer(_), DataValue::Integer(0)] => Err(ReefDBError::Other("mod: division by zero".to_string())),
+            [DataValue::Integer(a), DataValue::Integer(b)] => Ok(DataValue::Integer(a.wrapping_rem(*b))),
+            [a, b] => {
+                let (a, b) = (float_arg("mod", a)?, float_arg("mod", b)?);
+                if b == 0.0 {
+                    return Err(ReefDBError::Other("mod: division by zero".to_string()));
+                }
+                Ok(DataValue::Float(a % b))
+            }
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for mod".to_string())),
+        },
+    })?;
+
+    // The `&`, `|` and `^` operators
+    let bitwise: [(&str, FunctionHandler); 3] = [
+        ("bitand", |args| Ok(bit_args("bitand", &args)?.map_or(DataValue::Null, |(a, b)| DataValue::Integer(a & b)))),
+        ("bitor", |args| Ok(bit_args("bitor", &args)?.map_or(DataValue::Null, |(a, b)| DataValue::Integer(a | b)))),
+        ("bitxor", |args| Ok(bit_args("bitxor", &args)?.map_or(DataValue::Null, |(a, b)| DataValue::Integer(a ^ b)))),
+    ];
+    for (name, handler) in bitwise {
+        registry.register(Function {
+            name: name.to_string(),
+            args: vec![
+                FunctionArg::new("a".to_string(), FunctionArgType::Any),
+                FunctionArg::new("b".to_string(), FunctionArgType::Any),
+            ],
+            return_type: FunctionReturnType::Integer,
+            variadic: None,
+            handler,
+        })?;
+    }
+
+    registry.register(Function {
+        name: "power".to_string(),
+        args: vec![
+            FunctionArg::new("base".to_string(), FunctionArgType::Any),
+            FunctionArg::new("exp".to_string(), FunctionArgType::Any),
+        ],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::Null, _] | [_, DataValue::Null] => Ok(DataValue::Null),
+            // Integer powers stay integers as long as they fit
+            [DataValue::Integer(base), DataValue::Integer(exp)] if *exp >= 0 => u32::try_from(*exp).ok()
+                .and_then(|exp| base.checked_pow(exp))
+                .map(DataValue::Integer)
+                .ok_or_else(|| ReefDBError::Other("power: integer overflow".to_string())),
+            [base, exp] => Ok(DataValue::Float(float_arg("power", base)?.powf(float_arg("power", exp)?))),
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for power".to_string())),
+        },
+    })?;
+
     // Full-text search functions
     registry.register(Function {
         name: "to_tsvector".to_string(),
@@ -110,13 +276,14 @@ pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBEr
             },
         ],
         return_type: FunctionReturnType::TSVector,
+        variadic: None,
         handler: |args| {
             if let [DataValue::Text(text)] = args.as_slice() {
                 let processor = DefaultTextProcessor::new();
                 let vector = processor.process_document(text, None);
                 Ok(DataValue::TSVector(vector))
             } else {
-                Err(ReefDBError::Other("Invalid argument types for to_tsvector".to_string()))
+                Err(ReefDBError::TypeMismatch("invalid argument types for to_tsvector".to_string()))
             }
         },
     })?;
@@ -131,14 +298,42 @@ pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBEr
             },
         ],
         return_type: FunctionReturnType::TSQuery,
+        variadic: None,
         handler: |args| {
             if let [DataValue::Text(query)] = args.as_slice() {
                 let processor = DefaultTextProcessor::new();
                 let processed = processor.process_query(query, None);
                 Ok(DataValue::TSQuery(TSQuery::new(processed.tokens, processed.operators)))
             } else {
-                Err(ReefDBError::Other("Invalid argument types for to_tsquery".to_string()))
+                Err(ReefDBError::TypeMismatch("invalid argument types for to_tsquery".to_string()))
+            }
+        },
+    })?;
+
+    // Labels every lexeme of a vector with a weight, which ts_rank scales
+    // its matches by: setweight(to_tsvector(title), 'A')
+    registry.register(Function {
+        name: "setweight".to_string(),
+        args: vec![
+            FunctionArg::new("tsvector".to_string(), FunctionArgType::TSVector),
+            FunctionArg::new("weight".to_string(), FunctionArgType::String),
+        ],
+        return_type: FunctionReturnType::TSVector,
+        variadic: None,
+        handler: |args| match args.as_slice() {
+            [DataValue::TSVector(vector), DataValue::Text(label)] => {
+                let mut chars = label.chars();
+                let weight = match (chars.next().and_then(TextWeight::from_char), chars.next()) {
+                    (Some(weight), None) => weight,
+                    _ => return Err(ReefDBError::Other(format!(
+                        "setweight: unrecognized weight '{}', expected A, B, C or D", label
+                    ))),
+                };
+                let mut vector = vector.clone();
+                vector.set_weight(weight);
+                Ok(DataValue::TSVector(vector))
             }
+            _ => Err(ReefDBError::TypeMismatch("invalid argument types for setweight".to_string())),
         },
     })?;
 
@@ -158,25 +353,62 @@ pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBEr
             },
         ],
         return_type: FunctionReturnType::Float,
-        handler: |args| {
-            if let [DataValue::TSVector(ref vector), DataValue::TSQuery(query)] = args.as_slice() {
-                // Create a ranking system with default configuration
-                let ranking_system = BM25Ranking::new();
-                let config = RankingConfig::default();
-                
-                // Convert TSQuery to ProcessedQuery
-                let processed_query: ProcessedQuery = query.clone().into();
-                
-                // Calculate the rank using the ProcessedQuery
-                let rank = ranking_system.rank(vector, &processed_query, &config);
-                
-                Ok(DataValue::Float(rank))
-            } else {
-                Err(ReefDBError::Other("Invalid argument types for ts_rank".to_string()))
-            }
+        variadic: None,
+        handler: |args| ts_rank(&args, None),
+    })?;
+
+    // Null handling functions
+    registry.register(Function {
+        name: "coalesce".to_string(),
+        args: vec![FunctionArg::new("value".to_string(), FunctionArgType::Any)],
+        return_type: FunctionReturnType::Any,
+        variadic: Some(FunctionArgType::Any),
+        handler: first_non_null,
+    })?;
+
+    registry.register(Function {
+        name: "ifnull".to_string(),
+        args: vec![
+            FunctionArg::new("value".to_string(), FunctionArgType::Any),
+            FunctionArg::new("fallback".to_string(), FunctionArgType::Any),
+        ],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: first_non_null,
+    })?;
+
+    registry.register(Function {
+        name: "nullif".to_string(),
+        args: vec![
+            FunctionArg::new("value".to_string(), FunctionArgType::Any),
+            FunctionArg::new("other".to_string(), FunctionArgType::Any),
+        ],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        // Numbers compare by value, so `nullif(1, 1.0)` is NULL
+        handler: |args| match args.as_slice() {
+            [value, other] if value == other => Ok(DataValue::Null),
+            [value, _] => Ok(value.clone()),
+            _ => Err(ReefDBError::ArityMismatch("wrong number of arguments for nullif".to_string())),
         },
     })?;
 
+    registry.register(Function {
+        name: "greatest".to_string(),
+        args: vec![FunctionArg::new("value".to_string(), FunctionArgType::Any)],
+        return_type: FunctionReturnType::Any,
+        variadic: Some(FunctionArgType::Any),
+        handler: |args| extreme("greatest", args, std::cmp::Ordering::Greater),
+    })?;
+
+    registry.register(Function {
+        name: "least".to_string(),
+        args: vec![FunctionArg::new("value".to_string(), FunctionArgType::Any)],
+        return_type: FunctionReturnType::Any,
+        variadic: Some(FunctionArgType::Any),
+        handler: |args| extreme("least", args, std::cmp::Ordering::Less),
+    })?;
+
     // Type conversion functions
     registry.register(Function {
         name: "to_string".to_string(),
@@ -188,18 +420,145 @@ pub fn register_builtins(registry: &mut FunctionRegistry) -> Result<(), ReefDBEr
             },
         ],
         return_type: FunctionReturnType::String,
+        variadic: None,
         handler: |args| {
             if let [value] = args.as_slice() {
                 Ok(DataValue::Text(value.to_string()))
             } else {
-                Err(ReefDBError::Other("Invalid argument count for to_string".to_string()))
+                Err(ReefDBError::ArityMismatch("wrong number of arguments for to_string".to_string()))
             }
         },
     })?;
 
+    // Date and time functions
+    registry.register(Function {
+        name: "now".to_string(),
+        args: vec![],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |_| {
+            let now = chrono::Utc::now().naive_utc();
+            Ok(DataValue::Timestamp(now.format(TIMESTAMP_FORMAT).to_string()))
+        },
+    })?;
+
+    registry.register(Function {
+        name: "date_trunc".to_string(),
+        args: vec![
+            FunctionArg::new("unit".to_string(), FunctionArgType::String),
+            FunctionArg::new("timestamp".to_string(), FunctionArgType::Any),
+        ],
+        return_type: FunctionReturnType::Any,
+        variadic: None,
+        handler: |args| {
+            let (unit, ts) = match args.as_slice() {
+                [_, DataValue::Null] => return Ok(DataValue::Null),
+                [DataValue::Text(unit), value] => (unit.to_lowercase(), timestamp_arg("date_trunc", value)?),
+                _ => return Err(ReefDBError::TypeMismatch("invalid argument types for date_trunc".to_string())),
+            };
+            let date = ts.date();
+            let truncated = match unit.as_str() {
+                "year" => date.with_month(1).and_then(|d| d.with_day(1)).and_then(|d| d.and_hms_opt(0, 0, 0)),
+                "month" => date.with_day(1).and_then(|d| d.and_hms_opt(0, 0, 0)),
+                "day" => date.and_hms_opt(0, 0, 0),
+                "hour" => date.and_hms_opt(ts.hour(), 0, 0),
+                "minute" => date.and_hms_opt(ts.hour(), ts.minute(), 0),
+                "second" => date.and_hms_opt(ts.hour(), ts.minute(), ts.second()),
+                _ => return Err(ReefDBError::Other(format!("date_trunc: unknown unit '{}'", unit))),
+            };
+            let truncated = truncated.ok_or_else(|| ReefDBError::Other("date_trunc: invalid timestamp".to_string()))?;
+            Ok(DataValue::Timestamp(truncated.format(TIMESTAMP_FORMAT).to_string()))
+        },
+    })?;
+
+    registry.register(Function {
+        name: "extract".to_string(),
+        args: vec![
+            FunctionArg::new("field".to_string(), FunctionArgType::String),
+            FunctionArg::new("timestamp".to_string(), FunctionArgType::Any),
+        ],
+        return_type: FunctionReturnType::Integer,
+        variadic: None,
+        handler: |args| {
+            let (field, ts) = match args.as_slice() {
+                [_, DataValue::Null] => return Ok(DataValue::Null),
+                [DataValue::Text(field), value] => (field.to_lowercase(), timestamp_arg("extract", value)?),
+                _ => return Err(ReefDBError::TypeMismatch("invalid argument types for extract".to_string())),
+            };
+            let value = match field.as_str() {
+                "year" => ts.year() as i64,
+                "month" => ts.month() as i64,
+                "day" => ts.day() as i64,
+                "hour" => ts.hour() as i64,
+                "minute" => ts.minute() as i64,
+                "second" => ts.second() as i64,
+                "dow" => ts.weekday().num_days_from_sunday() as i64,
+                "doy" => ts.ordinal() as i64,
+                "epoch" => ts.and_utc().timestamp(),
+                _ => return Err(ReefDBError::Other(format!("extract: unknown field '{}'", field))),
+            };
+            Ok(DataValue::Integer(value))
+        },
+    })?;
+
     Ok(())
 }
 
+/// Returns the first argument that isn't NULL, or NULL if they all are
+fn first_non_null(args: Vec<DataValue>) -> Result<DataValue, ReefDBError> {
+    Ok(args.into_iter()
+        .find(|value| *value != DataValue::Null)
+        .unwrap_or(DataValue::Null))
+}
+
+/// Returns the argument that compares as `wanted` against all the others,
+/// ignoring NULLs
+fn extreme(function: &str, args: Vec<DataValue>, wanted: std::cmp::Ordering) -> Result<DataValue, ReefDBError> {
+    let mut best: Option<DataValue> = None;
+    for value in args.into_iter().filter(|value| *value != DataValue::Null) {
+        let replace = match &best {
+            None => true,
+            Some(current) => value.sort_cmp(current)
+                .map_err(|e| ReefDBError::Other(format!("{}: {}", function, e)))? == wanted,
+        };
+        if replace {
+            best = Some(value);
+        }
+    }
+    Ok(best.unwrap_or(DataValue::Null))
+}
+
+/// Reads an INTEGER or FLOAT argument as a float
+fn float_arg(function: &str, value: &DataValue) -> Result<f64, ReefDBError> {
+    match value {
+        DataValue::Integer(i) => Ok(*i as f64),
+        DataValue::Float(f) => Ok(*f),
+        _ => Err(ReefDBError::TypeMismatch(format!("{}: expected a number, got {:?}", function, value))),
+    }
+}
+
+/// Reads the two integer operands of a bitwise function, `None` when either
+/// is NULL
+fn bit_args(function: &str, args: &[DataValue]) -> Result<Option<(i64, i64)>, ReefDBError> {
+    match args {
+        [DataValue::Null, _] | [_, DataValue::Null] => Ok(None),
+        [DataValue::Integer(a), DataValue::Integer(b)] => Ok(Some((*a, *b))),
+        [a, b] => Err(ReefDBError::TypeMismatch(format!("{}: expected integers, got {:?} and {:?}", function, a, b))),
+        _ => Err(ReefDBError::ArityMismatch(format!("wrong number of arguments for {}", function))),
+    }
+}
+
+/// Reads a DATE, TIMESTAMP or timestamp text argument
+fn timestamp_arg(function: &str, value: &DataValue) -> Result<NaiveDateTime, ReefDBError> {
+    let parsed = match value {
+        DataValue::Timestamp(text) | DataValue::Text(text) => parse_timestamp_text(text),
+        DataValue::Date(text) => NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0)),
+        _ => None,
+    };
+    parsed.ok_or_else(|| ReefDBError::TypeMismatch(format!("{}: expected a timestamp, got {:?}", function, value)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -218,6 +577,9 @@ mod tests {
             ],
         ).unwrap();
         assert_eq!(result, DataValue::Text("Hello, World!".to_string()));
+        let result = registry.call("textcat", vec![DataValue::Text("id ".to_string()), DataValue::Integer(7)]).unwrap();
+        assert_eq!(result, DataValue::Text("id 7".to_string()));
+        assert_eq!(registry.call("textcat", vec![DataValue::Null, DataValue::Integer(7)]).unwrap(), DataValue::Null);
 
         // Test add
         let result = registry.call(
@@ -240,4 +602,134 @@ mod tests {
         ).unwrap();
         assert_eq!(result, DataValue::Text("42".to_string()));
     }
+
+    #[test]
+    fn test_variadic_functions() {
+        let mut registry = FunctionRegistry::new();
+        register_builtins(&mut registry).unwrap();
+        let text = |s: &str| DataValue::Text(s.to_string());
+
+        // Zero variadic arguments
+        assert_eq!(registry.call("concat", vec![]).unwrap(), text(""));
+        assert_eq!(registry.call("greatest", vec![DataValue::Integer(4)]).unwrap(), DataValue::Integer(4));
+
+        // One variadic argument
+        assert_eq!(registry.call("concat", vec![text("a")]).unwrap(), text("a"));
+        assert_eq!(registry.call("least", vec![DataValue::Integer(4), DataValue::Integer(2)]).unwrap(), DataValue::Integer(2));
+
+        // Many variadic arguments
+        assert_eq!(
+            registry.call("concat", vec![text("a"), DataValue::Integer(1), DataValue::Null, text("b")]).unwrap(),
+            text("a1b")
+        );
+        let numbers = vec![DataValue::Integer(3), DataValue::Null, DataValue::Float(7.5), DataValue::Integer(-1)];
+        assert_eq!(registry.call("GREATEST", numbers.clone()).unwrap(), DataValue::Float(7.5));
+        assert_eq!(registry.call("LEAST", numbers).unwrap(), DataValue::Integer(-1));
+        assert_eq!(registry.call("greatest", vec![DataValue::Null, DataValue::Null]).unwrap(), DataValue::Null);
+
+        // The fixed argument is still required, and values must be comparable
+        assert!(registry.call("greatest", vec![]).is_err());
+        assert!(registry.call("least", vec![text("a"), DataValue::Integer(1)]).is_err());
+    }
+
+    #[test]
+    fn test_null_functions() {
+        let mut registry = FunctionRegistry::new();
+        register_builtins(&mut registry).unwrap();
+        let text = |s: &str| DataValue::Text(s.to_string());
+
+        // All NULL
+        assert_eq!(registry.call("coalesce", vec![DataValue::Null, DataValue::Null]).unwrap(), DataValue::Null);
+        assert_eq!(registry.call("IFNULL", vec![DataValue::Null, DataValue::Null]).unwrap(), DataValue::Null);
+
+        // First NULL
+        assert_eq!(
+            registry.call("COALESCE", vec![DataValue::Null, DataValue::Null, text("default")]).unwrap(),
+            text("default")
+        );
+        assert_eq!(registry.call("ifnull", vec![DataValue::Null, DataValue::Integer(0)]).unwrap(), DataValue::Integer(0));
+
+        // None NULL
+        assert_eq!(
+            registry.call("coalesce", vec![DataValue::Integer(1), DataValue::Null, DataValue::Integer(2)]).unwrap(),
+            DataValue::Integer(1)
+        );
+        assert_eq!(registry.call("coalesce", vec![text("only")]).unwrap(), text("only"));
+        assert_eq!(registry.call("ifnull", vec![text("a"), text("b")]).unwrap(), text("a"));
+
+        assert!(registry.call("coalesce", vec![]).unwrap_err().to_string().contains("at least 1"));
+        assert!(registry.call("ifnull", vec![DataValue::Null, DataValue::Null, DataValue::Null]).is_err());
+
+        // NULLIF is NULL when its arguments are equal, else the first one
+        assert_eq!(registry.call("nullif", vec![DataValue::Integer(1), DataValue::Integer(1)]).unwrap(), DataValue::Null);
+        assert_eq!(registry.call("NULLIF", vec![DataValue::Integer(1), DataValue::Float(1.0)]).unwrap(), DataValue::Null);
+        assert_eq!(registry.call("nullif", vec![text("a"), text("b")]).unwrap(), text("a"));
+        assert_eq!(registry.call("nullif", vec![text("a"), DataValue::Null]).unwrap(), text("a"));
+        assert_eq!(registry.call("nullif", vec![DataValue::Null, text("a")]).unwrap(), DataValue::Null);
+        assert!(registry.call("nullif", vec![text("a")]).is_err());
+    }
+
+    #[test]
+    fn test_math_functions() {
+        let mut registry = FunctionRegistry::new();
+        register_builtins(&mut registry).unwrap();
+        let call = |name: &str, args: Vec<DataValue>| registry.call(name, args).unwrap();
+
+        assert_eq!(call("abs", vec![DataValue::Integer(-3)]), DataValue::Integer(3));
+        assert_eq!(call("ABS", vec![DataValue::Float(-2.5)]), DataValue::Float(2.5));
+        assert_eq!(call("abs", vec![DataValue::Null]), DataValue::Null);
+
+        assert_eq!(call("round", vec![DataValue::Float(2.5)]), DataValue::Float(3.0));
+        assert_eq!(call("round", vec![DataValue::Float(2.345), DataValue::Integer(2)]), DataValue::Float(2.35));
+        assert_eq!(call("round", vec![DataValue::Integer(7)]), DataValue::Integer(7));
+        assert_eq!(call("round", vec![DataValue::Integer(1250), DataValue::Integer(-2)]), DataValue::Integer(1300));
+
+        assert_eq!(call("ceil", vec![DataValue::Float(1.2)]), DataValue::Float(2.0));
+        assert_eq!(call("ceil", vec![DataValue::Integer(4)]), DataValue::Integer(4));
+        assert_eq!(call("floor", vec![DataValue::Float(-1.2)]), DataValue::Float(-2.0));
+        assert_eq!(call("floor", vec![DataValue::Integer(4)]), DataValue::Integer(4));
+
+        assert_eq!(call("mod", vec![DataValue::Integer(10), DataValue::Integer(3)]), DataValue::Integer(1));
+        assert_eq!(call("mod", vec![DataValue::Float(5.5), DataValue::Integer(2)]), DataValue::Float(1.5));
+        assert!(registry.call("mod", vec![DataValue::Integer(1), DataValue::Integer(0)]).is_err());
+        assert!(registry.call("mod", vec![DataValue::Float(1.0), DataValue::Float(0.0)]).is_err());
+
+        assert_eq!(call("bitand", vec![DataValue::Integer(6), DataValue::Integer(3)]), DataValue::Integer(2));
+        assert_eq!(call("bitor", vec![DataValue::Integer(6), DataValue::Integer(3)]), DataValue::Integer(7));
+        assert_eq!(call("bitxor", vec![DataValue::Integer(6), DataValue::Integer(3)]), DataValue::Integer(5));
+        assert_eq!(call("bitand", vec![DataValue::Null, DataValue::Integer(3)]), DataValue::Null);
+        assert!(registry.call("bitor", vec![DataValue::Float(1.0), DataValue::Integer(3)]).is_err());
+
+        assert_eq!(call("power", vec![DataValue::Integer(2), DataValue::Integer(10)]), DataValue::Integer(1024));
+        assert_eq!(call("power", vec![DataValue::Integer(2), DataValue::Integer(-1)]), DataValue::Float(0.5));
+        assert_eq!(call("power", vec![DataValue::Float(9.0), DataValue::Float(0.5)]), DataValue::Float(3.0));
+        assert!(registry.call("power", vec![DataValue::Integer(10), DataValue::Integer(100)]).is_err());
+        assert!(registry.call("abs", vec![DataValue::Text("x".to_string())]).is_err());
+    }
+
+    #[test]
+    fn test_date_functions() {
+        let mut registry = FunctionRegistry::new();
+        register_builtins(&mut registry).unwrap();
+
+        let ts = DataValue::Timestamp("2024-03-14 12:34:56".to_string());
+        let trunc = |unit: &str| registry.call("date_trunc", vec![DataValue::Text(unit.to_string()), ts.clone()]).unwrap();
+        assert_eq!(trunc("year"), DataValue::Timestamp("2024-01-01 00:00:00".to_string()));
+        assert_eq!(trunc("month"), DataValue::Timestamp("2024-03-01 00:00:00".to_string()));
+        assert_eq!(trunc("day"), DataValue::Timestamp("2024-03-14 00:00:00".to_string()));
+        assert_eq!(trunc("hour"), DataValue::Timestamp("2024-03-14 12:00:00".to_string()));
+        assert!(registry.call("date_trunc", vec![DataValue::Text("fortnight".to_string()), ts.clone()]).is_err());
+
+        let extract = |field: &str| registry.call("extract", vec![DataValue::Text(field.to_string()), ts.clone()]).unwrap();
+        assert_eq!(extract("year"), DataValue::Integer(2024));
+        assert_eq!(extract("minute"), DataValue::Integer(34));
+        assert_eq!(extract("dow"), DataValue::Integer(4));
+        assert_eq!(extract("epoch"), DataValue::Integer(1710419696));
+        assert_eq!(
+            registry.call("extract", vec![DataValue::Text("day".to_string()), DataValue::Date("2024-02-29".to_string())]).unwrap(),
+            DataValue::Integer(29)
+        );
+
+        assert!(matches!(registry.call("NOW", vec![]).unwrap(), DataValue::Timestamp(_)));
+    }
 } 
\ No newline at end of file
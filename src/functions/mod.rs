@@ -1,15 +1,33 @@
 use std::collections::HashMap;
+use lazy_static::lazy_static;
+use crate::sql::column_def::ColumnDef;
 use crate::sql::data_value::DataValue;
 use crate::error::ReefDBError;
 
 mod builtins;
+mod aggregate;
 pub use builtins::register_builtins;
+pub(crate) use builtins::ts_rank;
+pub use aggregate::Aggregate;
+pub(crate) use aggregate::{aggregate_argument, Accumulator, DISTINCT};
+
+lazy_static! {
+    /// The builtin functions, for evaluating expressions where no database
+    /// and its own functions are at hand
+    pub(crate) static ref BUILTINS: FunctionRegistry = {
+        let mut registry = FunctionRegistry::new();
+        register_builtins(&mut registry).expect("builtin functions have distinct names");
+        registry
+    };
+}
 
 #[derive(Debug, Clone)]
 pub struct Function {
     pub name: String,
     pub args: Vec<FunctionArg>,
     pub return_type: FunctionReturnType,
+    /// Type of the trailing arguments accepted beyond `args`, if any
+    pub variadic: Option<FunctionArgType>,
     pub handler: FunctionHandler,
 }
 
@@ -89,8 +107,11 @@ impl FunctionRegistry {
         Ok(())
     }
 
+    /// Looks up a function, falling back to its lowercase name so SQL such
+    /// as `NOW()` finds the builtin `now`
     pub fn get(&self, name: &str) -> Option<&Function> {
         self.functions.get(name)
+            .or_else(|| self.functions.get(&name.to_lowercase()))
     }
 
     pub fn call(&self, name: &str, args: Vec<DataValue>) -> Result<DataValue, ReefDBError> {
@@ -103,8 +124,16 @@ impl FunctionRegistry {
         let max_args = function.args.len();
 
         // Validate argument count
-        if args.len() < required_args || args.len() > max_args {
-            return Err(ReefDBError::Other(format!(
+        if function.variadic.is_some() && args.len() < required_args {
+            return Err(ReefDBError::ArityMismatch(format!(
+                "Function '{}' expects at least {} arguments, got {}",
+                name,
+                required_args,
+                args.len()
+            )));
+        }
+        if function.variadic.is_none() && (args.len() < required_args || args.len() > max_args) {
+            return Err(ReefDBError::ArityMismatch(format!(
                 "Function '{}' expects {} to {} arguments, got {}. Required arguments: {}",
                 name,
                 required_args,
@@ -118,26 +147,31 @@ impl FunctionRegistry {
             )));
         }
 
-        // Validate argument types
-        for (i, (arg, provided)) in function.args.iter().zip(args.iter()).enumerate() {
-            let type_matches = match (provided, &arg.arg_type) {
-                (DataValue::Text(_), FunctionArgType::String) => true,
-                (DataValue::Integer(_), FunctionArgType::Integer) => true,
-                (DataValue::Float(_), FunctionArgType::Float) => true,
-                (DataValue::Boolean(_), FunctionArgType::Boolean) => true,
-                (DataValue::TSVector(_), FunctionArgType::TSVector) => true,
-                (DataValue::TSQuery(_), FunctionArgType::TSQuery) => true,
-                (_, FunctionArgType::Any) => true,
-                _ => false,
+        // Validate argument types; trailing arguments take the variadic type
+        for (i, provided) in args.iter().enumerate() {
+            let (arg_name, arg_type) = match (function.args.get(i), &function.variadic) {
+                (Some(arg), _) => (arg.name.as_str(), &arg.arg_type),
+                (None, Some(arg_type)) => ("...", arg_type),
+                (None, None) => break,
             };
+            let type_matches = matches!(
+                (provided, arg_type),
+                (DataValue::Text(_), FunctionArgType::String)
+                    | (DataValue::Integer(_), FunctionArgType::Integer)
+                    | (DataValue::Float(_), FunctionArgType::Float)
+                    | (DataValue::Boolean(_), FunctionArgType::Boolean)
+                    | (DataValue::TSVector(_), FunctionArgType::TSVector)
+                    | (DataValue::TSQuery(_), FunctionArgType::TSQuery)
+                    | (_, FunctionArgType::Any)
+            );
 
             if !type_matches {
                 return Err(ReefDBError::Other(format!(
                     "Function '{}': argument '{}' (position {}) expects type {:?}, got {:?}",
                     name,
-                    arg.name,
+                    arg_name,
                     i + 1,
-                    arg.arg_type,
+                    arg_type,
                     provided
                 )));
             }
@@ -147,6 +181,25 @@ impl FunctionRegistry {
         (function.handler)(args)
     }
 
+    /// Evaluates a function argument against a row. Words and strings that
+    /// name a column of the row are column references, anything else is a
+    /// literal; nested calls are evaluated recursively.
+    pub fn evaluate_arg(&self, arg: &DataValue, row: &[DataValue], schema: &[ColumnDef]) -> Result<DataValue, ReefDBError> {
+        match arg {
+            DataValue::Text(name) => Ok(schema.iter()
+                .position(|c| &c.name == name)
+                .map(|idx| row[idx].clone())
+                .unwrap_or_else(|| arg.clone())),
+            DataValue::Function { name, args } => {
+                let evaluated_args = args.iter()
+                    .map(|arg| self.evaluate_arg(arg, row, schema))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(name, evaluated_args)
+            }
+            _ => Ok(arg.clone()),
+        }
+    }
+
     pub fn list_functions(&self) -> Vec<String> {
         self.functions.keys().cloned().collect()
     }
@@ -176,11 +229,12 @@ mod tests {
                 },
             ],
             return_type: FunctionReturnType::Integer,
+            variadic: None,
             handler: |args| {
                 if let [DataValue::Integer(a), DataValue::Integer(b)] = args.as_slice() {
                     Ok(DataValue::Integer(a + b))
                 } else {
-                    Err(ReefDBError::Other("Invalid argument types".to_string()))
+                    Err(ReefDBError::TypeMismatch("invalid argument types".to_string()))
                 }
             },
         };
@@ -222,6 +276,7 @@ mod tests {
                 },
             ],
             return_type: FunctionReturnType::String,
+            variadic: None,
             handler: |args| {
                 match args.as_slice() {
                     [DataValue::Text(s1), DataValue::Text(s2), DataValue::Text(sep)] => {
@@ -230,7 +285,7 @@ mod tests {
                     [DataValue::Text(s1), DataValue::Text(s2)] => {
                         Ok(DataValue::Text(format!("{} {}", s1, s2)))
                     }
-                    _ => Err(ReefDBError::Other("Invalid argument types".to_string()))
+                    _ => Err(ReefDBError::TypeMismatch("invalid argument types".to_string()))
                 }
             },
         };
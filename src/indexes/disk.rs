@@ -157,6 +157,12 @@ impl IndexManager for OnDiskIndexManager {
                 }
                 btree.add_entry(new_value, row_id);
             }
+            IndexType::Hash(hash) => {
+                if !old_value.is_empty() {
+                    hash.remove_entry(old_value.clone(), row_id);
+                }
+                hash.add_entry(new_value, row_id);
+            }
             IndexType::GIN(gin) => {
                 if !old_value.is_empty() {
                     gin.remove_document(table, column, row_id);
@@ -5,6 +5,14 @@ use serde::{Serialize, Deserialize};
 
 use super::{TokenMap, DocumentMap};
 
+/// The most edits a fuzzy term (`term~n`) may allow. Higher distances match
+/// most of a vocabulary of short tokens, so larger `n` are capped to this.
+const MAX_EDITS: usize = 2;
+
+/// A query token: its text, the operator joining it to the previous token,
+/// whether it is negated and how many edits away a matching token may be
+type QueryToken = (String, QueryOperator, bool, usize);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryEvaluator {
     text_processor: DefaultTextProcessor,
@@ -18,7 +26,12 @@ impl QueryEvaluator {
     }
 
     pub fn evaluate(&self, column_entry: &TokenMap, query: &str) -> HashSet<usize> {
-        let processed = self.text_processor.process_query(query, Some("english"));
+        self.evaluate_with(column_entry, query, &self.text_processor)
+    }
+
+    /// Evaluates a query using a column-specific text processor
+    pub fn evaluate_with(&self, column_entry: &TokenMap, query: &str, text_processor: &DefaultTextProcessor) -> HashSet<usize> {
+        let processed = text_processor.process_query(query, Some("english"));
         println!("Processed query tokens: {:?}", processed.tokens);
         println!("Processed query operators: {:?}", processed.operators);
         
@@ -26,111 +39,99 @@ impl QueryEvaluator {
             return HashSet::new();
         }
 
-        // Convert processed tokens and operators into the format expected by the evaluator
-        let mut token_ops: Vec<(String, QueryOperator)> = Vec::new();
+        // Pair each token with the operator joining it to the previous one
+        // and whether it is negated or fuzzy
+        let mut token_ops: Vec<QueryToken> = Vec::new();
         for (i, token) in processed.tokens.iter().enumerate() {
-            let op = if token.type_ == TokenType::NotWord {
-                QueryOperator::Not
-            } else if i > 0 && i - 1 < processed.operators.len() {
-                processed.operators[i - 1].clone()
-            } else {
-                QueryOperator::And // Default to AND if no operator specified
+            let op = match i.checked_sub(1).and_then(|i| processed.operators.get(i)) {
+                Some(op) => op.clone(),
+                None => QueryOperator::And, // Default to AND if no operator specified
+            };
+            let max_edits = match token.type_ {
+                TokenType::Fuzzy(edits) => edits,
+                _ => 0,
             };
-            token_ops.push((token.text.to_lowercase(), op));
+            token_ops.push((token.text.to_lowercase(), op, token.type_ == TokenType::NotWord, max_edits));
         }
 
         self.evaluate_tokens(column_entry, token_ops)
     }
 
-    fn evaluate_tokens(&self, column_entry: &TokenMap, processed_tokens: Vec<(String, QueryOperator)>) -> HashSet<usize> {
+    /// NOT binds tighter than AND, which binds tighter than OR: the tokens
+    /// are split into OR groups, each group intersects its tokens (negated
+    /// ones as the documents without them), and the groups are unioned.
+    fn evaluate_tokens(&self, column_entry: &TokenMap, processed_tokens: Vec<QueryToken>) -> HashSet<usize> {
         if processed_tokens.is_empty() {
             return HashSet::new();
         }
 
         // If there's only one token and no operators, return all documents containing that token
-        if processed_tokens.len() == 1 && matches!(processed_tokens[0].1, QueryOperator::And) {
-            let token_text = &processed_tokens[0].0;
-            println!("Single token search for: {}", token_text);
-            return match column_entry.get(token_text) {
-                Some(doc_map) => doc_map.doc_ids(),
-                None => HashSet::new(),
-            };
+        if let [(token_text, QueryOperator::And, false, max_edits)] = processed_tokens.as_slice() {
+            return Self::token_docs(column_entry, token_text, *max_edits);
         }
 
-        let mut current_set: Option<HashSet<usize>> = None;
-
         // Get all document IDs in the column for NOT operations
         let mut all_docs = HashSet::new();
         for doc_map in column_entry.0.values() {
             all_docs.extend(doc_map.doc_ids());
         }
 
-        for (i, (token_text, operator)) in processed_tokens.iter().enumerate() {
-            println!("Processing token {}: {}", i, token_text);
-            let token_results = match column_entry.get(token_text) {
-                Some(doc_map) => {
-                    let results = doc_map.doc_ids();
-                    println!("Documents containing '{}': {:?}", token_text, results);
-                    results
-                },
-                None => HashSet::new(),
-            };
+        let mut groups: Vec<HashSet<usize>> = Vec::new();
+        let mut current_set: Option<HashSet<usize>> = None;
+
+        for (token_text, operator, negated, max_edits) in &processed_tokens {
+            let mut token_results = Self::token_docs(column_entry, token_text, *max_edits);
+            if *negated {
+                token_results = all_docs.difference(&token_results).cloned().collect();
+            }
 
-            if current_set.is_none() {
+            let Some(current) = current_set.take() else {
                 current_set = Some(token_results);
-                println!("Initial result set: {:?}", current_set);
                 continue;
-            }
+            };
 
-            let mut new_set = HashSet::new();
-            match operator {
-                QueryOperator::And => {
-                    for id in current_set.as_ref().unwrap() {
-                        if token_results.contains(id) {
-                            new_set.insert(*id);
-                        }
-                    }
-                    println!("After AND operation: {:?}", new_set);
-                }
+            current_set = Some(match operator {
                 QueryOperator::Or => {
-                    new_set.extend(current_set.as_ref().unwrap());
-                    new_set.extend(token_results);
-                    println!("After OR operation: {:?}", new_set);
+                    groups.push(current);
+                    token_results
                 }
-                QueryOperator::Not => {
-                    // For NOT operation, we want documents that are in current_set but NOT in token_results
-                    for id in current_set.as_ref().unwrap() {
-                        if !token_results.contains(id) {
-                            new_set.insert(*id);
-                        }
-                    }
-                    println!("After NOT operation: {:?}", new_set);
+                QueryOperator::And | QueryOperator::Not => {
+                    current.intersection(&token_results).cloned().collect()
                 }
                 QueryOperator::Phrase(ref tokens) => {
                     let token_strings: Vec<String> = tokens.iter().map(|t| t.text.to_lowercase()).collect();
-                    println!("Checking phrase: {:?}", token_strings);
-                    for id in current_set.as_ref().unwrap() {
-                        if Self::check_phrase(column_entry, *id, &token_strings) {
-                            new_set.insert(*id);
-                        }
-                    }
-                    println!("After phrase check: {:?}", new_set);
+                    current.into_iter()
+                        .filter(|id| Self::check_phrase(column_entry, *id, &token_strings))
+                        .collect()
                 }
                 QueryOperator::Proximity(ref tokens, distance) => {
                     let token_strings: Vec<String> = tokens.iter().map(|t| t.text.to_lowercase()).collect();
-                    println!("Checking proximity for tokens: {:?} with distance {}", token_strings, distance);
-                    for id in current_set.as_ref().unwrap() {
-                        if Self::check_proximity(column_entry, *id, &token_strings, *distance) {
-                            new_set.insert(*id);
-                        }
-                    }
-                    println!("After proximity check: {:?}", new_set);
+                    current.into_iter()
+                        .filter(|id| Self::check_proximity(column_entry, *id, &token_strings, *distance))
+                        .collect()
                 }
-            }
-            current_set = Some(new_set);
+            });
         }
 
-        current_set.unwrap_or_default()
+        groups.extend(current_set);
+        groups.into_iter().flatten().collect()
+    }
+
+    /// Documents containing `token` or, when `max_edits` is above zero, any
+    /// token at most that many insertions, deletions or substitutions away.
+    /// A fuzzy lookup compares against every token of the column, so it
+    /// costs time linear in the vocabulary; tokens whose length alone rules
+    /// them out are skipped without computing a distance.
+    fn token_docs(column_entry: &TokenMap, token: &str, max_edits: usize) -> HashSet<usize> {
+        if max_edits == 0 {
+            return column_entry.get(token).map(DocumentMap::doc_ids).unwrap_or_default();
+        }
+        let max_edits = max_edits.min(MAX_EDITS);
+        let token: Vec<char> = token.chars().collect();
+        column_entry.0.iter()
+            .filter(|(candidate, _)| within_edits(&token, candidate, max_edits))
+            .flat_map(|(_, doc_map)| doc_map.0.keys().copied())
+            .collect()
     }
 
     fn check_phrase(column_entry: &TokenMap, doc_id: usize, tokens: &[String]) -> bool {
@@ -182,22 +183,35 @@ impl QueryEvaluator {
             }
         }
 
-        for &pos1 in &all_positions[0] {
-            for positions in all_positions.iter().skip(1) {
-                let mut found = false;
-                for &pos2 in positions {
-                    if pos1.abs_diff(pos2) <= max_distance {
-                        found = true;
-                        break;
-                    }
-                }
-                if !found {
-                    return false;
-                }
-            }
+        // Some occurrence of the first token must have every other token nearby
+        all_positions[0].iter().any(|&pos1| {
+            all_positions.iter().skip(1).all(|positions| {
+                positions.iter().any(|&pos2| pos1.abs_diff(pos2) <= max_distance)
+            })
+        })
+    }
+}
+
+/// Whether the Levenshtein distance between `target` and `candidate` is at
+/// most `max_edits`, giving up as soon as every alignment exceeds it
+fn within_edits(target: &[char], candidate: &str, max_edits: usize) -> bool {
+    if target.len().abs_diff(candidate.chars().count()) > max_edits {
+        return false;
+    }
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut previous: Vec<usize> = (0..=candidate.len()).collect();
+    for (i, a) in target.iter().enumerate() {
+        let mut current = vec![i + 1; candidate.len() + 1];
+        for (j, b) in candidate.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        if current.iter().all(|&distance| distance > max_edits) {
+            return false;
         }
-        true
+        previous = current;
     }
+    previous[candidate.len()] <= max_edits
 }
 
 #[cfg(test)]
@@ -293,6 +307,26 @@ mod tests {
 
         let results = evaluator.evaluate(&token_map, "rust !web");
         assert_eq!(results, [1].iter().cloned().collect::<HashSet<_>>());
+
+        let results = evaluator.evaluate(&token_map, "rust AND NOT web");
+        assert_eq!(results, [1].iter().cloned().collect::<HashSet<_>>());
+
+        let results = evaluator.evaluate(&token_map, "-rust");
+        assert_eq!(results, [3].iter().cloned().collect::<HashSet<_>>());
+    }
+
+    #[test]
+    fn test_not_binds_tighter_than_and_or() {
+        let evaluator = QueryEvaluator::new();
+        let token_map = setup_test_index();
+
+        // (database) OR (rust AND NOT web)
+        let results = evaluator.evaluate(&token_map, "database OR rust AND NOT web");
+        assert_eq!(results, [1, 3].iter().cloned().collect::<HashSet<_>>());
+
+        // (rust AND NOT web) OR (NOT rust)
+        let results = evaluator.evaluate(&token_map, "rust -web OR NOT rust");
+        assert_eq!(results, [1, 3].iter().cloned().collect::<HashSet<_>>());
     }
 
     #[test]
@@ -310,6 +344,53 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[test]
+    fn test_proximity_search() {
+        let evaluator = QueryEvaluator::new();
+        let mut token_map = setup_test_index();
+        // Doc 4: "rust systems web", "rust" and "web" are 2 apart
+        token_map.0.get_mut("rust").unwrap().0.insert(4, vec![0]);
+        token_map.0.get_mut("system").unwrap().0.insert(4, vec![1]);
+        token_map.0.get_mut("web").unwrap().0.insert(4, vec![2]);
+
+        let results = evaluator.evaluate(&token_map, "rust <3> web");
+        assert_eq!(results, [2, 4].iter().cloned().collect::<HashSet<_>>());
+
+        let results = evaluator.evaluate(&token_map, "rust <1> web");
+        assert_eq!(results, [2].iter().cloned().collect::<HashSet<_>>());
+
+        let results = evaluator.evaluate(&token_map, "rust <3> database");
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let evaluator = QueryEvaluator::new();
+        let token_map = setup_test_index();
+
+        let results = evaluator.evaluate(&token_map, "databse~1");
+        assert_eq!(results, [3].iter().cloned().collect::<HashSet<_>>());
+
+        let results = evaluator.evaluate(&token_map, "databse~0");
+        assert!(results.is_empty());
+
+        let results = evaluator.evaluate(&token_map, "rust databse~1");
+        assert!(results.is_empty());
+
+        let results = evaluator.evaluate(&token_map, "rust OR databse~1");
+        assert_eq!(results, [1, 2, 3].iter().cloned().collect::<HashSet<_>>());
+    }
+
+    #[test]
+    fn test_within_edits() {
+        let target: Vec<char> = "databas".chars().collect();
+        assert!(within_edits(&target, "databas", 0));
+        assert!(within_edits(&target, "databs", 1));
+        assert!(!within_edits(&target, "databs", 0));
+        assert!(within_edits(&target, "dtabase", 2));
+        assert!(!within_edits(&target, "system", 2));
+    }
+
     #[ignore]
     fn test_complex_queries() {
         let evaluator = QueryEvaluator::new();
@@ -10,7 +10,10 @@ use serde::Serialize;
 use crate::fts::text_processor::QueryOperator;
 use crate::fts::text_processor::TokenType;
 use crate::fts::DefaultTextProcessor;
+use crate::fts::language::LanguageConfig;
+use crate::fts::ranking::CollectionStats;
 use crate::fts::search::Search;
+use crate::fts::tokenizers::registry::SharedTokenizer;
 use crate::fts::tokenizers::tokenizer::Tokenizer;
 use crate::fts::tokenizers::default::DefaultTokenizer;
 
@@ -35,6 +38,35 @@ pub struct GinIndex<T: Tokenizer> {
     tokenizer: T,
     text_processor: DefaultTextProcessor,
     evaluator: QueryEvaluator,
+    /// Per-column text processors keyed by table, then column
+    #[serde(default)]
+    column_processors: HashMap<String, HashMap<String, DefaultTextProcessor>>,
+    /// Custom tokenizers of the columns that have one. They are code, so
+    /// they are not saved with the index and have to be set again on load.
+    #[serde(skip)]
+    column_tokenizers: ColumnTokenizers,
+    /// Statistics of each column as of the last `refresh_stats`, keyed by
+    /// table, then column
+    #[serde(skip)]
+    collection_stats: HashMap<String, HashMap<String, CollectionStats>>,
+}
+
+/// Tokenizers keyed by table, then column
+#[derive(Clone, Default)]
+struct ColumnTokenizers(HashMap<String, HashMap<String, SharedTokenizer>>);
+
+impl ColumnTokenizers {
+    fn get(&self, table: &str, column: &str) -> Option<&SharedTokenizer> {
+        self.0.get(table).and_then(|columns| columns.get(column))
+    }
+}
+
+impl Debug for ColumnTokenizers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(table, columns)| (table, columns.keys().collect::<Vec<_>>())))
+            .finish()
+    }
 }
 
 impl DocumentMap {
@@ -92,9 +124,86 @@ impl<T: Tokenizer> GinIndex<T> {
             tokenizer: T::new(),
             text_processor: DefaultTextProcessor::new(),
             evaluator: QueryEvaluator::new(),
+            column_processors: HashMap::new(),
+            column_tokenizers: ColumnTokenizers::default(),
+            collection_stats: HashMap::new(),
         }
     }
 
+    /// Splits the text of a column with `tokenizer` instead of the language
+    /// processing: tokens are only lowercased, and a query matches the
+    /// documents holding all of its tokens. Existing documents keep their
+    /// tokens until they are re-added.
+    pub fn set_column_tokenizer(&mut self, table: &str, column: &str, tokenizer: SharedTokenizer) {
+        self.column_tokenizers.0
+            .entry(table.to_string())
+            .or_default()
+            .insert(column.to_string(), tokenizer);
+    }
+
+    /// Configures language processing (e.g. stop words) for a single column.
+    /// Existing documents keep their tokens until they are re-added.
+    pub fn set_column_config(&mut self, table: &str, column: &str, config: LanguageConfig) {
+        self.column_processors
+            .entry(table.to_string())
+            .or_default()
+            .insert(column.to_string(), DefaultTextProcessor::with_config(config));
+    }
+
+    /// Removes every document from a column while keeping its configuration
+    pub fn clear_column(&mut self, table: &str, column: &str) {
+        if let Some(token_map) = self.index.get_mut(table).and_then(|c| c.get_mut(column)) {
+            token_map.0.clear();
+        }
+    }
+
+    /// Recomputes the statistics of every column from the documents it
+    /// holds now. They are not kept up to date as documents change, so
+    /// ranking uses the ones of the last refresh.
+    pub fn refresh_stats(&mut self) {
+        self.collection_stats = self.index.iter()
+            .map(|(table, column_map)| {
+                let columns = column_map.0.iter()
+                    .map(|(column, token_map)| (column.clone(), Self::column_stats(token_map)))
+                    .collect();
+                (table.clone(), columns)
+            })
+            .collect();
+    }
+
+    /// The statistics of a column as of the last `refresh_stats`
+    pub fn collection_stats(&self, table: &str, column: &str) -> Option<&CollectionStats> {
+        self.collection_stats.get(table).and_then(|columns| columns.get(column))
+    }
+
+    fn column_stats(token_map: &TokenMap) -> CollectionStats {
+        // Removed documents leave their tokens behind with no positions
+        let mut doc_lengths: HashMap<usize, usize> = HashMap::new();
+        let mut term_doc_frequencies = HashMap::new();
+        for (token, doc_map) in &token_map.0 {
+            if doc_map.0.is_empty() {
+                continue;
+            }
+            term_doc_frequencies.insert(token.clone(), doc_map.0.len());
+            for (doc_id, positions) in &doc_map.0 {
+                *doc_lengths.entry(*doc_id).or_default() += positions.len();
+            }
+        }
+        let total_docs = doc_lengths.len();
+        let total_length: usize = doc_lengths.values().sum();
+        CollectionStats {
+            total_docs,
+            term_doc_frequencies,
+            avg_doc_length: if total_docs == 0 { 0.0 } else { total_length as f64 / total_docs as f64 },
+        }
+    }
+
+    fn column_processor(&self, table: &str, column: &str) -> Option<&DefaultTextProcessor> {
+        self.column_processors
+            .get(table)
+            .and_then(|columns| columns.get(column))
+    }
+
     pub fn add_column(&mut self, table: &str, column: &str) {
         self.index.entry(table.to_string())
             .or_insert_with(ColumnMap::default)
@@ -127,6 +236,19 @@ impl<T: Tokenizer> GinIndex<T> {
     }
 
     fn add_document(&mut self, table: &str, column: &str, row_id: usize, text: &str) {
+        let tokens = match self.column_tokenizers.get(table, column) {
+            Some(tokenizer) => tokenizer.tokenize(text)
+                .enumerate()
+                .map(|(i, token)| (token.to_lowercase(), i + 1))
+                .collect::<Vec<_>>(),
+            None => self.column_processor(table, column)
+                .unwrap_or(&self.text_processor)
+                .process_document(text, Some("english"))
+                .tokens.into_iter()
+                .map(|token| (token.text, token.position))
+                .collect(),
+        };
+
         let table_entry = self.index
             .entry(table.to_string())
             .or_insert_with(ColumnMap::default);
@@ -135,15 +257,14 @@ impl<T: Tokenizer> GinIndex<T> {
             .entry(column.to_string())
             .or_insert_with(TokenMap::default);
 
-        let processed = self.text_processor.process_document(text, Some("english"));
-        for token in processed.tokens {
+        for (token, position) in tokens {
             column_entry
-                .entry(token.text)
+                .entry(token)
                 .or_insert_with(DocumentMap::default)
                 .0
                 .entry(row_id)
                 .or_insert_with(Vec::new)
-                .push(token.position);
+                .push(position);
         }
     }
 
@@ -165,7 +286,13 @@ impl<T: Tokenizer> GinIndex<T> {
     pub fn search(&self, table: &str, column: &str, query: &str) -> HashSet<usize> {
         if let Some(table_entry) = self.index.get(table) {
             if let Some(column_entry) = table_entry.get(column) {
-                self.evaluator.evaluate(column_entry, query)
+                if let Some(tokenizer) = self.column_tokenizers.get(table, column) {
+                    return Self::search_tokens(column_entry, tokenizer.tokenize(query));
+                }
+                match self.column_processor(table, column) {
+                    Some(processor) => self.evaluator.evaluate_with(column_entry, query, processor),
+                    None => self.evaluator.evaluate(column_entry, query),
+                }
             } else {
                 HashSet::new()
             }
@@ -174,6 +301,20 @@ impl<T: Tokenizer> GinIndex<T> {
         }
     }
 
+    /// Documents holding every one of `tokens`, none for an empty query
+    fn search_tokens<'a>(column_entry: &TokenMap, mut tokens: impl Iterator<Item = &'a str>) -> HashSet<usize> {
+        let docs = |token: &str| column_entry.get(&token.to_lowercase())
+            .map(DocumentMap::doc_ids)
+            .unwrap_or_default();
+        let Some(first) = tokens.next() else {
+            return HashSet::new();
+        };
+        tokens.fold(docs(first), |matches, token| {
+            let token_docs = docs(token);
+            matches.into_iter().filter(|doc| token_docs.contains(doc)).collect()
+        })
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, HashSet<usize>)> + '_ {
         self.index
             .values()
@@ -209,6 +350,26 @@ impl<T: Tokenizer + Serialize + for<'de> Deserialize<'de>> Search for GinIndex<T
     fn update_document(&mut self, table: &str, column: &str, row_id: usize, text: &str) {
         GinIndex::update_document(self, table, column, row_id, text)
     }
+
+    fn set_column_config(&mut self, table: &str, column: &str, config: LanguageConfig) {
+        GinIndex::set_column_config(self, table, column, config)
+    }
+
+    fn set_column_tokenizer(&mut self, table: &str, column: &str, tokenizer: SharedTokenizer) {
+        GinIndex::set_column_tokenizer(self, table, column, tokenizer)
+    }
+
+    fn clear_column(&mut self, table: &str, column: &str) {
+        GinIndex::clear_column(self, table, column)
+    }
+
+    fn refresh_stats(&mut self) {
+        GinIndex::refresh_stats(self)
+    }
+
+    fn collection_stats(&self, table: &str, column: &str) -> Option<&CollectionStats> {
+        GinIndex::collection_stats(self, table, column)
+    }
 }
 
 #[cfg(test)]
@@ -255,4 +416,39 @@ mod tests {
         let results = index.search("table1", "column1", "hello");
         assert!(results.is_empty());
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_column_stop_words() {
+        let mut index: GinIndex<DefaultTokenizer> = GinIndex::new();
+        let config = LanguageConfig {
+            stop_words: ["acme"].iter().map(|w| w.to_string()).collect(),
+            ..Default::default()
+        };
+        index.set_column_config("companies", "name", config);
+
+        index.add_document("companies", "name", 1, "Acme Widgets");
+        index.add_document("companies", "other", 1, "Acme Widgets");
+
+        assert!(index.search("companies", "name", "acme").is_empty());
+        assert_eq!(index.search("companies", "name", "widgets").len(), 1);
+        // Other columns keep the default stop words
+        assert_eq!(index.search("companies", "other", "acme").len(), 1);
+    }
+
+    #[test]
+    fn test_collection_stats() {
+        let mut index: GinIndex<DefaultTokenizer> = GinIndex::new();
+        index.add_document("table1", "column1", 0, "hello world");
+        index.add_document("table1", "column1", 1, "goodbye world");
+        index.add_document("table1", "column1", 2, "world peace");
+        index.remove_document("table1", "column1", 2);
+        assert!(index.collection_stats("table1", "column1").is_none());
+
+        index.refresh_stats();
+        let stats = index.collection_stats("table1", "column1").unwrap();
+        assert_eq!(stats.total_docs, 2);
+        assert_eq!(stats.term_doc_frequencies.get("world"), Some(&2));
+        assert!(!stats.term_doc_frequencies.contains_key("peace"));
+        assert_eq!(stats.avg_doc_length, 2.0);
+    }
+}
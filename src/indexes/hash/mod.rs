@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+
+/// Equality-only index: cheaper than a BTree for `=` lookups on
+/// high-cardinality columns, but it cannot answer range queries
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HashIndex {
+    // Map of column value to set of row IDs
+    index: HashMap<Vec<u8>, HashSet<usize>>,
+}
+
+impl HashIndex {
+    pub fn new() -> Self {
+        HashIndex {
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn add_entry(&mut self, value: Vec<u8>, row_id: usize) {
+        self.index
+            .entry(value)
+            .or_default()
+            .insert(row_id);
+    }
+
+    pub fn remove_entry(&mut self, value: Vec<u8>, row_id: usize) {
+        if let Some(rows) = self.index.get_mut(&value) {
+            rows.remove(&row_id);
+            if rows.is_empty() {
+                self.index.remove(&value);
+            }
+        }
+    }
+
+    pub fn search(&self, value: Vec<u8>) -> Option<&HashSet<usize>> {
+        self.index.get(&value)
+    }
+
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, Vec<u8>, HashSet<usize>> {
+        self.index.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let index = HashIndex::new();
+        assert!(index.index.is_empty());
+    }
+
+    #[test]
+    fn test_add_entry() {
+        let mut index = HashIndex::new();
+        index.add_entry(vec![1, 2, 3], 1);
+
+        assert_eq!(index.index.len(), 1);
+        assert!(index.index.contains_key(&vec![1, 2, 3]));
+        assert_eq!(index.index.get(&vec![1, 2, 3]).unwrap().len(), 1);
+        assert!(index.index.get(&vec![1, 2, 3]).unwrap().contains(&1));
+    }
+
+    #[test]
+    fn test_remove_entry() {
+        let mut index = HashIndex::new();
+        index.add_entry(vec![1, 2, 3], 1);
+        index.add_entry(vec![1, 2, 3], 2);
+
+        index.remove_entry(vec![1, 2, 3], 1);
+        assert_eq!(index.index.get(&vec![1, 2, 3]).unwrap().len(), 1);
+        assert!(index.index.get(&vec![1, 2, 3]).unwrap().contains(&2));
+
+        index.remove_entry(vec![1, 2, 3], 2);
+        assert!(!index.index.contains_key(&vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut index = HashIndex::new();
+        index.add_entry(vec![1, 2, 3], 1);
+        index.add_entry(vec![1, 2, 3], 2);
+
+        let result = index.search(vec![1, 2, 3]);
+        assert!(result.is_some());
+        assert_eq!(result.unwrap().len(), 2);
+        assert!(result.unwrap().contains(&1));
+        assert!(result.unwrap().contains(&2));
+
+        assert!(index.search(vec![4, 5, 6]).is_none());
+    }
+}
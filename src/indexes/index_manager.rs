@@ -7,10 +7,10 @@ use std::fs::{File, OpenOptions};
 use std::path::Path;
 use serde::{Serialize, Deserialize};
 use crate::fts::default::DefaultSearchIdx;
-use crate::fts::tokenizers::tokenizer::Tokenizer;
 use crate::fts::tokenizers::default::DefaultTokenizer;
 use crate::indexes::gin::GinIndex;
 use crate::indexes::btree::BTreeIndex;
+use crate::indexes::hash::HashIndex;
 use crate::fts::search::Search;
 use crate::error::ReefDBError;
 
@@ -18,6 +18,7 @@ use crate::error::ReefDBError;
 pub enum IndexType {
     BTree(BTreeIndex),
     GIN(GinIndex<DefaultTokenizer>),
+    Hash(HashIndex),
 }
 
 impl Clone for IndexType {
@@ -25,6 +26,35 @@ impl Clone for IndexType {
         match self {
             IndexType::BTree(btree) => IndexType::BTree(btree.clone()),
             IndexType::GIN(gin) => IndexType::GIN(gin.clone()),
+            IndexType::Hash(hash) => IndexType::Hash(hash.clone()),
+        }
+    }
+}
+
+impl IndexType {
+    pub fn kind(&self) -> IndexKind {
+        match self {
+            IndexType::BTree(_) => IndexKind::BTree,
+            IndexType::GIN(_) => IndexKind::GIN,
+            IndexType::Hash(_) => IndexKind::Hash,
+        }
+    }
+}
+
+/// The kind of an index, without its contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    BTree,
+    GIN,
+    Hash,
+}
+
+impl std::fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexKind::BTree => write!(f, "BTREE"),
+            IndexKind::GIN => write!(f, "GIN"),
+            IndexKind::Hash => write!(f, "HASH"),
         }
     }
 }
@@ -164,6 +194,10 @@ impl IndexManager for DefaultIndexManager {
                     btree.remove_entry(old_value, row_id);
                     btree.add_entry(new_value, row_id);
                 }
+                IndexType::Hash(hash) => {
+                    hash.remove_entry(old_value, row_id);
+                    hash.add_entry(new_value, row_id);
+                }
                 IndexType::GIN(gin) => {
                     // For GIN indexes, we need to handle text differently
                     let old_text = String::from_utf8_lossy(&old_value).to_string();
@@ -287,6 +321,9 @@ impl OnDiskIndexManager {
                         Some(IndexType::BTree(btree)) => {
                             btree.add_entry(new_value.clone(), update.row_id);
                         },
+                        Some(IndexType::Hash(hash)) => {
+                            hash.add_entry(new_value.clone(), update.row_id);
+                        },
                         Some(IndexType::GIN(gin)) => {
                             gin.add_document(&update.table_name, &update.column_name, update.row_id, std::str::from_utf8(new_value).unwrap_or_default());
                         },
@@ -301,6 +338,10 @@ impl OnDiskIndexManager {
                             btree.remove_entry(old_value.clone(), update.row_id);
                             btree.add_entry(new_value.clone(), update.row_id);
                         },
+                        Some(IndexType::Hash(hash)) => {
+                            hash.remove_entry(old_value.clone(), update.row_id);
+                            hash.add_entry(new_value.clone(), update.row_id);
+                        },
                         Some(IndexType::GIN(gin)) => {
                             gin.remove_document(&update.table_name, &update.column_name, update.row_id);
                             gin.add_document(&update.table_name, &update.column_name, update.row_id, std::str::from_utf8(new_value).unwrap_or_default());
@@ -315,6 +356,9 @@ impl OnDiskIndexManager {
                         Some(IndexType::BTree(btree)) => {
                             btree.remove_entry(old_value.clone(), update.row_id);
                         },
+                        Some(IndexType::Hash(hash)) => {
+                            hash.remove_entry(old_value.clone(), update.row_id);
+                        },
                         Some(IndexType::GIN(gin)) => {
                             gin.remove_document(&update.table_name, &update.column_name, update.row_id);
                         },
@@ -390,6 +434,9 @@ impl OnDiskIndexManager {
                                 Some(IndexType::BTree(btree)) => {
                                     btree.remove_entry(new_value.clone(), update.row_id);
                                 },
+                                Some(IndexType::Hash(hash)) => {
+                                    hash.remove_entry(new_value.clone(), update.row_id);
+                                },
                                 Some(IndexType::GIN(gin)) => {
                                     gin.remove_document(&update.table_name, &update.column_name, update.row_id);
                                 },
@@ -403,6 +450,9 @@ impl OnDiskIndexManager {
                                 Some(IndexType::BTree(btree)) => {
                                     btree.add_entry(old_value.clone(), update.row_id);
                                 },
+                                Some(IndexType::Hash(hash)) => {
+                                    hash.add_entry(old_value.clone(), update.row_id);
+                                },
                                 Some(IndexType::GIN(gin)) => {
                                     gin.add_document(&update.table_name, &update.column_name, update.row_id, std::str::from_utf8(old_value).unwrap_or_default());
                                 },
@@ -456,6 +506,10 @@ impl IndexManager for OnDiskIndexManager {
                     btree.remove_entry(old_value, row_id);
                     btree.add_entry(new_value, row_id);
                 }
+                IndexType::Hash(hash) => {
+                    hash.remove_entry(old_value, row_id);
+                    hash.add_entry(new_value, row_id);
+                }
                 IndexType::GIN(gin) => {
                     // For GIN indexes, we need to handle text differently
                     let old_text = String::from_utf8_lossy(&old_value).to_string();
@@ -629,6 +683,41 @@ mod tests {
         manager.rollback_index_transaction(1).unwrap();
     }
 
+    #[test]
+    fn test_hash_index_transaction_rollback() {
+        let mut manager = DefaultIndexManager::new();
+        let mut hash = HashIndex::new();
+        hash.add_entry(vec![1, 2, 3], 1);
+        manager.create_index("test_table", "test_column", IndexType::Hash(hash)).unwrap();
+
+        let update = IndexUpdate {
+            table_name: "test_table".to_string(),
+            column_name: "test_column".to_string(),
+            old_value: Some(vec![1, 2, 3]),
+            new_value: Some(vec![4, 5, 6]),
+            row_id: 1,
+            transaction_id: 1,
+            operation_type: IndexOperationType::Update,
+        };
+        manager.track_index_update(update).unwrap();
+        match manager.get_index("test_table", "test_column").unwrap() {
+            IndexType::Hash(hash) => {
+                assert!(hash.search(vec![1, 2, 3]).is_none());
+                assert!(hash.search(vec![4, 5, 6]).unwrap().contains(&1));
+            }
+            _ => panic!("Expected Hash index"),
+        }
+
+        manager.rollback_index_transaction(1).unwrap();
+        match manager.get_index("test_table", "test_column").unwrap() {
+            IndexType::Hash(hash) => {
+                assert!(hash.search(vec![1, 2, 3]).unwrap().contains(&1));
+                assert!(hash.search(vec![4, 5, 6]).is_none());
+            }
+            _ => panic!("Expected Hash index"),
+        }
+    }
+
     #[test]
     fn test_concurrent_transactions() {
         let mut manager = DefaultIndexManager::new();
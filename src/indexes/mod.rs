@@ -1,7 +1,8 @@
 pub mod btree;
 pub mod gin;
+pub mod hash;
 pub mod index_manager;
 pub mod verification;
 pub mod disk;
 
-pub use index_manager::{IndexManager, IndexType};
\ No newline at end of file
+pub use index_manager::{IndexKind, IndexManager, IndexType};
\ No newline at end of file
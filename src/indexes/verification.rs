@@ -32,6 +32,32 @@ pub enum VerificationIssue {
         value: Vec<u8>,
         reason: String,
     },
+    /// A row whose value has no index entry
+    MissingEntry {
+        value: Vec<u8>,
+        row_id: usize,
+    },
+}
+
+impl VerificationIssue {
+    /// Short name of the kind of issue, as reported by `VERIFY INDEX`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            VerificationIssue::DuplicateRowId { .. } => "duplicate_row_id",
+            VerificationIssue::OrphanedRowId { .. } => "orphaned_row_id",
+            VerificationIssue::InvalidValue { .. } => "invalid_value",
+            VerificationIssue::MissingEntry { .. } => "missing_entry",
+        }
+    }
+
+    pub fn row_id(&self) -> Option<usize> {
+        match self {
+            VerificationIssue::DuplicateRowId { row_id, .. }
+            | VerificationIssue::OrphanedRowId { row_id, .. }
+            | VerificationIssue::MissingEntry { row_id, .. } => Some(*row_id),
+            VerificationIssue::InvalidValue { .. } => None,
+        }
+    }
 }
 
 impl<T> IndexVerification for T where T: IndexManager {
@@ -39,18 +65,55 @@ impl<T> IndexVerification for T where T: IndexManager {
         let index = self.get_index(table, column)?;
         
         match index {
-            IndexType::BTree(btree) => verify_btree_consistency(btree),
+            IndexType::BTree(btree) => verify_row_ids(btree.iter()),
+            IndexType::Hash(hash) => verify_row_ids(hash.iter()),
             IndexType::GIN(gin) => verify_gin_consistency(gin),
         }
     }
 }
 
-fn verify_btree_consistency(index: &crate::indexes::btree::BTreeIndex) -> Result<VerificationResult, ReefDBError> {
+/// Checks `index` and cross-checks it against the column it indexes, where
+/// `values[i]` is the encoded value of row `i + 1`. Entries for rows that
+/// are gone or now hold another value are orphaned; rows without an entry
+/// are missing. GIN indexes only get the structural checks.
+pub fn verify_against_rows(index: &IndexType, values: &[Vec<u8>]) -> Result<VerificationResult, ReefDBError> {
+    let (mut result, mismatches) = match index {
+        IndexType::BTree(btree) => (verify_row_ids(btree.iter())?, cross_check(btree.iter(), values)),
+        IndexType::Hash(hash) => (verify_row_ids(hash.iter())?, cross_check(hash.iter(), values)),
+        IndexType::GIN(gin) => return verify_gin_consistency(gin),
+    };
+    result.issues.extend(mismatches);
+    result.is_consistent = result.issues.is_empty();
+    Ok(result)
+}
+
+fn cross_check<'a>(entries: impl Iterator<Item = (&'a Vec<u8>, &'a HashSet<usize>)>, values: &[Vec<u8>]) -> Vec<VerificationIssue> {
+    let mut issues = Vec::new();
+    let mut indexed = HashSet::new();
+    for (value, row_ids) in entries {
+        for &row_id in row_ids {
+            if row_id.checked_sub(1).and_then(|i| values.get(i)) == Some(value) {
+                indexed.insert(row_id);
+            } else {
+                issues.push(VerificationIssue::OrphanedRowId { value: value.clone(), row_id });
+            }
+        }
+    }
+    for (i, value) in values.iter().enumerate() {
+        if !indexed.contains(&(i + 1)) {
+            issues.push(VerificationIssue::MissingEntry { value: value.clone(), row_id: i + 1 });
+        }
+    }
+    issues
+}
+
+/// Checks a value index (BTree or hash) for rows filed under several values
+fn verify_row_ids<'a>(entries: impl Iterator<Item = (&'a Vec<u8>, &'a HashSet<usize>)>) -> Result<VerificationResult, ReefDBError> {
     let mut issues = Vec::new();
     let mut seen_row_ids = HashSet::new();
 
     // Check for duplicate row IDs across different values
-    for (value, row_ids) in index.iter() {
+    for (value, row_ids) in entries {
         for row_id in row_ids {
             if !seen_row_ids.insert(*row_id) {
                 issues.push(VerificationIssue::DuplicateRowId {
@@ -135,6 +198,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_verify_against_rows() {
+        let mut btree = BTreeIndex::new();
+        btree.add_entry(vec![1], 1);
+        btree.add_entry(vec![2], 2);
+        btree.add_entry(vec![3], 3);
+        let index = IndexType::BTree(btree);
+
+        let result = verify_against_rows(&index, &[vec![1], vec![2], vec![3]]).unwrap();
+        assert!(result.is_consistent);
+
+        // Row 2 now holds another value and row 3 is gone
+        let result = verify_against_rows(&index, &[vec![1], vec![9]]).unwrap();
+        let mut issues: Vec<_> = result.issues.iter().map(|i| (i.kind(), i.row_id())).collect();
+        issues.sort();
+        assert_eq!(issues, vec![
+            ("missing_entry", Some(2)),
+            ("orphaned_row_id", Some(2)),
+            ("orphaned_row_id", Some(3)),
+        ]);
+    }
+
     #[test]
     fn test_gin_index_verification() {
         let mut manager = DefaultIndexManager::new();
@@ -0,0 +1,149 @@
+use std::any::Any;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::{IndexKind, IndexManager};
+use crate::indexes::verification::{verify_against_rows, VerificationResult};
+use crate::key_format::encode_value;
+use crate::result::{ColumnInfo, QueryResult, ReefDBResult};
+use crate::sql::column_def::ColumnDef;
+use crate::sql::constraints::constraint::Constraint;
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+use crate::storage::Storage;
+use crate::ReefDB;
+
+pub(crate) fn column_info(name: &str, data_type: DataType, nullable: bool) -> ColumnInfo {
+    ColumnInfo { name: name.to_string(), data_type, table: None, nullable }
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Names of all tables, sorted
+    pub fn list_tables(&self) -> Vec<String> {
+        let mut tables: Vec<String> = self.storage.get_all_tables().keys().cloned().collect();
+        tables.sort();
+        tables
+    }
+
+    /// Column definitions of `table_name`, in table order
+    pub fn describe_table(&self, table_name: &str) -> Result<Vec<ColumnDef>, ReefDBError> {
+        self.storage.get_schema_ref(table_name)
+            .cloned()
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))
+    }
+
+    /// Rows of `table_name` with their row ids, in table order, without
+    /// going through SQL. This reads the committed rows: changes made by a
+    /// transaction that is still open are not seen.
+    pub fn scan_table(&self, table_name: &str) -> Result<impl Iterator<Item = (usize, &Vec<DataValue>)> + '_, ReefDBError> {
+        let (_, rows) = self.storage.get_table_ref(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        Ok(rows.iter().enumerate())
+    }
+
+    /// The committed row of `table_name` with id `row_id`, if there is one
+    pub fn get_row(&self, table_name: &str, row_id: usize) -> Result<Option<&Vec<DataValue>>, ReefDBError> {
+        let (_, rows) = self.storage.get_table_ref(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        Ok(rows.get(row_id))
+    }
+
+    /// Indexed columns of `table_name` with the kind of index on each, in
+    /// table order. A missing table has no indexes.
+    pub fn list_indexes(&self, table_name: &str) -> Vec<(String, IndexKind)> {
+        let Some(schema) = self.storage.get_schema_ref(table_name) else {
+            return Vec::new();
+        };
+        schema.iter()
+            .filter_map(|column| self.storage.get_index(table_name, &column.name).ok()
+                .map(|index| (column.name.clone(), index.kind())))
+            .collect()
+    }
+
+    /// Cross-checks the index on `column_name` against the table's rows,
+    /// reporting entries missing for a row or pointing at a row that is gone
+    /// or holds another value
+    pub fn verify_index(&self, table_name: &str, column_name: &str) -> Result<VerificationResult, ReefDBError> {
+        let (schema, rows) = self.storage.get_table_ref(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let col_idx = schema.iter()
+            .position(|c| c.name == column_name)
+            .ok_or_else(|| ReefDBError::ColumnNotFound(column_name.to_string()))?;
+        let index = self.storage.get_index(table_name, column_name)?;
+        let values = rows.iter()
+            .map(|row| encode_value(&row[col_idx]))
+            .collect::<Result<Vec<_>, _>>()?;
+        verify_against_rows(index, &values)
+    }
+
+    /// Verifies every index, returning the table, column and result of each
+    /// in table then column order
+    pub fn verify_indexes(&self) -> Result<Vec<(String, String, VerificationResult)>, ReefDBError> {
+        let mut results = Vec::new();
+        for table in self.list_tables() {
+            for (column, _) in self.list_indexes(&table) {
+                let result = self.verify_index(&table, &column)?;
+                results.push((table.clone(), column, result));
+            }
+        }
+        Ok(results)
+    }
+
+    /// `VERIFY INDEX t (c)`: one row per issue found, none when consistent
+    pub(crate) fn handle_verify_index(&self, table_name: &str, column_name: &str) -> Result<ReefDBResult, ReefDBError> {
+        let rows = self.verify_index(table_name, column_name)?.issues.iter()
+            .enumerate()
+            .map(|(i, issue)| (i, vec![
+                DataValue::Text(issue.kind().to_string()),
+                issue.row_id().map_or(DataValue::Null, |row_id| DataValue::Integer(row_id as i64)),
+            ]))
+            .collect();
+        let columns = vec![
+            column_info("issue", DataType::Text, false),
+            column_info("row_id", DataType::Integer, true),
+        ];
+        Ok(ReefDBResult::Select(QueryResult::with_columns(rows, columns)))
+    }
+
+    /// `SHOW TABLES`: one row per table
+    pub(crate) fn handle_show_tables(&self) -> Result<ReefDBResult, ReefDBError> {
+        let rows = self.list_tables().into_iter()
+            .enumerate()
+            .map(|(i, name)| (i, vec![DataValue::Text(name)]))
+            .collect();
+        let columns = vec![column_info("table_name", DataType::Text, false)];
+        Ok(ReefDBResult::Select(QueryResult::with_columns(rows, columns)))
+    }
+
+    /// `DESCRIBE t`: one row per column with its type, nullability, whether
+    /// it is the primary key and the kind of index on it
+    pub(crate) fn handle_describe(&self, table_name: &str) -> Result<ReefDBResult, ReefDBError> {
+        let indexes = self.list_indexes(table_name);
+        let rows = self.describe_table(table_name)?.into_iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let index = indexes.iter()
+                    .find(|(name, _)| *name == column.name)
+                    .map_or(DataValue::Null, |(_, kind)| DataValue::Text(kind.to_string()));
+                (i, vec![
+                    DataValue::Text(column.name.clone()),
+                    DataValue::Text(column.data_type.to_string()),
+                    DataValue::Boolean(!column.constraints.contains(&Constraint::NotNull)),
+                    DataValue::Boolean(column.constraints.contains(&Constraint::PrimaryKey)),
+                    index,
+                ])
+            })
+            .collect();
+        let columns = vec![
+            column_info("column_name", DataType::Text, false),
+            column_info("data_type", DataType::Text, false),
+            column_info("nullable", DataType::Boolean, false),
+            column_info("primary_key", DataType::Boolean, false),
+            column_info("index", DataType::Text, true),
+        ];
+        Ok(ReefDBResult::Select(QueryResult::with_columns(rows, columns)))
+    }
+}
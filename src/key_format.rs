@@ -1,5 +1,8 @@
 use std::fmt;
 
+use crate::error::ReefDBError;
+use crate::sql::data_value::DataValue;
+
 /// Namespace prefixes for different key types
 pub const KEY_NAMESPACE_TABLE: &str = "t";     // Table metadata
 pub const KEY_NAMESPACE_ROW: &str = "r";       // Row data
@@ -104,6 +107,57 @@ impl KeyFormat {
     }
 }
 
+/// Leading byte of an encoded value, so values of different types never
+/// compare equal and NULL sorts first
+const VALUE_TAG_NULL: u8 = 0x00;
+const VALUE_TAG_BOOLEAN: u8 = 0x01;
+const VALUE_TAG_INTEGER: u8 = 0x02;
+const VALUE_TAG_FLOAT: u8 = 0x03;
+const VALUE_TAG_TEXT: u8 = 0x04;
+const VALUE_TAG_DATE: u8 = 0x05;
+const VALUE_TAG_TIMESTAMP: u8 = 0x06;
+const VALUE_TAG_OTHER: u8 = 0xFF;
+
+/// Encodes a value as an index key whose byte order matches the `Ord` of
+/// values of the same type, as a column holds: integers big-endian with
+/// the sign bit flipped, floats with the sign bit flipped for positives and
+/// every bit flipped for negatives, and text, dates and timestamps as their
+/// UTF-8 bytes. Values without a natural order are serialized as is.
+pub fn encode_value(value: &DataValue) -> Result<Vec<u8>, ReefDBError> {
+    let mut key = Vec::with_capacity(9);
+    match value {
+        DataValue::Null => key.push(VALUE_TAG_NULL),
+        DataValue::Boolean(b) => key.extend([VALUE_TAG_BOOLEAN, *b as u8]),
+        DataValue::Integer(i) => {
+            key.push(VALUE_TAG_INTEGER);
+            key.extend(((*i as u64) ^ (1 << 63)).to_be_bytes());
+        }
+        DataValue::Float(f) => {
+            let bits = f.to_bits();
+            let ordered = if bits >> 63 == 1 { !bits } else { bits | (1 << 63) };
+            key.push(VALUE_TAG_FLOAT);
+            key.extend(ordered.to_be_bytes());
+        }
+        DataValue::Text(s) => {
+            key.push(VALUE_TAG_TEXT);
+            key.extend(s.as_bytes());
+        }
+        DataValue::Date(s) => {
+            key.push(VALUE_TAG_DATE);
+            key.extend(s.as_bytes());
+        }
+        DataValue::Timestamp(s) => {
+            key.push(VALUE_TAG_TIMESTAMP);
+            key.extend(s.as_bytes());
+        }
+        other => {
+            key.push(VALUE_TAG_OTHER);
+            key.extend(bincode::serialize(other)?);
+        }
+    }
+    Ok(key)
+}
+
 impl fmt::Display for KeyFormat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -172,4 +226,28 @@ mod tests {
         let parsed = KeyFormat::parse(meta_key).unwrap();
         assert_eq!(parsed, KeyFormat::Meta("schema_version".to_string()));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_encode_value_preserves_order() {
+        let ordered = [
+            vec![DataValue::Integer(i64::MIN), DataValue::Integer(-10), DataValue::Integer(-9),
+                 DataValue::Integer(-1), DataValue::Integer(0), DataValue::Integer(9),
+                 DataValue::Integer(10), DataValue::Integer(i64::MAX)],
+            vec![DataValue::Float(f64::NEG_INFINITY), DataValue::Float(-2.5), DataValue::Float(-0.5),
+                 DataValue::Float(0.0), DataValue::Float(0.5), DataValue::Float(10.0),
+                 DataValue::Float(f64::INFINITY)],
+            vec![DataValue::Text("".to_string()), DataValue::Text("10".to_string()),
+                 DataValue::Text("9".to_string()), DataValue::Text("a".to_string()),
+                 DataValue::Text("ab".to_string())],
+        ];
+        for values in ordered {
+            for a in &values {
+                for b in &values {
+                    let keys = (encode_value(a).unwrap(), encode_value(b).unwrap());
+                    assert_eq!(keys.0.cmp(&keys.1), a.cmp(b), "encoding of {:?} and {:?}", a, b);
+                }
+            }
+        }
+        assert!(encode_value(&DataValue::Null).unwrap() < encode_value(&DataValue::Integer(i64::MIN)).unwrap());
+    }
+}
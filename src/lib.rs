@@ -1,16 +1,19 @@
-use functions::{register_builtins, FunctionRegistry};
+use functions::{aggregate_argument, register_builtins, ts_rank, Accumulator, Aggregate, FunctionRegistry};
 use result::{QueryResult, ColumnInfo};
 use sql::column::ColumnType;
 use sql::data_type::DataType;
 
 use crate::sql::{
     clauses::{
+        full_text_search::clause::FTSClause,
         join_clause::JoinClause,
-        wheres::where_type::WhereType,
-        order_by::{OrderByClause, OrderDirection},
+        wheres::where_type::{parse_where_expression, CaseExpression, WhereType, WhereClause, SubqueryClause, InClause, InSubqueryClause},
+        order_by::{OrderByClause, OrderByTarget, OrderDirection},
     },
     column_def::ColumnDef,
+    constraints::constraint::Constraint,
     data_value::DataValue,
+    operators::op::Op,
     table_reference::TableReference,
     column::Column,
     statements::{
@@ -18,26 +21,38 @@ use crate::sql::{
         create::CreateStatement,
         drop::DropStatement,
         alter::{AlterStatement, AlterType},
-        insert::InsertStatement,
-        select::SelectStatement,
+        insert::{ConflictAction, InsertStatement, OnConflict},
+        select::{resolve_aliases, SelectStatement},
+        union::UnionStatement,
         update::UpdateStatement,
         delete::DeleteStatement,
-        create_index::CreateIndexStatement,
+        create_index::{CreateIndexStatement, IndexType as CreateIndexType},
         drop_index::DropIndexStatement,
     },
 };
 use crate::result::ReefDBResult;
+use crate::stats::{PlanStep, QueryStats};
 use crate::error::ReefDBError;
-use crate::transaction::IsolationLevel;
+use crate::transaction::{IsolationLevel, QueryLimits};
 use crate::transaction_manager::TransactionManager;
-use crate::wal::WriteAheadLog;
+use crate::wal::{replay::{self, UndoImage}, WriteAheadLog, WALEntry, WALOperation};
 use crate::mvcc::MVCCManager;
 use crate::storage::{Storage, TableStorage};
-use crate::indexes::{index_manager::IndexManager, btree::BTreeIndex, index_manager::IndexType};
+use crate::indexes::{index_manager::IndexManager, btree::BTreeIndex, gin::GinIndex, hash::HashIndex, index_manager::{IndexKind, IndexType}};
+use crate::key_format::encode_value;
 use crate::fts::search::Search;
+use crate::fts::tokenizers::default::DefaultTokenizer;
+use crate::fts::tokenizers::registry::TokenizerRegistry;
+use crate::fts::tokenizers::tokenizer::Tokenizer;
+use crate::fts::language::{LanguageConfig, EnglishProcessor};
+use crate::fts::ranking::CollectionStats;
 use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use serde::Serialize;
 
 pub mod storage;
 pub mod transaction;
@@ -55,6 +70,20 @@ pub mod locks;
 pub mod key_format;
 pub mod fts;
 pub mod functions;
+pub mod backup;
+pub mod builder;
+pub mod csv;
+pub mod row_iter;
+pub mod bulk;
+pub mod transaction_handle;
+pub mod introspection;
+pub mod server;
+pub mod replication;
+pub mod validation;
+pub mod stats;
+pub mod explain;
+pub mod autovacuum;
+pub mod migrations;
 #[cfg(test)]
 pub mod tests;
 
@@ -62,6 +91,57 @@ pub type InMemoryReefDB = ReefDB<storage::memory::InMemoryStorage, fts::default:
 pub type OnDiskReefDB = ReefDB<storage::disk::OnDiskStorage, fts::default::DefaultSearchIdx>;
 pub type MmapReefDB = ReefDB<storage::mmap::MmapStorage, fts::default::DefaultSearchIdx>;
 
+/// Rows a table needs before a full scan of it is warned about, see
+/// `ReefDB::set_warn_on_full_scan`
+pub const DEFAULT_FULL_SCAN_WARNING_ROWS: usize = 1000;
+
+/// The full-text document id of the row stored at `row_index`. Documents
+/// are numbered from 1, matching the id `Storage::push_value` returns for a
+/// newly inserted row.
+pub(crate) fn fts_document_id(row_index: usize) -> usize {
+    row_index + 1
+}
+
+/// A GIN index over the text in column `col_idx` of `rows`, with documents
+/// numbered like the inverted index's
+fn build_gin_index(table_name: &str, column_name: &str, rows: &[Vec<DataValue>], col_idx: usize) -> GinIndex<DefaultTokenizer> {
+    let mut gin = GinIndex::new();
+    gin.add_column(table_name, column_name);
+    for (i, row) in rows.iter().enumerate() {
+        if let DataValue::Text(text) = &row[col_idx] {
+            Search::add_document(&mut gin, table_name, column_name, fts_document_id(i), text);
+        }
+    }
+    gin
+}
+
+/// The tokenizer a column names in its `TOKENIZER` clause, if any
+pub(crate) fn column_tokenizer(column: &ColumnDef) -> Option<&str> {
+    column.constraints.iter().find_map(|constraint| match constraint {
+        Constraint::Tokenizer(name) => Some(name.as_str()),
+        _ => None,
+    })
+}
+
+/// A WHERE clause ready to be evaluated against the rows of one query, with
+/// the rows matching each of its full-text predicates already searched.
+/// It owns what it took from the clause, so it can live beside the clause
+/// for as long as the query does.
+pub(crate) struct WhereContext<'a> {
+    schema: &'a [ColumnDef],
+    join_schema: &'a [ColumnDef],
+    main_table: String,
+    fts_matches: Vec<(FTSClause, HashSet<usize>)>,
+}
+
+/// A CHECK constraint ready to be tested against rows, from `row_checks`
+pub(crate) struct RowCheck<'a> {
+    column_index: usize,
+    expr: &'a str,
+    predicate: WhereType,
+    context: WhereContext<'a>,
+}
+
 impl InMemoryReefDB {
     pub fn create_in_memory() -> Result<Self, ReefDBError> {
         let mut function_registry = FunctionRegistry::new();
@@ -76,7 +156,13 @@ impl InMemoryReefDB {
             autocommit_isolation_level: IsolationLevel::ReadCommitted,
             mvcc_manager: Arc::new(Mutex::new(MVCCManager::new())),
             current_transaction_id: None,
+            transaction_snapshot: None,
             function_registry: function_registry,
+            tokenizers: TokenizerRegistry::new(),
+            query_stats: RefCell::new(None),
+            warn_on_full_scan: false,
+            full_scan_warning_rows: DEFAULT_FULL_SCAN_WARNING_ROWS,
+            query_deadline: Cell::new(None),
         };
         db.transaction_manager = Some(TransactionManager::create(
             db.clone(),
@@ -89,17 +175,31 @@ impl InMemoryReefDB {
 impl OnDiskReefDB {
     pub fn create_on_disk(kv_path: String, _index_path: String) -> Result<Self, ReefDBError> {
         let mut db = ReefDB::<storage::disk::OnDiskStorage, fts::default::DefaultSearchIdx>::create_with_args(
-            storage::disk::OnDiskStorage::new(kv_path.clone()),
+            storage::disk::OnDiskStorage::open(kv_path.clone(), None)?,
             Default::default(),
         );
-        db.transaction_manager = Some(TransactionManager::create(
+        let mut transaction_manager = TransactionManager::create(
             db.clone(),
             WriteAheadLog::new(PathBuf::from(kv_path + ".wal"))?,
-        ));
+        );
+
+        // Bring storage back in line with the committed transactions in the WAL
+        if transaction_manager.recover()? > 0 {
+            db = transaction_manager.database()?;
+        }
+        db.load_tables_from_storage();
+        db.transaction_manager = Some(transaction_manager);
         Ok(db)
     }
 }
 
+impl MmapReefDB {
+    /// Sets when commits are synced to disk, see `SyncPolicy`
+    pub fn set_sync_policy(&mut self, sync_policy: storage::mmap::SyncPolicy) {
+        self.storage.set_sync_policy(sync_policy);
+    }
+}
+
 #[derive(Clone)]
 pub struct ReefDB<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone>
 where
@@ -114,7 +214,21 @@ where
     pub(crate) autocommit_isolation_level: IsolationLevel,
     pub(crate) mvcc_manager: Arc<Mutex<MVCCManager>>,
     pub(crate) current_transaction_id: Option<u64>,
+    /// Tables and full-text index as they were when the current explicit
+    /// transaction began, restored if it rolls back
+    pub(crate) transaction_snapshot: Option<Box<(TableStorage, FTS)>>,
     pub(crate) function_registry: FunctionRegistry,
+    pub(crate) tokenizers: TokenizerRegistry,
+    /// Stats of the statement `query_with_stats` is running, `None` the
+    /// rest of the time so that plain queries don't collect any
+    pub(crate) query_stats: RefCell<Option<QueryStats>>,
+    /// Whether a filtered SELECT that reads every row of a table with more
+    /// than `full_scan_warning_rows` rows logs a warning
+    pub(crate) warn_on_full_scan: bool,
+    pub(crate) full_scan_warning_rows: usize,
+    /// When the running statement started and how long it may take, set
+    /// by `query_with_timeout` or the configured `QueryLimits::timeout`
+    pub(crate) query_deadline: Cell<Option<(Instant, Duration)>>,
 }
 
 impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
@@ -134,7 +248,13 @@ where
             autocommit_isolation_level: IsolationLevel::ReadCommitted,
             mvcc_manager: Arc::new(Mutex::new(MVCCManager::new())),
             current_transaction_id: None,
+            transaction_snapshot: None,
             function_registry: function_registry,
+            tokenizers: TokenizerRegistry::new(),
+            query_stats: RefCell::new(None),
+            warn_on_full_scan: false,
+            full_scan_warning_rows: DEFAULT_FULL_SCAN_WARNING_ROWS,
+            query_deadline: Cell::new(None),
         };
 
         let transaction_manager = Some(TransactionManager::create(
@@ -145,37 +265,130 @@ where
         db
     }
 
+    /// Fills `tables` with what storage holds. An opened database only has
+    /// the tables its replayed WAL created, not those already in the data
+    /// file.
+    pub(crate) fn load_tables_from_storage(&mut self) {
+        self.tables = TableStorage { tables: self.storage.get_all_tables().clone() };
+    }
+
+    /// Records a mutation in the WAL when it runs inside a transaction.
+    fn log_wal<T: Serialize>(&self, operation: WALOperation, table_name: &str, payload: &T) -> Result<(), ReefDBError> {
+        if let (Some(tx_id), Some(tm)) = (self.current_transaction_id, &self.transaction_manager) {
+            tm.log_operation(tx_id, operation, table_name, bincode::serialize(payload)?)?;
+        }
+        Ok(())
+    }
+
+    /// Logs `table_name` as it was before the write about to be logged, so
+    /// recovery can take the write back if the transaction never finishes
+    fn log_undo(&self, table_name: &str, image: impl FnOnce(&(Vec<ColumnDef>, Vec<Vec<DataValue>>)) -> UndoImage) -> Result<(), ReefDBError> {
+        if self.current_transaction_id.is_none() {
+            return Ok(());
+        }
+        let table = self.get_table_schema(table_name)?;
+        self.log_wal(WALOperation::Undo, table_name, &image(table))
+    }
+
+    /// The rows at `row_ids`, or else those the storage layer's filter
+    /// `where_clause` selects, with their positions
+    fn rows_written(
+        (schema, rows): &(Vec<ColumnDef>, Vec<Vec<DataValue>>),
+        row_ids: Option<&[usize]>,
+        where_clause: Option<&(String, DataValue)>,
+    ) -> Vec<(usize, Vec<DataValue>)> {
+        let selected: Vec<usize> = match (row_ids, where_clause) {
+            (Some(row_ids), _) => row_ids.to_vec(),
+            (None, Some((column, value))) => match schema.iter().position(|c| &c.name == column) {
+                Some(idx) => (0..rows.len()).filter(|&i| rows[i][idx] == *value).collect(),
+                None => Vec::new(),
+            },
+            (None, None) => (0..rows.len()).collect(),
+        };
+        selected.into_iter()
+            .filter_map(|i| rows.get(i).map(|row| (i, row.clone())))
+            .collect()
+    }
+
+    /// Reapplies committed WAL operations. Tables created within the log are
+    /// rebuilt from scratch; operations on tables that predate the log are
+    /// skipped because storage already holds them, see `undo_wal_entries`
+    /// for those of unfinished transactions.
+    pub(crate) fn replay_wal_entries(&mut self, entries: &[WALEntry]) -> Result<(), ReefDBError> {
+        let rebuilt = replay::logged_tables(entries);
+
+        for table_name in &rebuilt {
+            self.storage.remove_table(table_name);
+            self.tables.remove_table(table_name);
+        }
 
-    fn verify_table_exists(&self, table_name: &str) -> Result<(), ReefDBError> {
+        for entry in entries.iter().filter(|entry| rebuilt.contains(&entry.table_name)) {
+            replay::apply_entry(&mut self.storage, entry)?;
+            replay::apply_entry(&mut self.tables, entry)?;
+        }
+        self.reindex_recovered_tables(&rebuilt.into_iter().collect::<Vec<_>>())
+    }
+
+    /// Takes back what transactions that never finished wrote before a
+    /// crash, including to tables that predate the log
+    pub(crate) fn undo_wal_entries(&mut self, entries: &[WALEntry]) -> Result<(), ReefDBError> {
+        replay::undo_entries(&mut self.storage, entries)?;
+        replay::undo_entries(&mut self.tables, entries)?;
+
+        let touched: HashSet<String> = entries.iter()
+            .map(|entry| entry.table_name.clone())
+            .filter(|table_name| self.storage.table_exists(table_name))
+            .collect();
+        self.reindex_recovered_tables(&touched.into_iter().collect::<Vec<_>>())
+    }
+
+    /// Rebuilds the value and full-text indexes of tables recovery changed
+    fn reindex_recovered_tables(&mut self, table_names: &[String]) -> Result<(), ReefDBError> {
+        self.rebuild_value_indexes(table_names)?;
+
+        for table_name in table_names {
+            let fts_columns = match self.storage.get_table_ref(table_name) {
+                Some((columns, _)) => columns.iter()
+                    .filter(|c| c.data_type == DataType::TSVector)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                None => continue,
+            };
+            for column in fts_columns {
+                self.add_fts_column(table_name, &column);
+                self.reindex_fts_column(table_name, &column.name)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn verify_table_exists(&self, table_name: &str) -> Result<(), ReefDBError> {
         if !self.storage.table_exists(table_name) {
             return Err(ReefDBError::TableNotFound(table_name.to_string()));
         }
         Ok(())
     }
 
-    fn get_table_schema(&self, table_name: &str) -> Result<&(Vec<ColumnDef>, Vec<Vec<DataValue>>), ReefDBError> {
+    pub(crate) fn get_table_schema(&self, table_name: &str) -> Result<&(Vec<ColumnDef>, Vec<Vec<DataValue>>), ReefDBError> {
         self.storage.get_table_ref(table_name)
             .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))
     }
 
     fn handle_create(&mut self, name: String, columns: Vec<ColumnDef>) -> Result<ReefDBResult, ReefDBError> {
-        if columns.is_empty() {
-            return Err(ReefDBError::Other("Cannot create table with empty column list".to_string()));
-        }
-        
-        // Check if table exists in either storage or tables
-        if self.storage.table_exists(&name) || self.tables.table_exists(&name) {
-            return Err(ReefDBError::Other(format!("Table {} already exists", name)));
-        }
-        
+        self.validate_create(&name, &columns)?;
+
         // Create table in both storage and tables
         self.storage.insert_table(name.clone(), columns.clone(), vec![]);
         self.tables.insert_table(name.clone(), columns.clone(), vec![]);
+        self.log_wal(WALOperation::CreateTable, &name, &(&columns, Vec::<Vec<DataValue>>::new()))?;
 
-        // Register FTS columns with the inverted index
+        // Register FTS columns with the inverted index and give each a GIN
+        // index the planner and VERIFY INDEX can see
         for column in columns.iter() {
             if column.data_type == DataType::TSVector {
-                self.inverted_index.add_column(&name, &column.name);
+                self.add_fts_column(&name, column);
+                self.rebuild_gin_index(&name, &column.name)?;
             }
         }
 
@@ -188,42 +401,25 @@ where
     }
 
     fn handle_insert(&mut self, table_name: String, values: Vec<DataValue>) -> Result<ReefDBResult, ReefDBError> {
-        // First, collect all the information we need
-        let schema = {
-            let (schema, _) = self.get_table_schema(&table_name)?;
-            schema.clone()
-        };
-
-        // Validate number of values matches number of columns
-        if values.len() != schema.len() {
-            return Err(ReefDBError::Other(format!(
-                "Number of values ({}) does not match number of columns ({})",
-                values.len(),
-                schema.len()
-            )));
-        }
-
-        // Validate value types match column types
-        for (value, column) in values.iter().zip(schema.iter()) {
-            if !value.matches_type(&column.data_type) {
-                return Err(ReefDBError::Other(format!(
-                    "Value type mismatch for column {}: expected {:?}, got {:?}",
-                    column.name,
-                    column.data_type,
-                    value
-                )));
-            }
+        let values = self.coerce_insert(&table_name, values);
+        let schema = self.validate_insert(&table_name, &values)?;
+        // Both copies must take the row, so check before writing either
+        if !self.tables.table_exists(&table_name) {
+            return Err(ReefDBError::TableNotFound(table_name));
         }
 
         // Insert the values into both storage and tables
         let row_id = self.storage.push_value(&table_name, values.clone())?;
         self.tables.push_value(&table_name, values.clone())?;
+        self.log_wal(WALOperation::Insert, &table_name, &values)?;
+        self.index_row(&table_name, row_id, &values)?;
 
         // Update FTS index for any FTS columns
         for (i, col) in schema.iter().enumerate() {
             if col.data_type == DataType::TSVector {
                 if let DataValue::Text(text) = &values[i] {
                     self.inverted_index.add_document(&table_name, &col.name, row_id, text);
+                    self.add_gin_document(&table_name, &col.name, row_id, text)?;
                 }
             }
         }
@@ -231,15 +427,59 @@ where
         Ok(ReefDBResult::Insert(row_id))
     }
 
-    fn handle_select(
+    /// Turns an `INSERT ... ON CONFLICT` into the statement it amounts to:
+    /// the plain insert, or an update of the conflicting row. `None` means
+    /// the row conflicts and the insert is skipped.
+    pub(crate) fn resolve_upsert(
+        &self,
+        table_name: String,
+        values: Vec<DataValue>,
+        on_conflict: OnConflict,
+    ) -> Result<Option<Statement>, ReefDBError> {
+        let (schema, rows) = self.get_table_schema(&table_name)?;
+        let is_key = |column: &ColumnDef| column.constraints.iter()
+            .any(|c| matches!(c, Constraint::Unique | Constraint::PrimaryKey));
+
+        let key_columns: Vec<usize> = match &on_conflict.target {
+            Some(target) => {
+                let idx = schema.iter()
+                    .position(|c| &c.name == target)
+                    .ok_or_else(|| ReefDBError::ColumnNotFound(target.clone()))?;
+                if !is_key(&schema[idx]) {
+                    return Err(ReefDBError::Other(format!(
+                        "ON CONFLICT column {} has no UNIQUE or PRIMARY KEY constraint", target
+                    )));
+                }
+                vec![idx]
+            }
+            None => (0..schema.len()).filter(|&idx| is_key(&schema[idx])).collect(),
+        };
+
+        let conflict = key_columns.into_iter().find(|&idx| {
+            values.get(idx).is_some_and(|value| rows.iter().any(|row| row[idx] == *value))
+        });
+
+        Ok(match (conflict, on_conflict.action) {
+            (None, _) => Some(Statement::Insert(InsertStatement::IntoTable(table_name, values))),
+            (Some(_), ConflictAction::Nothing) => None,
+            (Some(idx), ConflictAction::Update(updates)) => {
+                let key = WhereClause::new(schema[idx].name.clone(), Op::Equal, values[idx].clone(), None);
+                Some(Statement::Update(UpdateStatement::UpdateTable(table_name, updates, Some(WhereType::Regular(key)), None)))
+            }
+        })
+    }
+
+    pub(crate) fn handle_select(
         &self,
         table_ref: TableReference,
-        columns: Vec<Column>,
-        where_clause: Option<WhereType>,
-        joins: Vec<JoinClause>,
-        order_by: Vec<OrderByClause>,
+        mut columns: Vec<Column>,
+        mut where_clause: Option<WhereType>,
+        mut joins: Vec<JoinClause>,
+        mut order_by: Vec<OrderByClause>,
+        mut group_by: Vec<Column>,
     ) -> Result<ReefDBResult, ReefDBError> {
         self.verify_table_exists(&table_ref.name)?;
+        resolve_aliases(&table_ref, &mut joins, &mut columns, where_clause.as_mut(), &mut order_by, &mut group_by);
         let (schema, data) = self.get_table_schema(&table_ref.name)?;
         
         let mut result = Vec::new();
@@ -253,43 +493,166 @@ where
             }
         }
 
-        // Handle joins if present
-        if !joins.is_empty() {
-            self.handle_join_select(&table_ref.name, schema, data, &columns, where_clause, &joins, &mut result)?;
+        // Aggregates and groups fold whole rows, so those are scanned unprojected
+        let aggregate = !group_by.is_empty() || columns.iter().any(|c| Aggregate::of_column(c).is_some());
+        let mut scan_columns = if aggregate {
+            vec![Column { table: None, name: "*".to_string(), column_type: ColumnType::Wildcard }]
+        } else {
+            columns.clone()
+        };
+
+        let mut column_info = if joins.is_empty() {
+            ColumnInfo::from_schema_and_columns(schema, &columns, &table_ref.name)?
+        } else {
+            ColumnInfo::from_joined_schemas(schema, &table_ref.name, &joined_schemas, &columns)?
+        };
+
+        // A join has no single source row to read other columns from, so
+        // ORDER BY columns the select list leaves out are scanned as extra
+        // trailing columns and dropped once the rows are sorted
+        let output_len = column_info.len();
+        if !aggregate && !joins.is_empty() {
+            let hidden: Vec<Column> = order_by.iter()
+                .filter_map(|clause| clause.column())
+                .filter(|column| Self::output_column(&column_info, column).is_none())
+                .cloned()
+                .collect();
+            column_info.extend(ColumnInfo::from_joined_schemas(schema, &table_ref.name, &joined_schemas, &hidden)?);
+            scan_columns.extend(hidden);
+        }
+
+        let count_all = explain::counts_all_rows(&columns, where_clause.as_ref(), &joins, &group_by);
+        if count_all {
+            // Every row counts, so there is no need to read any of them
+            let started = self.step_timer();
+            result.push((0, vec![DataValue::Integer(data.len() as i64)]));
+            self.record_step(started, || explain::count_step(&table_ref.name), 0, 1);
+        } else if !joins.is_empty() {
+            self.handle_join_select(&table_ref.name, schema, data, &scan_columns, where_clause, &joins, &mut result)?;
         } else {
-            self.handle_simple_select(&table_ref.name, schema, data, &columns, where_clause, &mut result)?;
+            self.handle_simple_select(&table_ref.name, schema, data, &scan_columns, where_clause, &mut result)?;
+        }
+
+        if aggregate && !count_all {
+            let started = self.step_timer();
+            // Every scanned column with the table it comes from, in row order
+            let scanned_schema: Vec<(&str, &ColumnDef)> = schema.iter()
+                .map(|c| (table_ref.name.as_str(), c))
+                .chain(joined_schemas.iter().flat_map(|(table, s)| s.iter().map(move |c| (*table, c))))
+                .collect();
+            let rows_in = result.len();
+            result = self.group_rows(&result, &scanned_schema, &columns, &group_by)?;
+            self.record_step(started, || explain::aggregate_step(&group_by), rows_in, result.len());
         }
 
         // Apply ordering if present
         if !order_by.is_empty() {
-            result.sort_by(|(_, row1), (_, row2)| {
-                for order_clause in &order_by {
-                    let col_name = &order_clause.column.name;
-                    let col_idx = schema.iter().position(|col| col.name == *col_name)
-                        .expect("Column not found in schema");
-                    
-                    let cmp = row1[col_idx].partial_cmp(&row2[col_idx])
-                        .unwrap_or(std::cmp::Ordering::Equal);
-                    
-                    match order_clause.direction {
-                        OrderDirection::Asc => if cmp != std::cmp::Ordering::Equal { return cmp; },
-                        OrderDirection::Desc => if cmp != std::cmp::Ordering::Equal { return cmp.reverse(); },
-                    }
-                }
-                std::cmp::Ordering::Equal
-            });
+            let started = self.step_timer();
+            Self::order_rows(&mut result, &order_by, &column_info, schema, data)?;
+            self.record_step(started, || "Sort".to_string(), result.len(), result.len());
+        }
+        if column_info.len() > output_len {
+            column_info.truncate(output_len);
+            for (_, row) in &mut result {
+                row.truncate(output_len);
+            }
         }
 
-        // Create column info
-        let column_info = if joins.is_empty() {
-            ColumnInfo::from_schema_and_columns(schema, &columns, &table_ref.name)?
-        } else {
-            ColumnInfo::from_joined_schemas(schema, &table_ref.name, &joined_schemas, &columns)?
-        };
-        
         Ok(ReefDBResult::Select(QueryResult::with_columns(result, column_info)))
     }
 
+    pub(crate) fn handle_union(&self, union: UnionStatement) -> Result<ReefDBResult, ReefDBError> {
+        let mut results = Vec::with_capacity(2);
+        for select in [union.left, union.right] {
+            let SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by) = select;
+            match self.handle_select(table_ref, columns, where_clause, joins, order_by, group_by)? {
+                ReefDBResult::Select(result) => results.push(result),
+                _ => unreachable!("handle_select always returns a Select result"),
+            }
+        }
+        let right = results.pop().unwrap();
+        let left = results.pop().unwrap();
+        Ok(ReefDBResult::Select(left.union(right, union.all)?))
+    }
+
+    /// Sorts projected rows by the ORDER BY clauses. A clause refers to an
+    /// output column by position, name or alias; failing that, it falls back
+    /// to a column of the base table, read from the source row each result
+    /// row came from. Ties are broken by that source row, so they stay in
+    /// stored order.
+    fn order_rows(
+        result: &mut [(usize, Vec<DataValue>)],
+        order_by: &[OrderByClause],
+        column_info: &[ColumnInfo],
+        schema: &[ColumnDef],
+        data: &[Vec<DataValue>],
+    ) -> Result<(), ReefDBError> {
+        use std::cmp::Ordering;
+
+        enum SortKey {
+            Output(usize),
+            Source(usize),
+        }
+
+        let keys = order_by.iter().map(|clause| {
+            let column = match &clause.target {
+                OrderByTarget::Column(column) => column,
+                OrderByTarget::Position(position) => {
+                    if *position == 0 || *position > column_info.len() {
+                        return Err(ReefDBError::Other(format!("ORDER BY position {} is not in select list", position)));
+                    }
+                    return Ok((SortKey::Output(position - 1), clause));
+                }
+            };
+            let key = match Self::output_column(column_info, column) {
+                Some(idx) => SortKey::Output(idx),
+                None => SortKey::Source(schema.iter().position(|c| c.name == column.name)
+                    .ok_or_else(|| ReefDBError::ColumnNotFound(column.name.clone()))?),
+            };
+            Ok((key, clause))
+        }).collect::<Result<Vec<_>, ReefDBError>>()?;
+
+        let mut error = None;
+        result.sort_by(|(src1, row1), (src2, row2)| {
+            for (key, clause) in &keys {
+                let (a, b) = match key {
+                    SortKey::Output(idx) => (&row1[*idx], &row2[*idx]),
+                    SortKey::Source(idx) => (&data[*src1][*idx], &data[*src2][*idx]),
+                };
+                // NULL placement does not flip with the direction
+                let nulls_first = clause.nulls_first();
+                let cmp = match (a, b) {
+                    (DataValue::Null, DataValue::Null) => continue,
+                    (DataValue::Null, _) => return if nulls_first { Ordering::Less } else { Ordering::Greater },
+                    (_, DataValue::Null) => return if nulls_first { Ordering::Greater } else { Ordering::Less },
+                    _ => match clause.collation().compare(a, b) {
+                        Ok(cmp) => cmp,
+                        Err(e) => {
+                            error.get_or_insert(e);
+                            Ordering::Equal
+                        }
+                    },
+                };
+                if cmp != Ordering::Equal {
+                    return match clause.direction {
+                        OrderDirection::Asc => cmp,
+                        OrderDirection::Desc => cmp.reverse(),
+                    };
+                }
+            }
+            src1.cmp(src2)
+        });
+        error.map_or(Ok(()), Err)
+    }
+
+    /// The output column an ORDER BY column names, if it is selected
+    fn output_column(column_info: &[ColumnInfo], column: &Column) -> Option<usize> {
+        column_info.iter().position(|info| {
+            info.name == column.name
+                && column.table.as_ref().is_none_or(|table| info.table.as_ref() == Some(table))
+        })
+    }
+
     fn handle_simple_select(
         &self,
         table_name: &str,
@@ -299,40 +662,216 @@ where
         where_clause: Option<WhereType>,
         result: &mut Vec<(usize, Vec<DataValue>)>,
     ) -> Result<(), ReefDBError> {
-        for (i, row) in data.iter().enumerate() {
-            let include_row = if let Some(where_clause) = &where_clause {
-                self.evaluate_where_clause(where_clause, row, &[], schema, &[], table_name)?
+        let started = self.step_timer();
+        let limits = self.get_query_limits();
+        let where_context = where_clause.as_ref()
+            .map(|where_clause| (where_clause, self.where_context(where_clause, schema, &[], table_name)));
+
+        // A full-text predicate every matching row has to satisfy narrows
+        // the scan to the rows the inverted index found, unless those are
+        // so many that reading the table in order is cheaper
+        // An `=` predicate on a column with a BTree or hash index does the
+        // same through that index
+        let table_rows = self.storage.row_count(table_name).unwrap_or(data.len());
+        let indexed = where_context.as_ref()
+            .and_then(|(where_clause, context)| Self::required_fts_matches(where_clause, context, table_name))
+            .filter(|(_, matches)| explain::index_pays_off(matches.len(), table_rows))
+            .map(|(clause, matches)| {
+                let mut row_indexes: Vec<usize> = matches.iter()
+                    .map(|document_id| document_id - 1)
+                    .collect();
+                row_indexes.sort_unstable();
+                (clause.column.name.clone(), IndexKind::GIN, row_indexes)
+            })
+            .or_else(|| where_clause.as_ref()
+                .and_then(|where_clause| self.equality_index_matches(where_clause, table_name))
+                .filter(|(_, _, row_indexes)| explain::index_pays_off(row_indexes.len(), table_rows)));
+        let (index, row_indexes): (Option<(String, IndexKind)>, Vec<usize>) = match indexed {
+            Some((column_name, kind, row_indexes)) => (
+                Some((format!("{}.{}", table_name, column_name), kind)),
+                row_indexes.into_iter().filter(|&i| i < data.len()).collect(),
+            ),
+            None => (None, (0..data.len()).collect()),
+        };
+        limits.check_rows_scanned(row_indexes.len())?;
+        let rows_read = row_indexes.len();
+        if let (None, Some(where_clause)) = (&index, &where_clause) {
+            if self.warn_on_full_scan && rows_read > self.full_scan_warning_rows {
+                log::warn!("Full scan of {} rows on table {} for predicate {:?}", rows_read, table_name, where_clause);
+            }
+        }
+        let rows_before = result.len();
+
+        for i in row_indexes {
+            self.check_deadline()?;
+            let row = &data[i];
+            let include_row = if let Some((where_clause, context)) = &where_context {
+                self.evaluate_where(where_clause, i, row, 0, &[], context)?
             } else {
                 true
             };
 
             if include_row {
-                let mut selected_values = Vec::new();
-                for col in columns {
-                    if col.name == "*" {
+                result.push((i, self.project_row(i, row, schema, table_name, columns)?));
+                limits.check_result_rows(result.len())?;
+            }
+        }
+
+        let rows_kept = result.len() - rows_before;
+        self.record_step(started, || explain::scan_step(table_name, index.as_ref().map(|(index, kind)| (index.as_str(), *kind))), rows_read, rows_kept);
+        self.with_stats(|stats| {
+            stats.rows_scanned += rows_read;
+            if stats.index_used.is_none() {
+                stats.index_used = index.map(|(index, _)| index);
+            }
+        });
+        Ok(())
+    }
+
+    /// Folds the rows matched by an aggregate query into one result row
+    /// per distinct value of the GROUP BY columns, in the order the groups
+    /// first appear. Without GROUP BY all rows form a single group, which
+    /// exists even when no row matched. A group keeps the source row of its
+    /// first row for ORDER BY to fall back on.
+    fn group_rows(
+        &self,
+        rows: &[(usize, Vec<DataValue>)],
+        scanned_schema: &[(&str, &ColumnDef)],
+        columns: &[Column],
+        group_by: &[Column],
+    ) -> Result<Vec<(usize, Vec<DataValue>)>, ReefDBError> {
+        let key_indexes = group_by.iter()
+            .map(|column| Self::scanned_column(scanned_schema, column.table.as_deref(), &column.name)
+                .ok_or_else(|| ReefDBError::ColumnNotFound(column.name.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut groups: Vec<(usize, Vec<&[DataValue]>)> = Vec::new();
+        let mut positions: HashMap<Vec<Vec<u8>>, usize> = HashMap::new();
+        for (source, row) in rows {
+            let key = key_indexes.iter().map(|&i| encode_value(&row[i])).collect::<Result<Vec<_>, _>>()?;
+            let position = *positions.entry(key).or_insert_with(|| {
+                groups.push((*source, Vec::new()));
+                groups.len() - 1
+            });
+            groups[position].1.push(row);
+        }
+        if group_by.is_empty() && groups.is_empty() {
+            groups.push((0, Vec::new()));
+        }
+
+        let schema: Vec<ColumnDef> = scanned_schema.iter().map(|(_, c)| (*c).clone()).collect();
+        groups.into_iter().map(|(source, group)| {
+            let values = columns.iter().map(|col| {
+                let grouped = group_by.iter().position(|g| {
+                    matches!(col.column_type, ColumnType::Regular(_))
+                        && g.name == col.name
+                        && (g.table.is_none() || col.table.is_none() || g.table == col.table)
+                });
+                match grouped {
+                    // Every row of the group holds the same key
+                    Some(position) => Ok(group[0][key_indexes[position]].clone()),
+                    None => self.aggregate_column(col, &group, scanned_schema, &schema),
+                }
+            }).collect::<Result<Vec<_>, _>>()?;
+            Ok((source, values))
+        }).collect()
+    }
+
+    /// Position of a possibly table-qualified column among the scanned ones.
+    /// An unqualified name refers to the first table that has it.
+    fn scanned_column(scanned_schema: &[(&str, &ColumnDef)], table: Option<&str>, name: &str) -> Option<usize> {
+        scanned_schema.iter()
+            .position(|(t, c)| c.name == name && table.is_none_or(|table| table == *t))
+    }
+
+    /// Folds one group's rows into the value of an aggregate column
+    fn aggregate_column(
+        &self,
+        col: &Column,
+        rows: &[&[DataValue]],
+        scanned_schema: &[(&str, &ColumnDef)],
+        schema: &[ColumnDef],
+    ) -> Result<DataValue, ReefDBError> {
+        let (aggregate, args) = match (Aggregate::of_column(col), &col.column_type) {
+            (Some(aggregate), ColumnType::Function(_, args)) => (aggregate, args),
+            _ => return Err(ReefDBError::Other(format!(
+                "Column {} must appear in GROUP BY or be used in an aggregate function", col.name
+            ))),
+        };
+        let (arg, distinct) = aggregate_argument(args).ok_or_else(|| ReefDBError::ArityMismatch(format!(
+            "Aggregate {} expects a single argument", col.name
+        )))?;
+        let count_rows = match arg {
+            DataValue::Text(arg) if arg == "*" => if aggregate == Aggregate::Count && !distinct {
+                true
+            } else {
+                return Err(ReefDBError::Other(format!("{} does not accept *", col.name)));
+            },
+            _ => false,
+        };
+
+        // A qualified argument names the table its column is read from
+        let arg_column = match arg {
+            DataValue::Text(arg) => match arg.split_once('.') {
+                Some((table, name)) => Self::scanned_column(scanned_schema, Some(table), name),
+                None => Self::scanned_column(scanned_schema, None, arg),
+            },
+            _ => None,
+        };
+
+        let mut accumulator = Accumulator::new(aggregate);
+        let mut seen = HashSet::new();
+        for row in rows {
+            if count_rows {
+                accumulator.update(None)?;
+            } else {
+                let value = match arg_column {
+                    Some(idx) => row[idx].clone(),
+                    None => self.function_registry.evaluate_arg(arg, row, schema)?,
+                };
+                // DISTINCT folds each value once
+                if distinct && !seen.insert(encode_value(&value)?) {
+                    continue;
+                }
+                accumulator.update(Some(&value))?;
+            }
+        }
+        Ok(accumulator.finish())
+    }
+
+    /// Evaluates the selected columns against the row of `table_name`
+    /// stored at `row_index`
+    fn project_row(
+        &self,
+        row_index: usize,
+        row: &[DataValue],
+        schema: &[ColumnDef],
+        table_name: &str,
+        columns: &[Column],
+    ) -> Result<Vec<DataValue>, ReefDBError> {
+        let mut selected_values = Vec::new();
+        for col in columns {
+            if col.name == "*" {
+                selected_values.extend(row.iter().cloned());
+            } else {
+                match &col.column_type {
+                    ColumnType::Regular(_) => {
+                        let col_idx = schema.iter()
+                            .position(|c| c.name == col.name)
+                            .ok_or_else(|| ReefDBError::ColumnNotFound(col.name.clone()))?;
+                        selected_values.push(row[col_idx].clone());
+                    }
+                    ColumnType::Function(_, _) | ColumnType::Case(_) => {
+                        let value = self.evaluate_column(col, row_index, row, schema, table_name)?;
+                        selected_values.push(value);
+                    }
+                    ColumnType::Wildcard => {
                         selected_values.extend(row.iter().cloned());
-                    } else {
-                        match &col.column_type {
-                            ColumnType::Regular(_) => {
-                                let col_idx = schema.iter()
-                                    .position(|c| c.name == col.name)
-                                    .ok_or_else(|| ReefDBError::ColumnNotFound(col.name.clone()))?;
-                                selected_values.push(row[col_idx].clone());
-                            }
-                            ColumnType::Function(name, args) => {
-                                let value = self.evaluate_column(col, row, schema)?;
-                                selected_values.push(value);
-                            }
-                            ColumnType::Wildcard => {
-                                selected_values.extend(row.iter().cloned());
-                            }
-                        }
                     }
                 }
-                result.push((i, selected_values));
             }
         }
-        Ok(())
+        Ok(selected_values)
     }
 
     fn handle_join_select(
@@ -345,20 +884,39 @@ where
         joins: &[JoinClause],
         result: &mut Vec<(usize, Vec<DataValue>)>,
     ) -> Result<(), ReefDBError> {
+        let limits = self.get_query_limits();
+        let mut rows_scanned = 0;
+        let mut join_rows = 0;
+        self.record_step(self.step_timer(), || explain::scan_step(table_name, None), data.len(), data.len());
         for join in joins {
+            let started = self.step_timer();
+            let (scanned_before, join_rows_before, result_before) = (rows_scanned, join_rows, result.len());
             if let Some((join_schema, join_data)) = self.storage.get_table_ref(&join.table_ref.name) {
-                let left_col_idx = schema.iter()
-                    .position(|c| c.name == join.on.0.column_name)
-                    .ok_or_else(|| ReefDBError::ColumnNotFound(join.on.0.column_name.clone()))?;
-                let right_col_idx = join_schema.iter()
-                    .position(|c| c.name == join.on.1.column_name)
-                    .ok_or_else(|| ReefDBError::ColumnNotFound(join.on.1.column_name.clone()))?;
+                // Cross joins have no condition and pair every row with every row
+                let on_indexes = match &join.on {
+                    Some((left, right)) => Some((
+                        schema.iter()
+                            .position(|c| c.name == left.column_name)
+                            .ok_or_else(|| ReefDBError::ColumnNotFound(left.column_name.clone()))?,
+                        join_schema.iter()
+                            .position(|c| c.name == right.column_name)
+                            .ok_or_else(|| ReefDBError::ColumnNotFound(right.column_name.clone()))?,
+                    )),
+                    None => None,
+                };
+                let where_context = where_clause.as_ref()
+                    .map(|where_clause| (where_clause, self.where_context(where_clause, schema, join_schema, table_name)));
 
                 for (i, row) in data.iter().enumerate() {
-                    for join_row in join_data.iter() {
-                        if row[left_col_idx] == join_row[right_col_idx] {
-                            let include_row = if let Some(where_clause) = &where_clause {
-                                self.evaluate_where_clause(where_clause, row, join_row, schema, join_schema, table_name)?
+                    rows_scanned += 1 + join_data.len();
+                    limits.check_rows_scanned(rows_scanned)?;
+                    for (j, join_row) in join_data.iter().enumerate() {
+                        self.check_deadline()?;
+                        if on_indexes.is_none_or(|(left_col_idx, right_col_idx)| row[left_col_idx] == join_row[right_col_idx]) {
+                            join_rows += 1;
+                            limits.check_join_rows(join_rows)?;
+                            let include_row = if let Some((where_clause, context)) = &where_context {
+                                self.evaluate_where(where_clause, i, row, j, join_row, context)?
                             } else {
                                 true
                             };
@@ -397,17 +955,155 @@ where
                                     }
                                 }
                                 result.push((i, selected_values));
+                                limits.check_result_rows(result.len())?;
                             }
                         }
                     }
                 }
             }
+            self.record_step(started, || explain::join_step(join), rows_scanned - scanned_before, join_rows - join_rows_before);
+            if where_clause.is_some() {
+                self.record_step(None, || "Filter".to_string(), join_rows - join_rows_before, result.len() - result_before);
+            }
         }
+        self.with_stats(|stats| {
+            stats.rows_scanned += rows_scanned;
+            stats.join_rows += join_rows;
+        });
         Ok(())
     }
 
-    
-    fn evaluate_column(&self, column: &Column, row: &[DataValue], schema: &[ColumnDef]) -> Result<DataValue, ReefDBError> {
+    /// A full-text predicate on `table_name` that `where_clause` can only
+    /// be true with: the clause itself, or one arm of an AND
+    pub(crate) fn required_fts_clause<'a>(where_clause: &'a WhereType, table_name: &str) -> Option<&'a FTSClause> {
+        match where_clause {
+            WhereType::FTS(clause) if clause.column.table.as_deref().is_none_or(|table| table == table_name) => Some(clause),
+            WhereType::And(left, right) => Self::required_fts_clause(left, table_name)
+                .or_else(|| Self::required_fts_clause(right, table_name)),
+            _ => None,
+        }
+    }
+
+    /// The `=` predicates on `table_name` every matching row has to satisfy
+    fn required_equality_clauses<'a>(where_clause: &'a WhereType, table_name: &str, clauses: &mut Vec<&'a WhereClause>) {
+        match where_clause {
+            WhereType::Regular(clause)
+                if clause.operator == Op::Equal && clause.table.as_deref().is_none_or(|table| table == table_name) =>
+            {
+                clauses.push(clause)
+            }
+            WhereType::And(left, right) => {
+                Self::required_equality_clauses(left, table_name, clauses);
+                Self::required_equality_clauses(right, table_name, clauses);
+            }
+            _ => {}
+        }
+    }
+
+    /// The positions of the rows a BTree or hash index finds for one of
+    /// the `=` predicates every matching row of `table_name` has to
+    /// satisfy, along with the indexed column. A hash index is preferred.
+    /// Only values of the column's own type are looked up, as the index
+    /// would miss rows the comparison converts.
+    pub(crate) fn equality_index_matches(&self, where_clause: &WhereType, table_name: &str) -> Option<(String, IndexKind, Vec<usize>)> {
+        let schema = self.storage.get_schema_ref(table_name)?;
+        let mut clauses = Vec::new();
+        Self::required_equality_clauses(where_clause, table_name, &mut clauses);
+        let (clause, kind, key) = clauses.into_iter()
+            .filter_map(|clause| {
+                let column = schema.iter().find(|c| c.name == clause.col_name)?;
+                let value = clause.value.clone().coerce_to(&column.data_type);
+                if matches!(value, DataValue::Null | DataValue::Function { .. }) || !value.matches_type(&column.data_type) {
+                    return None;
+                }
+                let kind = self.storage.get_index(table_name, &column.name).ok()?.kind();
+                Some((clause, kind, encode_value(&value).ok()?))
+            })
+            .filter(|(_, kind, _)| *kind != IndexKind::GIN)
+            .min_by_key(|(_, kind, _)| *kind != IndexKind::Hash)?;
+
+        let found = match self.storage.get_index(table_name, &clause.col_name).ok()? {
+            IndexType::Hash(hash) => hash.search(key),
+            IndexType::BTree(btree) => btree.search(key),
+            IndexType::GIN(_) => return None,
+        };
+        // Index entries hold row ids, one past the row's position
+        let mut row_indexes: Vec<usize> = found.into_iter().flatten().map(|row_id| row_id - 1).collect();
+        row_indexes.sort_unstable();
+        Some((clause.col_name.clone(), kind, row_indexes))
+    }
+
+    /// The rows the inverted index found for `required_fts_clause`, along
+    /// with the clause
+    fn required_fts_matches<'a>(
+        where_clause: &WhereType,
+        context: &'a WhereContext,
+        table_name: &str,
+    ) -> Option<(&'a FTSClause, &'a HashSet<usize>)> {
+        let clause = Self::required_fts_clause(where_clause, table_name)?;
+        context.fts_matches.iter()
+            .find(|(searched, _)| searched == clause)
+            .map(|(searched, matches)| (searched, matches))
+    }
+
+    /// Updates the stats of the running `query_with_stats`, if any
+    fn with_stats(&self, f: impl FnOnce(&mut QueryStats)) {
+        if let Some(stats) = self.query_stats.borrow_mut().as_mut() {
+            f(stats);
+        }
+    }
+
+    /// Fails with `QueryTimeout` once the running statement is past its
+    /// timeout. Called between rows, so a query overruns by one row at most.
+    pub(crate) fn check_deadline(&self) -> Result<(), ReefDBError> {
+        match self.query_deadline.get() {
+            Some((started, timeout)) if started.elapsed() > timeout => Err(ReefDBError::QueryTimeout(format!(
+                "query ran for more than {:?}", timeout
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// The time a step starts, taken only while stats are being collected
+    fn step_timer(&self) -> Option<Instant> {
+        self.query_stats.borrow().as_ref().map(|_| Instant::now())
+    }
+
+    /// Records a finished step in the running `query_with_stats`. Steps
+    /// that ran as part of another one have no `started` time of their own.
+    fn record_step(&self, started: Option<Instant>, description: impl FnOnce() -> String, rows_in: usize, rows_out: usize) {
+        self.with_stats(|stats| stats.steps.push(PlanStep {
+            description: description(),
+            rows_in,
+            rows_out,
+            duration: started.map_or(Duration::ZERO, |started| started.elapsed()),
+        }));
+    }
+
+    /// The statistics of the column the vector `ts_rank` ranks is built
+    /// from, as of the last `refresh_fts_stats`
+    fn ranked_column_stats(&self, args: &[DataValue], schema: &[ColumnDef], table_name: &str) -> Option<&CollectionStats> {
+        let mut vector = args.first()?;
+        // Through to_tsvector, setweight and the like to the column itself
+        while let DataValue::Function { args, .. } = vector {
+            vector = args.first()?;
+        }
+        match vector {
+            DataValue::Text(column) if schema.iter().any(|c| &c.name == column) => {
+                self.inverted_index.collection_stats(table_name, column)
+            }
+            _ => None,
+        }
+    }
+
+    fn evaluate_column(
+        &self,
+        column: &Column,
+        row_index: usize,
+        row: &[DataValue],
+        schema: &[ColumnDef],
+        table_name: &str,
+    ) -> Result<DataValue, ReefDBError> {
         match &column.column_type {
             ColumnType::Regular(name) => {
                 if let Some(idx) = schema.iter().position(|c| c.name == *name) {
@@ -418,119 +1114,236 @@ where
             }
             ColumnType::Function(name, args) => {
                 // Evaluate function arguments
-                let mut evaluated_args = Vec::new();
-                for arg in args {
-                    let arg_value = match arg {
-                        DataValue::Text(s) => Ok(DataValue::Text(s.clone())),
-                        DataValue::Function { name, args } => self.function_registry.call(name, args.clone()),
-                        _ => Ok(arg.clone()),
-                    }?;
-                    evaluated_args.push(arg_value);
+                let evaluated_args = args.iter()
+                    .map(|arg| self.function_registry.evaluate_arg(arg, row, schema))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // Ranking a full-text column weighs terms by the column's statistics
+                if name == "ts_rank" {
+                    if let Some(stats) = self.ranked_column_stats(args, schema, table_name) {
+                        return ts_rank(&evaluated_args, Some(stats));
+                    }
                 }
-                
+
                 // Call function
                 self.function_registry.call(name, evaluated_args)
             }
+            ColumnType::Case(case) => {
+                let mut fts_matches = Vec::new();
+                for (predicate, _) in &case.branches {
+                    self.collect_fts_matches(predicate, table_name, &mut fts_matches);
+                }
+                let context = WhereContext { schema, join_schema: &[], main_table: table_name.to_string(), fts_matches };
+                self.case_value(case, row_index, row, 0, &[], &context)
+            }
             ColumnType::Wildcard => {
                 Err(ReefDBError::Other("Cannot evaluate wildcard in expression".to_string()))
             }
         }
     }
 
-    fn evaluate_where_clause(
+    /// Locates a column referenced by a WHERE clause. Qualified names other
+    /// than the main table refer to the joined table; an unqualified name
+    /// must belong to only one of them.
+    fn resolve_where_column<'r>(
+        table: Option<&String>,
+        col_name: &str,
+        row: &'r [DataValue],
+        join_row: &'r [DataValue],
+        schema: &'r [ColumnDef],
+        join_schema: &'r [ColumnDef],
+        main_table: &str,
+    ) -> Result<(usize, &'r [DataValue], &'r [ColumnDef]), ReefDBError> {
+        if let Some(table) = table {
+            if table == main_table {
+                let idx = schema.iter()
+                    .position(|c| c.name == col_name)
+                    .ok_or_else(|| ReefDBError::ColumnNotFound(format!("{}.{}", table, col_name)))?;
+                Ok((idx, row, schema))
+            } else {
+                let idx = join_schema.iter()
+                    .position(|c| c.name == col_name)
+                    .ok_or_else(|| ReefDBError::ColumnNotFound(format!("{}.{}", table, col_name)))?;
+                Ok((idx, join_row, join_schema))
+            }
+        } else {
+            // If no table is specified, the name must pick out one schema
+            match (
+                schema.iter().position(|c| c.name == col_name),
+                join_schema.iter().position(|c| c.name == col_name),
+            ) {
+                (Some(_), Some(_)) => Err(ReefDBError::AmbiguousColumn(col_name.to_string())),
+                (Some(idx), None) => Ok((idx, row, schema)),
+                (None, Some(idx)) => Ok((idx, join_row, join_schema)),
+                (None, None) => Err(ReefDBError::ColumnNotFound(col_name.to_string())),
+            }
+        }
+    }
+
+    /// Prepares `where_clause` for evaluation against the rows of one
+    /// query, running each of its full-text searches once up front
+    pub(crate) fn where_context<'a>(
+        &self,
+        where_clause: &WhereType,
+        schema: &'a [ColumnDef],
+        join_schema: &'a [ColumnDef],
+        main_table: &str,
+    ) -> WhereContext<'a> {
+        let mut fts_matches = Vec::new();
+        self.collect_fts_matches(where_clause, main_table, &mut fts_matches);
+        WhereContext { schema, join_schema, main_table: main_table.to_string(), fts_matches }
+    }
+
+    fn collect_fts_matches(
+        &self,
+        where_clause: &WhereType,
+        main_table: &str,
+        fts_matches: &mut Vec<(FTSClause, HashSet<usize>)>,
+    ) {
+        match where_clause {
+            // The same predicate appearing twice is searched once
+            WhereType::FTS(clause) if !fts_matches.iter().any(|(searched, _)| searched == clause) => {
+                let table_name = clause.column.table.as_deref().unwrap_or(main_table);
+                let rows = self.inverted_index.search(table_name, &clause.column.name, &clause.query.text);
+                fts_matches.push((clause.clone(), rows));
+            }
+            WhereType::And(left, right) | WhereType::Or(left, right) => {
+                self.collect_fts_matches(left, main_table, fts_matches);
+                self.collect_fts_matches(right, main_table, fts_matches);
+            }
+            WhereType::Case(clause) => {
+                for (predicate, _) in &clause.case.branches {
+                    self.collect_fts_matches(predicate, main_table, fts_matches);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Evaluates `where_clause` against the row of `main_table` stored at
+    /// `row_index`. When filtering many rows, build a `where_context` once
+    /// and use `evaluate_where` instead.
+    pub(crate) fn evaluate_where_clause(
         &self,
         where_clause: &WhereType,
+        row_index: usize,
         row: &[DataValue],
-        join_row: &[DataValue],
         schema: &[ColumnDef],
-        join_schema: &[ColumnDef],
         main_table: &str,
     ) -> Result<bool, ReefDBError> {
+        let context = self.where_context(where_clause, schema, &[], main_table);
+        self.evaluate_where(where_clause, row_index, row, 0, &[], &context)
+    }
+
+    /// Evaluates `where_clause` against a row, paired with a row of the
+    /// joined table for joins. The indexes are the rows' positions in
+    /// storage, which full-text predicates match on.
+    pub(crate) fn evaluate_where(
+        &self,
+        where_clause: &WhereType,
+        row_index: usize,
+        row: &[DataValue],
+        join_row_index: usize,
+        join_row: &[DataValue],
+        context: &WhereContext<'_>,
+    ) -> Result<bool, ReefDBError> {
+        let WhereContext { schema, join_schema, ref main_table, .. } = *context;
         match where_clause {
             WhereType::Regular(clause) => {
-                let (col_idx, row_to_check, schema_to_use) = if let Some(table) = &clause.table {
-                    if table == main_table {
-                        let idx = schema.iter()
-                            .position(|c| c.name == clause.col_name)
-                            .ok_or_else(|| ReefDBError::ColumnNotFound(format!("{}.{}", table, clause.col_name)))?;
-                        (idx, row, schema)
-                    } else {
-                        let idx = join_schema.iter()
-                            .position(|c| c.name == clause.col_name)
-                            .ok_or_else(|| ReefDBError::ColumnNotFound(format!("{}.{}", table, clause.col_name)))?;
-                        (idx, join_row, join_schema)
-                    }
-                } else {
-                    // If no table is specified, try both schemas in order
-                    if let Some(idx) = schema.iter().position(|c| c.name == clause.col_name) {
-                        (idx, row, schema)
-                    } else if let Some(idx) = join_schema.iter().position(|c| c.name == clause.col_name) {
-                        (idx, join_row, join_schema)
-                    } else {
-                        return Err(ReefDBError::ColumnNotFound(clause.col_name.clone()));
-                    }
-                };
+                let (col_idx, row_to_check, schema_to_use) = Self::resolve_where_column(
+                    clause.table.as_ref(), &clause.col_name, row, join_row, schema, join_schema, main_table,
+                )?;
 
                 // Handle function calls in the value
                 let evaluated_value = match &clause.value {
-                    DataValue::Function { name, args } => {
-                        let mut evaluated_args = Vec::new();
-                        for arg in args {
-                            let arg_value = match arg {
-                                DataValue::Text(col_name) => {
-                                    // Treat text values as column references
-                                    let col = Column {
-                                        name: col_name.clone(),
-                                        table: None,
-                                        column_type: ColumnType::Regular(col_name.clone()),
-                                    };
-                                    self.evaluate_column(&col, row_to_check, schema_to_use)?
-                                }
-                                _ => arg.clone(),
-                            };
-                            evaluated_args.push(arg_value);
-                        }
-                        self.function_registry.call(name, evaluated_args)?
-                    }
+                    DataValue::Function { .. } => self.function_registry.evaluate_arg(&clause.value, row_to_check, schema_to_use)?,
                     _ => clause.value.clone(),
                 };
 
                 Ok(clause.operator.evaluate(&row_to_check[col_idx], &evaluated_value))
             }
-            WhereType::FTS(clause) => {
-                let table_name = if let Some(table) = &clause.column.table {
-                    table
+            WhereType::ColumnComparison(comparison) => {
+                let (left_idx, left_row, _) = Self::resolve_where_column(
+                    comparison.left.table.as_ref(), &comparison.left.name, row, join_row, schema, join_schema, main_table,
+                )?;
+                let (right_idx, right_row, _) = Self::resolve_where_column(
+                    comparison.right.table.as_ref(), &comparison.right.name, row, join_row, schema, join_schema, main_table,
+                )?;
+                Ok(comparison.operator.evaluate(&left_row[left_idx], &right_row[right_idx]))
+            }
+            WhereType::In(clause) => {
+                let (col_idx, row_to_check, _) = Self::resolve_where_column(
+                    clause.column.table.as_ref(), &clause.column.name, row, join_row, schema, join_schema, main_table,
+                )?;
+                let value = &row_to_check[col_idx];
+                Ok(*value != DataValue::Null && clause.values.contains(value))
+            }
+            WhereType::Case(clause) => {
+                let value = self.case_value(&clause.case, row_index, row, join_row_index, join_row, context)?;
+                Ok(clause.operator.evaluate(&value, &clause.value))
+            }
+            WhereType::Expression(clause) => {
+                // Operands may come from either side of a join
+                let value = if join_schema.is_empty() {
+                    self.function_registry.evaluate_arg(&clause.expression, row, schema)?
                 } else {
-                    main_table
+                    let joined_row = [row, join_row].concat();
+                    let joined_schema = [schema, join_schema].concat();
+                    self.function_registry.evaluate_arg(&clause.expression, &joined_row, &joined_schema)?
                 };
-                let col_name = &clause.column.name;
-                let query = &clause.query.text;
-                
-                // Get the row ID from the current row
-                let row_id = match row.first()
-                    .ok_or_else(|| ReefDBError::Other("Row is empty".to_string()))? {
-                    DataValue::Integer(id) => *id,
-                    _ => return Err(ReefDBError::Other("First column is not an integer".to_string())),
+                Ok(clause.operator.evaluate(&value, &clause.value))
+            }
+            WhereType::Subquery(_) | WhereType::InSubquery(_) => {
+                Err(ReefDBError::Other("Subqueries must be resolved before the WHERE clause is evaluated".to_string()))
+            }
+            WhereType::FTS(clause) => {
+                let index = match &clause.column.table {
+                    Some(table) if table != main_table => join_row_index,
+                    _ => row_index,
                 };
-                
-                // Search the inverted index
-                let results = self.inverted_index.search(table_name, col_name, query);
-                
-                Ok(results.contains(&(row_id as usize)))
+                let (_, matches) = context.fts_matches.iter()
+                    .find(|(searched, _)| searched == clause)
+                    .ok_or_else(|| ReefDBError::Other("Full-text search was not prepared for this WHERE clause".to_string()))?;
+                Ok(matches.contains(&fts_document_id(index)))
             }
             WhereType::And(left, right) => {
-                let left_result = self.evaluate_where_clause(left, row, join_row, schema, join_schema, main_table)?;
-                let right_result = self.evaluate_where_clause(right, row, join_row, schema, join_schema, main_table)?;
+                let left_result = self.evaluate_where(left, row_index, row, join_row_index, join_row, context)?;
+                let right_result = self.evaluate_where(right, row_index, row, join_row_index, join_row, context)?;
                 Ok(left_result && right_result)
             }
             WhereType::Or(left, right) => {
-                let left_result = self.evaluate_where_clause(left, row, join_row, schema, join_schema, main_table)?;
-                let right_result = self.evaluate_where_clause(right, row, join_row, schema, join_schema, main_table)?;
+                let left_result = self.evaluate_where(left, row_index, row, join_row_index, join_row, context)?;
+                let right_result = self.evaluate_where(right, row_index, row, join_row_index, join_row, context)?;
                 Ok(left_result || right_result)
             }
         }
     }
 
+    /// Evaluates a CASE expression against a row, paired with a row of the
+    /// joined table for joins
+    fn case_value(
+        &self,
+        case: &CaseExpression,
+        row_index: usize,
+        row: &[DataValue],
+        join_row_index: usize,
+        join_row: &[DataValue],
+        context: &WhereContext<'_>,
+    ) -> Result<DataValue, ReefDBError> {
+        let mut value = case.else_value.as_ref();
+        for (predicate, branch_value) in &case.branches {
+            if self.evaluate_where(predicate, row_index, row, join_row_index, join_row, context)? {
+                value = Some(branch_value);
+                break;
+            }
+        }
+        match value {
+            Some(value) if matches!(value, DataValue::Function { .. }) => self.function_registry.evaluate_arg(value, row, context.schema),
+            Some(value) => Ok(value.clone()),
+            None => Ok(DataValue::Null),
+        }
+    }
+
     fn evaluate_function(&self, name: &str, args: &[DataValue]) -> Result<DataValue, ReefDBError> {
         match &args[0] {
             DataValue::Function { name, args } => {
@@ -550,55 +1363,149 @@ where
         table_name: String,
         updates: Vec<(String, DataValue)>,
         where_clause: Option<WhereType>,
+        limit: Option<usize>,
     ) -> Result<ReefDBResult, ReefDBError> {
-        self.verify_table_exists(&table_name)?;
+        let updates = self.coerce_updates(&table_name, updates);
+        self.validate_update(&table_name, &updates, where_clause.as_ref())?;
+
+        let row_ids = self.written_rows(&table_name, where_clause.as_ref(), limit)?;
+        // Convert WhereType to simple where clause for storage layer
+        let storage_where = Self::storage_where_clause(where_clause);
+
         let (schema, _) = self.get_table_schema(&table_name)?;
+        let fts_columns: Vec<String> = updates.iter()
+            .filter(|(name, _)| schema.iter().any(|c| &c.name == name && c.data_type == DataType::TSVector))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let indexed_columns: Vec<ColumnDef> = self.value_indexed_columns(&table_name).into_iter()
+            .filter(|(_, column)| updates.iter().any(|(name, _)| *name == column.name))
+            .map(|(_, column)| column)
+            .collect();
+
+        self.log_undo(&table_name, |table| UndoImage::Updated(
+            Self::rows_written(table, row_ids.as_deref(), storage_where.as_ref())
+        ))?;
+        let updated_count = match row_ids {
+            // Rows matched one by one, which replay has to find by position
+            Some(row_ids) => {
+                self.log_wal(WALOperation::UpdateRows, &table_name, &(&updates, &row_ids))?;
+                self.storage.update_rows(&table_name, &row_ids, &updates)?
+            }
+            None => {
+                self.log_wal(WALOperation::Update, &table_name, &(&updates, &storage_where))?;
+                self.storage.update_table(&table_name, updates, storage_where)
+            }
+        };
 
-        // Validate update columns exist and value types match
-        for (col_name, value) in &updates {
-            let column = schema.iter()
-                .find(|c| &c.name == col_name)
-                .ok_or_else(|| ReefDBError::ColumnNotFound(col_name.clone()))?;
+        // Updated full-text values are re-added under the same document ids
+        for column_name in fts_columns {
+            self.reindex_fts_column(&table_name, &column_name)?;
+        }
+        self.rebuild_indexes(&table_name, &indexed_columns)?;
+        Ok(ReefDBResult::Update(updated_count))
+    }
 
-            if !value.matches_type(&column.data_type) {
-                return Err(ReefDBError::Other(format!(
-                    "Value type mismatch for column {}: expected {:?}, got {:?}",
-                    col_name,
-                    column.data_type,
-                    value
-                )));
+    /// The CHECK constraints of `schema`, parsed and prepared once so that
+    /// `check_row` can test any number of rows against them
+    pub(crate) fn row_checks<'a>(&self, table_name: &str, schema: &'a [ColumnDef]) -> Result<Vec<RowCheck<'a>>, ReefDBError> {
+        let mut checks = Vec::new();
+        for (column_index, column) in schema.iter().enumerate() {
+            for constraint in &column.constraints {
+                let Constraint::Check(expr) = constraint else {
+                    continue;
+                };
+                let (_, predicate) = parse_where_expression(expr)
+                    .map_err(|e| ReefDBError::Other(format!("Invalid CHECK constraint on {}: {}", column.name, e)))?;
+                let context = self.where_context(&predicate, schema, &[], table_name);
+                checks.push(RowCheck { column_index, expr, predicate, context });
             }
         }
+        Ok(checks)
+    }
 
-        // Validate where clause column exists if present
-        if let Some(where_clause) = &where_clause {
-            self.validate_where_clause(where_clause, &schema)?;
+    /// Fails with `CheckViolation` unless `row`, to be stored at `row_index`,
+    /// satisfies every one of `checks`. As in SQL a NULL in the constrained
+    /// column passes: only a false predicate is a violation.
+    pub(crate) fn check_row(&self, table_name: &str, checks: &[RowCheck<'_>], row_index: usize, row: &[DataValue]) -> Result<(), ReefDBError> {
+        for check in checks {
+            if matches!(row[check.column_index], DataValue::Null) {
+                continue;
+            }
+            if !self.evaluate_where(&check.predicate, row_index, row, 0, &[], &check.context)? {
+                let column = &check.context.schema[check.column_index].name;
+                return Err(ReefDBError::CheckViolation(format!("{}.{}: {}", table_name, column, check.expr)));
+            }
         }
+        Ok(())
+    }
 
-        // Convert WhereType to simple where clause for storage layer
-        let storage_where = where_clause.and_then(|w| match w {
-            WhereType::Regular(clause) => Some((clause.col_name, clause.value)),
-            WhereType::FTS(_) => None, // FTS not supported for updates
-            WhereType::And(_, _) => None, // Complex conditions not supported for updates
-            WhereType::Or(_, _) => None, // Complex conditions not supported for updates
-        });
+    /// Runs the scalar subqueries of `stmt` against the current state and
+    /// substitutes their values, so the handlers only see plain comparisons.
+    pub(crate) fn resolve_subqueries(&self, stmt: Statement) -> Result<Statement, ReefDBError> {
+        stmt.resolve_subqueries(&mut |select| {
+            let SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by) = select;
+            match self.handle_select(table_ref, columns, where_clause, joins, order_by, group_by)? {
+                ReefDBResult::Select(result) => Ok(result),
+                _ => unreachable!("handle_select always returns a Select result"),
+            }
+        })
+    }
 
-        let updated_count = self.storage.update_table(&table_name, updates, storage_where);
-        Ok(ReefDBResult::Update(updated_count))
+    /// Converts a where clause into the single-column filter the storage
+    /// layer supports, which is only the whole clause when
+    /// `storage_filter_is_exact` says so.
+    pub(crate) fn storage_where_clause(where_clause: Option<WhereType>) -> Option<(String, DataValue)> {
+        where_clause.and_then(|w| match w {
+            WhereType::Regular(clause) => Some((clause.col_name, clause.value)),
+            WhereType::ColumnComparison(_) => None, // Column comparisons not supported for updates or deletes
+            WhereType::Subquery(_) => None, // Resolved to a regular clause before execution
+            WhereType::In(_) | WhereType::InSubquery(_) => None, // IN lists not supported for updates or deletes
+            WhereType::FTS(_) => None, // FTS not supported for updates or deletes
+            WhereType::Case(_) => None, // CASE not supported for updates or deletes
+            WhereType::Expression(_) => None, // Expressions not supported for updates or deletes
+            WhereType::And(_, _) => None, // Complex conditions not supported for updates or deletes
+            WhereType::Or(_, _) => None, // Complex conditions not supported for updates or deletes
+        })
     }
 
-    fn validate_where_clause(&self, where_clause: &WhereType, schema: &[ColumnDef]) -> Result<(), ReefDBError> {
+    pub(crate) fn validate_where_clause(&self, where_clause: &WhereType, schema: &[ColumnDef]) -> Result<(), ReefDBError> {
         match where_clause {
             WhereType::Regular(clause) => {
                 if !schema.iter().any(|c| c.name == clause.col_name) {
                     return Err(ReefDBError::ColumnNotFound(clause.col_name.clone()));
                 }
             }
+            WhereType::ColumnComparison(comparison) => {
+                for column in [&comparison.left, &comparison.right] {
+                    if !schema.iter().any(|c| c.name == column.name) {
+                        return Err(ReefDBError::ColumnNotFound(column.name.clone()));
+                    }
+                }
+            }
+            WhereType::Subquery(SubqueryClause { column, .. })
+            | WhereType::In(InClause { column, .. })
+            | WhereType::InSubquery(InSubqueryClause { column, .. }) => {
+                if !schema.iter().any(|c| c.name == column.name) {
+                    return Err(ReefDBError::ColumnNotFound(column.name.clone()));
+                }
+            }
             WhereType::FTS(clause) => {
                 if !schema.iter().any(|c| c.name == clause.column.name) {
                     return Err(ReefDBError::ColumnNotFound(clause.column.name.clone()));
                 }
             }
+            WhereType::Case(clause) => {
+                for (predicate, _) in &clause.case.branches {
+                    self.validate_where_clause(predicate, schema)?;
+                }
+            }
+            WhereType::Expression(clause) => {
+                for column in clause.expression_columns() {
+                    if !schema.iter().any(|c| c.name == column) {
+                        return Err(ReefDBError::ColumnNotFound(column.to_string()));
+                    }
+                }
+            }
             WhereType::And(left, right) => {
                 self.validate_where_clause(left, schema)?;
                 self.validate_where_clause(right, schema)?;
@@ -615,79 +1522,253 @@ where
         &mut self,
         table_name: String,
         where_clause: Option<WhereType>,
+        limit: Option<usize>,
     ) -> Result<ReefDBResult, ReefDBError> {
-        self.verify_table_exists(&table_name)?;
+        self.validate_delete(&table_name, where_clause.as_ref())?;
         let (schema, _) = self.get_table_schema(&table_name)?;
 
-        // Validate where clause column exists if present
-        if let Some(where_clause) = &where_clause {
-            self.validate_where_clause(where_clause, &schema)?;
+        let row_ids = self.written_rows(&table_name, where_clause.as_ref(), limit)?;
+        // Convert WhereType to simple where clause for storage layer
+        let storage_where = Self::storage_where_clause(where_clause.clone());
+
+        // Every row after the first deleted one moves up in storage, so its
+        // full-text documents are renumbered to keep matching its position
+        let fts_columns: Vec<(usize, String)> = schema.iter().enumerate()
+            .filter(|(_, c)| c.data_type == DataType::TSVector)
+            .map(|(i, c)| (i, c.name.clone()))
+            .collect();
+        let first_deleted = match &row_ids {
+            _ if fts_columns.is_empty() => None,
+            Some(row_ids) => row_ids.first().copied(),
+            None => self.matching_rows(&table_name, where_clause.as_ref(), None)?.first().copied(),
+        };
+        if let Some(first_deleted) = first_deleted {
+            let (_, rows) = self.get_table_schema(&table_name)?;
+            for row_index in first_deleted..rows.len() {
+                for (_, column_name) in &fts_columns {
+                    self.inverted_index.remove_document(&table_name, column_name, fts_document_id(row_index));
+                }
+            }
         }
 
-        // Convert WhereType to simple where clause for storage layer
-        let storage_where = where_clause.and_then(|w| match w {
-            WhereType::Regular(clause) => Some((clause.col_name, clause.value)),
-            WhereType::FTS(_) => None, // FTS not supported for deletes
-            WhereType::And(_, _) => None, // Complex conditions not supported for deletes
-            WhereType::Or(_, _) => None, // Complex conditions not supported for deletes
-        });
+        self.log_undo(&table_name, |table| UndoImage::Deleted(
+            table.1.len(),
+            Self::rows_written(table, row_ids.as_deref(), storage_where.as_ref()),
+        ))?;
+        let deleted_count = match &row_ids {
+            Some(row_ids) => {
+                self.log_wal(WALOperation::DeleteRows, &table_name, row_ids)?;
+                self.storage.delete_rows(&table_name, row_ids)?
+            }
+            None => {
+                self.log_wal(WALOperation::Delete, &table_name, &storage_where)?;
+                self.storage.delete_table(&table_name, storage_where)
+            }
+        };
 
-        let deleted_count = self.storage.delete_table(&table_name, storage_where);
+        if let Some(first_deleted) = first_deleted {
+            let (_, rows) = self.get_table_schema(&table_name)?;
+            let documents: Vec<(usize, &String, String)> = (first_deleted..rows.len())
+                .flat_map(|row_index| fts_columns.iter().filter_map(move |(i, column_name)| match &rows[row_index][*i] {
+                    DataValue::Text(text) => Some((row_index, column_name, text.clone())),
+                    _ => None,
+                }))
+                .collect();
+            for (row_index, column_name, text) in documents {
+                self.inverted_index.add_document(&table_name, column_name, fts_document_id(row_index), &text);
+            }
+            for (_, column_name) in &fts_columns {
+                self.rebuild_gin_index(&table_name, column_name)?;
+            }
+        }
+        // Rows after a deleted one moved up, so their index entries moved too
+        if deleted_count > 0 {
+            self.rebuild_value_indexes(&[table_name])?;
+        }
         Ok(ReefDBResult::Delete(deleted_count))
     }
 
+    /// Positions of the rows of `table_name` that `where_clause` matches,
+    /// in storage order, evaluated like a SELECT's filter. A `limit` keeps
+    /// the first matches.
+    pub(crate) fn matching_rows(
+        &self,
+        table_name: &str,
+        where_clause: Option<&WhereType>,
+        limit: Option<usize>,
+    ) -> Result<Vec<usize>, ReefDBError> {
+        let (schema, rows) = self.get_table_schema(table_name)?;
+        let context = where_clause.map(|where_clause| self.where_context(where_clause, schema, &[], table_name));
+        let mut row_ids = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            if limit.is_some_and(|limit| row_ids.len() >= limit) {
+                break;
+            }
+            let matches = match (where_clause, &context) {
+                (Some(where_clause), Some(context)) => self.evaluate_where(where_clause, i, row, 0, &[], context)?,
+                _ => true,
+            };
+            if matches {
+                row_ids.push(i);
+            }
+        }
+        Ok(row_ids)
+    }
+
+    /// The rows an UPDATE or DELETE changes, or `None` when the storage
+    /// layer's single-column filter selects exactly those rows itself
+    fn written_rows(
+        &self,
+        table_name: &str,
+        where_clause: Option<&WhereType>,
+        limit: Option<usize>,
+    ) -> Result<Option<Vec<usize>>, ReefDBError> {
+        if limit.is_none() && Self::storage_filter_is_exact(where_clause) {
+            return Ok(None);
+        }
+        self.matching_rows(table_name, where_clause, limit).map(Some)
+    }
+
+    /// Whether `storage_where_clause` keeps all of `where_clause`: nothing,
+    /// or one column equal to a constant. NULL equals nothing in SQL but
+    /// would match NULLs in storage, and functions are evaluated per row.
+    pub(crate) fn storage_filter_is_exact(where_clause: Option<&WhereType>) -> bool {
+        match where_clause {
+            None => true,
+            Some(WhereType::Regular(clause)) => clause.operator == Op::Equal
+                && !matches!(clause.value, DataValue::Null | DataValue::Function { .. }),
+            Some(_) => false,
+        }
+    }
+
+    /// Runs an UPDATE or DELETE and projects `columns` from the rows it
+    /// touched: their new values for an update, their old values for a delete.
+    pub(crate) fn handle_returning(&mut self, stmt: Statement, columns: Vec<Column>) -> Result<ReefDBResult, ReefDBError> {
+        let (table_name, where_clause, limit) = match &stmt {
+            Statement::Update(UpdateStatement::UpdateTable(table_name, _, where_clause, limit))
+            | Statement::Delete(DeleteStatement::FromTable(table_name, where_clause, limit)) => {
+                (table_name.clone(), where_clause.clone(), *limit)
+            }
+            _ => return Err(ReefDBError::Other("RETURNING is only supported on UPDATE and DELETE".to_string())),
+        };
+        let is_delete = matches!(stmt, Statement::Delete(_));
+
+        // Find the rows the statement will touch
+        let (schema, affected, deleted) = {
+            let affected = self.matching_rows(&table_name, where_clause.as_ref(), limit)?;
+            let (schema, rows) = self.get_table_schema(&table_name)?;
+            let deleted: Vec<Vec<DataValue>> = if is_delete {
+                affected.iter().map(|&i| rows[i].clone()).collect()
+            } else {
+                Vec::new()
+            };
+            (schema.clone(), affected, deleted)
+        };
+        let column_info = ColumnInfo::from_schema_and_columns(&schema, &columns, &table_name)?;
+
+        self.execute_statement_in_transaction(stmt)?;
+
+        let touched = if is_delete {
+            deleted
+        } else {
+            let (_, rows) = self.get_table_schema(&table_name)?;
+            affected.iter().map(|&i| rows[i].clone()).collect()
+        };
+        let mut result = Vec::with_capacity(touched.len());
+        for (i, (row_index, row)) in affected.iter().zip(&touched).enumerate() {
+            result.push((i, self.project_row(*row_index, row, &schema, &table_name, &columns)?));
+        }
+        Ok(ReefDBResult::Returning(QueryResult::with_columns(result, column_info)))
+    }
+
     fn handle_alter(&mut self, table_name: String, alter_type: AlterType) -> Result<ReefDBResult, ReefDBError> {
-        self.verify_table_exists(&table_name)?;
-        let (schema, _) = self.get_table_schema(&table_name)?;
+        self.validate_alter(&table_name, &alter_type)?;
+        let logged_alter = alter_type.clone();
+        self.log_undo(&table_name, |(columns, rows)| UndoImage::Table(columns.clone(), rows.clone()))?;
 
         match alter_type {
             AlterType::AddColumn(column_def) => {
-                // Verify column doesn't already exist
-                if schema.iter().any(|c| c.name == column_def.name) {
-                    return Err(ReefDBError::Other(
-                        format!("Column {} already exists in table {}", column_def.name, table_name)
-                    ));
-                }
-
                 self.storage.add_column(&table_name, column_def)?;
             },
             AlterType::DropColumn(column_name) => {
                 self.storage.drop_column(&table_name, &column_name)?;
+                self.storage.drop_index(&table_name, &column_name);
             },
             AlterType::RenameColumn(old_name, new_name) => {
-                // Verify new name doesn't already exist
-                if schema.iter().any(|c| c.name == new_name) {
-                    return Err(ReefDBError::Other(
-                        format!("Column {} already exists in table {}", new_name, table_name)
-                    ));
-                }
-
                 self.storage.rename_column(&table_name, &old_name, &new_name)?;
             }
+            AlterType::AlterColumnType { column, new_type } => {
+                self.storage.alter_column_type(&table_name, &column, new_type)?;
+                // The converted values are indexed under new keys
+                self.rebuild_value_indexes(std::slice::from_ref(&table_name))?;
+            }
+            AlterType::RenameTable(new_name) => {
+                self.rename_table(&table_name, &new_name)?;
+            }
         }
 
+        self.log_wal(WALOperation::AlterTable, &table_name, &logged_alter)?;
         Ok(ReefDBResult::AlterTable)
     }
 
+    /// Moves a table to `new_name` along with its indexes and full-text
+    /// documents, which are kept by table name
+    fn rename_table(&mut self, table_name: &str, new_name: &str) -> Result<(), ReefDBError> {
+        let (schema, _) = self.get_table_schema(table_name)?;
+        let schema = schema.clone();
+        let indexes: Vec<Option<IndexType>> = schema.iter()
+            .map(|column| self.storage.get_index(table_name, &column.name).ok().cloned())
+            .collect();
+
+        self.storage.rename_table(table_name, new_name)?;
+        if self.tables.table_exists(table_name) {
+            self.tables.rename_table(table_name, new_name)?;
+        }
+
+        for (column, index) in schema.iter().zip(indexes) {
+            self.storage.drop_index(table_name, &column.name);
+            if column.data_type == DataType::TSVector {
+                // Rebuilding the GIN index recreates it, so one that was
+                // dropped is dropped again
+                self.inverted_index.clear_column(table_name, &column.name);
+                self.add_fts_column(new_name, column);
+                self.reindex_fts_column(new_name, &column.name)?;
+                if index.is_none() {
+                    self.storage.drop_index(new_name, &column.name);
+                }
+            } else if let Some(index) = index {
+                self.storage.create_index(new_name, &column.name, index)?;
+            }
+        }
+        Ok(())
+    }
+
     fn handle_drop(&mut self, table_name: String) -> Result<ReefDBResult, ReefDBError> {
         self.verify_table_exists(&table_name)?;
+        self.log_undo(&table_name, |(columns, rows)| UndoImage::Table(columns.clone(), rows.clone()))?;
         self.storage.drop_table(&table_name);
         self.tables.drop_table(&table_name);
+        self.log_wal(WALOperation::DropTable, &table_name, &())?;
         Ok(ReefDBResult::DropTable)
     }
 
     fn handle_create_index(&mut self, stmt: CreateIndexStatement) -> Result<ReefDBResult, ReefDBError> {
         self.verify_table_exists(&stmt.table_name)?;
+        self.validate_create_index(&stmt)?;
         let (schema, _) = self.get_table_schema(&stmt.table_name)?;
 
-        // Verify column exists
-        if !schema.iter().any(|c| c.name == stmt.column_name) {
-            return Err(ReefDBError::ColumnNotFound(stmt.column_name));
-        }
-
-        // Create B-Tree index
-        let btree = BTreeIndex::new();
-        self.storage.create_index(&stmt.table_name, &stmt.column_name, IndexType::BTree(btree));
+        let is_fts = schema.iter().any(|c| c.name == stmt.column_name && c.data_type == DataType::TSVector);
+        let index = match stmt.index_type {
+            CreateIndexType::Hash => IndexType::Hash(HashIndex::new()),
+            CreateIndexType::GIN if is_fts => return self.rebuild_gin_index(&stmt.table_name, &stmt.column_name)
+                .map(|_| ReefDBResult::CreateIndex),
+            // Full-text search on other columns goes through the inverted index
+            CreateIndexType::BTree | CreateIndexType::GIN => IndexType::BTree(BTreeIndex::new()),
+        };
+        self.storage.drop_index(&stmt.table_name, &stmt.column_name);
+        self.storage.create_index(&stmt.table_name, &stmt.column_name, index)?;
+        // Fill the new index with the rows already in the table
+        self.handle_reindex(stmt.table_name, Some(stmt.column_name))?;
 
         Ok(ReefDBResult::CreateIndex)
     }
@@ -707,10 +1788,120 @@ where
         Ok(ReefDBResult::DropIndex)
     }
 
+    fn handle_reindex(&mut self, table_name: String, column_name: Option<String>) -> Result<ReefDBResult, ReefDBError> {
+        self.verify_table_exists(&table_name)?;
+        let (schema, _) = self.get_table_schema(&table_name)?;
+
+        let columns: Vec<ColumnDef> = match &column_name {
+            Some(name) => vec![schema.iter()
+                .find(|c| &c.name == name)
+                .cloned()
+                .ok_or_else(|| ReefDBError::ColumnNotFound(name.clone()))?],
+            None => schema.clone(),
+        };
+
+        self.rebuild_indexes(&table_name, &columns)?;
+
+        // Rebuild the full-text index for TSVECTOR columns
+        for column in columns.iter().filter(|c| c.data_type == DataType::TSVector) {
+            self.reindex_fts_column(&table_name, &column.name)?;
+        }
+
+        Ok(ReefDBResult::Reindex)
+    }
+
+    /// Rebuilds the storage indexes on `columns` of `table_name` from its
+    /// current rows. Columns without an index are skipped.
+    fn rebuild_indexes(&mut self, table_name: &str, columns: &[ColumnDef]) -> Result<(), ReefDBError> {
+        let (schema, rows) = self.get_table_schema(table_name)?;
+        let mut rebuilt = Vec::new();
+        for column in columns {
+            let existing = match self.storage.get_index(table_name, &column.name) {
+                Ok(index) => index,
+                Err(_) => continue,
+            };
+            let col_idx = schema.iter().position(|c| c.name == column.name).unwrap();
+            let index = match existing {
+                IndexType::BTree(_) => {
+                    let mut btree = BTreeIndex::new();
+                    for (i, row) in rows.iter().enumerate() {
+                        btree.add_entry(encode_value(&row[col_idx])?, i + 1);
+                    }
+                    IndexType::BTree(btree)
+                }
+                IndexType::Hash(_) => {
+                    let mut hash = HashIndex::new();
+                    for (i, row) in rows.iter().enumerate() {
+                        hash.add_entry(encode_value(&row[col_idx])?, i + 1);
+                    }
+                    IndexType::Hash(hash)
+                }
+                IndexType::GIN(_) => IndexType::GIN(build_gin_index(table_name, &column.name, rows, col_idx)),
+            };
+            rebuilt.push((column.name.clone(), index));
+        }
+
+        for (column, index) in rebuilt {
+            self.storage.drop_index(table_name, &column);
+            self.storage.create_index(table_name, &column, index)?;
+        }
+        Ok(())
+    }
+
+    /// The columns of `table_name` with a BTree or hash index, which writes
+    /// keep up to date and `=` predicates can look rows up in
+    pub(crate) fn value_indexed_columns(&self, table_name: &str) -> Vec<(usize, ColumnDef)> {
+        let Some(schema) = self.storage.get_schema_ref(table_name) else {
+            return Vec::new();
+        };
+        schema.iter().enumerate()
+            .filter(|(_, column)| self.storage.get_index(table_name, &column.name)
+                .is_ok_and(|index| index.kind() != IndexKind::GIN))
+            .map(|(i, column)| (i, column.clone()))
+            .collect()
+    }
+
+    /// Adds a new row, stored at `row_id`, to the BTree and hash indexes of
+    /// `table_name`
+    pub(crate) fn index_row(&mut self, table_name: &str, row_id: usize, row: &[DataValue]) -> Result<(), ReefDBError> {
+        for (i, column) in self.value_indexed_columns(table_name) {
+            self.storage.update_index(table_name, &column.name, Vec::new(), encode_value(&row[i])?, row_id)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the BTree and hash indexes of every table, after its rows
+    /// were put back by a rollback or a recovery
+    pub(crate) fn rebuild_value_indexes(&mut self, table_names: &[String]) -> Result<(), ReefDBError> {
+        for table_name in table_names {
+            let columns: Vec<ColumnDef> = self.value_indexed_columns(table_name).into_iter()
+                .map(|(_, column)| column)
+                .collect();
+            if !columns.is_empty() {
+                self.rebuild_indexes(table_name, &columns)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `VACUUM`: compacts storage, then rebuilds every table's indexes and
+    /// full-text documents against the rows' new positions
+    fn handle_vacuum(&mut self) -> Result<ReefDBResult, ReefDBError> {
+        self.storage.vacuum()?;
+        for table_name in self.list_tables() {
+            self.handle_reindex(table_name, None)?;
+        }
+        self.inverted_index.refresh_stats();
+        Ok(ReefDBResult::Vacuum)
+    }
+
     fn handle_savepoint(&mut self, name: String) -> Result<ReefDBResult, ReefDBError> {
         if let Some(tx_id) = self.current_transaction_id {
             if let Some(tm) = &mut self.transaction_manager {
-                tm.create_savepoint(tx_id, name)?;
+                // Statements of an explicit transaction run against the
+                // database's own tables, so those are what a rollback restores
+                let tables = TableStorage { tables: self.storage.get_all_tables().clone() };
+                tm.create_savepoint_of(tx_id, name, tables)?;
                 Ok(ReefDBResult::Savepoint)
             } else {
                 Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
@@ -724,6 +1915,16 @@ where
         if let Some(tx_id) = self.current_transaction_id {
             if let Some(tm) = &mut self.transaction_manager {
                 let restored_state = tm.rollback_to_savepoint(tx_id, &name)?;
+
+                // Log the restored tables so recovery replays the rollback too
+                for (table_name, (columns, rows)) in self.storage.get_all_tables() {
+                    let image = UndoImage::Table(columns.clone(), rows.clone());
+                    tm.log_operation(tx_id, WALOperation::Undo, table_name, bincode::serialize(&image)?)?;
+                    tm.log_operation(tx_id, WALOperation::DropTable, table_name, bincode::serialize(&())?)?;
+                }
+                for (table_name, table) in restored_state.tables.iter() {
+                    tm.log_operation(tx_id, WALOperation::CreateTable, table_name, bincode::serialize(table)?)?;
+                }
                 
                 // First clear both states
                 self.tables = TableStorage::new();
@@ -735,6 +1936,7 @@ where
                     self.storage.insert_table(table_name.clone(), columns.clone(), rows.clone());
                     self.tables.insert_table(table_name.clone(), columns.clone(), rows.clone());
                 }
+                self.rebuild_value_indexes(&restored_state.tables.keys().cloned().collect::<Vec<_>>())?;
                 
                 Ok(ReefDBResult::RollbackToSavepoint)
             } else {
@@ -760,7 +1962,7 @@ where
 
     fn handle_begin_transaction(&mut self) -> Result<ReefDBResult, ReefDBError> {
         if let Some(tm) = &mut self.transaction_manager {
-            let tx_id = tm.begin_transaction(IsolationLevel::Serializable)?;
+            let tx_id = tm.begin_external_transaction(IsolationLevel::Serializable)?;
             self.current_transaction_id = Some(tx_id);
             Ok(ReefDBResult::BeginTransaction)
         } else {
@@ -768,11 +1970,59 @@ where
         }
     }
 
+    /// Starts an explicit transaction, remembering the current state so a
+    /// rollback can restore it
+    fn begin_explicit_transaction(&mut self, isolation_level: IsolationLevel) -> Result<u64, ReefDBError> {
+        if self.current_transaction_id.is_some() {
+            return Err(ReefDBError::Other("Cannot begin a new transaction while another transaction is active".to_string()));
+        }
+        let tm = self.transaction_manager.as_mut()
+            .ok_or_else(|| ReefDBError::Other("Transaction manager not initialized".to_string()))?;
+        let tx_id = tm.begin_external_transaction(isolation_level)?;
+        self.current_transaction_id = Some(tx_id);
+
+        let tables = TableStorage { tables: self.storage.get_all_tables().clone() };
+        self.transaction_snapshot = Some(Box::new((tables, self.inverted_index.clone())));
+        Ok(tx_id)
+    }
+
+    /// Aborts the current transaction, restoring the tables and full-text
+    /// index it started from
+    pub(crate) fn rollback_explicit_transaction(&mut self) -> Result<(), ReefDBError> {
+        let tx_id = self.current_transaction_id.take()
+            .ok_or(ReefDBError::TransactionNotActive)?;
+
+        if let Some(snapshot) = self.transaction_snapshot.take() {
+            let (tables, inverted_index) = *snapshot;
+            self.storage.restore_from(&tables);
+            self.tables.restore_from(&tables);
+            self.inverted_index = inverted_index;
+
+            // GIN indexes live in storage, which keeps the transaction's entries
+            let fts_columns: Vec<(String, String)> = tables.tables.iter()
+                .flat_map(|(table_name, (schema, _))| schema.iter()
+                    .filter(|c| c.data_type == DataType::TSVector)
+                    .map(move |c| (table_name.clone(), c.name.clone())))
+                .collect();
+            for (table_name, column_name) in fts_columns {
+                self.rebuild_gin_index(&table_name, &column_name)?;
+            }
+            self.rebuild_value_indexes(&tables.tables.keys().cloned().collect::<Vec<_>>())?;
+        }
+
+        if let Some(tm) = &mut self.transaction_manager {
+            tm.rollback_transaction(tx_id)?;
+        }
+        Ok(())
+    }
+
     fn handle_commit(&mut self) -> Result<ReefDBResult, ReefDBError> {
         if let Some(tx_id) = self.current_transaction_id {
             if let Some(tm) = &mut self.transaction_manager {
                 tm.commit_transaction(tx_id)?;
                 self.current_transaction_id = None;
+                self.transaction_snapshot = None;
+                self.storage.sync_after_commit()?;
                 Ok(ReefDBResult::Commit)
             } else {
                 Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
@@ -783,11 +2033,26 @@ where
     }
 
     pub fn execute_statement(&mut self, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
+        // A statement already running under `query_with_timeout` keeps its
+        // own deadline
+        if self.query_deadline.get().is_some() {
+            return self.execute_statement_unbounded(stmt);
+        }
+        let Some(timeout) = self.get_query_limits().timeout else {
+            return self.execute_statement_unbounded(stmt);
+        };
+        self.query_deadline.set(Some((Instant::now(), timeout)));
+        let result = self.execute_statement_unbounded(stmt);
+        self.query_deadline.set(None);
+        result
+    }
+
+    fn execute_statement_unbounded(&mut self, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
         // If we're in an explicit transaction, just execute the statement
         if self.current_transaction_id.is_some() {
             match &stmt {
                 Statement::BeginTransaction => {
-                    return Err(ReefDBError::Other("Cannot begin a transaction within another transaction".to_string()));
+                    return Err(ReefDBError::Other("Cannot begin a new transaction while another transaction is active".to_string()));
                 }
                 _ => return self.execute_statement_in_transaction(stmt),
             }
@@ -795,56 +2060,23 @@ where
 
         match &stmt {
             Statement::BeginTransaction => {
-                if let Some(tx_id) = self.current_transaction_id {
-                    return Err(ReefDBError::Other("Cannot begin a new transaction while another transaction is active".to_string()));
-                }
-                let tx_id = self.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
-                self.current_transaction_id = Some(tx_id);
+                self.begin_explicit_transaction(IsolationLevel::ReadCommitted)?;
                 return Ok(ReefDBResult::BeginTransaction);
             }
             Statement::Commit => return self.handle_commit(),
+            Statement::Rollback => return Err(ReefDBError::TransactionNotActive),
             _ => {}
         }
 
         // If autocommit is enabled, wrap the statement in an implicit transaction
         if self.autocommit {
-            // Start an implicit transaction
-            let tx_id = self.handle_begin_transaction()
-                .and_then(|_| {
-                    if let Some(tm) = &mut self.transaction_manager {
-                        tm.begin_transaction(self.autocommit_isolation_level)
-                    } else {
-                        Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
-                    }
-                })?;
-            self.current_transaction_id = Some(tx_id);
-
-            // Execute the statement
-            let result = self.execute_statement_in_transaction(stmt);
-
-            // Commit or rollback based on the result
-            match &result {
-                Ok(_) => {
-                    self.handle_commit()?;
-                }
-                Err(_) => {
-                    // Best effort rollback - if it fails, we can't do much about it
-                    if let Some(tx_id) = self.current_transaction_id {
-                        if let Some(tm) = &mut self.transaction_manager {
-                            let _ = tm.rollback_transaction(tx_id);
-                        }
-                    }
-                }
-            }
-
-            self.current_transaction_id = None;
-            result
+            self.with_implicit_transaction(|db| db.execute_statement_in_transaction(stmt))
         } else {
             // When autocommit is disabled, require explicit transactions for all statements except BEGIN and DDL
             match stmt {
                 Statement::BeginTransaction => {
                     if self.current_transaction_id.is_some() {
-                        return Err(ReefDBError::Other("Cannot begin a transaction within another transaction".to_string()));
+                        return Err(ReefDBError::Other("Cannot begin a new transaction while another transaction is active".to_string()));
                     }
                     self.handle_begin_transaction()
                 }
@@ -853,7 +2085,7 @@ where
                         CreateStatement::Table(table_name, columns) => {
                             if !self.autocommit && self.current_transaction_id.is_none() {
                                 // Start an implicit transaction for DDL statements
-                                let tx_id = self.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+                                let tx_id = self.transaction_manager.as_mut().unwrap().begin_external_transaction(IsolationLevel::ReadCommitted)?;
                                 self.current_transaction_id = Some(tx_id);
                                 let result = self.handle_create(table_name, columns)?;
                                 // Commit the implicit transaction
@@ -876,7 +2108,7 @@ where
                     let tx_id = self.handle_begin_transaction()
                         .and_then(|_| {
                             if let Some(tm) = &mut self.transaction_manager {
-                                tm.begin_transaction(self.autocommit_isolation_level)
+                                tm.begin_external_transaction(self.autocommit_isolation_level)
                             } else {
                                 Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
                             }
@@ -914,7 +2146,7 @@ where
                     let tx_id = self.handle_begin_transaction()
                         .and_then(|_| {
                             if let Some(tm) = &mut self.transaction_manager {
-                                tm.begin_transaction(self.autocommit_isolation_level)
+                                tm.begin_external_transaction(self.autocommit_isolation_level)
                             } else {
                                 Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
                             }
@@ -942,17 +2174,17 @@ where
                     self.current_transaction_id = None;
                     result
                 }
-                Statement::Select(select_stmt) => {
+                stmt @ (Statement::Select(_) | Statement::SelectForUpdate(_) | Statement::Union(_)) => {
                     // If we're in a transaction, execute the select statement in that transaction
                     if let Some(_) = self.current_transaction_id {
-                        return self.execute_statement_in_transaction(Statement::Select(select_stmt));
+                        return self.execute_statement_in_transaction(stmt);
                     }
 
                     // Otherwise, start an implicit read-only transaction
                     let tx_id = self.handle_begin_transaction()
                         .and_then(|_| {
                             if let Some(tm) = &mut self.transaction_manager {
-                                tm.begin_transaction(IsolationLevel::ReadCommitted)
+                                tm.begin_external_transaction(IsolationLevel::ReadCommitted)
                             } else {
                                 Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
                             }
@@ -960,7 +2192,7 @@ where
                     self.current_transaction_id = Some(tx_id);
 
                     // Execute the select statement
-                    let result = self.execute_statement_in_transaction(Statement::Select(select_stmt));
+                    let result = self.execute_statement_in_transaction(stmt);
 
                     // Commit or rollback based on the result
                     match &result {
@@ -991,7 +2223,46 @@ where
         }
     }
 
+    /// Runs `f` in an implicit transaction at the autocommit isolation level,
+    /// committing if it succeeds and rolling back otherwise.
+    fn with_implicit_transaction<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, ReefDBError>,
+    ) -> Result<T, ReefDBError> {
+        // Start an implicit transaction
+        let tx_id = self.handle_begin_transaction()
+            .and_then(|_| {
+                if let Some(tm) = &mut self.transaction_manager {
+                    tm.begin_external_transaction(self.autocommit_isolation_level)
+                } else {
+                    Err(ReefDBError::Other("Transaction manager not initialized".to_string()))
+                }
+            })?;
+        self.current_transaction_id = Some(tx_id);
+
+        let result = f(self);
+
+        // Commit or rollback based on the result
+        match &result {
+            Ok(_) => {
+                self.handle_commit()?;
+            }
+            Err(_) => {
+                // Best effort rollback - if it fails, we can't do much about it
+                if let Some(tx_id) = self.current_transaction_id {
+                    if let Some(tm) = &mut self.transaction_manager {
+                        let _ = tm.rollback_transaction(tx_id);
+                    }
+                }
+            }
+        }
+
+        self.current_transaction_id = None;
+        result
+    }
+
     fn execute_statement_in_transaction(&mut self, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
+        let stmt = self.resolve_subqueries(stmt)?;
         match stmt {
             Statement::Create(CreateStatement::Table(name, columns)) => {
                 self.handle_create(name, columns)
@@ -1001,26 +2272,42 @@ where
                     InsertStatement::IntoTable(table_name, values) => {
                         self.handle_insert(table_name, values)
                     }
+                    InsertStatement::Upsert(table_name, values, on_conflict) => {
+                        match self.resolve_upsert(table_name, values, on_conflict)? {
+                            Some(stmt) => self.execute_statement_in_transaction(stmt),
+                            None => Ok(ReefDBResult::Insert(0)),
+                        }
+                    }
                 }
             }
             Statement::Select(select_stmt) => {
                 match select_stmt {
-                    SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by) => {
-                        self.handle_select(table_ref, columns, where_clause, joins, order_by)
+                    SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by) => {
+                        self.handle_select(table_ref, columns, where_clause, joins, order_by, group_by)
                     }
                 }
             }
+            // A ReefDB has a single writer, so there is nobody to lock out
+            Statement::SelectForUpdate(select_stmt) => {
+                self.execute_statement_in_transaction(Statement::Select(select_stmt))
+            }
+            Statement::Union(union_stmt) => {
+                self.handle_union(union_stmt)
+            }
+            Statement::Returning { statement, columns } => {
+                self.handle_returning(*statement, columns)
+            }
             Statement::Update(update_stmt) => {
                 match update_stmt {
-                    UpdateStatement::UpdateTable(table_name, updates, where_clause) => {
-                        self.handle_update(table_name, updates, where_clause)
+                    UpdateStatement::UpdateTable(table_name, updates, where_clause, limit) => {
+                        self.handle_update(table_name, updates, where_clause, limit)
                     }
                 }
             }
             Statement::Delete(delete_stmt) => {
                 match delete_stmt {
-                    DeleteStatement::FromTable(table_name, where_clause) => {
-                        self.handle_delete(table_name, where_clause)
+                    DeleteStatement::FromTable(table_name, where_clause, limit) => {
+                        self.handle_delete(table_name, where_clause, limit)
                     }
                 }
             }
@@ -1046,11 +2333,38 @@ where
                 self.handle_release_savepoint(name)
             }
             Statement::BeginTransaction => {
-                Err(ReefDBError::Other("Cannot begin a transaction within another transaction".to_string()))
+                Err(ReefDBError::Other("Cannot begin a new transaction while another transaction is active".to_string()))
             }
             Statement::Commit => {
                 self.handle_commit()
             }
+            Statement::Rollback => {
+                self.rollback_explicit_transaction()?;
+                Ok(ReefDBResult::Rollback)
+            }
+            Statement::Reindex { table, column } => {
+                self.handle_reindex(table, column)
+            }
+            // Like REINDEX it rewrites indexes wholesale, which a rollback
+            // of an explicit transaction could not undo
+            Statement::Vacuum if self.transaction_snapshot.is_some() => {
+                Err(ReefDBError::Other("VACUUM cannot run inside a transaction block".to_string()))
+            }
+            Statement::Vacuum => {
+                self.handle_vacuum()
+            }
+            Statement::ShowTables => {
+                self.handle_show_tables()
+            }
+            Statement::Describe(table) => {
+                self.handle_describe(&table)
+            }
+            Statement::VerifyIndex { table, column } => {
+                self.handle_verify_index(&table, &column)
+            }
+            Statement::Explain { analyze, statement } => {
+                self.handle_explain(analyze, *statement)
+            }
         }
     }
 
@@ -1060,6 +2374,49 @@ where
         self.execute_statement(stmt)
     }
 
+    /// Runs `sql` like `query`, aborting it with `QueryTimeout` if it runs
+    /// for longer than `timeout`. The timeout replaces the one in the
+    /// `QueryLimits` for this query, and an implicit transaction the query
+    /// ran in is rolled back.
+    ///
+    /// The timeout is only checked while a SELECT scans rows, including the
+    /// SELECTs of subqueries. Writes run to completion once they start.
+    pub fn query_with_timeout(&mut self, sql: &str, timeout: Duration) -> Result<ReefDBResult, ReefDBError> {
+        let previous = self.query_deadline.replace(Some((Instant::now(), timeout)));
+        let result = self.query(sql);
+        self.query_deadline.set(previous);
+        result
+    }
+
+    /// Runs `sql` like `query`, also reporting how many rows it read and
+    /// returned, whether it used an index and how long it took
+    pub fn query_with_stats(&mut self, sql: &str) -> Result<(ReefDBResult, QueryStats), ReefDBError> {
+        let start = Instant::now();
+        self.query_stats.replace(Some(QueryStats::default()));
+        let result = self.query(sql);
+        let mut stats = self.query_stats.take().unwrap_or_default();
+        let result = result?;
+        stats.rows_returned = match &result {
+            ReefDBResult::Select(rows) | ReefDBResult::Returning(rows) => rows.len(),
+            _ => 0,
+        };
+        stats.duration = start.elapsed();
+        Ok((result, stats))
+    }
+
+    /// Recomputes the statistics `ts_rank` weighs terms with from every
+    /// full-text column as it is now. VACUUM refreshes them as well; until
+    /// either runs, every term counts the same.
+    pub fn refresh_fts_stats(&mut self) {
+        self.inverted_index.refresh_stats();
+    }
+
+    /// Forces every committed write to disk, whatever the storage's sync
+    /// settings
+    pub fn flush(&mut self) -> Result<(), ReefDBError> {
+        self.storage.flush()
+    }
+
     pub fn set_autocommit(&mut self, enabled: bool) {
         self.autocommit = enabled;
     }
@@ -1075,4 +2432,164 @@ where
     pub fn get_autocommit_isolation_level(&self) -> IsolationLevel {
         self.autocommit_isolation_level
     }
+
+    /// Logs a warning whenever a SELECT with a WHERE clause reads every row
+    /// of a table larger than the full scan threshold, pointing at
+    /// predicates that could use an index
+    pub fn set_warn_on_full_scan(&mut self, enabled: bool) {
+        self.warn_on_full_scan = enabled;
+    }
+
+    /// Sets how many rows a table needs for a full scan of it to be
+    /// warned about, `DEFAULT_FULL_SCAN_WARNING_ROWS` by default
+    pub fn set_full_scan_warning_rows(&mut self, rows: usize) {
+        self.full_scan_warning_rows = rows;
+    }
+
+    /// Sets the limits that abort runaway queries, see `QueryLimits`
+    pub fn set_query_limits(&mut self, query_limits: QueryLimits) {
+        if let Some(tm) = self.transaction_manager.as_mut() {
+            tm.set_query_limits(query_limits);
+        }
+    }
+
+    pub fn get_query_limits(&self) -> QueryLimits {
+        self.transaction_manager.as_ref()
+            .map(|tm| tm.get_query_limits())
+            .unwrap_or_default()
+    }
+
+    /// Sets how many savepoints a transaction may hold at once
+    pub fn set_max_savepoint_depth(&mut self, max_depth: usize) {
+        if let Some(tm) = self.transaction_manager.as_mut() {
+            tm.set_max_savepoint_depth(max_depth);
+        }
+    }
+
+    /// Ids of the transactions in flight, in ascending order
+    pub fn active_transaction_ids(&self) -> Vec<u64> {
+        self.transaction_manager.as_ref()
+            .map(|tm| tm.active_transaction_ids())
+            .unwrap_or_default()
+    }
+
+    /// Isolation level of a transaction in flight
+    pub fn transaction_isolation(&self, transaction_id: u64) -> Option<IsolationLevel> {
+        self.transaction_manager.as_ref()
+            .and_then(|tm| tm.transaction_isolation(transaction_id))
+    }
+
+    /// Transactions holding a lock on `table_name`, in ascending order
+    pub fn lock_holders(&self, table_name: &str) -> Vec<u64> {
+        self.transaction_manager.as_ref()
+            .map(|tm| tm.lock_holders(table_name))
+            .unwrap_or_default()
+    }
+
+    /// Sets a custom stop-word list for a TSVECTOR column and reindexes its rows.
+    /// When `extend_defaults` is true the words are added to the language's
+    /// built-in list, otherwise they replace it.
+    pub fn set_fts_stop_words(
+        &mut self,
+        table_name: &str,
+        column_name: &str,
+        stop_words: &[&str],
+        extend_defaults: bool,
+    ) -> Result<(), ReefDBError> {
+        let (schema, _) = self.get_table_schema(table_name)?;
+        let column = schema.iter()
+            .find(|c| c.name == column_name)
+            .ok_or_else(|| ReefDBError::ColumnNotFound(column_name.to_string()))?;
+        if column.data_type != DataType::TSVector {
+            return Err(ReefDBError::Other(format!(
+                "Column {} is not a TSVECTOR column", column_name
+            )));
+        }
+
+        let mut config = LanguageConfig::default();
+        if extend_defaults {
+            config.stop_words = EnglishProcessor::default_stop_words();
+        } else if stop_words.is_empty() {
+            config.enable_stop_words = false;
+        }
+        config.stop_words.extend(stop_words.iter().map(|w| w.to_lowercase()));
+
+        self.inverted_index.set_column_config(table_name, column_name, config);
+        self.reindex_fts_column(table_name, column_name)
+    }
+
+    /// Registers a tokenizer TSVECTOR columns can name with
+    /// `TOKENIZER 'name'`. Columns that already name it, e.g. after the
+    /// database was reopened, start using it and are reindexed.
+    pub fn register_tokenizer<T: Tokenizer + Send + Sync + 'static>(&mut self, name: &str, tokenizer: T) -> Result<(), ReefDBError> {
+        self.tokenizers.register(name, tokenizer)?;
+        self.attach_tokenizer(name)
+    }
+
+    /// Hands the tokenizer registered as `name` to every column naming it
+    /// and reindexes them
+    pub(crate) fn attach_tokenizer(&mut self, name: &str) -> Result<(), ReefDBError> {
+        let columns: Vec<(String, ColumnDef)> = self.storage.get_all_tables().iter()
+            .flat_map(|(table_name, (schema, _))| schema.iter()
+                .filter(|c| c.data_type == DataType::TSVector && column_tokenizer(c) == Some(name))
+                .map(move |c| (table_name.clone(), c.clone())))
+            .collect();
+        for (table_name, column) in columns {
+            self.add_fts_column(&table_name, &column);
+            self.reindex_fts_column(&table_name, &column.name)?;
+        }
+        Ok(())
+    }
+
+    /// Registers a TSVECTOR column with the inverted index, along with its
+    /// tokenizer if it names one that is registered
+    pub(crate) fn add_fts_column(&mut self, table_name: &str, column: &ColumnDef) {
+        self.inverted_index.add_column(table_name, &column.name);
+        if let Some(tokenizer) = column_tokenizer(column).and_then(|name| self.tokenizers.get(name)) {
+            self.inverted_index.set_column_tokenizer(table_name, &column.name, tokenizer);
+        }
+    }
+
+    /// Re-adds every row of a TSVECTOR column to the inverted index
+    fn reindex_fts_column(&mut self, table_name: &str, column_name: &str) -> Result<(), ReefDBError> {
+        let (schema, rows) = self.get_table_schema(table_name)?;
+        let col_idx = schema.iter()
+            .position(|c| c.name == column_name)
+            .ok_or_else(|| ReefDBError::ColumnNotFound(column_name.to_string()))?;
+
+        let documents: Vec<(usize, String)> = rows.iter()
+            .enumerate()
+            .filter_map(|(i, row)| match &row[col_idx] {
+                DataValue::Text(text) => Some((fts_document_id(i), text.clone())),
+                _ => None,
+            })
+            .collect();
+
+        self.inverted_index.clear_column(table_name, column_name);
+        for (row_id, text) in documents {
+            self.inverted_index.add_document(table_name, column_name, row_id, &text);
+        }
+        self.rebuild_gin_index(table_name, column_name)
+    }
+
+    /// Adds a TSVECTOR value to its column's GIN index, unless the index
+    /// has been dropped
+    pub(crate) fn add_gin_document(&mut self, table_name: &str, column_name: &str, row_id: usize, text: &str) -> Result<(), ReefDBError> {
+        if self.storage.get_index(table_name, column_name).is_err() {
+            return Ok(());
+        }
+        self.storage.update_index(table_name, column_name, Vec::new(), text.as_bytes().to_vec(), row_id)
+    }
+
+    /// Replaces the GIN index on a TSVECTOR column with one built from its
+    /// current rows
+    fn rebuild_gin_index(&mut self, table_name: &str, column_name: &str) -> Result<(), ReefDBError> {
+        let (schema, rows) = self.get_table_schema(table_name)?;
+        let col_idx = schema.iter()
+            .position(|c| c.name == column_name)
+            .ok_or_else(|| ReefDBError::ColumnNotFound(column_name.to_string()))?;
+        let gin = build_gin_index(table_name, column_name, rows, col_idx);
+        self.storage.drop_index(table_name, column_name);
+        self.storage.create_index(table_name, column_name, IndexType::GIN(gin))
+    }
 }
@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+/// Controls how long a transaction waits for a conflicting lock and how
+/// deadlocked statements are retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LockConfig {
+    /// Maximum time to wait for a conflicting lock to be released.
+    /// A zero timeout fails immediately with `LockConflict`.
+    pub lock_timeout: Duration,
+    /// Interval between lock acquisition attempts while waiting
+    pub poll_interval: Duration,
+    /// Number of times a statement is retried after a deadlock
+    pub max_retries: u32,
+    /// Base delay for the exponential backoff between deadlock retries
+    pub retry_backoff: Duration,
+}
+
+impl Default for LockConfig {
+    fn default() -> Self {
+        LockConfig {
+            lock_timeout: Duration::ZERO,
+            poll_interval: Duration::from_millis(5),
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(10),
+        }
+    }
+}
+
+impl LockConfig {
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+}
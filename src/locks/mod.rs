@@ -1,5 +1,7 @@
+mod config;
 mod manager;
 mod types;
 
+pub use config::LockConfig;
 pub use manager::LockManager;
-pub use types::LockType;
\ No newline at end of file
+pub use types::LockType;
@@ -0,0 +1,76 @@
+//! Schema versions for applications that upgrade their database as they
+//! upgrade themselves. Each applied migration is recorded in the
+//! `_reefdb_migrations` table, which is created by the first one. SQL has
+//! to quote its name, as in `SELECT * FROM "_reefdb_migrations"`.
+
+use std::any::Any;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::sql::column_def::ColumnDef;
+use crate::sql::constraints::constraint::Constraint;
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+use crate::sql::parser::Parser;
+use crate::sql::statements::{create::CreateStatement, insert::InsertStatement, Statement};
+use crate::storage::Storage;
+use crate::transaction::IsolationLevel;
+use crate::ReefDB;
+
+/// The table applied migrations are recorded in, one row per version
+pub const MIGRATIONS_TABLE: &str = "_reefdb_migrations";
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Runs the statements in `sql`, separated by semicolons, as migration
+    /// `version` and records it, all in one transaction: if a statement
+    /// fails nothing is applied. A version that was already applied is
+    /// skipped. Returns whether the migration ran.
+    pub fn apply_migration(&mut self, version: i64, sql: &str) -> Result<bool, ReefDBError> {
+        let statements = Parser::parse_script(sql)?;
+        if self.applied_migrations().contains(&version) {
+            return Ok(false);
+        }
+
+        let create_table = !self.storage.table_exists(MIGRATIONS_TABLE);
+        let mut tx = self.begin(IsolationLevel::Serializable)?;
+        if create_table {
+            tx.execute(Statement::Create(CreateStatement::Table(
+                MIGRATIONS_TABLE.to_string(),
+                vec![ColumnDef::new("version", DataType::Integer, vec![Constraint::PrimaryKey])],
+            )))?;
+        }
+        for stmt in statements {
+            tx.execute(stmt)?;
+        }
+        tx.execute(Statement::Insert(InsertStatement::IntoTable(
+            MIGRATIONS_TABLE.to_string(),
+            vec![DataValue::Integer(version)],
+        )))?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Versions of the migrations applied so far, in ascending order
+    pub fn applied_migrations(&self) -> Vec<i64> {
+        let Ok(rows) = self.scan_table(MIGRATIONS_TABLE) else {
+            return Vec::new();
+        };
+        let mut versions: Vec<i64> = rows
+            .filter_map(|(_, row)| match row.first() {
+                Some(DataValue::Integer(version)) => Some(*version),
+                _ => None,
+            })
+            .collect();
+        versions.sort_unstable();
+        versions
+    }
+
+    /// The highest migration version applied, `None` before the first one
+    pub fn current_schema_version(&self) -> Option<i64> {
+        self.applied_migrations().last().copied()
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::time::SystemTime;
 use crate::error::ReefDBError;
 use crate::sql::data_value::DataValue;
@@ -11,6 +11,7 @@ pub struct MVCCManager {
     version_store: VersionStore,
     transaction_state: TransactionState,
     tables: HashSet<String>,
+    read_snapshots: HashMap<u64, SystemTime>,
 }
 
 impl MVCCManager {
@@ -19,6 +20,7 @@ impl MVCCManager {
             version_store: VersionStore::new(),
             transaction_state: TransactionState::new(),
             tables: HashSet::new(),
+            read_snapshots: HashMap::new(),
         }
     }
 
@@ -30,8 +32,38 @@ impl MVCCManager {
         self.transaction_state.set_isolation_level(transaction_id, isolation_level);
     }
 
+    /// Fails with `SerializationFailure` when a Serializable transaction
+    /// wrote a row that another transaction committed after it started
+    pub fn check_write_conflicts(&self, transaction_id: u64) -> Result<(), ReefDBError> {
+        if self.transaction_state.get_isolation_level(transaction_id) != Some(IsolationLevel::Serializable) {
+            return Ok(());
+        }
+        let (Some(keys), Some(start_time)) = (
+            self.transaction_state.get_transaction_writes(transaction_id),
+            self.transaction_state.get_transaction_start_time(transaction_id),
+        ) else {
+            return Ok(());
+        };
+
+        let committed_transactions = self.transaction_state.get_committed_transactions();
+        for key in keys {
+            let conflict = self.version_store.get_versions(key).is_some_and(|versions| versions.iter().any(|v| {
+                v.transaction_id != transaction_id
+                    && v.timestamp > start_time
+                    && committed_transactions.contains(&v.transaction_id)
+            }));
+            if conflict {
+                return Err(ReefDBError::SerializationFailure(format!(
+                    "{} was modified by a concurrent transaction", key
+                )));
+            }
+        }
+        Ok(())
+    }
+
     pub fn commit(&mut self, transaction_id: u64) -> Result<(), ReefDBError> {
         println!("[DEBUG] Committing transaction {}", transaction_id);
+        self.check_write_conflicts(transaction_id)?;
         // Update the timestamp for all versions of this transaction
         if let Some(keys) = self.transaction_state.get_transaction_writes(transaction_id) {
             println!("[DEBUG] Found keys to update for transaction {}: {:?}", transaction_id, keys);
@@ -71,6 +103,7 @@ impl MVCCManager {
                 }
             }
         }
+        self.read_snapshots.remove(&transaction_id);
         // First commit the transaction to update its state
         self.transaction_state.commit_transaction(transaction_id)?;
         println!("[DEBUG] Transaction {} committed successfully", transaction_id);
@@ -81,6 +114,7 @@ impl MVCCManager {
         if let Some(keys) = self.transaction_state.get_transaction_writes(transaction_id) {
             self.version_store.remove_transaction_versions(keys, transaction_id);
         }
+        self.read_snapshots.remove(&transaction_id);
         self.transaction_state.rollback_transaction(transaction_id)
     }
 
@@ -133,6 +167,29 @@ impl MVCCManager {
         }
     }
 
+    /// Reads the value as of the transaction's first read, ignoring versions
+    /// committed afterwards. The transaction's own writes are always visible.
+    pub fn read_repeatable(&mut self, transaction_id: u64, key: &str) -> Result<Option<Vec<DataValue>>, ReefDBError> {
+        let snapshot_time = *self.read_snapshots
+            .entry(transaction_id)
+            .or_insert_with(SystemTime::now);
+
+        if let Some(KeyFormat::Row { table_name, version: _, primary_key }) = KeyFormat::parse(key) {
+            let base_key = KeyFormat::row(&table_name, 0, &primary_key);
+
+            if let Some(version) = self.version_store.get_version_for_transaction(&base_key, transaction_id) {
+                return Ok(Some(version.value.clone()));
+            }
+
+            let committed_transactions = self.transaction_state.get_committed_transactions();
+            Ok(self.version_store
+                .get_latest_committed_version_before(&base_key, committed_transactions, snapshot_time)
+                .map(|version| version.value.clone()))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub fn read_uncommitted(&self, key: &str) -> Result<Option<Vec<DataValue>>, ReefDBError> {
         if let Some(KeyFormat::Row { table_name, version: _, primary_key }) = KeyFormat::parse(key) {
             let base_key = KeyFormat::row(&table_name, 0, &primary_key);
@@ -148,6 +205,28 @@ impl MVCCManager {
         }
     }
 
+    /// The active transaction that started first, which bounds how far back
+    /// `gc` has to keep versions
+    pub fn oldest_active_transaction(&self) -> Option<u64> {
+        self.transaction_state.oldest_active_transaction()
+    }
+
+    /// Removes the versions no active transaction can see anymore: those
+    /// superseded by a commit before `oldest_active_tx` started, or by the
+    /// latest commit when no transaction is active. Returns how many were removed.
+    pub fn gc(&mut self, oldest_active_tx: Option<u64>) -> usize {
+        let horizon = oldest_active_tx
+            .and_then(|id| self.transaction_state.get_transaction_start_time(id))
+            .unwrap_or_else(SystemTime::now);
+        let committed_transactions = self.transaction_state.get_committed_transactions();
+        self.version_store.gc(committed_transactions, horizon)
+    }
+
+    /// Number of row versions currently held
+    pub fn version_count(&self) -> usize {
+        self.version_store.version_count()
+    }
+
     pub fn is_active(&self, transaction_id: u64) -> bool {
         self.transaction_state.is_transaction_active(transaction_id)
     }
@@ -284,6 +363,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_read_repeatable_ignores_later_commits() -> Result<(), ReefDBError> {
+        let mut manager = MVCCManager::new();
+        let key = KeyFormat::row("users", 1, "1");
+
+        manager.begin_transaction(1);
+        manager.write(1, key.clone(), vec![DataValue::Integer(1)])?;
+        manager.commit(1)?;
+
+        manager.begin_transaction(2);
+        manager.begin_transaction(3);
+        assert_eq!(manager.read_repeatable(2, &key)?, Some(vec![DataValue::Integer(1)]));
+
+        thread::sleep(Duration::from_millis(10));
+        manager.write(3, key.clone(), vec![DataValue::Integer(2)])?;
+        manager.commit(3)?;
+
+        // Transaction 2 keeps its snapshot, a committed read sees the new value
+        assert_eq!(manager.read_repeatable(2, &key)?, Some(vec![DataValue::Integer(1)]));
+        assert_eq!(manager.read_committed(2, &key)?, Some(vec![DataValue::Integer(2)]));
+
+        Ok(())
+    }
+
     #[test]
     fn test_rollback() -> Result<(), ReefDBError> {
         let mut manager = MVCCManager::new();
@@ -315,4 +418,38 @@ mod tests {
         
         Ok(())
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_gc_reclaims_old_versions() -> Result<(), ReefDBError> {
+        let mut manager = MVCCManager::new();
+        let key = KeyFormat::row("users", 0, "1");
+
+        manager.begin_transaction(1);
+        manager.write(1, key.clone(), vec![DataValue::Integer(0)])?;
+        manager.commit(1)?;
+
+        // A long-running reader that has to keep seeing the first value
+        manager.begin_transaction(2);
+        manager.set_isolation_level(2, IsolationLevel::RepeatableRead);
+        assert_eq!(manager.read_repeatable(2, &key)?, Some(vec![DataValue::Integer(0)]));
+
+        for i in 1..=20u64 {
+            thread::sleep(Duration::from_millis(1));
+            manager.begin_transaction(100 + i);
+            manager.write(100 + i, key.clone(), vec![DataValue::Integer(i as i64)])?;
+            manager.commit(100 + i)?;
+        }
+        assert_eq!(manager.version_count(), 21);
+
+        // Everything committed after the reader started stays
+        assert_eq!(manager.oldest_active_transaction(), Some(2));
+        assert_eq!(manager.gc(manager.oldest_active_transaction()), 0);
+        assert_eq!(manager.read_repeatable(2, &key)?, Some(vec![DataValue::Integer(0)]));
+
+        manager.commit(2)?;
+        assert_eq!(manager.gc(manager.oldest_active_transaction()), 20);
+        assert_eq!(manager.version_count(), 1);
+        assert_eq!(manager.read_committed(3, &key)?, Some(vec![DataValue::Integer(20)]));
+        Ok(())
+    }
+}
@@ -114,6 +114,14 @@ impl TransactionState {
     pub fn get_transaction_start_time(&self, transaction_id: u64) -> Option<SystemTime> {
         self.transaction_timestamps.get(&transaction_id).cloned()
     }
+
+    /// The active transaction that started first
+    pub fn oldest_active_transaction(&self) -> Option<u64> {
+        self.active_transactions.iter()
+            .filter_map(|id| self.transaction_timestamps.get(id).map(|started| (started, *id)))
+            .min()
+            .map(|(_, id)| id)
+    }
 }
 
 #[cfg(test)]
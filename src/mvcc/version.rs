@@ -171,6 +171,34 @@ impl VersionStore {
             .and_then(|versions| versions.iter()
                 .find(|v| committed_transactions.contains(&v.transaction_id) && v.timestamp <= start_time))
     }
+
+    /// Drops the committed versions no transaction started after `horizon`
+    /// can see: every one at or before it except the newest. Uncommitted
+    /// versions are kept. Returns how many versions were removed.
+    pub fn gc(&mut self, committed_transactions: &HashSet<u64>, horizon: SystemTime) -> usize {
+        let mut removed = 0;
+        for versions in self.versions.values_mut() {
+            let before = versions.len();
+            // Versions are sorted newest first, so the first committed one at
+            // or before the horizon is the one still visible there
+            let mut seen_visible = false;
+            versions.retain(|v| {
+                if !committed_transactions.contains(&v.transaction_id) || v.timestamp > horizon {
+                    return true;
+                }
+                let keep = !seen_visible;
+                seen_visible = true;
+                keep
+            });
+            removed += before - versions.len();
+        }
+        removed
+    }
+
+    /// Total number of versions held across all keys
+    pub fn version_count(&self) -> usize {
+        self.versions.values().map(Vec::len).sum()
+    }
 }
 
 #[cfg(test)]
@@ -343,4 +371,31 @@ mod tests {
         // Verify key was completely removed since all versions were for transaction 1
         assert!(store.get_versions(&key).is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_gc_keeps_versions_visible_after_horizon() {
+        let mut store = VersionStore::new();
+        let committed: HashSet<u64> = [1, 2, 3].into_iter().collect();
+        let key = "test_key".to_string();
+        let start = SystemTime::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        store.store_version(key.clone(), Version::with_timestamp(1, vec![DataValue::Integer(1)], at(10)));
+        store.store_version(key.clone(), Version::with_timestamp(2, vec![DataValue::Integer(2)], at(20)));
+        store.store_version(key.clone(), Version::with_timestamp(3, vec![DataValue::Integer(3)], at(40)));
+        store.store_version(key.clone(), Version::with_timestamp(4, vec![DataValue::Integer(4)], at(50)));
+        assert_eq!(store.version_count(), 4);
+
+        // A transaction that started at 30ms still needs version 2; version 1
+        // is hidden from everyone
+        assert_eq!(store.gc(&committed, at(30)), 1);
+        let ids: Vec<u64> = store.get_versions(&key).unwrap().iter().map(|v| v.transaction_id).collect();
+        assert_eq!(ids, vec![4, 3, 2]);
+
+        // With no one looking back, only the latest committed and the
+        // uncommitted version survive
+        assert_eq!(store.gc(&committed, at(60)), 1);
+        assert_eq!(store.get_latest_committed_version_before(&key, &committed, at(60)).unwrap().transaction_id, 3);
+        assert_eq!(store.version_count(), 2);
+    }
+}
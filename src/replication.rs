@@ -0,0 +1,140 @@
+//! Read replicas fed from a leader's write-ahead log.
+//!
+//! `ReefDB::replicate` hands out a channel that receives, on every commit,
+//! that transaction's WAL entries followed by its commit record, each
+//! bincode-serialized. A `Follower` applies them to its own
+//! database one whole transaction at a time and refuses writes of its own.
+
+use std::any::Any;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::result::ReefDBResult;
+use crate::sql::data_type::DataType;
+use crate::sql::parser::Parser;
+use crate::storage::Storage;
+use crate::wal::{replay, WALEntry, WALOperation};
+use crate::ReefDB;
+
+pub struct Follower<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    db: ReefDB<S, FTS>,
+    /// Entries of the transaction being received, applied on its commit
+    pending: Vec<WALEntry>,
+    applied_transactions: usize,
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> Follower<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Follows a leader starting from `db`, which should hold the leader's
+    /// state as of the moment `replicate` was called
+    pub fn new(db: ReefDB<S, FTS>) -> Self {
+        Follower { db, pending: Vec::new(), applied_transactions: 0 }
+    }
+
+    pub fn database(&self) -> &ReefDB<S, FTS> {
+        &self.db
+    }
+
+    /// Number of leader transactions applied so far
+    pub fn applied_transactions(&self) -> usize {
+        self.applied_transactions
+    }
+
+    /// Applies one entry shipped by the leader. Mutations are held back
+    /// until their transaction's commit record arrives.
+    pub fn apply(&mut self, payload: &[u8]) -> Result<(), ReefDBError> {
+        let entry: WALEntry = bincode::deserialize(payload)?;
+        match entry.operation {
+            WALOperation::Commit => {
+                let entries = std::mem::take(&mut self.pending);
+                self.db.apply_replicated_entries(&entries)?;
+                self.applied_transactions += 1;
+            }
+            WALOperation::Rollback | WALOperation::RollbackToSavepoint => self.pending.clear(),
+            WALOperation::Checkpoint => {}
+            _ => self.pending.push(entry),
+        }
+        Ok(())
+    }
+
+    /// Applies whatever the leader has shipped so far without waiting for
+    /// more, returning the number of entries applied
+    pub fn catch_up(&mut self, receiver: &Receiver<Vec<u8>>) -> Result<usize, ReefDBError> {
+        let mut applied = 0;
+        loop {
+            match receiver.try_recv() {
+                Ok(payload) => {
+                    self.apply(&payload)?;
+                    applied += 1;
+                }
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => return Ok(applied),
+            }
+        }
+    }
+
+    /// Applies entries as they arrive until the leader goes away
+    pub fn run(&mut self, receiver: Receiver<Vec<u8>>) -> Result<(), ReefDBError> {
+        for payload in receiver {
+            self.apply(&payload)?;
+        }
+        Ok(())
+    }
+
+    /// Runs a statement that only reads. Anything that would modify the
+    /// database fails with `ReadOnlyReplica`.
+    pub fn query(&mut self, sql: &str) -> Result<ReefDBResult, ReefDBError> {
+        let stmt = Parser::parse_sql(sql)?;
+        if !stmt.is_read_only() {
+            return Err(ReefDBError::ReadOnlyReplica);
+        }
+        self.db.execute_statement(stmt)
+    }
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Registers a follower of this database and returns the channel its
+    /// `Follower` reads from. The follower should start from a copy of the
+    /// database as it is now.
+    pub fn replicate(&mut self) -> Result<Receiver<Vec<u8>>, ReefDBError> {
+        let tm = self.transaction_manager.as_mut()
+            .ok_or_else(|| ReefDBError::Other("Transaction manager not initialized".to_string()))?;
+        Ok(tm.replicate())
+    }
+
+    /// Applies the mutations of one committed leader transaction
+    fn apply_replicated_entries(&mut self, entries: &[WALEntry]) -> Result<(), ReefDBError> {
+        for entry in entries {
+            replay::apply_entry(&mut self.storage, entry)?;
+            replay::apply_entry(&mut self.tables, entry)?;
+        }
+
+        // Keep the full-text index in step with the touched tables
+        let mut touched: Vec<&str> = entries.iter().map(|entry| entry.table_name.as_str()).collect();
+        touched.sort_unstable();
+        touched.dedup();
+        for table_name in touched {
+            let fts_columns = match self.storage.get_table_ref(table_name) {
+                Some((columns, _)) => columns.iter()
+                    .filter(|c| c.data_type == DataType::TSVector)
+                    .cloned()
+                    .collect::<Vec<_>>(),
+                None => continue,
+            };
+            for column in fts_columns {
+                self.add_fts_column(table_name, &column);
+                self.reindex_fts_column(table_name, &column.name)?;
+            }
+        }
+        Ok(())
+    }
+}
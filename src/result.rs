@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+use std::fmt;
 use std::ops::Index;
 
 use crate::sql::data_value::DataValue;
@@ -6,9 +8,12 @@ use crate::sql::column::Column;
 use crate::sql::column_def::ColumnDef;
 use crate::sql::constraints::constraint::Constraint;
 use crate::sql::column::ColumnType;
+use crate::sql::clauses::wheres::where_type::CaseExpression;
 use crate::error::ReefDBError;
+use crate::functions::{aggregate_argument, Aggregate};
+use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnInfo {
     pub name: String,
     pub data_type: DataType,
@@ -17,6 +22,60 @@ pub struct ColumnInfo {
 }
 
 impl ColumnInfo {
+    /// Describes a function column. Aggregates take their type from the
+    /// column they fold; other functions are assumed to produce a nullable
+    /// Float, as the ranking functions do.
+    fn for_function(col: &Column, args: &[DataValue], schemas: &[&[ColumnDef]]) -> ColumnInfo {
+        let Some(aggregate) = Aggregate::of_column(col) else {
+            return ColumnInfo {
+                name: col.name.clone(),
+                data_type: DataType::Float,
+                table: None,
+                nullable: true,
+            };
+        };
+        let arg_type = match aggregate_argument(args) {
+            Some((DataValue::Text(arg), _)) => {
+                let name = arg.rsplit('.').next().unwrap_or(arg);
+                schemas.iter()
+                    .flat_map(|schema| schema.iter())
+                    .find(|c| c.name == name)
+                    .map(|c| &c.data_type)
+            }
+            _ => None,
+        };
+        ColumnInfo {
+            name: col.name.clone(),
+            data_type: aggregate.result_type(arg_type),
+            table: None,
+            nullable: aggregate.is_nullable(),
+        }
+    }
+
+    /// Describes a CASE column, typed after its first non-NULL literal
+    /// value. Without an ELSE it is NULL when no branch matches.
+    fn for_case(col: &Column, case: &CaseExpression) -> ColumnInfo {
+        let values: Vec<&DataValue> = case.branches.iter()
+            .map(|(_, value)| value)
+            .chain(case.else_value.as_ref())
+            .collect();
+        let literal_type = |value: &DataValue| match value {
+            DataValue::Text(_) => Some(DataType::Text),
+            DataValue::Integer(_) => Some(DataType::Integer),
+            DataValue::Boolean(_) => Some(DataType::Boolean),
+            DataValue::Float(_) => Some(DataType::Float),
+            DataValue::Date(_) => Some(DataType::Date),
+            DataValue::Timestamp(_) => Some(DataType::Timestamp),
+            _ => None,
+        };
+        ColumnInfo {
+            name: col.name.clone(),
+            data_type: values.iter().find_map(|value| literal_type(value)).unwrap_or(DataType::Null),
+            table: None,
+            nullable: case.else_value.is_none() || values.iter().any(|value| literal_type(value).is_none()),
+        }
+    }
+
     pub fn from_schema_and_columns(
         schema: &[ColumnDef],
         columns: &[Column],
@@ -45,15 +104,8 @@ impl ColumnInfo {
                             nullable: schema_col.constraints.iter().all(|c| !matches!(c, Constraint::NotNull)),
                         })
                     },
-                    ColumnType::Function(name, args) => {
-                        // For function-generated columns, assume they are nullable and use Float type for ranking functions
-                        Ok(ColumnInfo {
-                            name: col.name.clone(),
-                            data_type: DataType::Float,
-                            table: None,
-                            nullable: true,
-                        })
-                    },
+                    ColumnType::Function(_, args) => Ok(Self::for_function(col, args, &[schema])),
+                    ColumnType::Case(case) => Ok(Self::for_case(col, case)),
                     ColumnType::Wildcard => unreachable!("Wildcard should be handled by the first branch"),
                 }
             }).collect()
@@ -119,39 +171,31 @@ impl ColumnInfo {
                                 Err(ReefDBError::TableNotFound(table.clone()))
                             }
                         } else {
-                            // Try to find column in main schema first
-                            if let Some(schema_col) = main_schema.iter().find(|c| c.name == col.name) {
-                                Ok(ColumnInfo {
+                            // An unqualified name has to belong to exactly one table
+                            let mut matches = std::iter::once((main_table, main_schema))
+                                .chain(joined_tables.iter().copied())
+                                .filter_map(|(table_name, schema)| schema.iter()
+                                    .find(|c| c.name == col.name)
+                                    .map(|schema_col| (table_name, schema_col)));
+                            match (matches.next(), matches.next()) {
+                                (Some((table_name, schema_col)), None) => Ok(ColumnInfo {
                                     name: col.name.clone(),
                                     data_type: schema_col.data_type.clone(),
-                                    table: Some(main_table.to_string()),
+                                    table: Some(table_name.to_string()),
                                     nullable: schema_col.constraints.iter().all(|c| !matches!(c, Constraint::NotNull)),
-                                })
-                            } else {
-                                // Try joined tables
-                                for (table_name, schema) in joined_tables {
-                                    if let Some(schema_col) = schema.iter().find(|c| c.name == col.name) {
-                                        return Ok(ColumnInfo {
-                                            name: col.name.clone(),
-                                            data_type: schema_col.data_type.clone(),
-                                            table: Some(table_name.to_string()),
-                                            nullable: schema_col.constraints.iter().all(|c| !matches!(c, Constraint::NotNull)),
-                                        });
-                                    }
-                                }
-                                Err(ReefDBError::ColumnNotFound(col.name.clone()))
+                                }),
+                                (Some(_), Some(_)) => Err(ReefDBError::AmbiguousColumn(col.name.clone())),
+                                (None, _) => Err(ReefDBError::ColumnNotFound(col.name.clone())),
                             }
                         }
                     },
-                    ColumnType::Function(name, args) => {
-                        // For function-generated columns, assume they are nullable and use Float type for ranking functions
-                        Ok(ColumnInfo {
-                            name: col.name.clone(),
-                            data_type: DataType::Float,
-                            table: None,
-                            nullable: true,
-                        })
+                    ColumnType::Function(_, args) => {
+                        let schemas: Vec<&[ColumnDef]> = std::iter::once(main_schema)
+                            .chain(joined_tables.iter().map(|(_, schema)| *schema))
+                            .collect();
+                        Ok(Self::for_function(col, args, &schemas))
                     },
+                    ColumnType::Case(case) => Ok(Self::for_case(col, case)),
                     ColumnType::Wildcard => unreachable!("Wildcard should be handled by the first branch"),
                 }
             }).collect()
@@ -159,9 +203,13 @@ impl ColumnInfo {
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
 pub struct QueryResult {
     pub columns: Vec<ColumnInfo>,
+    /// Each row with the id of the stored row it came from. Without ORDER
+    /// BY rows are in stored order, which is insertion order, and joined
+    /// rows follow the main table's rows; rows ORDER BY ranks equal keep
+    /// that order.
     pub rows: Vec<(usize, Vec<DataValue>)>,
     pub row_count: usize,
 }
@@ -208,9 +256,122 @@ impl QueryResult {
     pub fn get_column_by_name(&self, name: &str) -> Option<&ColumnInfo> {
         self.columns.iter().find(|col| col.name == name)
     }
+
+    /// Position of a column in each row. `name` may be qualified as
+    /// `table.column`; an unqualified name must match exactly one column.
+    pub fn column_index(&self, name: &str) -> Result<usize, ReefDBError> {
+        let (table, column) = match name.split_once('.') {
+            Some((table, column)) => (Some(table), column),
+            None => (None, name),
+        };
+        let mut matches = self.columns.iter().enumerate().filter(|(_, info)| {
+            info.name == column && table.is_none_or(|t| info.table.as_deref() == Some(t))
+        });
+        match (matches.next(), matches.next()) {
+            (Some((idx, _)), None) => Ok(idx),
+            (Some(_), Some(_)) => Err(ReefDBError::AmbiguousColumn(name.to_string())),
+            (None, _) => Err(ReefDBError::ColumnNotFound(name.to_string())),
+        }
+    }
+
+    /// The value of `column` in row `row`
+    pub fn get_value(&self, row: usize, column: &str) -> Result<&DataValue, ReefDBError> {
+        let idx = self.column_index(column)?;
+        self.rows.get(row)
+            .and_then(|(_, values)| values.get(idx))
+            .ok_or_else(|| ReefDBError::Other(format!("Row {} out of range ({} rows)", row, self.row_count)))
+    }
+
+    fn get_typed<'a, T>(
+        &'a self,
+        row: usize,
+        column: &str,
+        expected: &str,
+        extract: impl Fn(&'a DataValue) -> Option<T>,
+    ) -> Result<Option<T>, ReefDBError> {
+        match self.get_value(row, column)? {
+            DataValue::Null => Ok(None),
+            value => extract(value).map(Some).ok_or_else(|| ReefDBError::Other(format!(
+                "Column {} is not {}: found {:?}", column, expected, value
+            ))),
+        }
+    }
+
+    /// Reads an INTEGER column, `None` if the value is NULL
+    pub fn get_int(&self, row: usize, column: &str) -> Result<Option<i64>, ReefDBError> {
+        self.get_typed(row, column, "an INTEGER", |v| match v {
+            DataValue::Integer(i) => Some(*i),
+            _ => None,
+        })
+    }
+
+    /// Reads a FLOAT column, `None` if the value is NULL
+    pub fn get_float(&self, row: usize, column: &str) -> Result<Option<f64>, ReefDBError> {
+        self.get_typed(row, column, "a FLOAT", |v| match v {
+            DataValue::Float(f) => Some(*f),
+            _ => None,
+        })
+    }
+
+    /// Reads a TEXT column, `None` if the value is NULL
+    pub fn get_text(&self, row: usize, column: &str) -> Result<Option<&str>, ReefDBError> {
+        self.get_typed(row, column, "TEXT", |v| match v {
+            DataValue::Text(s) => Some(s.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Reads a BOOLEAN column, `None` if the value is NULL
+    pub fn get_bool(&self, row: usize, column: &str) -> Result<Option<bool>, ReefDBError> {
+        self.get_typed(row, column, "a BOOLEAN", |v| match v {
+            DataValue::Boolean(b) => Some(*b),
+            _ => None,
+        })
+    }
+
+    /// Appends the rows of `other` for `UNION [ALL]`. Both sides must have the
+    /// same number of columns with matching types; the column metadata of
+    /// `self` is kept. Unless `all` is set, duplicate rows are dropped,
+    /// keeping the first occurrence.
+    pub fn union(mut self, other: QueryResult, all: bool) -> Result<QueryResult, ReefDBError> {
+        if self.columns.len() != other.columns.len() {
+            return Err(ReefDBError::ArityMismatch(format!(
+                "UNION requires both sides to have the same number of columns, got {} and {}",
+                self.columns.len(),
+                other.columns.len()
+            )));
+        }
+        for (left, right) in self.columns.iter().zip(&other.columns) {
+            if left.data_type != right.data_type {
+                return Err(ReefDBError::TypeMismatch(format!(
+                    "UNION column {} is {:?} but {} is {:?}",
+                    left.name, left.data_type, right.name, right.data_type
+                )));
+            }
+        }
+
+        self.rows.extend(other.rows);
+        if !all {
+            let mut seen = BTreeSet::new();
+            self.rows.retain(|(_, row)| seen.insert(row.clone()));
+        }
+        self.row_count = self.rows.len();
+        Ok(self)
+    }
+
+    /// Renders the rows as a JSON array of objects keyed by column name
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Array(self.rows.iter().map(|(_, row)| {
+            let object = self.columns.iter()
+                .zip(row.iter())
+                .map(|(column, value)| (column.name.clone(), value.to_json()))
+                .collect::<serde_json::Map<_, _>>();
+            serde_json::Value::Object(object)
+        }).collect())
+    }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
 pub enum ReefDBResult {
     Select(QueryResult),
     Insert(usize),
@@ -226,4 +387,121 @@ pub enum ReefDBResult {
     ReleaseSavepoint,
     BeginTransaction,
     Commit,
+    Rollback,
+    Reindex,
+    Vacuum,
+    /// The rows touched by an UPDATE or DELETE with a RETURNING clause
+    Returning(QueryResult),
+}
+
+/// Renders the rows as an aligned, bordered table with a row count, numbers
+/// right-aligned and everything else left-aligned
+impl fmt::Display for QueryResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.rows.iter()
+            .map(|(_, row)| row.len())
+            .fold(self.columns.len(), usize::max);
+        let headers: Vec<String> = (0..width)
+            .map(|i| self.columns.get(i).map_or_else(|| format!("column{}", i + 1), |c| c.name.clone()))
+            .collect();
+        let rows: Vec<Vec<(String, bool)>> = self.rows.iter().map(|(_, row)| {
+            (0..width).map(|i| match row.get(i) {
+                Some(value) => (value.to_string(), matches!(value, DataValue::Integer(_) | DataValue::Float(_))),
+                None => (String::new(), false),
+            }).collect()
+        }).collect();
+
+        let widths: Vec<usize> = (0..width).map(|i| {
+            rows.iter()
+                .map(|row| row[i].0.chars().count())
+                .fold(headers[i].chars().count(), usize::max)
+        }).collect();
+        let border: String = widths.iter()
+            .map(|w| "-".repeat(w + 2))
+            .collect::<Vec<_>>()
+            .join("+");
+        let border = format!("+{}+", border);
+
+        let write_row = |f: &mut fmt::Formatter<'_>, cells: &[(String, bool)]| -> fmt::Result {
+            write!(f, "|")?;
+            for ((text, right_align), w) in cells.iter().zip(&widths) {
+                if *right_align {
+                    write!(f, " {:>w$} |", text, w = w)?;
+                } else {
+                    write!(f, " {:<w$} |", text, w = w)?;
+                }
+            }
+            writeln!(f)
+        };
+
+        writeln!(f, "{}", border)?;
+        let header_cells: Vec<(String, bool)> = headers.into_iter().map(|h| (h, false)).collect();
+        write_row(f, &header_cells)?;
+        writeln!(f, "{}", border)?;
+        for row in &rows {
+            write_row(f, row)?;
+        }
+        writeln!(f, "{}", border)?;
+        write!(f, "({} row{})", self.rows.len(), if self.rows.len() == 1 { "" } else { "s" })
+    }
+}
+
+/// Query results render as tables; other statements as a status line such
+/// as `UPDATE 3`
+impl fmt::Display for ReefDBResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReefDBResult::Select(result) | ReefDBResult::Returning(result) => write!(f, "{}", result),
+            ReefDBResult::Insert(count) => write!(f, "INSERT {}", count),
+            ReefDBResult::Update(count) => write!(f, "UPDATE {}", count),
+            ReefDBResult::Delete(count) => write!(f, "DELETE {}", count),
+            ReefDBResult::CreateTable => write!(f, "CREATE TABLE"),
+            ReefDBResult::AlterTable => write!(f, "ALTER TABLE"),
+            ReefDBResult::DropTable => write!(f, "DROP TABLE"),
+            ReefDBResult::CreateIndex => write!(f, "CREATE INDEX"),
+            ReefDBResult::DropIndex => write!(f, "DROP INDEX"),
+            ReefDBResult::Savepoint => write!(f, "SAVEPOINT"),
+            ReefDBResult::RollbackToSavepoint => write!(f, "ROLLBACK TO SAVEPOINT"),
+            ReefDBResult::ReleaseSavepoint => write!(f, "RELEASE SAVEPOINT"),
+            ReefDBResult::BeginTransaction => write!(f, "BEGIN"),
+            ReefDBResult::Commit => write!(f, "COMMIT"),
+            ReefDBResult::Rollback => write!(f, "ROLLBACK"),
+            ReefDBResult::Reindex => write!(f, "REINDEX"),
+            ReefDBResult::Vacuum => write!(f, "VACUUM"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_select() {
+        let columns = vec![
+            ColumnInfo { name: "id".to_string(), data_type: DataType::Integer, table: None, nullable: false },
+            ColumnInfo { name: "name".to_string(), data_type: DataType::Text, table: None, nullable: true },
+        ];
+        let result = ReefDBResult::Select(QueryResult::with_columns(vec![
+            (0, vec![DataValue::Integer(1), DataValue::Text("Alice".to_string())]),
+            (1, vec![DataValue::Integer(10), DataValue::Null]),
+        ], columns));
+
+        let expected = "\
++----+-------+
+| id | name  |
++----+-------+
+|  1 | Alice |
+| 10 | NULL  |
++----+-------+
+(2 rows)";
+        assert_eq!(result.to_string(), expected);
+    }
+
+    #[test]
+    fn test_display_status() {
+        assert_eq!(ReefDBResult::Update(3).to_string(), "UPDATE 3");
+        assert_eq!(ReefDBResult::CreateTable.to_string(), "CREATE TABLE");
+        assert_eq!(ReefDBResult::Insert(1).to_string(), "INSERT 1");
+    }
 }
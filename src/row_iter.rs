@@ -0,0 +1,135 @@
+use std::any::Any;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::functions::Aggregate;
+use crate::indexes::index_manager::IndexManager;
+use crate::result::{ColumnInfo, ReefDBResult};
+use crate::sql::clauses::wheres::where_type::WhereType;
+use crate::sql::column::Column;
+use crate::sql::column_def::ColumnDef;
+use crate::sql::data_value::DataValue;
+use crate::sql::parser::Parser;
+use crate::sql::statements::{select::SelectStatement, Statement};
+use crate::storage::Storage;
+use crate::{ReefDB, WhereContext};
+
+/// Lazily evaluated rows of a `SELECT`, returned by [`ReefDB::query_iter`].
+pub struct RowIter<'a, S, FTS>
+where
+    S: Storage + IndexManager + Clone + Any,
+    FTS: Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    columns: Vec<ColumnInfo>,
+    source: RowSource<'a, S, FTS>,
+}
+
+enum RowSource<'a, S, FTS>
+where
+    S: Storage + IndexManager + Clone + Any,
+    FTS: Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    Streaming {
+        db: &'a ReefDB<S, FTS>,
+        schema: &'a [ColumnDef],
+        rows: std::iter::Enumerate<std::slice::Iter<'a, Vec<DataValue>>>,
+        table_name: String,
+        columns: Vec<Column>,
+        /// The WHERE clause with the context built for it once per query
+        where_clause: Option<(Box<WhereType>, WhereContext<'a>)>,
+    },
+    Materialized(std::vec::IntoIter<(usize, Vec<DataValue>)>),
+}
+
+impl<S, FTS> RowIter<'_, S, FTS>
+where
+    S: Storage + IndexManager + Clone + Any,
+    FTS: Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    /// Column metadata for the rows this iterator yields
+    pub fn columns(&self) -> &[ColumnInfo] {
+        &self.columns
+    }
+}
+
+impl<S, FTS> Iterator for RowIter<'_, S, FTS>
+where
+    S: Storage + IndexManager + Clone + Any,
+    FTS: Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    type Item = Result<Vec<DataValue>, ReefDBError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            RowSource::Streaming { db, schema, rows, table_name, columns, where_clause } => {
+                for (i, row) in rows.by_ref() {
+                    if let Some((where_clause, context)) = where_clause {
+                        match db.evaluate_where(where_clause, i, row, 0, &[], context) {
+                            Ok(true) => {}
+                            Ok(false) => continue,
+                            Err(e) => return Some(Err(e)),
+                        }
+                    }
+                    return Some(db.project_row(i, row, schema, table_name, columns));
+                }
+                None
+            }
+            RowSource::Materialized(rows) => rows.next().map(|(_, row)| Ok(row)),
+        }
+    }
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Runs a single-table `SELECT` and yields its rows one at a time, applying
+    /// the WHERE clause and projection as the iterator advances instead of
+    /// building the whole result up front. Joins are not supported. A query
+    /// with ORDER BY, GROUP BY or aggregates has to see every row before it
+    /// can yield the first one, so it is fully materialized before iteration
+    /// starts.
+    pub fn query_iter(&self, sql: &str) -> Result<RowIter<'_, S, FTS>, ReefDBError> {
+        let (table_ref, columns, where_clause, joins, order_by, group_by) = match self.resolve_subqueries(Parser::parse_sql(sql)?)? {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by)) => {
+                (table_ref, columns, where_clause, joins, order_by, group_by)
+            }
+            _ => return Err(ReefDBError::Other("query_iter only supports SELECT statements".to_string())),
+        };
+        if !joins.is_empty() {
+            return Err(ReefDBError::Other("query_iter does not support joins".to_string()));
+        }
+
+        let aggregate = !group_by.is_empty() || columns.iter().any(|c| Aggregate::of_column(c).is_some());
+        if !order_by.is_empty() || aggregate {
+            return match self.handle_select(table_ref, columns, where_clause, joins, order_by, group_by)? {
+                ReefDBResult::Select(result) => Ok(RowIter {
+                    columns: result.columns,
+                    source: RowSource::Materialized(result.rows.into_iter()),
+                }),
+                _ => unreachable!("handle_select always returns a Select result"),
+            };
+        }
+
+        let (schema, rows) = self.get_table_schema(&table_ref.name)?;
+        let where_clause = where_clause.map(|where_clause| {
+            let context = self.where_context(&where_clause, schema, &[], &table_ref.name);
+            (Box::new(where_clause), context)
+        });
+        Ok(RowIter {
+            columns: ColumnInfo::from_schema_and_columns(schema, &columns, &table_ref.name)?,
+            source: RowSource::Streaming {
+                db: self,
+                schema,
+                rows: rows.iter().enumerate(),
+                table_name: table_ref.name,
+                columns,
+                where_clause,
+            },
+        })
+    }
+}
@@ -4,24 +4,43 @@ use crate::{
     storage::TableStorage,
 };
 use super::savepoint::{Savepoint, SavepointState};
+use super::DEFAULT_MAX_SAVEPOINT_DEPTH;
 
+/// Savepoints of each transaction, oldest first
 pub struct SavepointManager {
     savepoints: HashMap<u64, Vec<Savepoint>>,
+    max_depth: usize,
 }
 
 impl SavepointManager {
     pub fn new() -> Self {
         SavepointManager {
             savepoints: HashMap::new(),
+            max_depth: DEFAULT_MAX_SAVEPOINT_DEPTH,
         }
     }
 
+    /// Most savepoints a transaction may hold at once
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
     pub(crate) fn create_savepoint(&mut self, transaction_id: u64, name: String, tables: TableStorage) -> Result<(), ReefDBError> {
-        // Check if savepoint with same name already exists
         if let Some(transaction_savepoints) = self.savepoints.get(&transaction_id) {
+            // Check if savepoint with same name already exists
             if transaction_savepoints.iter().any(|sp| sp.name == name) {
                 return Err(ReefDBError::Other(format!("Savepoint {} already exists", name)));
             }
+            if transaction_savepoints.len() >= self.max_depth {
+                return Err(ReefDBError::ResourceLimitExceeded(format!(
+                    "cannot create savepoint {}: a transaction may hold at most {} savepoints",
+                    name, self.max_depth
+                )));
+            }
         }
 
         let savepoint = Savepoint {
@@ -39,6 +58,8 @@ impl SavepointManager {
         Ok(())
     }
 
+    /// Returns the tables as they were at savepoint `name`, which stays
+    /// usable. Savepoints created after it are released.
     pub(crate) fn rollback_to_savepoint(&mut self, transaction_id: u64, name: &str) -> Result<TableStorage, ReefDBError> {
         let transaction_savepoints = self.savepoints.get_mut(&transaction_id)
             .ok_or_else(|| ReefDBError::SavepointNotFound(name.to_string()))?;
@@ -54,7 +75,7 @@ impl SavepointManager {
         // Get the savepoint state
         let snapshot = transaction_savepoints[savepoint_index].table_snapshot.clone();
 
-        // Remove all savepoints after this one
+        // Release all savepoints after this one
         transaction_savepoints.truncate(savepoint_index + 1);
 
         Ok(snapshot)
@@ -127,6 +148,44 @@ mod tests {
         assert_eq!(manager.get_active_savepoints(1), vec!["sp1"]);
     }
 
+    #[test]
+    fn test_rollback_releases_later_savepoints() {
+        let mut manager = SavepointManager::new();
+        let tables = TableStorage::new();
+
+        for name in ["sp1", "sp2", "sp3"] {
+            manager.create_savepoint(1, name.to_string(), tables.clone()).unwrap();
+        }
+        manager.rollback_to_savepoint(1, "sp1").unwrap();
+        assert_eq!(manager.get_active_savepoints(1), vec!["sp1"]);
+        assert!(matches!(manager.rollback_to_savepoint(1, "sp3"), Err(ReefDBError::SavepointNotFound(_))));
+        assert!(matches!(manager.release_savepoint(1, "sp2"), Err(ReefDBError::SavepointNotFound(_))));
+
+        // The released names can be reused
+        assert!(manager.create_savepoint(1, "sp2".to_string(), tables).is_ok());
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut manager = SavepointManager::new();
+        let tables = TableStorage::new();
+        manager.set_max_depth(2);
+
+        manager.create_savepoint(1, "sp1".to_string(), tables.clone()).unwrap();
+        manager.create_savepoint(1, "sp2".to_string(), tables.clone()).unwrap();
+        assert!(matches!(
+            manager.create_savepoint(1, "sp3".to_string(), tables.clone()),
+            Err(ReefDBError::ResourceLimitExceeded(_))
+        ));
+
+        // The limit is per transaction
+        assert!(manager.create_savepoint(2, "sp1".to_string(), tables.clone()).is_ok());
+
+        // Rolling back frees the savepoints it releases
+        manager.rollback_to_savepoint(1, "sp1").unwrap();
+        assert!(manager.create_savepoint(1, "sp3".to_string(), tables).is_ok());
+    }
+
     #[test]
     fn test_release_savepoint() {
         let mut manager = SavepointManager::new();
@@ -2,4 +2,8 @@ mod savepoint;
 mod manager;
 
 pub use manager::SavepointManager;
-pub use savepoint::{Savepoint, SavepointState}; 
\ No newline at end of file
+pub use savepoint::{Savepoint, SavepointState};
+
+/// How many savepoints a transaction may hold at once unless configured
+/// otherwise
+pub const DEFAULT_MAX_SAVEPOINT_DEPTH: usize = 64;
@@ -0,0 +1,170 @@
+//! A TCP server speaking a line protocol, for `nc` and small clients that
+//! don't want a driver.
+//!
+//! Each request is one line of SQL. Each response is one line of JSON:
+//! `{"ok":true,"rows":[...]}` for queries, `{"ok":true,"count":n}` for
+//! INSERT, UPDATE and DELETE, `{"ok":true,"result":"CREATE TABLE"}` for
+//! other statements, and `{"ok":false,"error":"..."}` when the statement
+//! fails. A failed statement doesn't close the connection.
+//!
+//! Every statement commits on its own. All connections share one database,
+//! where a transaction one client began would take in the statements of
+//! every other client, so BEGIN, COMMIT, ROLLBACK and savepoints are
+//! refused.
+
+use std::any::Any;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::result::ReefDBResult;
+use crate::sql::parser::Parser;
+use crate::sql::statements::Statement;
+use crate::storage::Storage;
+use crate::ReefDB;
+
+/// How often blocked reads wake up to check for shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Stops a running `TextServer` from another thread
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    shutdown: Arc<AtomicBool>,
+    addr: SocketAddr,
+}
+
+impl ShutdownHandle {
+    /// Stops accepting connections and closes open ones once their current
+    /// statement has run. `serve` returns when they are all closed.
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop, which has no timeout of its own
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+pub struct TextServer {
+    listener: TcpListener,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl TextServer {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(TextServer {
+            listener: TcpListener::bind(addr)?,
+            shutdown: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn shutdown_handle(&self) -> io::Result<ShutdownHandle> {
+        Ok(ShutdownHandle { shutdown: self.shutdown.clone(), addr: self.local_addr()? })
+    }
+
+    /// Serves connections, one thread each, until shut down
+    pub fn serve<S, FTS>(self, db: Arc<Mutex<ReefDB<S, FTS>>>) -> io::Result<()>
+    where
+        S: Storage + IndexManager + Clone + Any + Send,
+        FTS: Search + Clone + Send + 'static,
+        FTS::NewArgs: Clone + Default,
+    {
+        let mut connections = Vec::new();
+        for stream in self.listener.incoming() {
+            if self.shutdown.load(Ordering::SeqCst) {
+                break;
+            }
+            let stream = match stream {
+                Ok(stream) => stream,
+                // A client that went away before being accepted
+                Err(_) => continue,
+            };
+            let db = db.clone();
+            let shutdown = self.shutdown.clone();
+            connections.push(thread::spawn(move || {
+                // Errors only end this connection
+                let _ = handle_connection(stream, &db, &shutdown);
+            }));
+            connections.retain(|connection| !connection.is_finished());
+        }
+
+        for connection in connections {
+            let _ = connection.join();
+        }
+        Ok(())
+    }
+}
+
+fn handle_connection<S, FTS>(stream: TcpStream, db: &Mutex<ReefDB<S, FTS>>, shutdown: &AtomicBool) -> io::Result<()>
+where
+    S: Storage + IndexManager + Clone + Any,
+    FTS: Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    while !shutdown.load(Ordering::SeqCst) {
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            // Partial input stays in `line` until the rest arrives
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(e) => return Err(e),
+        }
+
+        let sql = line.trim();
+        if !sql.is_empty() {
+            let response = match db.lock() {
+                Ok(mut db) => response(run(&mut db, sql)),
+                Err(_) => response(Err(ReefDBError::LockPoisoned("database"))),
+            };
+            writeln!(writer, "{}", response)?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+/// Runs one request, refusing the statements that control transactions
+fn run<S, FTS>(db: &mut ReefDB<S, FTS>, sql: &str) -> Result<ReefDBResult, ReefDBError>
+where
+    S: Storage + IndexManager + Clone + Any,
+    FTS: Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    let stmt = Parser::parse_sql(sql)?;
+    if matches!(
+        stmt,
+        Statement::BeginTransaction | Statement::Commit | Statement::Rollback
+            | Statement::Savepoint(_) | Statement::RollbackToSavepoint(_) | Statement::ReleaseSavepoint(_)
+    ) {
+        return Err(ReefDBError::Other(
+            "Transactions are not supported over the text server, each statement commits on its own".to_string()
+        ));
+    }
+    db.execute_statement(stmt)
+}
+
+fn response(result: Result<ReefDBResult, ReefDBError>) -> serde_json::Value {
+    match result {
+        Ok(ReefDBResult::Select(rows) | ReefDBResult::Returning(rows)) => json!({"ok": true, "rows": rows.to_json()}),
+        Ok(ReefDBResult::Insert(count) | ReefDBResult::Update(count) | ReefDBResult::Delete(count)) => {
+            json!({"ok": true, "count": count})
+        }
+        Ok(result) => json!({"ok": true, "result": result.to_string()}),
+        Err(e) => json!({"ok": false, "error": e.to_string()}),
+    }
+}
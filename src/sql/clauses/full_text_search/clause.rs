@@ -1,16 +1,17 @@
 use super::{QueryType, TSQuery};
 use super::language::Language;
 use crate::sql::column::Column;
+use crate::sql::data_value::string_literal;
 use crate::sql::clauses::full_text_search::weight::TextWeight;
 use super::{set_weight::SetWeight, ts_vector::TSVector};
 use nom::{
     IResult,
-    bytes::complete::{tag, tag_no_case, take_until},
+    branch::alt,
+    bytes::complete::{tag, tag_no_case},
     character::complete::multispace0,
-    sequence::{tuple, delimited},
-    combinator::opt,
+    sequence::tuple,
+    combinator::{map, opt},
 };
-use crate::sql::operators::op::Op;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FTSClause {
@@ -64,35 +65,29 @@ impl FTSClause {
         )))(input)?;
 
         // Parse search query
-        let (input, query_text) = delimited(
-            tag("'"),
-            take_until("'"),
-            tag("'"),
-        )(input)?;
+        let (input, query_text) = string_literal(input)?;
 
         let (input, _) = tag(")")(input)?;
 
-        Ok((input, (language.map(|(lang, _, _)| lang), query_text.to_string())))
+        Ok((input, (language.map(|(lang, _, _)| lang), query_text)))
     }
 
     pub fn parse(input: &str) -> IResult<&str, Self> {
-        // Try parsing setweight first
-        if let Ok((remaining, clause)) = SetWeight::parse(input) {
-            return Ok((remaining, clause));
-        }
-
-        // Parse tsvector part
-        let (input, clause) = TSVector::parse(input)?;
+        // Parse tsvector part, possibly weighted, or a bare TSVECTOR column
+        let (input, clause) = alt((
+            SetWeight::parse,
+            TSVector::parse,
+            map(Column::parse_table_column, |column| FTSClause::new(column, String::new())),
+        ))(input)?;
 
         // Parse @@ operator
-        let (input, _) = tuple((
-            multispace0,
-            |i| Op::parse(i).map(|(i, _)| (i, ())),
-            multispace0,
-        ))(input)?;
+        let (input, _) = tuple((multispace0, tag("@@"), multispace0))(input)?;
 
-        // Parse tsquery part
-        let (input, (query_language, query_text)) = Self::parse_tsquery(input)?;
+        // Parse tsquery part, or a bare string standing for one
+        let (input, (query_language, query_text)) = alt((
+            Self::parse_tsquery,
+            map(string_literal, |text| (None, text)),
+        ))(input)?;
 
         let mut result = FTSClause::new(clause.column, query_text);
         
@@ -100,6 +95,9 @@ impl FTSClause {
         if let Some(lang) = query_language.or(clause.query.language) {
             result = result.with_language(lang);
         }
+        if let Some(weight) = clause.weight {
+            result = result.with_weight(weight);
+        }
 
         Ok((input, result))
     }
@@ -119,6 +117,27 @@ mod tests {
         assert_eq!(clause.query.language, None);
     }
 
+    #[test]
+    fn test_parse_fts_setweight() {
+        let input = "setweight(to_tsvector(title), 'A') @@ to_tsquery('rust')";
+        let (remaining, clause) = FTSClause::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(clause.column.name, "title");
+        assert_eq!(clause.query.text, "rust");
+        assert_eq!(clause.weight, Some(TextWeight::A));
+    }
+
+    #[test]
+    fn test_parse_fts_bare_column() {
+        let (remaining, clause) = FTSClause::parse("body @@ 'disk error'").unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(clause.column.name, "body");
+        assert_eq!(clause.query.text, "disk error");
+
+        // Other operators are plain comparisons
+        assert!(FTSClause::parse("body = 'disk error'").is_err());
+    }
+
     #[test]
     fn test_parse_fts_with_language() {
         let input = "to_tsvector('english', content) @@ to_tsquery('english', 'web & development')";
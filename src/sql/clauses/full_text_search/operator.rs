@@ -3,6 +3,8 @@ pub enum QueryOperator {
     And,
     Or,
     Not,
+    /// `<N>`: the terms on either side appear within N positions
+    Proximity(usize),
 }
 
 impl QueryOperator {
@@ -11,7 +13,10 @@ impl QueryOperator {
             "AND" => Some(QueryOperator::And),
             "OR" => Some(QueryOperator::Or),
             "NOT" => Some(QueryOperator::Not),
-            _ => None,
+            op => op.strip_prefix('<')
+                .and_then(|op| op.strip_suffix('>'))
+                .and_then(|distance| distance.parse().ok())
+                .map(QueryOperator::Proximity),
         }
     }
 
@@ -20,6 +25,7 @@ impl QueryOperator {
             QueryOperator::And => "AND",
             QueryOperator::Or => "OR",
             QueryOperator::Not => "NOT",
+            QueryOperator::Proximity(_) => "<N>",
         }
     }
 }
@@ -33,6 +39,8 @@ mod tests {
         assert_eq!(QueryOperator::from_str("AND"), Some(QueryOperator::And));
         assert_eq!(QueryOperator::from_str("OR"), Some(QueryOperator::Or));
         assert_eq!(QueryOperator::from_str("NOT"), Some(QueryOperator::Not));
+        assert_eq!(QueryOperator::from_str("<3>"), Some(QueryOperator::Proximity(3)));
+        assert_eq!(QueryOperator::from_str("<x>"), None);
         assert_eq!(QueryOperator::from_str("INVALID"), None);
     }
 
@@ -38,11 +38,21 @@ impl TSQuery {
         self
     }
 
+    /// Adds a term, joining it to the previous one with an implicit AND when
+    /// no operator came between them, so `operators[i]` always sits between
+    /// `terms[i]` and `terms[i + 1]`
+    fn push_term(term: ParsedTerm, terms: &mut Vec<ParsedTerm>, operators: &mut Vec<QueryOperator>) {
+        if !terms.is_empty() && operators.len() < terms.len() {
+            operators.push(QueryOperator::And);
+        }
+        terms.push(term);
+    }
+
     fn handle_special_char(&self, c: char, current_term: &mut String, terms: &mut Vec<ParsedTerm>, operators: &mut Vec<QueryOperator>, is_negated: &mut bool) {
         match c {
             '&' => {
                 if let Some(term) = ParsedTerm::parse(current_term, *is_negated) {
-                    terms.push(term);
+                    Self::push_term(term, terms, operators);
                 }
                 operators.push(QueryOperator::And);
                 current_term.clear();
@@ -50,7 +60,7 @@ impl TSQuery {
             }
             '|' => {
                 if let Some(term) = ParsedTerm::parse(current_term, *is_negated) {
-                    terms.push(term);
+                    Self::push_term(term, terms, operators);
                 }
                 operators.push(QueryOperator::Or);
                 current_term.clear();
@@ -59,14 +69,23 @@ impl TSQuery {
             '!' => {
                 *is_negated = true;
             }
+            // A leading `-` negates the term, one inside a word is part of it
+            '-' if current_term.is_empty() => {
+                *is_negated = true;
+            }
             ' ' => {
                 if let Some(term) = ParsedTerm::parse(current_term, *is_negated) {
-                    terms.push(term);
+                    Self::push_term(term, terms, operators);
+                    *is_negated = false;
                 } else if let Some(op) = QueryOperator::from_str(current_term) {
-                    operators.push(op);
+                    // NOT negates the next term rather than joining two
+                    if op == QueryOperator::Not {
+                        *is_negated = true;
+                    } else {
+                        operators.push(op);
+                    }
                 }
                 current_term.clear();
-                *is_negated = false;
             }
             _ => {
                 current_term.push(c);
@@ -93,9 +112,7 @@ impl TSQuery {
         // Handle the last term if any
         if !current_term.is_empty() {
             if let Some(term) = ParsedTerm::parse(&current_term, is_negated) {
-                terms.push(term);
-            } else if let Some(op) = QueryOperator::from_str(&current_term) {
-                operators.push(op);
+                Self::push_term(term, &mut terms, &mut operators);
             }
         }
 
@@ -192,6 +209,25 @@ mod tests {
         assert_eq!(parsed.operators[1], QueryOperator::Or);
     }
 
+    #[test]
+    fn test_parse_negation() {
+        for text in ["rust AND NOT python", "rust -python", "rust !python"] {
+            let parsed = TSQuery::new(text.to_string()).parse();
+            assert_eq!(parsed.terms, vec![
+                ParsedTerm::new("rust".to_string(), false),
+                ParsedTerm::new("python".to_string(), true),
+            ]);
+            assert_eq!(parsed.operators, vec![QueryOperator::And]);
+        }
+
+        let parsed = TSQuery::new("full-text OR NOT python".to_string()).parse();
+        assert_eq!(parsed.terms, vec![
+            ParsedTerm::new("full-text".to_string(), false),
+            ParsedTerm::new("python".to_string(), true),
+        ]);
+        assert_eq!(parsed.operators, vec![QueryOperator::Or]);
+    }
+
     #[test]
     fn test_single_term() {
         let query = TSQuery::new("hello".to_string());
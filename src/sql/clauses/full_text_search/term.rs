@@ -1,12 +1,17 @@
+use super::operator::QueryOperator;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParsedTerm {
     pub text: String,
     pub is_negated: bool,
+    /// `n` of a `term~n` suffix: how many edits away from the term a
+    /// matching token may be
+    pub max_edits: Option<usize>,
 }
 
 impl ParsedTerm {
     pub fn new(text: String, is_negated: bool) -> Self {
-        Self { text, is_negated }
+        Self { text, is_negated, max_edits: None }
     }
 
     pub fn parse(term: &str, is_negated: bool) -> Option<Self> {
@@ -15,10 +20,15 @@ impl ParsedTerm {
             return None;
         }
 
-        // Check if it's an operator keyword
-        match term.to_uppercase().as_str() {
-            "AND" | "OR" | "NOT" => None,
-            _ => Some(ParsedTerm::new(term.to_string(), is_negated)),
+        // Operators such as AND or <3> are not terms
+        match QueryOperator::from_str(term) {
+            Some(_) => None,
+            None => Some(match term.rsplit_once('~') {
+                Some((word, edits)) if !word.is_empty() && !edits.is_empty() && edits.chars().all(|c| c.is_ascii_digit()) => {
+                    ParsedTerm { max_edits: edits.parse().ok(), ..ParsedTerm::new(word.to_string(), is_negated) }
+                }
+                _ => ParsedTerm::new(term.to_string(), is_negated),
+            }),
         }
     }
 }
@@ -37,6 +47,11 @@ mod tests {
             ParsedTerm::parse("world", true),
             Some(ParsedTerm::new("world".to_string(), true))
         );
+        assert_eq!(
+            ParsedTerm::parse("databse~1", false),
+            Some(ParsedTerm { max_edits: Some(1), ..ParsedTerm::new("databse".to_string(), false) })
+        );
+        assert_eq!(ParsedTerm::parse("~1", false), Some(ParsedTerm::new("~1".to_string(), false)));
         assert_eq!(ParsedTerm::parse("AND", false), None);
         assert_eq!(ParsedTerm::parse("", false), None);
         assert_eq!(ParsedTerm::parse("  ", false), None);
@@ -4,23 +4,24 @@ use nom::{
     character::complete::{multispace0, multispace1, alphanumeric1, alpha1},
     combinator::{opt, value, recognize},
     multi::many0,
-    sequence::{preceded, tuple},
+    sequence::{terminated, tuple},
     IResult,
 };
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use crate::sql::{
-    column_def::table_name,
+    column_def::quoted_identifier,
     column_value_pair::ColumnValuePair,
-    table_reference::TableReference,
+    table_reference::{parse_alias, TableReference},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct JoinClause {
     pub join_type: JoinType,
     pub table_ref: TableReference,
-    pub on: (ColumnValuePair, ColumnValuePair),
+    /// Join condition, `None` for a cross join
+    pub on: Option<(ColumnValuePair, ColumnValuePair)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -29,6 +30,7 @@ pub enum JoinType {
     Left,
     Right,
     Full,
+    Cross,
 }
 
 fn join_type(input: &str) -> IResult<&str, JoinType> {
@@ -52,50 +54,90 @@ impl JoinClause {
                 name: table_name.to_owned(),
                 alias: None,
             },
-            on,
+            on: Some(on),
         }
     }
 
+    pub fn cross(table_name: &str) -> JoinClause {
+        cross_join(table_name, None)
+    }
+
     pub fn parse(input: &str) -> IResult<&str, JoinClause> {
-        let (input, join_type) = join_type(input)?;
-        let (input, _) = multispace1(input)?;
-        let (input, _) = tag_no_case("JOIN")(input)?;
-        let (input, _) = multispace1(input)?;
-        let (input, table_name) = identifier(input)?;
-        let (input, alias) = opt(preceded(
-            tuple((multispace1, tag_no_case("AS"), multispace1)),
-            identifier
-        ))(input)?;
-        let (input, _) = multispace1(input)?;
-        let (input, _) = tag_no_case("ON")(input)?;
-        let (input, _) = multispace1(input)?;
-        let (input, col1) = ColumnValuePair::parse(input)?;
-        let (input, _) = multispace0(input)?;
-        let (input, _) = tag("=")(input)?;
-        let (input, _) = multispace0(input)?;
-        let (input, col2) = ColumnValuePair::parse(input)?;
-
-        Ok((
-            input,
-            JoinClause {
-                join_type,
-                table_ref: TableReference {
-                    name: table_name.to_string(),
-                    alias: alias.map(|a| a.to_string()),
-                },
-                on: (col1, col2),
-            },
-        ))
+        alt((parse_cross_join, parse_comma_join, parse_conditional_join))(input)
     }
 }
 
+fn cross_join(table_name: &str, alias: Option<&str>) -> JoinClause {
+    JoinClause {
+        join_type: JoinType::Cross,
+        table_ref: TableReference {
+            name: table_name.to_string(),
+            alias: alias.map(|a| a.to_string()),
+        },
+        on: None,
+    }
+}
+
+// a CROSS JOIN b
+fn parse_cross_join(input: &str) -> IResult<&str, JoinClause> {
+    let (input, _) = tag_no_case("CROSS")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, _) = tag_no_case("JOIN")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, table_name) = identifier(input)?;
+    let (input, alias) = parse_alias(input)?;
+    Ok((input, cross_join(table_name, alias)))
+}
+
+// FROM a, b
+fn parse_comma_join(input: &str) -> IResult<&str, JoinClause> {
+    let (input, _) = tag(",")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, table_name) = identifier(input)?;
+    let (input, alias) = parse_alias(input)?;
+    Ok((input, cross_join(table_name, alias)))
+}
+
+fn parse_conditional_join(input: &str) -> IResult<&str, JoinClause> {
+    // A bare JOIN is an inner join
+    let (input, join_type) = opt(terminated(join_type, multispace1))(input)?;
+    let join_type = join_type.unwrap_or(JoinType::Inner);
+    let (input, _) = tag_no_case("JOIN")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, table_name) = identifier(input)?;
+    let (input, alias) = parse_alias(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, _) = tag_no_case("ON")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, col1) = ColumnValuePair::parse(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("=")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, col2) = ColumnValuePair::parse(input)?;
+
+    Ok((
+        input,
+        JoinClause {
+            join_type,
+            table_ref: TableReference {
+                name: table_name.to_string(),
+                alias: alias.map(|a| a.to_string()),
+            },
+            on: Some((col1, col2)),
+        },
+    ))
+}
+
 fn identifier(input: &str) -> IResult<&str, &str> {
-    recognize(
-        tuple((
-            alpha1,
-            many0(alt((alphanumeric1, tag("_"))))
-        ))
-    )(input)
+    alt((
+        quoted_identifier,
+        recognize(
+            tuple((
+                alpha1,
+                many0(alt((alphanumeric1, tag("_"))))
+            ))
+        ),
+    ))(input)
 }
 
 #[cfg(test)]
@@ -111,14 +153,14 @@ mod tests {
         assert_eq!(join.table_ref.name, "table1");
         assert_eq!(join.table_ref.alias, None);
         assert_eq!(
-            join.on.0,
+            join.on.as_ref().unwrap().0,
             ColumnValuePair {
                 column_name: "id".to_owned(),
                 table_name: "table1".to_owned()
             }
         );
         assert_eq!(
-            join.on.1,
+            join.on.as_ref().unwrap().1,
             ColumnValuePair {
                 column_name: "id".to_owned(),
                 table_name: "table2".to_owned()
@@ -135,18 +177,44 @@ mod tests {
         assert_eq!(join.table_ref.name, "table1");
         assert_eq!(join.table_ref.alias, Some("t1".to_string()));
         assert_eq!(
-            join.on.0,
+            join.on.as_ref().unwrap().0,
             ColumnValuePair {
                 column_name: "id".to_owned(),
                 table_name: "t1".to_owned()
             }
         );
         assert_eq!(
-            join.on.1,
+            join.on.as_ref().unwrap().1,
             ColumnValuePair {
                 column_name: "id".to_owned(),
                 table_name: "table2".to_owned()
             }
         );
     }
+
+    #[test]
+    fn cross_join_parse_test() {
+        let (input, join) = JoinClause::parse("CROSS JOIN table1 AS t1").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(join.join_type, JoinType::Cross);
+        assert_eq!(join.table_ref.alias, Some("t1".to_string()));
+        assert_eq!(join.on, None);
+
+        let (input, join) = JoinClause::parse(", table2 WHERE x = 1").unwrap();
+        assert_eq!(input, " WHERE x = 1");
+        assert_eq!(join, JoinClause::cross("table2"));
+    }
+
+    #[test]
+    fn join_parse_without_as_test() {
+        let (input, join) = JoinClause::parse("JOIN orders o ON u.id = o.user_id").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(join.join_type, JoinType::Inner);
+        assert_eq!(join.table_ref.alias, Some("o".to_string()));
+
+        let (input, join) = JoinClause::parse("LEFT JOIN orders ON users.id = orders.user_id").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(join.join_type, JoinType::Left);
+        assert_eq!(join.table_ref.alias, None);
+    }
 }
@@ -2,13 +2,17 @@ use nom::{
     IResult,
     branch::alt,
     bytes::complete::{tag_no_case, tag},
-    character::complete::{multispace0, multispace1},
+    character::complete::{digit1, multispace0, multispace1},
     sequence::{tuple, preceded},
     multi::separated_list1,
-    combinator::{opt, map},
+    combinator::{opt, map, map_res},
 };
 
+use std::cmp::Ordering;
+
+use crate::error::ReefDBError;
 use crate::sql::column::Column;
+use crate::sql::data_value::DataValue;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum OrderDirection {
@@ -16,10 +20,47 @@ pub enum OrderDirection {
     Desc,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+/// How text is compared when sorting, from `COLLATE name`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Collation {
+    /// Byte order of the UTF-8 text, the default
+    Binary,
+    /// Case-insensitive, with case only breaking ties
+    NoCase,
+}
+
+impl Collation {
+    /// Orders two values, applying the collation when both are text
+    pub fn compare(&self, a: &DataValue, b: &DataValue) -> Result<Ordering, ReefDBError> {
+        match (self, a, b) {
+            (Collation::NoCase, DataValue::Text(a), DataValue::Text(b)) => {
+                Ok(a.to_lowercase().cmp(&b.to_lowercase()).then_with(|| a.cmp(b)))
+            }
+            _ => a.sort_cmp(b),
+        }
+    }
+}
+
+/// What an ORDER BY item sorts on
+#[derive(Debug, PartialEq, Clone)]
+pub enum OrderByTarget {
+    Column(Column),
+    /// `ORDER BY 2`: a 1-based position in the SELECT list
+    Position(usize),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct OrderByClause {
-    pub column: Column,
+    pub target: OrderByTarget,
     pub direction: OrderDirection,
+    pub nulls: Option<NullsOrder>,
+    pub collation: Option<Collation>,
 }
 
 impl OrderByClause {
@@ -37,20 +78,65 @@ impl OrderByClause {
             )
         )(input)
     }
+
+    /// The column sorted on, unless the clause names a SELECT list position
+    pub fn column(&self) -> Option<&Column> {
+        match &self.target {
+            OrderByTarget::Column(column) => Some(column),
+            OrderByTarget::Position(_) => None,
+        }
+    }
+
+    /// The collation values are compared with, binary unless COLLATE says
+    /// otherwise
+    pub fn collation(&self) -> Collation {
+        self.collation.unwrap_or(Collation::Binary)
+    }
+
+    /// Whether NULLs sort before other values. Without an explicit NULLS
+    /// FIRST/LAST, NULL sorts as the smallest value.
+    pub fn nulls_first(&self) -> bool {
+        match self.nulls {
+            Some(NullsOrder::First) => true,
+            Some(NullsOrder::Last) => false,
+            None => self.direction == OrderDirection::Asc,
+        }
+    }
 }
 
 fn parse_order_by_item(input: &str) -> IResult<&str, OrderByClause> {
-    let (input, column) = Column::parse(input)?;
+    let (input, target) = alt((
+        map(map_res(digit1, str::parse), OrderByTarget::Position),
+        map(Column::parse, OrderByTarget::Column),
+    ))(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, collation) = opt(preceded(
+        tuple((tag_no_case("COLLATE"), multispace1)),
+        alt((
+            map(tag_no_case("NOCASE"), |_| Collation::NoCase),
+            map(tag_no_case("BINARY"), |_| Collation::Binary),
+        )),
+    ))(input)?;
     let (input, _) = multispace0(input)?;
     let (input, direction) = opt(alt((
         map(tag_no_case("DESC"), |_| OrderDirection::Desc),
         map(tag_no_case("ASC"), |_| OrderDirection::Asc),
     )))(input)?;
     let (input, _) = multispace0(input)?;
+    let (input, nulls) = opt(preceded(
+        tuple((tag_no_case("NULLS"), multispace1)),
+        alt((
+            map(tag_no_case("FIRST"), |_| NullsOrder::First),
+            map(tag_no_case("LAST"), |_| NullsOrder::Last),
+        )),
+    ))(input)?;
+    let (input, _) = multispace0(input)?;
 
     Ok((input, OrderByClause {
-        column,
+        target,
         direction: direction.unwrap_or(OrderDirection::Asc),
+        nulls,
+        collation,
     }))
 }
 
@@ -65,8 +151,8 @@ mod tests {
         let (remaining, clauses) = OrderByClause::parse(input).unwrap();
         assert_eq!(remaining, "");
         assert_eq!(clauses.len(), 1);
-        assert_eq!(clauses[0].column.name, "age");
-        assert_eq!(clauses[0].column.column_type, ColumnType::Regular("age".to_string()));
+        assert_eq!(clauses[0].column().unwrap().name, "age");
+        assert_eq!(clauses[0].column().unwrap().column_type, ColumnType::Regular("age".to_string()));
         assert_eq!(clauses[0].direction, OrderDirection::Desc);
     }
 
@@ -76,11 +162,11 @@ mod tests {
         let (remaining, clauses) = OrderByClause::parse(input).unwrap();
         assert_eq!(remaining, "");
         assert_eq!(clauses.len(), 2);
-        assert_eq!(clauses[0].column.name, "age");
-        assert_eq!(clauses[0].column.column_type, ColumnType::Regular("age".to_string()));
+        assert_eq!(clauses[0].column().unwrap().name, "age");
+        assert_eq!(clauses[0].column().unwrap().column_type, ColumnType::Regular("age".to_string()));
         assert_eq!(clauses[0].direction, OrderDirection::Desc);
-        assert_eq!(clauses[1].column.name, "name");
-        assert_eq!(clauses[1].column.column_type, ColumnType::Regular("name".to_string()));
+        assert_eq!(clauses[1].column().unwrap().name, "name");
+        assert_eq!(clauses[1].column().unwrap().column_type, ColumnType::Regular("name".to_string()));
         assert_eq!(clauses[1].direction, OrderDirection::Asc);
     }
 
@@ -90,8 +176,51 @@ mod tests {
         let (remaining, clauses) = OrderByClause::parse(input).unwrap();
         assert_eq!(remaining, "");
         assert_eq!(clauses.len(), 1);
-        assert_eq!(clauses[0].column.name, "age");
-        assert_eq!(clauses[0].column.column_type, ColumnType::Regular("age".to_string()));
+        assert_eq!(clauses[0].column().unwrap().name, "age");
+        assert_eq!(clauses[0].column().unwrap().column_type, ColumnType::Regular("age".to_string()));
         assert_eq!(clauses[0].direction, OrderDirection::Asc);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_parse_order_by_position() {
+        let input = "ORDER BY 2 DESC, name";
+        let (remaining, clauses) = OrderByClause::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(clauses[0].target, OrderByTarget::Position(2));
+        assert_eq!(clauses[0].column(), None);
+        assert_eq!(clauses[0].direction, OrderDirection::Desc);
+        assert_eq!(clauses[1].column().unwrap().name, "name");
+    }
+
+    #[test]
+    fn test_parse_order_by_nulls() {
+        let input = "ORDER BY age DESC NULLS FIRST, name NULLS LAST, id";
+        let (remaining, clauses) = OrderByClause::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(clauses.len(), 3);
+        assert_eq!(clauses[0].direction, OrderDirection::Desc);
+        assert_eq!(clauses[0].nulls, Some(NullsOrder::First));
+        assert!(clauses[0].nulls_first());
+        assert_eq!(clauses[1].direction, OrderDirection::Asc);
+        assert_eq!(clauses[1].nulls, Some(NullsOrder::Last));
+        assert!(!clauses[1].nulls_first());
+        assert_eq!(clauses[2].nulls, None);
+        assert!(clauses[2].nulls_first());
+    }
+
+    #[test]
+    fn test_parse_order_by_collate() {
+        let input = "ORDER BY name COLLATE NOCASE DESC, id";
+        let (remaining, clauses) = OrderByClause::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(clauses[0].column().unwrap().name, "name");
+        assert_eq!(clauses[0].collation, Some(Collation::NoCase));
+        assert_eq!(clauses[0].direction, OrderDirection::Desc);
+        assert_eq!(clauses[1].collation(), Collation::Binary);
+
+        let text = |s: &str| DataValue::Text(s.to_string());
+        assert_eq!(Collation::NoCase.compare(&text("alice"), &text("Bob")), Ok(Ordering::Less));
+        assert_eq!(Collation::Binary.compare(&text("alice"), &text("Bob")), Ok(Ordering::Greater));
+        assert_eq!(Collation::NoCase.compare(&text("Bob"), &text("bob")), Ok(Ordering::Less));
+    }
+}
@@ -4,12 +4,13 @@ use nom::{
     bytes::complete::{tag, tag_no_case, take_until},
     character::complete::{multispace0, multispace1},
     sequence::{tuple, delimited},
-    multi::many0,
+    multi::{many0, many1, separated_list1},
     combinator::{map, opt},
 };
 
 use crate::sql::{
-    column::Column,
+    column::{Column, ColumnType},
+    column_value_pair::ColumnValuePair,
     data_value::DataValue,
     operators::op::Op,
     clauses::full_text_search::{
@@ -18,7 +19,10 @@ use crate::sql::{
         QueryType,
         Language,
     },
+    statements::{Statement, select::SelectStatement},
 };
+use crate::error::ReefDBError;
+use crate::result::QueryResult;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct WhereClause {
@@ -28,10 +32,76 @@ pub struct WhereClause {
     pub table: Option<String>,
 }
 
+/// Comparison between two columns, e.g. `authors.id = books.author_id`
+#[derive(Debug, PartialEq, Clone)]
+pub struct ColumnComparison {
+    pub left: Column,
+    pub operator: Op,
+    pub right: Column,
+}
+
+/// Comparison against an uncorrelated scalar subquery, e.g.
+/// `id = (SELECT id FROM users WHERE name = 'bob')`
+#[derive(Debug, PartialEq, Clone)]
+pub struct SubqueryClause {
+    pub column: Column,
+    pub operator: Op,
+    pub subquery: Box<SelectStatement>,
+}
+
+/// Membership test against a list of values, e.g. `id IN (1, 2, 3)`
+#[derive(Debug, PartialEq, Clone)]
+pub struct InClause {
+    pub column: Column,
+    pub values: Vec<DataValue>,
+}
+
+/// Membership test against the single-column result of an uncorrelated
+/// subquery, e.g. `user_id IN (SELECT id FROM active_users)`
+#[derive(Debug, PartialEq, Clone)]
+pub struct InSubqueryClause {
+    pub column: Column,
+    pub subquery: Box<SelectStatement>,
+}
+
+/// `CASE WHEN p THEN v ... [ELSE v] END`: the value of the first branch
+/// whose predicate holds, else the ELSE value, else NULL
+#[derive(Debug, PartialEq, Clone)]
+pub struct CaseExpression {
+    pub branches: Vec<(WhereType, DataValue)>,
+    pub else_value: Option<DataValue>,
+}
+
+/// Comparison of a CASE expression against a value, e.g.
+/// `CASE WHEN age >= 18 THEN 'adult' ELSE 'minor' END = 'adult'`
+#[derive(Debug, PartialEq, Clone)]
+pub struct CaseComparison {
+    pub case: CaseExpression,
+    pub operator: Op,
+    pub value: DataValue,
+}
+
+/// Comparison of an operator expression or a function call against a
+/// value, e.g. `flags & 4 = 4` or `ABS(score) > 2`. An operator expression
+/// is the call of the builtin its operator stands for, as parsed by
+/// `DataValue::parse_operator_expression`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExpressionComparison {
+    pub expression: DataValue,
+    pub operator: Op,
+    pub value: DataValue,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum WhereType {
     Regular(WhereClause),
+    ColumnComparison(ColumnComparison),
+    Subquery(SubqueryClause),
+    In(InClause),
+    InSubquery(InSubqueryClause),
     FTS(FTSClause),
+    Case(CaseComparison),
+    Expression(ExpressionComparison),
     And(Box<WhereType>, Box<WhereType>),
     Or(Box<WhereType>, Box<WhereType>),
 }
@@ -64,6 +134,248 @@ impl WhereClause {
     }
 }
 
+impl ColumnComparison {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, left) = Column::parse(input)?;
+        let (input, operator) = delimited(
+            multispace0,
+            Op::parse,
+            multispace0
+        )(input)?;
+        // Parsed as a plain `[table.]column` so trailing whitespace is left for
+        // the AND/OR that may follow
+        let (input, right) = ColumnValuePair::parse(input)?;
+
+        if !matches!(left.column_type, ColumnType::Regular(_)) {
+            return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)));
+        }
+
+        let right = Column {
+            table: Some(right.table_name).filter(|t| !t.is_empty()),
+            name: right.column_name.clone(),
+            column_type: ColumnType::Regular(right.column_name),
+        };
+        Ok((input, ColumnComparison { left, operator, right }))
+    }
+}
+
+impl SubqueryClause {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, column) = Column::parse(input)?;
+        let (input, operator) = delimited(
+            multispace0,
+            Op::parse,
+            multispace0
+        )(input)?;
+        let (input, _) = tag("(")(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, stmt) = SelectStatement::parse(input)?;
+        let (input, _) = tag(")")(input)?;
+
+        let subquery = match stmt {
+            Statement::Select(select) => select,
+            _ => return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify))),
+        };
+        Ok((input, SubqueryClause { column, operator, subquery: Box::new(subquery) }))
+    }
+
+    /// Runs the subquery through `run` and turns the clause into a plain
+    /// comparison against its single value
+    fn resolve<F>(self, run: &mut F) -> Result<WhereType, ReefDBError>
+    where
+        F: FnMut(SelectStatement) -> Result<QueryResult, ReefDBError>,
+    {
+        let subquery = resolve_select_subqueries(*self.subquery, run)?;
+        let result = run(subquery)?;
+        if result.column_count() != 1 || result.rows.len() != 1 {
+            return Err(ReefDBError::Other(format!(
+                "Scalar subquery must return exactly one row and one column, got {} row(s) and {} column(s)",
+                result.rows.len(),
+                result.column_count()
+            )));
+        }
+        let value = result.rows.into_iter().next()
+            .and_then(|(_, row)| row.into_iter().next())
+            .unwrap_or(DataValue::Null);
+        Ok(WhereType::Regular(WhereClause {
+            col_name: self.column.name,
+            operator: self.operator,
+            value,
+            table: self.column.table,
+        }))
+    }
+}
+
+impl CaseExpression {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, _) = tuple((tag_no_case("CASE"), multispace1))(input)?;
+        let (input, branches) = many1(map(
+            tuple((
+                tag_no_case("WHEN"),
+                multispace1,
+                parse_where_expression,
+                delimited(multispace0, tag_no_case("THEN"), multispace1),
+                DataValue::parse,
+                multispace0,
+            )),
+            |(_, _, predicate, _, value, _)| (predicate, value),
+        ))(input)?;
+        let (input, else_value) = opt(delimited(
+            tuple((tag_no_case("ELSE"), multispace1)),
+            DataValue::parse,
+            multispace0,
+        ))(input)?;
+        let (input, _) = tag_no_case("END")(input)?;
+        Ok((input, CaseExpression { branches, else_value }))
+    }
+
+    /// Replaces the subqueries in the branch predicates with their values
+    pub fn resolve_subqueries<F>(self, run: &mut F) -> Result<CaseExpression, ReefDBError>
+    where
+        F: FnMut(SelectStatement) -> Result<QueryResult, ReefDBError>,
+    {
+        let branches = self.branches.into_iter()
+            .map(|(predicate, value)| Ok((predicate.resolve_subqueries(run)?, value)))
+            .collect::<Result<Vec<_>, ReefDBError>>()?;
+        Ok(CaseExpression { branches, else_value: self.else_value })
+    }
+}
+
+impl CaseComparison {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, case) = CaseExpression::parse(input)?;
+        let (input, operator) = delimited(
+            multispace0,
+            Op::parse,
+            multispace0
+        )(input)?;
+        let (input, value) = DataValue::parse(input)?;
+        Ok((input, CaseComparison { case, operator, value }))
+    }
+}
+
+impl ExpressionComparison {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, expression) = alt((
+            DataValue::parse_operator_expression,
+            DataValue::parse_function,
+        ))(input)?;
+        let (input, operator) = delimited(
+            multispace0,
+            Op::parse,
+            multispace0
+        )(input)?;
+        let (input, value) = DataValue::parse(input)?;
+        Ok((input, ExpressionComparison { expression, operator, value }))
+    }
+
+    /// The columns the expression reads, the words among its operands
+    pub fn expression_columns(&self) -> Vec<&str> {
+        fn collect<'a>(value: &'a DataValue, columns: &mut Vec<&'a str>) {
+            match value {
+                DataValue::Text(name) => columns.push(name),
+                DataValue::Function { args, .. } => args.iter().for_each(|arg| collect(arg, columns)),
+                _ => {}
+            }
+        }
+        let mut columns = Vec::new();
+        collect(&self.expression, &mut columns);
+        columns
+    }
+}
+
+fn parse_in_prefix(input: &str) -> IResult<&str, Column> {
+    let (input, column) = Column::parse(input)?;
+    if !matches!(column.column_type, ColumnType::Regular(_)) {
+        return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)));
+    }
+    let (input, _) = tuple((multispace0, tag_no_case("IN"), multispace0, tag("("), multispace0))(input)?;
+    Ok((input, column))
+}
+
+impl InClause {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, column) = parse_in_prefix(input)?;
+        let (input, values) = separated_list1(
+            delimited(multispace0, tag(","), multispace0),
+            DataValue::parse,
+        )(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = tag(")")(input)?;
+        Ok((input, InClause { column, values }))
+    }
+}
+
+impl InSubqueryClause {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, column) = parse_in_prefix(input)?;
+        let (input, stmt) = SelectStatement::parse(input)?;
+        let (input, _) = tag(")")(input)?;
+
+        let subquery = match stmt {
+            Statement::Select(select) => select,
+            _ => return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify))),
+        };
+        Ok((input, InSubqueryClause { column, subquery: Box::new(subquery) }))
+    }
+
+    /// Runs the subquery through `run` and collects its column into an
+    /// `IN` list
+    fn resolve<F>(self, run: &mut F) -> Result<WhereType, ReefDBError>
+    where
+        F: FnMut(SelectStatement) -> Result<QueryResult, ReefDBError>,
+    {
+        let subquery = resolve_select_subqueries(*self.subquery, run)?;
+        let result = run(subquery)?;
+        if result.column_count() != 1 {
+            return Err(ReefDBError::Other(format!(
+                "Subquery in IN must return exactly one column, got {}",
+                result.column_count()
+            )));
+        }
+        let values = result.rows.into_iter()
+            .filter_map(|(_, row)| row.into_iter().next())
+            .collect();
+        Ok(WhereType::In(InClause { column: self.column, values }))
+    }
+}
+
+impl WhereType {
+    /// Replaces every subquery in the clause with the value it evaluates to,
+    /// innermost first. `run` executes a subselect against the current state.
+    pub fn resolve_subqueries<F>(self, run: &mut F) -> Result<WhereType, ReefDBError>
+    where
+        F: FnMut(SelectStatement) -> Result<QueryResult, ReefDBError>,
+    {
+        Ok(match self {
+            WhereType::Subquery(clause) => clause.resolve(run)?,
+            WhereType::InSubquery(clause) => clause.resolve(run)?,
+            WhereType::Case(clause) => WhereType::Case(CaseComparison {
+                case: clause.case.resolve_subqueries(run)?,
+                ..clause
+            }),
+            WhereType::And(left, right) => WhereType::And(
+                Box::new(left.resolve_subqueries(run)?),
+                Box::new(right.resolve_subqueries(run)?),
+            ),
+            WhereType::Or(left, right) => WhereType::Or(
+                Box::new(left.resolve_subqueries(run)?),
+                Box::new(right.resolve_subqueries(run)?),
+            ),
+            other => other,
+        })
+    }
+}
+
+pub(crate) fn resolve_select_subqueries<F>(select: SelectStatement, run: &mut F) -> Result<SelectStatement, ReefDBError>
+where
+    F: FnMut(SelectStatement) -> Result<QueryResult, ReefDBError>,
+{
+    let SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by) = select;
+    let where_clause = where_clause.map(|w| w.resolve_subqueries(run)).transpose()?;
+    Ok(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by))
+}
+
 pub fn parse_where_clause(input: &str) -> IResult<&str, WhereType> {
     let (input, _) = tag_no_case("WHERE")(input)?;
     let (input, _) = multispace1(input)?;
@@ -92,11 +404,37 @@ fn parse_parenthesized(input: &str) -> IResult<&str, WhereType> {
     Ok((input, expr))
 }
 
+/// `c BETWEEN low AND high`, which holds for `low <= c <= high` and is
+/// parsed as the two comparisons joined by AND
+fn parse_between(input: &str) -> IResult<&str, WhereType> {
+    let (input, column) = Column::parse(input)?;
+    if !matches!(column.column_type, ColumnType::Regular(_)) {
+        return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)));
+    }
+    let (input, _) = tuple((multispace0, tag_no_case("BETWEEN"), multispace1))(input)?;
+    let (input, low) = DataValue::parse(input)?;
+    let (input, _) = tuple((multispace1, tag_no_case("AND"), multispace1))(input)?;
+    let (input, high) = DataValue::parse(input)?;
+
+    let bound = |operator, value| WhereType::Regular(WhereClause::new(column.name.clone(), operator, value, column.table.clone()));
+    Ok((input, WhereType::And(
+        Box::new(bound(Op::GreaterThanOrEqual, low)),
+        Box::new(bound(Op::LessThanOrEqual, high)),
+    )))
+}
+
 fn parse_simple_where(input: &str) -> IResult<&str, WhereType> {
     alt((
         parse_parenthesized,
         parse_fts_where_clause,
-        map(WhereClause::parse, WhereType::Regular)
+        map(CaseComparison::parse, WhereType::Case),
+        parse_between,
+        map(ExpressionComparison::parse, WhereType::Expression),
+        map(WhereClause::parse, WhereType::Regular),
+        map(SubqueryClause::parse, WhereType::Subquery),
+        map(InSubqueryClause::parse, WhereType::InSubquery),
+        map(InClause::parse, WhereType::In),
+        map(ColumnComparison::parse, WhereType::ColumnComparison),
     ))(input)
 }
 
@@ -285,4 +623,148 @@ mod tests {
             _ => panic!("Expected OR clause"),
         }
     }
+
+    #[test]
+    fn test_parse_column_comparison() {
+        let (remaining, where_type) = parse_where_clause("WHERE a.id = b.a_id AND b.n > 2").unwrap();
+        assert_eq!(remaining, "");
+        match where_type {
+            WhereType::And(left, right) => {
+                match *left {
+                    WhereType::ColumnComparison(comparison) => {
+                        assert_eq!(comparison.left.table, Some("a".to_string()));
+                        assert_eq!(comparison.left.name, "id");
+                        assert_eq!(comparison.operator, Op::Equal);
+                        assert_eq!(comparison.right.table, Some("b".to_string()));
+                        assert_eq!(comparison.right.name, "a_id");
+                    }
+                    other => panic!("Expected column comparison, got {:?}", other),
+                }
+                assert!(matches!(*right, WhereType::Regular(_)));
+            }
+            other => panic!("Expected AND clause, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_scalar_subquery() {
+        let (remaining, where_type) = parse_where_clause("WHERE id = (SELECT user_id FROM banned WHERE reason = 'spam') AND age > 3").unwrap();
+        assert_eq!(remaining, "");
+        match where_type {
+            WhereType::And(left, _) => match *left {
+                WhereType::Subquery(clause) => {
+                    assert_eq!(clause.column.name, "id");
+                    assert_eq!(clause.operator, Op::Equal);
+                    let SelectStatement::FromTable(table_ref, _, where_clause, _, _, _) = *clause.subquery;
+                    assert_eq!(table_ref.name, "banned");
+                    assert!(matches!(where_clause, Some(WhereType::Regular(_))));
+                }
+                other => panic!("Expected subquery clause, got {:?}", other),
+            },
+            other => panic!("Expected AND clause, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_in() {
+        let (remaining, where_type) = parse_where_clause("WHERE id IN (1, 2,3)").unwrap();
+        assert_eq!(remaining, "");
+        match where_type {
+            WhereType::In(clause) => {
+                assert_eq!(clause.column.name, "id");
+                assert_eq!(clause.values, vec![DataValue::Integer(1), DataValue::Integer(2), DataValue::Integer(3)]);
+            }
+            other => panic!("Expected IN clause, got {:?}", other),
+        }
+
+        let (remaining, where_type) = parse_where_clause("WHERE user_id in ( SELECT id FROM active_users ) OR id = 1").unwrap();
+        assert_eq!(remaining, "");
+        match where_type {
+            WhereType::Or(left, _) => match *left {
+                WhereType::InSubquery(clause) => {
+                    assert_eq!(clause.column.name, "user_id");
+                    let SelectStatement::FromTable(table_ref, _, _, _, _, _) = *clause.subquery;
+                    assert_eq!(table_ref.name, "active_users");
+                }
+                other => panic!("Expected IN subquery, got {:?}", other),
+            },
+            other => panic!("Expected OR clause, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_case() {
+        let (remaining, where_type) = parse_where_clause(
+            "WHERE CASE WHEN age >= 18 THEN 'adult' WHEN age > 12 THEN 'teen' END = 'adult' AND id = 1"
+        ).unwrap();
+        assert_eq!(remaining, "");
+        match where_type {
+            WhereType::And(left, _) => match *left {
+                WhereType::Case(clause) => {
+                    assert_eq!(clause.case.branches.len(), 2);
+                    assert_eq!(clause.case.branches[1].1, DataValue::Text("teen".to_string()));
+                    assert_eq!(clause.case.else_value, None);
+                    assert_eq!(clause.operator, Op::Equal);
+                    assert_eq!(clause.value, DataValue::Text("adult".to_string()));
+                }
+                other => panic!("Expected CASE comparison, got {:?}", other),
+            },
+            other => panic!("Expected AND clause, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_between() {
+        let (remaining, where_type) = parse_where_clause(
+            "WHERE created_at BETWEEN '2024-01-01' AND '2024-02-01' AND id = 1"
+        ).unwrap();
+        assert_eq!(remaining, "");
+        let bound = |operator, date: &str| WhereType::Regular(WhereClause::new(
+            "created_at".to_string(), operator, DataValue::Date(date.to_string()), None,
+        ));
+        let between = WhereType::And(
+            Box::new(bound(Op::GreaterThanOrEqual, "2024-01-01")),
+            Box::new(bound(Op::LessThanOrEqual, "2024-02-01")),
+        );
+        match where_type {
+            WhereType::And(left, right) => {
+                assert_eq!(*left, between);
+                assert!(matches!(*right, WhereType::Regular(ref clause) if clause.col_name == "id"));
+            }
+            other => panic!("Expected AND clause, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_operator_expression() {
+        let (remaining, where_type) = parse_where_clause("WHERE flags % 8 & 4 = 4 AND id = 1").unwrap();
+        assert_eq!(remaining, "");
+        let call = |name: &str, args| DataValue::Function { name: name.to_string(), args };
+        match where_type {
+            WhereType::And(left, _) => assert_eq!(*left, WhereType::Expression(ExpressionComparison {
+                expression: call("bitand", vec![
+                    call("mod", vec![DataValue::Text("flags".to_string()), DataValue::Integer(8)]),
+                    DataValue::Integer(4),
+                ]),
+                operator: Op::Equal,
+                value: DataValue::Integer(4),
+            })),
+            other => panic!("Expected AND clause, got {:?}", other),
+        }
+
+        // `||` binds looser than the bitwise operators
+        let (_, expression) = DataValue::parse_operator_expression("flags | 1 || 'x'").unwrap();
+        assert_eq!(expression, call("textcat", vec![
+            call("bitor", vec![DataValue::Text("flags".to_string()), DataValue::Integer(1)]),
+            DataValue::Text("x".to_string()),
+        ]));
+
+        // A lone operand is a plain comparison, a lone call an expression
+        assert!(matches!(parse_where_clause("WHERE flags = 4").unwrap().1, WhereType::Regular(_)));
+        assert_eq!(parse_where_clause("WHERE ABS(score) > 2").unwrap().1, WhereType::Expression(ExpressionComparison {
+            expression: call("ABS", vec![DataValue::Text("score".to_string())]),
+            operator: Op::GreaterThan,
+            value: DataValue::Integer(2),
+        }));
+    }
 }
@@ -10,6 +10,8 @@ use nom::{
     sequence::{tuple, delimited},
 };
 use crate::sql::data_value::DataValue;
+use crate::sql::clauses::wheres::where_type::CaseExpression;
+use super::column_def::quoted_identifier;
 use super::function_parser::{parse_function, FunctionCall};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -24,6 +26,7 @@ pub enum ColumnType {
     Regular(String),
     Wildcard,
     Function(String, Vec<DataValue>),
+    Case(CaseExpression),
 }
 
 impl Column {
@@ -101,12 +104,15 @@ fn identifier(input: &str) -> IResult<&str, &str> {
 }
 
 fn identifier_no_space(input: &str) -> IResult<&str, &str> {
-    recognize(
-        tuple((
-            alt((alpha1, tag("_"))),
-            many0(alt((alphanumeric1, tag("_")))),
-        ))
-    )(input)
+    alt((
+        quoted_identifier,
+        recognize(
+            tuple((
+                alt((alpha1, tag("_"))),
+                many0(alt((alphanumeric1, tag("_")))),
+            ))
+        ),
+    ))(input)
 }
 
 #[cfg(test)]
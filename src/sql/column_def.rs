@@ -1,10 +1,10 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case},
-    character::complete::{alpha1, alphanumeric1, multispace1},
+    bytes::complete::{tag, take_while1},
+    character::complete::{alpha1, alphanumeric1, char, multispace1},
     combinator::{opt, recognize},
     multi::{many0, separated_list0},
-    sequence::{preceded, tuple},
+    sequence::{delimited, preceded, tuple},
     IResult,
 };
 use serde::{Deserialize, Serialize};
@@ -19,19 +19,32 @@ pub struct ColumnDef {
 }
 
 
+/// A double-quoted identifier such as `"Order"` or `"first name"`, which
+/// keeps its case and may hold spaces and reserved words. Yields the name
+/// without its quotes.
+pub fn quoted_identifier(input: &str) -> IResult<&str, &str> {
+    delimited(char('"'), take_while1(|c| c != '"'), char('"'))(input)
+}
+
 pub fn table_name(input: &str) -> IResult<&str, &str> {
-    recognize(tuple((
-        alpha1,
-        many0(alt((alphanumeric1, tag("_")))),
-        opt(preceded(tag("."), alpha1)),
-    )))(input)
+    alt((
+        quoted_identifier,
+        recognize(tuple((
+            alpha1,
+            many0(alt((alphanumeric1, tag("_")))),
+            opt(preceded(tag("."), alpha1)),
+        ))),
+    ))(input)
 }
 
 pub fn column_name(input: &str) -> IResult<&str, &str> {
-    recognize(tuple((
-        alt((alpha1, tag("_"))),
-        many0(alt((alphanumeric1, tag("_")))),
-    )))(input)
+    alt((
+        quoted_identifier,
+        recognize(tuple((
+            alt((alpha1, tag("_"))),
+            many0(alt((alphanumeric1, tag("_")))),
+        ))),
+    ))(input)
 }
 
 impl ColumnDef {
@@ -111,4 +124,14 @@ mod tests {
         let actual = ColumnDef::parse(input).unwrap().1;
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_parse_quoted_column_def() {
+        let (_, column) = ColumnDef::parse("\"Order Date\" TEXT NOT NULL").unwrap();
+        assert_eq!(column.name, "Order Date");
+        assert_eq!(column.constraints, vec![Constraint::NotNull]);
+
+        let (_, column) = ColumnDef::parse("\"select\" INTEGER").unwrap();
+        assert_eq!(column.name, "select");
+    }
 }
@@ -1,4 +1,5 @@
 use nom::{
+    branch::alt,
     bytes::complete::{tag, take_while1},
     combinator::opt,
     sequence::tuple,
@@ -6,6 +7,8 @@ use nom::{
 };
 use serde::{Deserialize, Serialize};
 
+use super::column_def::quoted_identifier;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ColumnValuePair {
     pub column_name: String,
@@ -22,7 +25,10 @@ impl ColumnValuePair {
 }
 
 pub fn identifier(input: &str) -> IResult<&str, &str> {
-    take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)
+    alt((
+        quoted_identifier,
+        take_while1(|c: char| c.is_alphanumeric() || c == '_'),
+    ))(input)
 }
 
 impl ColumnValuePair {
@@ -1,7 +1,15 @@
-use nom::{branch::alt, bytes::complete::{tag, tag_no_case}, combinator::map, IResult};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case, take_while1},
+    character::complete::{multispace0, multispace1},
+    combinator::{map, recognize},
+    sequence::{delimited, tuple},
+    IResult,
+};
 use serde::{Deserialize, Serialize};
 
 use super::foreignkey::ForeignKeyConstraint;
+use crate::sql::clauses::wheres::where_type::parse_where_expression;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Constraint {
@@ -9,6 +17,12 @@ pub enum Constraint {
     PrimaryKey,
     Unique,
     ForeignKey(ForeignKeyConstraint),
+    /// `CHECK (expr)`, holding the predicate's source text. It is parsed
+    /// when the table is created, so the text is known to be valid.
+    Check(String),
+    /// `TOKENIZER 'name'` on a TSVECTOR column: the registered tokenizer its
+    /// text is split with instead of the default language processing
+    Tokenizer(String),
     // You can add more constraints here as needed.
 }
 
@@ -19,10 +33,28 @@ impl Constraint {
             map(tag_no_case("PRIMARY KEY"), |_| Constraint::PrimaryKey),
             map(tag_no_case("UNIQUE"), |_| Constraint::Unique),
             ForeignKeyConstraint::parse,
+            parse_check,
+            parse_tokenizer,
         ))(input)
     }
 }
 
+fn parse_check(input: &str) -> IResult<&str, Constraint> {
+    let (input, _) = tuple((tag_no_case("CHECK"), multispace0))(input)?;
+    let (input, expr) = delimited(
+        tuple((tag("("), multispace0)),
+        recognize(parse_where_expression),
+        tuple((multispace0, tag(")"))),
+    )(input)?;
+    Ok((input, Constraint::Check(expr.to_string())))
+}
+
+fn parse_tokenizer(input: &str) -> IResult<&str, Constraint> {
+    let (input, _) = tuple((tag_no_case("TOKENIZER"), multispace1))(input)?;
+    let (input, name) = delimited(tag("'"), take_while1(|c| c != '\''), tag("'"))(input)?;
+    Ok((input, Constraint::Tokenizer(name.to_string())))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::sql::constraints::foreignkey::ForeignKeyConstraint;
@@ -37,6 +69,15 @@ mod tests {
             Ok(("", Constraint::PrimaryKey))
         );
         assert_eq!(Constraint::parse("UNIQUE"), Ok(("", Constraint::Unique)));
+        assert_eq!(
+            Constraint::parse("CHECK (age >= 0 AND age < 150)"),
+            Ok(("", Constraint::Check("age >= 0 AND age < 150".to_string())))
+        );
+        assert!(Constraint::parse("CHECK (age >=)").is_err());
+        assert_eq!(
+            Constraint::parse("TOKENIZER 'ngram'"),
+            Ok(("", Constraint::Tokenizer("ngram".to_string())))
+        );
         assert_eq!(
             Constraint::parse("FOREIGN KEY (id) REFERENCES users"),
             Ok((
@@ -1,4 +1,6 @@
-use nom::{IResult, bytes::complete::{tag_no_case, tag}, character::complete::{multispace1, alphanumeric1}};
+use nom::{IResult, bytes::complete::{tag_no_case, tag}, character::complete::multispace1};
+
+use crate::sql::column_def::{column_name, table_name};
 use serde::{Deserialize, Serialize};
 
 use super::constraint::Constraint;
@@ -15,12 +17,12 @@ impl ForeignKeyConstraint {
         let (input, _) = tag_no_case("FOREIGN KEY")(input)?;
         let (input, _) = multispace1(input)?;
         let (input, _) = tag("(")(input)?; // expect an opening parenthesis
-        let (input, referenced_column) = alphanumeric1(input)?;
+        let (input, referenced_column) = column_name(input)?;
         let (input, _) = tag(")")(input)?; // expect a closing parenthesis
         let (input, _) = multispace1(input)?;
         let (input, _) = tag_no_case("REFERENCES")(input)?;
         let (input, _) = multispace1(input)?;
-        let (input, referenced_table) = alphanumeric1(input)?;
+        let (input, referenced_table) = table_name(input)?;
 
         Ok((
             input,
@@ -28,6 +28,22 @@ impl DataType {
     }
 }
 
+impl std::fmt::Display for DataType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DataType::Text => "TEXT",
+            DataType::Integer => "INTEGER",
+            DataType::TSVector => "TSVECTOR",
+            DataType::Boolean => "BOOLEAN",
+            DataType::Float => "FLOAT",
+            DataType::Date => "DATE",
+            DataType::Timestamp => "TIMESTAMP",
+            DataType::Null => "NULL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -1,16 +1,18 @@
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take_while1},
+    bytes::complete::{tag, tag_no_case},
     character::complete::{multispace0, multispace1, alphanumeric1, digit1},
-    combinator::{map, opt, value, recognize},
-    multi::separated_list0,
+    combinator::{map, not, opt, value, recognize},
+    multi::{many0, separated_list0},
     number::complete::double,
-    sequence::{delimited, preceded, tuple},
+    sequence::{delimited, preceded, terminated, tuple},
     IResult,
 };
 use serde::{Deserialize, Serialize};
 use std::{fmt, cmp::Ordering};
 use crate::fts::text_processor::{TsVector, TSQuery};
+use crate::error::ReefDBError;
+use crate::functions::DISTINCT;
 
 use crate::sql::{
     column_def::table_name,
@@ -19,7 +21,84 @@ use crate::sql::{
     data_type::DataType,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// A leading minus sign, possibly followed by whitespace, negating the
+/// number after it. Without arithmetic expressions a minus where a value is
+/// expected is always unary.
+fn unary_minus(input: &str) -> IResult<&str, bool> {
+    map(opt(tuple((tag("-"), multispace0))), |minus| minus.is_some())(input)
+}
+
+/// A binary operator as a call of the builtin `name`
+fn operator(name: &str, left: DataValue, right: DataValue) -> DataValue {
+    DataValue::Function { name: name.to_string(), args: vec![left, right] }
+}
+
+/// `left % right`
+fn modulo(left: DataValue, right: DataValue) -> DataValue {
+    operator("mod", left, right)
+}
+
+/// A quoted string literal, unescaped. A quote inside it is written twice,
+/// as in `'O''Brien'`. With an `E` prefix, as in PostgreSQL, a backslash
+/// escapes the character after it: `\n`, `\t` and `\r` stand for a
+/// newline, tab and carriage return, anything else for itself.
+pub fn string_literal(input: &str) -> IResult<&str, String> {
+    let (input, escapes) = map(opt(tag_no_case("E")), |prefix| prefix.is_some())(input)?;
+    let (input, _) = tag("'")(input)?;
+    let mut result = String::new();
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\'' if input[i + 1..].starts_with('\'') => {
+                result.push('\'');
+                chars.next();
+            }
+            '\'' => return Ok((&input[i + 1..], result)),
+            '\\' if escapes => match chars.next() {
+                Some((_, 'n')) => result.push('\n'),
+                Some((_, 't')) => result.push('\t'),
+                Some((_, 'r')) => result.push('\r'),
+                Some((_, escaped)) => result.push(escaped),
+                None => break,
+            },
+            c => result.push(c),
+        }
+    }
+
+    Err(nom::Err::Error(nom::error::Error::new(
+        input,
+        nom::error::ErrorKind::Tag,
+    )))
+}
+
+/// Whether `input` starts with the exponent of a number, as in `1e-2`
+fn has_exponent(input: &str) -> bool {
+    let Some(exponent) = input.strip_prefix(['e', 'E']) else {
+        return false;
+    };
+    let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+    exponent.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Format timestamps are stored in. It sorts chronologically as text.
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// Parses an ISO-8601 timestamp (`2024-01-01 12:00:00`, `2024-01-01T12:00:00Z`,
+/// `2024-01-01T12:00:00.5+02:00`, ...) and renders it in UTC using
+/// [`TIMESTAMP_FORMAT`]. Returns `None` if the text is not a timestamp.
+pub fn normalize_timestamp(text: &str) -> Option<String> {
+    parse_timestamp_text(text).map(|ts| ts.format(TIMESTAMP_FORMAT).to_string())
+}
+
+pub(crate) fn parse_timestamp_text(text: &str) -> Option<chrono::NaiveDateTime> {
+    if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(text) {
+        return Some(ts.naive_utc());
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"].iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(text, format).ok())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DataValue {
     Text(String),
     Integer(i64),
@@ -36,32 +115,61 @@ pub enum DataValue {
     },
 }
 
-impl PartialOrd for DataValue {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+/// Compares floats with NaN after every other number and equal to itself
+fn cmp_floats(a: f64, b: f64) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+    }
+}
+
+/// Values are equal when they compare equal, so `1 = 1.0`; full-text
+/// values, which have no meaningful order, compare structurally
+impl PartialEq for DataValue {
+    fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (DataValue::Integer(a), DataValue::Integer(b)) => Some(a.cmp(b)),
-            (DataValue::Text(a), DataValue::Text(b)) => Some(a.cmp(b)),
-            (DataValue::TSVector(a), DataValue::TSVector(b)) => Some(a.tokens.len().cmp(&b.tokens.len())),
-            (DataValue::TSQuery(a), DataValue::TSQuery(b)) => Some(a.tokens.len().cmp(&b.tokens.len())),
-            (DataValue::Boolean(a), DataValue::Boolean(b)) => Some(a.cmp(b)),
-            (DataValue::Float(a), DataValue::Float(b)) => a.partial_cmp(b),
-            (DataValue::Date(a), DataValue::Date(b)) => Some(a.cmp(b)),
-            (DataValue::Timestamp(a), DataValue::Timestamp(b)) => Some(a.cmp(b)),
-            (DataValue::Null, DataValue::Null) => Some(Ordering::Equal),
-            (DataValue::Null, _) => Some(Ordering::Less),
-            (_, DataValue::Null) => Some(Ordering::Greater),
-            (DataValue::Function { .. }, _) => None,
-            (_, DataValue::Function { .. }) => None,
-            _ => None,
+            (DataValue::TSVector(a), DataValue::TSVector(b)) => a == b,
+            (DataValue::TSQuery(a), DataValue::TSQuery(b)) => a == b,
+            _ => self.cmp(other) == Ordering::Equal,
         }
     }
 }
 
+impl PartialOrd for DataValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Eq for DataValue {}
 
+/// The one ordering of values, used by comparisons, ORDER BY, MIN/MAX and
+/// index keys. NULL sorts first. Integers and floats compare numerically,
+/// dates compare with timestamps as midnight, and values of unrelated types
+/// order by type: booleans, numbers, text, dates, then full-text values
+/// and functions.
 impl Ord for DataValue {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Less)
+        match (self, other) {
+            (DataValue::Integer(a), DataValue::Integer(b)) => a.cmp(b),
+            (DataValue::Float(a), DataValue::Float(b)) => cmp_floats(*a, *b),
+            (DataValue::Integer(a), DataValue::Float(b)) => cmp_floats(*a as f64, *b),
+            (DataValue::Float(a), DataValue::Integer(b)) => cmp_floats(*a, *b as f64),
+            (DataValue::Text(a), DataValue::Text(b)) => a.cmp(b),
+            (DataValue::Boolean(a), DataValue::Boolean(b)) => a.cmp(b),
+            (DataValue::Date(a), DataValue::Date(b)) => a.cmp(b),
+            (DataValue::Timestamp(a), DataValue::Timestamp(b)) => a.cmp(b),
+            (DataValue::Date(a), DataValue::Timestamp(b)) => format!("{} 00:00:00", a).cmp(b),
+            (DataValue::Timestamp(a), DataValue::Date(b)) => a.cmp(&format!("{} 00:00:00", b)),
+            (DataValue::TSVector(a), DataValue::TSVector(b)) => a.to_string().cmp(&b.to_string()),
+            (DataValue::TSQuery(a), DataValue::TSQuery(b)) => a.to_string().cmp(&b.to_string()),
+            (DataValue::Function { name: a, args: x }, DataValue::Function { name: b, args: y }) => {
+                a.cmp(b).then_with(|| x.cmp(y))
+            }
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
     }
 }
 
@@ -82,12 +190,108 @@ impl DataValue {
         }
     }
 
+    /// Converts the value to `data_type`, as `ALTER COLUMN ... TYPE` does.
+    /// Integers widen to floats, floats narrow only when they are whole,
+    /// text is parsed, and anything converts to text. NULL stays NULL.
+    pub fn cast_to(&self, data_type: &DataType) -> Result<DataValue, ReefDBError> {
+        let invalid = || ReefDBError::Other(format!("Cannot convert {} to {:?}", self, data_type));
+        Ok(match (self, data_type) {
+            (DataValue::Null, _) => DataValue::Null,
+            (DataValue::Function { .. }, _) => return Err(invalid()),
+            (value, data_type) if value.matches_type(data_type) => value.clone(),
+            (DataValue::Integer(i), DataType::Float) => DataValue::Float(*i as f64),
+            (DataValue::Float(f), DataType::Integer) => {
+                if f.fract() != 0.0 || !f.is_finite() || f.abs() >= i64::MAX as f64 {
+                    return Err(invalid());
+                }
+                DataValue::Integer(*f as i64)
+            }
+            (DataValue::Boolean(b), DataType::Integer) => DataValue::Integer(*b as i64),
+            (DataValue::Text(s), DataType::Integer) => DataValue::Integer(s.trim().parse().map_err(|_| invalid())?),
+            (DataValue::Text(s), DataType::Float) => DataValue::Float(s.trim().parse().map_err(|_| invalid())?),
+            (DataValue::Text(s), DataType::Boolean) => match s.trim().to_lowercase().as_str() {
+                "true" => DataValue::Boolean(true),
+                "false" => DataValue::Boolean(false),
+                _ => return Err(invalid()),
+            },
+            (DataValue::Text(s), DataType::Date) => {
+                chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|_| invalid())?;
+                DataValue::Date(s.trim().to_string())
+            }
+            (DataValue::Text(s), DataType::Timestamp) => {
+                DataValue::Timestamp(normalize_timestamp(s.trim()).ok_or_else(invalid)?)
+            }
+            (DataValue::Date(d), DataType::Timestamp) => {
+                DataValue::Timestamp(normalize_timestamp(&format!("{} 00:00:00", d)).ok_or_else(invalid)?)
+            }
+            (DataValue::Timestamp(ts), DataType::Date) => {
+                DataValue::Date(parse_timestamp_text(ts).ok_or_else(invalid)?.date().to_string())
+            }
+            (value, DataType::Text) => DataValue::Text(value.to_string()),
+            _ => return Err(invalid()),
+        })
+    }
+
+    /// Reads a quoted literal as the type of the column or operand it
+    /// meets. Quoted text stays text until then, so only a TIMESTAMP turns
+    /// ISO-8601 text into a timestamp; everything else is left as it is.
+    pub fn coerce_to(self, data_type: &DataType) -> DataValue {
+        match (self, data_type) {
+            (DataValue::Text(s), DataType::Timestamp) => match normalize_timestamp(s.trim()) {
+                Some(timestamp) => DataValue::Timestamp(timestamp),
+                None => DataValue::Text(s),
+            },
+            (value, _) => value,
+        }
+    }
+
+    /// Position of the value's type in the cross-type order. Integers and
+    /// floats share a rank, as do dates and timestamps.
+    fn type_rank(&self) -> u8 {
+        match self {
+            DataValue::Null => 0,
+            DataValue::Boolean(_) => 1,
+            DataValue::Integer(_) | DataValue::Float(_) => 2,
+            DataValue::Text(_) => 3,
+            DataValue::Date(_) | DataValue::Timestamp(_) => 4,
+            DataValue::TSVector(_) => 5,
+            DataValue::TSQuery(_) => 6,
+            DataValue::Function { .. } => 7,
+        }
+    }
+
+    /// `cmp` for sorting, MIN/MAX and comparison operators, which refuse to
+    /// order values of unrelated types rather than fall back on type order.
+    /// NULL still sorts before every other value.
+    pub fn sort_cmp(&self, other: &Self) -> Result<Ordering, ReefDBError> {
+        let comparable = matches!((self, other), (DataValue::Null, _) | (_, DataValue::Null))
+            || self.type_rank() == other.type_rank();
+        if !comparable {
+            return Err(ReefDBError::Other(format!("Cannot compare {:?} with {:?}", self, other)));
+        }
+        Ok(self.cmp(other))
+    }
+
+    /// Converts the value to a JSON scalar. Dates, timestamps and full-text
+    /// values become their text form; non-finite floats become `null`.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            DataValue::Text(s) | DataValue::Date(s) | DataValue::Timestamp(s) => serde_json::Value::String(s.clone()),
+            DataValue::Integer(i) => serde_json::Value::from(*i),
+            DataValue::Boolean(b) => serde_json::Value::Bool(*b),
+            DataValue::Float(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            DataValue::Null => serde_json::Value::Null,
+            other => serde_json::Value::String(other.to_string()),
+        }
+    }
+
     pub fn parse(input: &str) -> IResult<&str, Self> {
         let (input, _) = multispace0(input)?;
         alt((
             Self::parse_function,
             Self::parse_date,
-            Self::parse_timestamp,
             Self::parse_quoted_text,
             Self::parse_integer,
             Self::parse_float,
@@ -97,20 +301,20 @@ impl DataValue {
     }
 
     fn parse_integer(input: &str) -> IResult<&str, DataValue> {
-        let (input, value) = recognize(tuple((
-            opt(tag("-")),
-            digit1
-        )))(input)?;
+        let (rest, (negative, digits)) = tuple((unary_minus, digit1))(input)?;
         
-        // Check if the next character is a decimal point
-        if input.starts_with('.') {
+        // A decimal point or an exponent makes it a float
+        if rest.starts_with('.') || has_exponent(rest) {
             return Err(nom::Err::Error(nom::error::Error::new(
-                input,
+                rest,
                 nom::error::ErrorKind::Digit,
             )));
         }
         
-        Ok((input, DataValue::Integer(value.parse().unwrap())))
+        // Too large for an integer, so it is left to the float parser
+        let value = format!("{}{}", if negative { "-" } else { "" }, digits).parse()
+            .map_err(|_| nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Digit)))?;
+        Ok((rest, DataValue::Integer(value)))
     }
     
     fn parse_boolean(input: &str) -> IResult<&str, DataValue> {
@@ -121,8 +325,13 @@ impl DataValue {
     }
     
     fn parse_float(input: &str) -> IResult<&str, DataValue> {
+        let (input, negative) = unary_minus(input)?;
+        // `double` takes a sign of its own, which must not follow a minus
+        if input.starts_with(['-', '+']) {
+            return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Float)));
+        }
         let (input, value) = double(input)?;
-        Ok((input, DataValue::Float(value)))
+        Ok((input, DataValue::Float(if negative { -value } else { value })))
     }
     
     fn parse_date(input: &str) -> IResult<&str, DataValue> {
@@ -141,68 +350,19 @@ impl DataValue {
         Ok((input, DataValue::Date(date_str.to_string())))
     }
     
-    fn parse_timestamp(input: &str) -> IResult<&str, DataValue> {
-        let (input, timestamp_str) = delimited(
-            tag("'"),
-            recognize(nom::sequence::tuple((
-                nom::character::complete::digit1,
-                tag("-"),
-                nom::character::complete::digit1,
-                tag("-"),
-                nom::character::complete::digit1,
-                tag(" "),
-                nom::character::complete::digit1,
-                tag(":"),
-                nom::character::complete::digit1,
-                tag(":"),
-                nom::character::complete::digit1,
-            ))),
-            tag("'"),
-        )(input)?;
-        
-        Ok((input, DataValue::Timestamp(timestamp_str.to_string())))
-    }
-    
     fn parse_null(input: &str) -> IResult<&str, DataValue> {
         map(tag_no_case("NULL"), |_| DataValue::Null)(input)
     }
     
     fn parse_quoted_text(input: &str) -> IResult<&str, DataValue> {
-        let (input, _) = tag("'")(input)?;
-        let mut result = String::new();
-        let mut chars = input.chars();
-        let mut pos = 0;
-    
-        while let Some(c) = chars.next() {
-            pos += 1;
-            if c == '\'' {
-                // Look ahead for another quote
-                if let Some(next_c) = chars.clone().next() {
-                    if next_c == '\'' {
-                        // This is an escaped quote
-                        result.push('\'');
-                        chars.next(); // Skip the next quote
-                        pos += 1;
-                        continue;
-                    }
-                }
-                // This is the end of the string
-                return Ok((&input[pos..], DataValue::Text(result)));
-            } else {
-                result.push(c);
-            }
-        }
-    
-        Err(nom::Err::Error(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::Tag,
-        )))
+        map(string_literal, DataValue::Text)(input)
     }
 
     pub fn parse_function(input: &str) -> IResult<&str, DataValue> {
-        let (input, (name, _, args)) = tuple((
+        let (input, (name, _, distinct, args)) = tuple((
             preceded(multispace0, identifier),
             preceded(multispace0, tag("(")),
+            opt(tuple((multispace0, tag_no_case("DISTINCT"), multispace1))),
             delimited(
                 multispace0,
                 separated_list0(
@@ -215,13 +375,25 @@ impl DataValue {
                         Self::parse_float,
                         Self::parse_boolean,
                         Self::parse_null,
-                        map(identifier, |s: &str| DataValue::Text(s.to_string())),
+                        // A column, possibly qualified by its table
+                        map(tuple((opt(tuple((identifier, tag(".")))), identifier)), |(table, column)| match table {
+                            Some((table, _)) => DataValue::Text(format!("{}.{}", table, column)),
+                            None => DataValue::Text(column.to_string()),
+                        }),
+                        // The `*` of COUNT(*)
+                        map(tag("*"), |s: &str| DataValue::Text(s.to_string())),
                     )),
                 ),
                 tuple((multispace0, tag(")"))),
             ),
         ))(input)?;
 
+        // `COUNT(DISTINCT x)` keeps DISTINCT as a pseudo-function around x
+        let args = match distinct {
+            Some(_) => vec![DataValue::Function { name: DISTINCT.to_string(), args }],
+            None => args,
+        };
+
         Ok((
             input,
             DataValue::Function {
@@ -231,6 +403,72 @@ impl DataValue {
         ))
     }
 
+    /// Operands joined by `%`, `&`, `|`, `^` or `||`, as a call of the
+    /// builtin each operator stands for: `flags & 4` is `bitand(flags, 4)`
+    /// and `first || last` is `textcat(first, last)`. `%` binds tighter
+    /// than the bitwise operators, which bind tighter than `||`, and
+    /// operators of the same kind group from the left. Fails without at
+    /// least one operator.
+    pub fn parse_operator_expression(input: &str) -> IResult<&str, DataValue> {
+        let (rest, (first, modulos, bitwise, concatenated)) = tuple((
+            Self::parse_operand,
+            many0(Self::parse_modulo_operand),
+            many0(Self::parse_bitwise_operand),
+            many0(preceded(delimited(multispace0, tag("||"), multispace0), Self::parse_bitwise_term)),
+        ))(input)?;
+        if modulos.is_empty() && bitwise.is_empty() && concatenated.is_empty() {
+            return Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify)));
+        }
+        let first = modulos.into_iter().fold(first, modulo);
+        let first = bitwise.into_iter().fold(first, |left, (name, right)| operator(name, left, right));
+        Ok((rest, concatenated.into_iter().fold(first, |left, right| operator("textcat", left, right))))
+    }
+
+    fn parse_bitwise_term(input: &str) -> IResult<&str, DataValue> {
+        let (input, first) = Self::parse_modulo_term(input)?;
+        let (input, others) = many0(Self::parse_bitwise_operand)(input)?;
+        Ok((input, others.into_iter().fold(first, |left, (name, right)| operator(name, left, right))))
+    }
+
+    /// `&`, `|` or `^` and the operand after it, with the builtin the
+    /// operator stands for
+    fn parse_bitwise_operand(input: &str) -> IResult<&str, (&'static str, DataValue)> {
+        tuple((
+            delimited(multispace0, alt((
+                value("bitand", tag("&")),
+                value("bitxor", tag("^")),
+                // `||` is not `|` twice
+                value("bitor", terminated(tag("|"), not(tag("|")))),
+            )), multispace0),
+            Self::parse_modulo_term,
+        ))(input)
+    }
+
+    fn parse_modulo_term(input: &str) -> IResult<&str, DataValue> {
+        let (input, first) = Self::parse_operand(input)?;
+        let (input, others) = many0(Self::parse_modulo_operand)(input)?;
+        Ok((input, others.into_iter().fold(first, modulo)))
+    }
+
+    fn parse_modulo_operand(input: &str) -> IResult<&str, DataValue> {
+        preceded(delimited(multispace0, tag("%"), multispace0), Self::parse_operand)(input)
+    }
+
+    /// A function call, number, string or column, possibly qualified by
+    /// its table
+    fn parse_operand(input: &str) -> IResult<&str, DataValue> {
+        alt((
+            Self::parse_function,
+            Self::parse_integer,
+            Self::parse_float,
+            Self::parse_quoted_text,
+            map(tuple((opt(tuple((identifier, tag(".")))), identifier)), |(table, column)| match table {
+                Some((table, _)) => DataValue::Text(format!("{}.{}", table, column)),
+                None => DataValue::Text(column.to_string()),
+            }),
+        ))(input)
+    }
+
     pub fn parse_array(input: &str) -> IResult<&str, DataValue> {
         let (input, elements) = delimited(
             tuple((multispace0, tag("["), multispace0)),
@@ -268,10 +506,59 @@ mod tests {
             DataValue::parse("'Hello World'"),
             Ok(("", DataValue::Text("Hello World".to_string())))
         );
+        assert_eq!(
+            DataValue::parse("'東京タワー', 1"),
+            Ok((", 1", DataValue::Text("東京タワー".to_string())))
+        );
+        assert_eq!(
+            DataValue::parse("'O''Brien'"),
+            Ok(("", DataValue::Text("O'Brien".to_string())))
+        );
+        assert_eq!(
+            DataValue::parse("'a\\nb'"),
+            Ok(("", DataValue::Text("a\\nb".to_string())))
+        );
+        assert_eq!(
+            DataValue::parse("E'a\\nb\\t\\'c\\\\'"),
+            Ok(("", DataValue::Text("a\nb\t'c\\".to_string())))
+        );
+        assert_eq!(
+            DataValue::parse("'two\nlines'"),
+            Ok(("", DataValue::Text("two\nlines".to_string())))
+        );
+        assert!(DataValue::parse("'unterminated''").is_err());
         assert_eq!(
             DataValue::parse("123"),
             Ok(("", DataValue::Integer(123)))
         );
+        assert_eq!(
+            DataValue::parse("-123"),
+            Ok(("", DataValue::Integer(-123)))
+        );
+        assert_eq!(
+            DataValue::parse("- 5)"),
+            Ok((")", DataValue::Integer(-5)))
+        );
+        assert_eq!(
+            DataValue::parse("-9223372036854775808"),
+            Ok(("", DataValue::Integer(i64::MIN)))
+        );
+        assert_eq!(
+            DataValue::parse("99999999999999999999"),
+            Ok(("", DataValue::Float(1e20)))
+        );
+        assert_eq!(
+            DataValue::parse("-0.5"),
+            Ok(("", DataValue::Float(-0.5)))
+        );
+        assert!(DataValue::parse("--5").is_err());
+        assert_eq!(DataValue::parse("9.99"), Ok(("", DataValue::Float(9.99))));
+        assert_eq!(DataValue::parse(".5"), Ok(("", DataValue::Float(0.5))));
+        assert_eq!(DataValue::parse("5."), Ok(("", DataValue::Float(5.0))));
+        assert_eq!(DataValue::parse("1.5e3"), Ok(("", DataValue::Float(1500.0))));
+        assert_eq!(DataValue::parse("1e-2"), Ok(("", DataValue::Float(0.01))));
+        assert_eq!(DataValue::parse("-2E+2"), Ok(("", DataValue::Float(-200.0))));
+        assert_eq!(DataValue::parse("3 end"), Ok((" end", DataValue::Integer(3))));
         assert_eq!(
             DataValue::parse("TRUE"),
             Ok(("", DataValue::Boolean(true)))
@@ -290,7 +577,11 @@ mod tests {
         );
         assert_eq!(
             DataValue::parse("'2024-03-14 12:34:56'"),
-            Ok(("", DataValue::Timestamp("2024-03-14 12:34:56".to_string())))
+            Ok(("", DataValue::Text("2024-03-14 12:34:56".to_string())))
+        );
+        assert_eq!(
+            DataValue::parse("'2024-03-14T12:34:56.250-01:00'"),
+            Ok(("", DataValue::Text("2024-03-14T12:34:56.250-01:00".to_string())))
         );
         assert_eq!(
             DataValue::parse("NULL"),
@@ -350,4 +641,49 @@ mod tests {
             }))
         );
     }
+
+    #[test]
+    fn sort_cmp_test() {
+        assert_eq!(DataValue::Null.sort_cmp(&DataValue::Integer(1)), Ok(Ordering::Less));
+        assert_eq!(DataValue::Float(f64::NAN).sort_cmp(&DataValue::Float(1e9)), Ok(Ordering::Greater));
+        assert_eq!(DataValue::Float(f64::NAN).sort_cmp(&DataValue::Float(f64::NAN)), Ok(Ordering::Equal));
+        assert_eq!(DataValue::Integer(2).sort_cmp(&DataValue::Float(1.5)), Ok(Ordering::Greater));
+        assert!(DataValue::Text("a".to_string()).sort_cmp(&DataValue::Integer(1)).is_err());
+    }
+
+    #[test]
+    fn ordering_test() {
+        let mut values = vec![
+            DataValue::Float(2.5),
+            DataValue::Text("b".to_string()),
+            DataValue::Integer(-3),
+            DataValue::Null,
+            DataValue::Float(f64::NAN),
+            DataValue::Integer(10),
+            DataValue::Boolean(true),
+            DataValue::Float(-0.5),
+            DataValue::Integer(2),
+            DataValue::Text("a".to_string()),
+            DataValue::Float(-7.0),
+        ];
+        values.sort();
+        let expected = vec![
+            DataValue::Null,
+            DataValue::Boolean(true),
+            DataValue::Float(-7.0),
+            DataValue::Integer(-3),
+            DataValue::Float(-0.5),
+            DataValue::Integer(2),
+            DataValue::Float(2.5),
+            DataValue::Integer(10),
+            DataValue::Float(f64::NAN),
+            DataValue::Text("a".to_string()),
+            DataValue::Text("b".to_string()),
+        ];
+        assert_eq!(format!("{:?}", values), format!("{:?}", expected));
+
+        assert_eq!(DataValue::Integer(1), DataValue::Float(1.0));
+        assert!(DataValue::Integer(1) < DataValue::Float(1.5));
+        assert_eq!(DataValue::Date("2024-01-01".to_string()), DataValue::Timestamp("2024-01-01 00:00:00".to_string()));
+    }
 }
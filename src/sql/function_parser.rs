@@ -9,7 +9,8 @@ use nom::{
     IResult,
     error::Error,
 };
-use crate::sql::data_value::DataValue;
+use crate::sql::column_def::quoted_identifier;
+use crate::sql::data_value::{string_literal, DataValue};
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -31,7 +32,10 @@ fn is_function_identifier_char(c: char) -> bool {
 
 // Parser for identifiers (used for aliases)
 fn identifier_no_space(input: &str) -> IResult<&str, &str> {
-    nom::bytes::complete::take_while1(is_argument_identifier_char)(input)
+    alt((
+        quoted_identifier,
+        nom::bytes::complete::take_while1(is_argument_identifier_char),
+    ))(input)
 }
 
 // Parser for function names (no dots)
@@ -52,14 +56,7 @@ fn identifier(input: &str) -> IResult<&str, &str> {
 fn parse_argument(input: &str) -> IResult<&str, DataValue> {
     alt((
         map(tag("*"), |s: &str| DataValue::Text(s.to_string())),
-        map(
-            delimited(
-                tag("'"),
-                nom::bytes::complete::take_until("'"),
-                tag("'")
-            ),
-            |s: &str| DataValue::Text(s.to_string())
-        ),
+        map(string_literal, DataValue::Text),
         map(parse_function_call, |f| DataValue::Function {
             name: f.name,
             args: f.args,
@@ -1,5 +1,10 @@
+use std::cmp::Ordering;
+
 use nom::{branch::alt, IResult, combinator::map, bytes::complete::{tag_no_case, tag}};
 
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Op {
     Match,
@@ -10,6 +15,11 @@ pub enum Op {
     GreaterThanOrEqual,
     LessThanOrEqual,
     TextSearch,
+    /// `LIKE` pattern match, where `%` matches any run of characters and `_`
+    /// any single character
+    Like,
+    /// Case-insensitive `LIKE`
+    ILike,
 }
 
 impl Op {
@@ -23,21 +33,85 @@ impl Op {
             map(tag_no_case("="), |_| Op::Equal),
             map(tag_no_case("MATCH"), |_| Op::Match),
             map(tag_no_case("!="), |_| Op::NotEqual),
+            map(tag_no_case("LIKE"), |_| Op::Like),
+            map(tag_no_case("ILIKE"), |_| Op::ILike),
         ))(input)
     }
 
-    pub fn evaluate(&self, left: &crate::sql::data_value::DataValue, right: &crate::sql::data_value::DataValue) -> bool {
+    pub fn evaluate(&self, left: &DataValue, right: &DataValue) -> bool {
+        // As in SQL a comparison with NULL is never true. NULL only sorts
+        // first for ORDER BY.
+        if matches!(left, DataValue::Null) || matches!(right, DataValue::Null) {
+            return false;
+        }
+        // A quoted literal compared with a timestamp is read as one
+        let coerced;
+        let (left, right) = match (left, right) {
+            (DataValue::Timestamp(_), DataValue::Text(_)) => {
+                coerced = right.clone().coerce_to(&DataType::Timestamp);
+                (left, &coerced)
+            }
+            (DataValue::Text(_), DataValue::Timestamp(_)) => {
+                coerced = left.clone().coerce_to(&DataType::Timestamp);
+                (&coerced, right)
+            }
+            _ => (left, right),
+        };
         match self {
             Op::Equal => left == right,
             Op::NotEqual => left != right,
-            Op::GreaterThan => left > right,
-            Op::LessThan => left < right,
-            Op::GreaterThanOrEqual => left >= right,
-            Op::LessThanOrEqual => left <= right,
+            // Values of unrelated types are never less or greater
+            Op::GreaterThan => matches!(left.sort_cmp(right), Ok(Ordering::Greater)),
+            Op::LessThan => matches!(left.sort_cmp(right), Ok(Ordering::Less)),
+            Op::GreaterThanOrEqual => matches!(left.sort_cmp(right), Ok(Ordering::Greater | Ordering::Equal)),
+            Op::LessThanOrEqual => matches!(left.sort_cmp(right), Ok(Ordering::Less | Ordering::Equal)),
             Op::Match => false, // FTS matching is handled separately
             Op::TextSearch => false, // Full-text search matching is handled separately in the FTS module
+            Op::Like | Op::ILike => match (left, right) {
+                (DataValue::Text(text), DataValue::Text(pattern)) if *self == Op::ILike => {
+                    like_match(&text.to_lowercase(), &pattern.to_lowercase())
+                }
+                (DataValue::Text(text), DataValue::Text(pattern)) => like_match(text, pattern),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Matches `text` against a LIKE pattern, backtracking to the last `%` on a
+/// mismatch
+fn like_match(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    let (mut t, mut p) = (0, 0);
+    let mut last_wildcard = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('%') => {
+                last_wildcard = Some((p, t));
+                p += 1;
+            }
+            Some('_') => {
+                t += 1;
+                p += 1;
+            }
+            Some(c) if *c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            _ => match last_wildcard {
+                // Let the last % absorb one more character and retry
+                Some((wildcard_p, wildcard_t)) => {
+                    last_wildcard = Some((wildcard_p, wildcard_t + 1));
+                    p = wildcard_p + 1;
+                    t = wildcard_t + 1;
+                }
+                None => return false,
+            },
         }
     }
+    pattern[p..].iter().all(|c| *c == '%')
 }
 
 #[cfg(test)]
@@ -45,6 +119,25 @@ mod tests {
     use super::Op;
     use crate::sql::data_value::DataValue;
 
+    #[test]
+    fn like_test() {
+        let text = |s: &str| DataValue::Text(s.to_string());
+        assert_eq!(Op::parse("LIKE"), Ok(("", Op::Like)));
+        assert_eq!(Op::parse("ilike"), Ok(("", Op::ILike)));
+
+        assert!(Op::Like.evaluate(&text("Alice"), &text("Al%")));
+        assert!(!Op::Like.evaluate(&text("alice"), &text("Al%")));
+        assert!(Op::Like.evaluate(&text("Alice"), &text("%li_e")));
+        assert!(Op::Like.evaluate(&text("banana"), &text("%an%na")));
+        assert!(!Op::Like.evaluate(&text("Alice"), &text("A_e")));
+        assert!(Op::Like.evaluate(&text(""), &text("%")));
+        assert!(!Op::Like.evaluate(&DataValue::Integer(1), &text("%")));
+
+        assert!(Op::ILike.evaluate(&text("Alice"), &text("al%")));
+        assert!(Op::ILike.evaluate(&text("alice"), &text("AL%")));
+        assert!(!Op::ILike.evaluate(&text("Bob"), &text("al%")));
+    }
+
     #[test]
     fn parse_test() {
         assert_eq!(Op::parse("="), Ok(("", Op::Equal)));
@@ -68,5 +161,19 @@ mod tests {
         assert!(op.evaluate(&DataValue::Integer(5), &DataValue::Integer(3)));
         assert!(op.evaluate(&DataValue::Integer(3), &DataValue::Integer(3)));
         assert!(!op.evaluate(&DataValue::Integer(3), &DataValue::Integer(5)));
+
+        assert!(Op::GreaterThan.evaluate(&DataValue::Float(10.5), &DataValue::Integer(10)));
+        assert!(Op::LessThanOrEqual.evaluate(&DataValue::Integer(-2), &DataValue::Float(-2.0)));
+        assert!(!Op::GreaterThan.evaluate(&DataValue::Text("b".to_string()), &DataValue::Integer(1)));
+        assert!(Op::Equal.evaluate(&DataValue::Float(10.0), &DataValue::Integer(10)));
+        assert!(!Op::NotEqual.evaluate(&DataValue::Integer(10), &DataValue::Float(10.0)));
+        assert!(Op::NotEqual.evaluate(&DataValue::Integer(10), &DataValue::Float(10.5)));
+
+        // Comparisons with NULL are never true, even though NULL sorts first
+        for op in [Op::Equal, Op::NotEqual, Op::LessThan, Op::LessThanOrEqual, Op::GreaterThan] {
+            assert!(!op.evaluate(&DataValue::Null, &DataValue::Integer(1)), "{:?}", op);
+            assert!(!op.evaluate(&DataValue::Integer(1), &DataValue::Null), "{:?}", op);
+            assert!(!op.evaluate(&DataValue::Null, &DataValue::Null), "{:?}", op);
+        }
     }
 }
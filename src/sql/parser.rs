@@ -11,10 +11,97 @@ impl Parser {
                 if remaining.trim().is_empty() {
                     Ok(stmt)
                 } else {
-                    Err(ReefDBError::Other(format!("Unexpected input after statement: {}", remaining)))
+                    Err(syntax_error(input, remaining))
                 }
             }
-            Err(e) => Err(ReefDBError::Other(format!("Failed to parse SQL: {}", e))),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(syntax_error(input, e.input)),
+            Err(nom::Err::Incomplete(_)) => Err(syntax_error(input, "")),
         }
     }
-} 
\ No newline at end of file
+
+    /// Parses one or more statements separated by semicolons
+    pub fn parse_script(input: &str) -> Result<Vec<Statement>, ReefDBError> {
+        match Statement::parse_script(input) {
+            Ok((_, statements)) => Ok(statements),
+            Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(syntax_error(input, e.input)),
+            Err(nom::Err::Incomplete(_)) => Err(syntax_error(input, "")),
+        }
+    }
+}
+
+/// A syntax error for `sql` where parsing stopped with `rest` left over,
+/// naming the word it stopped at
+fn syntax_error(sql: &str, rest: &str) -> ReefDBError {
+    let rest = rest.trim_start();
+    let message = match rest.split_whitespace().next() {
+        Some(word) => format!("unexpected \"{}\"", word),
+        None => "unexpected end of input".to_string(),
+    };
+    ReefDBError::SyntaxError { message, position: sql.len() - rest.len() }
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(sql: &str) -> usize {
+        match Parser::parse_sql(sql) {
+            Err(ReefDBError::SyntaxError { position, .. }) => position,
+            other => panic!("Expected a syntax error for {:?}, got {:?}", sql, other),
+        }
+    }
+
+    #[test]
+    fn test_syntax_error_position() {
+        assert_eq!(position("SELCT * FROM t"), 0);
+        assert_eq!(position("SELECT * FORM t"), 9);
+        // LIMT reads as an alias of t
+        assert_eq!(position("  SELECT * FROM t LIMT 3"), 23);
+        assert_eq!(position("CREATE TABLE t (id INTEGR)"), 19);
+        assert_eq!(position("INSERT INTO t VALUES (1, 2"), 26);
+
+        let err = Parser::parse_sql("SELECT * FORM t").unwrap_err();
+        assert_eq!(err, ReefDBError::SyntaxError { message: "unexpected \"FORM\"".to_string(), position: 9 });
+        assert_eq!(err.to_string(), "Syntax error at position 9: unexpected \"FORM\"");
+    }
+
+    #[test]
+    fn test_trailing_input() {
+        assert!(Parser::parse_sql("SELECT * FROM t;").is_ok());
+        assert!(Parser::parse_sql("  SELECT * FROM t ;  -- all of them\n").is_ok());
+        assert!(Parser::parse_sql("-- everything\nSELECT * FROM t /* for now */").is_ok());
+
+        assert_eq!(position("SELECT * FROM t WHEN id=1"), 16);
+        assert_eq!(position("SELECT * FROM t AS u GARBAGE"), 21);
+        assert_eq!(position("SELECT * FROM t; SELECT * FROM u"), 17);
+        assert_eq!(position("SELECT * FROM t;;"), 16);
+        assert_eq!(position("SELECT * FROM t /* unterminated"), 16);
+    }
+
+    #[test]
+    fn test_syntax_error_caret() {
+        let sql = "SELECT *\nFORM t";
+        let err = Parser::parse_sql(sql).unwrap_err();
+        assert_eq!(err.caret(sql).unwrap(), "FORM t\n^");
+
+        let sql = "SELCT * FROM t";
+        assert_eq!(Parser::parse_sql(sql).unwrap_err().caret(sql).unwrap(), "SELCT * FROM t\n^");
+        assert_eq!(ReefDBError::TableNotFound("t".to_string()).caret(sql), None);
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let statements = Parser::parse_script(
+            "CREATE TABLE t (id INTEGER PRIMARY KEY);\n-- seed\nINSERT INTO t VALUES (1); INSERT INTO t VALUES (2)"
+        ).unwrap();
+        assert_eq!(statements.len(), 3);
+        assert!(matches!(statements[2], Statement::Insert(_)));
+        assert_eq!(Parser::parse_script("SELECT * FROM t;").unwrap().len(), 1);
+        assert!(Parser::parse_script(" -- nothing\n").unwrap().is_empty());
+
+        // Statements need a semicolon between them
+        let err = Parser::parse_script("SELECT * FROM t SELECT * FROM u").unwrap_err();
+        assert!(matches!(err, ReefDBError::SyntaxError { position: 16, .. }));
+        let err = Parser::parse_script("SELECT * FROM t; SELEC * FROM u").unwrap_err();
+        assert!(matches!(err, ReefDBError::SyntaxError { position: 17, .. }));
+    }
+}
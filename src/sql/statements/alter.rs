@@ -1,20 +1,25 @@
 use nom::{
     branch::alt,
     bytes::complete::tag_no_case,
-    character::complete::{multispace1, alphanumeric1},
-    combinator::map,
+    character::complete::multispace1,
+    combinator::opt,
     sequence::tuple,
     IResult,
 };
 
-use crate::sql::column_def::ColumnDef;
+use serde::{Deserialize, Serialize};
+
+use crate::sql::column_def::{column_name, table_name, ColumnDef};
+use crate::sql::data_type::DataType;
 use super::Statement;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum AlterType {
     AddColumn(ColumnDef),
     DropColumn(String),
     RenameColumn(String, String),
+    AlterColumnType { column: String, new_type: DataType },
+    RenameTable(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -27,13 +32,15 @@ impl AlterStatement {
     pub fn parse(input: &str) -> IResult<&str, Statement> {
         let (input, _) = tag_no_case("ALTER TABLE")(input)?;
         let (input, _) = multispace1(input)?;
-        let (input, table_name) = alphanumeric1(input)?;
+        let (input, table_name) = table_name(input)?;
         let (input, _) = multispace1(input)?;
         
         let (input, alter_type) = alt((
             parse_add_column,
             parse_drop_column,
             parse_rename_column,
+            parse_rename_table,
+            parse_alter_column_type,
         ))(input)?;
 
         Ok((
@@ -57,7 +64,7 @@ fn parse_add_column(input: &str) -> IResult<&str, AlterType> {
 fn parse_drop_column(input: &str) -> IResult<&str, AlterType> {
     let (input, _) = tag_no_case("DROP COLUMN")(input)?;
     let (input, _) = multispace1(input)?;
-    let (input, column_name) = alphanumeric1(input)?;
+    let (input, column_name) = column_name(input)?;
     
     Ok((input, AlterType::DropColumn(column_name.to_string())))
 }
@@ -65,19 +72,39 @@ fn parse_drop_column(input: &str) -> IResult<&str, AlterType> {
 fn parse_rename_column(input: &str) -> IResult<&str, AlterType> {
     let (input, _) = tag_no_case("RENAME COLUMN")(input)?;
     let (input, _) = multispace1(input)?;
-    let (input, old_name) = alphanumeric1(input)?;
+    let (input, old_name) = column_name(input)?;
     let (input, _) = multispace1(input)?;
     let (input, _) = tag_no_case("TO")(input)?;
     let (input, _) = multispace1(input)?;
-    let (input, new_name) = alphanumeric1(input)?;
+    let (input, new_name) = column_name(input)?;
     
     Ok((input, AlterType::RenameColumn(old_name.to_string(), new_name.to_string())))
 }
 
+fn parse_rename_table(input: &str) -> IResult<&str, AlterType> {
+    let (input, _) = tag_no_case("RENAME TO")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, new_name) = table_name(input)?;
+
+    Ok((input, AlterType::RenameTable(new_name.to_string())))
+}
+
+fn parse_alter_column_type(input: &str) -> IResult<&str, AlterType> {
+    let (input, _) = tag_no_case("ALTER COLUMN")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, column) = column_name(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, _) = opt(tuple((tag_no_case("SET DATA"), multispace1)))(input)?;
+    let (input, _) = tag_no_case("TYPE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, new_type) = DataType::parse(input)?;
+
+    Ok((input, AlterType::AlterColumnType { column: column.to_string(), new_type }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sql::data_type::DataType;
 
     #[test]
     fn test_alter_add_column() {
@@ -127,4 +154,34 @@ mod tests {
             ))
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_alter_rename_table() {
+        assert_eq!(
+            AlterStatement::parse("ALTER TABLE users RENAME TO accounts"),
+            Ok((
+                "",
+                Statement::Alter(AlterStatement {
+                    table_name: "users".to_string(),
+                    alter_type: AlterType::RenameTable("accounts".to_string()),
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn test_alter_column_type() {
+        let expected = Ok((
+            "",
+            Statement::Alter(AlterStatement {
+                table_name: "users".to_string(),
+                alter_type: AlterType::AlterColumnType {
+                    column: "age".to_string(),
+                    new_type: DataType::Float,
+                },
+            })
+        ));
+        assert_eq!(AlterStatement::parse("ALTER TABLE users ALTER COLUMN age TYPE FLOAT"), expected);
+        assert_eq!(AlterStatement::parse("ALTER TABLE users ALTER COLUMN age SET DATA TYPE FLOAT"), expected);
+    }
+}
@@ -1,17 +1,20 @@
 use nom::{
     bytes::complete::{tag_no_case, tag},
-    character::complete::{multispace0, multispace1, alphanumeric1},
+    character::complete::{multispace0, multispace1},
     combinator::opt,
     sequence::tuple,
     IResult,
 };
 
+use crate::sql::column_def::{column_name, table_name};
+
 use super::Statement;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum IndexType {
     BTree,
     GIN,
+    Hash,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -19,12 +22,21 @@ pub struct CreateIndexStatement {
     pub table_name: String,
     pub column_name: String,
     pub index_type: IndexType,
+    /// `CREATE OR REPLACE INDEX`: an existing index on the column is
+    /// dropped first instead of being an error
+    pub or_replace: bool,
 }
 
 impl CreateIndexStatement {
     pub fn parse(input: &str) -> IResult<&str, Statement> {
         let (input, _) = tag_no_case("CREATE")(input)?;
         let (input, _) = multispace1(input)?;
+        let (input, or_replace) = opt(tuple((
+            tag_no_case("OR"),
+            multispace1,
+            tag_no_case("REPLACE"),
+            multispace1,
+        )))(input)?;
         
         // Optional index type
         let (input, index_type) = opt(tuple((
@@ -36,20 +48,35 @@ impl CreateIndexStatement {
         let (input, _) = multispace1(input)?;
         let (input, _) = tag_no_case("ON")(input)?;
         let (input, _) = multispace1(input)?;
-        let (input, table_name) = alphanumeric1(input)?;
+        let (input, table_name) = table_name(input)?;
         let (input, _) = multispace0(input)?;
         let (input, _) = tag("(")(input)?;
         let (input, _) = multispace0(input)?;
-        let (input, column_name) = alphanumeric1(input)?;
+        let (input, column_name) = column_name(input)?;
         let (input, _) = multispace0(input)?;
         let (input, _) = tag(")")(input)?;
+
+        // Optional access method, only HASH differs from the default
+        let (input, using_hash) = opt(tuple((
+            multispace1,
+            tag_no_case("USING"),
+            multispace1,
+            tag_no_case("HASH"),
+        )))(input)?;
+
+        let index_type = match (index_type, using_hash) {
+            (Some(_), _) => IndexType::GIN,
+            (None, Some(_)) => IndexType::Hash,
+            (None, None) => IndexType::BTree,
+        };
         
         Ok((
             input,
             Statement::CreateIndex(CreateIndexStatement {
                 table_name: table_name.to_string(),
                 column_name: column_name.to_string(),
-                index_type: if index_type.is_some() { IndexType::GIN } else { IndexType::BTree },
+                index_type,
+                or_replace: or_replace.is_some(),
             }),
         ))
     }
@@ -69,6 +96,40 @@ mod tests {
                 assert_eq!(stmt.table_name, "users");
                 assert_eq!(stmt.column_name, "id");
                 assert_eq!(stmt.index_type, IndexType::BTree);
+                assert!(!stmt.or_replace);
+            }
+            _ => panic!("Expected CreateIndex statement"),
+        }
+    }
+
+    #[test]
+    fn test_create_or_replace_index_parse() {
+        let input = "CREATE OR REPLACE INDEX ON users(email) USING HASH";
+        let (remaining, stmt) = CreateIndexStatement::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        match stmt {
+            Statement::CreateIndex(stmt) => {
+                assert_eq!(stmt.column_name, "email");
+                assert_eq!(stmt.index_type, IndexType::Hash);
+                assert!(stmt.or_replace);
+            }
+            _ => panic!("Expected CreateIndex statement"),
+        }
+
+        let (_, stmt) = CreateIndexStatement::parse("create or replace gin index on articles(content)").unwrap();
+        assert!(matches!(stmt, Statement::CreateIndex(CreateIndexStatement { index_type: IndexType::GIN, or_replace: true, .. })));
+    }
+
+    #[test]
+    fn test_create_hash_index_parse() {
+        let input = "CREATE INDEX ON users(email) USING HASH";
+        let (remaining, stmt) = CreateIndexStatement::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        match stmt {
+            Statement::CreateIndex(stmt) => {
+                assert_eq!(stmt.table_name, "users");
+                assert_eq!(stmt.column_name, "email");
+                assert_eq!(stmt.index_type, IndexType::Hash);
             }
             _ => panic!("Expected CreateIndex statement"),
         }
@@ -1,19 +1,21 @@
 use nom::{
     bytes::complete::{tag, tag_no_case},
-    character::complete::{multispace0, multispace1, alphanumeric1},
+    character::complete::{multispace0, multispace1},
     combinator::{map, opt},
     sequence::{delimited, tuple},
     IResult,
 };
 
 use crate::sql::{
+    column_def::table_name,
     clauses::wheres::where_type::{parse_where_clause, WhereType},
-    statements::Statement,
+    statements::{parse_limit, parse_returning, Statement},
 };
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum DeleteStatement {
-    FromTable(String, Option<WhereType>),
+    /// Table, WHERE clause and LIMIT
+    FromTable(String, Option<WhereType>, Option<usize>),
 }
 
 impl DeleteStatement {
@@ -32,16 +34,18 @@ impl DeleteStatement {
 
         let (input, table_name) = delimited(
             multispace0,
-            alphanumeric1,
+            table_name,
             multispace0
         )(input)?;
 
         let (input, where_clause) = opt(parse_where_clause)(input)?;
+        let (input, limit) = parse_limit(input)?;
 
-        Ok((input, Statement::Delete(DeleteStatement::FromTable(
+        parse_returning(input, Statement::Delete(DeleteStatement::FromTable(
             table_name.to_string(),
             where_clause,
-        ))))
+            limit,
+        )))
     }
 }
 
@@ -60,7 +64,7 @@ mod tests {
         let (remaining, stmt) = DeleteStatement::parse(input).unwrap();
         assert_eq!(remaining, "");
         match stmt {
-            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause)) => {
+            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause, None)) => {
                 assert_eq!(table_name, "users");
                 assert!(where_clause.is_none());
             }
@@ -74,7 +78,7 @@ mod tests {
         let (remaining, stmt) = DeleteStatement::parse(input).unwrap();
         assert_eq!(remaining, "");
         match stmt {
-            Statement::Delete(DeleteStatement::FromTable(table_name, Some(WhereType::Regular(where_clause)))) => {
+            Statement::Delete(DeleteStatement::FromTable(table_name, Some(WhereType::Regular(where_clause)), None)) => {
                 assert_eq!(table_name, "users");
                 assert_eq!(where_clause.col_name, "id");
                 assert_eq!(where_clause.operator, Op::Equal);
@@ -90,7 +94,7 @@ mod tests {
         let (remaining, stmt) = DeleteStatement::parse(input).unwrap();
         assert_eq!(remaining, "");
         match stmt {
-            Statement::Delete(DeleteStatement::FromTable(table_name, Some(WhereType::Regular(where_clause)))) => {
+            Statement::Delete(DeleteStatement::FromTable(table_name, Some(WhereType::Regular(where_clause)), None)) => {
                 assert_eq!(table_name, "users");
                 assert_eq!(where_clause.col_name, "status");
                 assert_eq!(where_clause.operator, Op::Equal);
@@ -99,4 +103,15 @@ mod tests {
             _ => panic!("Expected Delete statement with where clause"),
         }
     }
+
+    #[test]
+    fn parse_delete_limit_test() {
+        let (remaining, stmt) = DeleteStatement::parse("DELETE FROM logs WHERE level = 'debug' LIMIT 100").unwrap();
+        assert_eq!(remaining, "");
+        assert!(matches!(stmt, Statement::Delete(DeleteStatement::FromTable(_, Some(_), Some(100)))));
+
+        let (remaining, stmt) = DeleteStatement::parse("DELETE FROM logs LIMIT 0").unwrap();
+        assert_eq!(remaining, "");
+        assert!(matches!(stmt, Statement::Delete(DeleteStatement::FromTable(_, None, Some(0)))));
+    }
 }
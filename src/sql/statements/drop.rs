@@ -1,9 +1,11 @@
 use nom::{
     bytes::complete::tag_no_case,
-    character::complete::{multispace1, alphanumeric1},
+    character::complete::multispace1,
     IResult,
 };
 
+use crate::sql::column_def::table_name;
+
 use super::Statement;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -15,7 +17,7 @@ impl DropStatement {
     pub fn parse(input: &str) -> IResult<&str, Statement> {
         let (input, _) = tag_no_case("DROP TABLE")(input)?;
         let (input, _) = multispace1(input)?;
-        let (input, table_name) = alphanumeric1(input)?;
+        let (input, table_name) = table_name(input)?;
         
         Ok((
             input,
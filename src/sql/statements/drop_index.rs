@@ -1,9 +1,11 @@
 use nom::{
     bytes::complete::tag_no_case,
-    character::complete::{multispace1, alphanumeric1},
+    character::complete::multispace1,
     IResult,
 };
 
+use crate::sql::column_def::{column_name, table_name};
+
 use super::Statement;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -16,10 +18,10 @@ impl DropIndexStatement {
     pub fn parse(input: &str) -> IResult<&str, Statement> {
         let (input, _) = tag_no_case("DROP INDEX ON")(input)?;
         let (input, _) = multispace1(input)?;
-        let (input, table_name) = alphanumeric1(input)?;
+        let (input, table_name) = table_name(input)?;
         let (input, _) = multispace1(input)?;
         let (input, _) = tag_no_case("(")(input)?;
-        let (input, column_name) = alphanumeric1(input)?;
+        let (input, column_name) = column_name(input)?;
         let (input, _) = tag_no_case(")")(input)?;
         
         Ok((
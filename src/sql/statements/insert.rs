@@ -1,20 +1,66 @@
 use crate::sql::data_value::DataValue;
-use crate::sql::column_def::table_name;
+use crate::sql::column_def::{column_name, table_name};
 
 use nom::{
+    branch::alt,
     bytes::complete::{tag, tag_no_case},
-    character::complete::{alphanumeric1, multispace0, multispace1},
+    character::complete::{multispace0, multispace1},
     multi::separated_list0,
-    sequence::{delimited, tuple},
+    sequence::{delimited, preceded, tuple},
     IResult,
-    combinator::opt,
+    combinator::{map, opt},
 };
 
+use super::update::parse_assignments;
 use super::Statement;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum InsertStatement {
     IntoTable(String, Vec<DataValue>),
+    /// `INSERT ... ON CONFLICT`: the insert becomes an update of the
+    /// conflicting row, or is skipped
+    Upsert(String, Vec<DataValue>, OnConflict),
+}
+
+/// The `ON CONFLICT [(column)] DO ...` suffix of an insert. Without a target
+/// column every UNIQUE and PRIMARY KEY column is checked.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OnConflict {
+    pub target: Option<String>,
+    pub action: ConflictAction,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConflictAction {
+    Nothing,
+    Update(Vec<(String, DataValue)>),
+}
+
+impl OnConflict {
+    pub fn parse(input: &str) -> IResult<&str, Self> {
+        let (input, _) = tuple((tag_no_case("ON"), multispace1, tag_no_case("CONFLICT"), multispace0))(input)?;
+        let (input, target) = opt(delimited(
+            tuple((tag("("), multispace0)),
+            column_name,
+            tuple((multispace0, tag(")"), multispace0)),
+        ))(input)?;
+        let (input, _) = tuple((tag_no_case("DO"), multispace1))(input)?;
+        let (input, action) = alt((
+            map(tag_no_case("NOTHING"), |_| ConflictAction::Nothing),
+            map(
+                preceded(
+                    tuple((tag_no_case("UPDATE"), multispace1, tag_no_case("SET"), multispace1)),
+                    parse_assignments,
+                ),
+                ConflictAction::Update,
+            ),
+        ))(input)?;
+
+        Ok((input, OnConflict {
+            target: target.map(|t| t.to_string()),
+            action,
+        }))
+    }
 }
 
 impl InsertStatement {
@@ -30,7 +76,7 @@ impl InsertStatement {
             multispace0,
             separated_list0(
                 tuple((multispace0, tag(","), multispace0)),
-                alphanumeric1
+                column_name
             ),
             multispace0,
             tag(")")
@@ -49,11 +95,13 @@ impl InsertStatement {
         )(input)?;
 
         let values: Vec<DataValue> = values.into_iter().collect();
+        let (input, on_conflict) = opt(preceded(multispace1, OnConflict::parse))(input)?;
 
-        Ok((
-            input,
-            Statement::Insert(InsertStatement::IntoTable(table_name.to_string(), values)),
-        ))
+        let insert = match on_conflict {
+            Some(on_conflict) => InsertStatement::Upsert(table_name.to_string(), values, on_conflict),
+            None => InsertStatement::IntoTable(table_name.to_string(), values),
+        };
+        Ok((input, Statement::Insert(insert)))
     }
 }
 
@@ -114,4 +162,31 @@ mod tests {
             _ => panic!("Expected Insert statement"),
         }
     }
+
+    #[test]
+    fn parse_insert_on_conflict() {
+        let input = "INSERT INTO users VALUES (1, 'Al') ON CONFLICT (id) DO UPDATE SET name = 'Al', age = 3";
+        let (remaining, stmt) = InsertStatement::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(stmt, Statement::Insert(InsertStatement::Upsert(
+            "users".to_string(),
+            vec![DataValue::Integer(1), DataValue::Text("Al".to_string())],
+            OnConflict {
+                target: Some("id".to_string()),
+                action: ConflictAction::Update(vec![
+                    ("name".to_string(), DataValue::Text("Al".to_string())),
+                    ("age".to_string(), DataValue::Integer(3)),
+                ]),
+            },
+        )));
+
+        let input = "INSERT INTO users VALUES (1, 'Al') ON CONFLICT DO NOTHING";
+        let (remaining, stmt) = InsertStatement::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        assert_eq!(stmt, Statement::Insert(InsertStatement::Upsert(
+            "users".to_string(),
+            vec![DataValue::Integer(1), DataValue::Text("Al".to_string())],
+            OnConflict { target: None, action: ConflictAction::Nothing },
+        )));
+    }
 }
@@ -2,12 +2,21 @@ use self::{
     create::CreateStatement, delete::DeleteStatement, insert::InsertStatement,
     select::SelectStatement, update::UpdateStatement, alter::AlterStatement, drop::DropStatement,
     create_index::CreateIndexStatement, drop_index::DropIndexStatement,
+    union::UnionStatement,
 };
 
+use crate::error::ReefDBError;
+use crate::result::QueryResult;
+use crate::sql::clauses::wheres::where_type::resolve_select_subqueries;
+use crate::sql::column::Column;
+use crate::sql::column_def::{column_name, table_name};
+
 use nom::{
     branch::alt,
-    bytes::complete::{tag_no_case, take_while1},
-    character::complete::{multispace0, multispace1},
+    bytes::complete::{tag, tag_no_case, take_until, take_while1},
+    character::complete::{digit1, multispace0, multispace1, not_line_ending},
+    combinator::{map_res, opt, recognize, value},
+    multi::many0,
     sequence::{preceded, tuple},
     IResult,
 };
@@ -21,12 +30,17 @@ pub mod alter;
 pub mod drop;
 pub mod create_index;
 pub mod drop_index;
+pub mod union;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Statement {
     Create(CreateStatement),
     Insert(InsertStatement),
     Select(SelectStatement),
+    /// `SELECT ... FOR UPDATE`: locks the selected table until the
+    /// transaction ends
+    SelectForUpdate(SelectStatement),
+    Union(UnionStatement),
     Update(UpdateStatement),
     Delete(DeleteStatement),
     Alter(AlterStatement),
@@ -38,6 +52,31 @@ pub enum Statement {
     ReleaseSavepoint(String),
     BeginTransaction,
     Commit,
+    Rollback,
+    ShowTables,
+    Describe(String),
+    Reindex {
+        table: String,
+        column: Option<String>,
+    },
+    /// `VACUUM`: compacts storage and rebuilds indexes over the moved rows
+    Vacuum,
+    /// `VERIFY INDEX t (c)`: cross-checks an index against the table rows
+    VerifyIndex {
+        table: String,
+        column: String,
+    },
+    /// `EXPLAIN [ANALYZE] SELECT ...`: the steps of a query, run and
+    /// measured with ANALYZE
+    Explain {
+        analyze: bool,
+        statement: Box<Statement>,
+    },
+    /// An UPDATE or DELETE that returns the rows it touched
+    Returning {
+        statement: Box<Statement>,
+        columns: Vec<Column>,
+    },
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -55,6 +94,11 @@ fn parse_commit(input: &str) -> IResult<&str, Statement> {
     Ok((input, Statement::Commit))
 }
 
+fn parse_rollback(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = tag_no_case("ROLLBACK")(input)?;
+    Ok((input, Statement::Rollback))
+}
+
 fn parse_savepoint(input: &str) -> IResult<&str, Statement> {
     let (input, _) = tag_no_case("SAVEPOINT")(input)?;
     let (input, _) = multispace1(input)?;
@@ -63,39 +107,163 @@ fn parse_savepoint(input: &str) -> IResult<&str, Statement> {
 }
 
 fn parse_rollback_to_savepoint(input: &str) -> IResult<&str, Statement> {
-    let (input, _) = tag_no_case("ROLLBACK TO SAVEPOINT")(input)?;
-    let (input, _) = multispace1(input)?;
+    let (input, _) = tuple((
+        tag_no_case("ROLLBACK"), multispace1,
+        tag_no_case("TO"), multispace1,
+        tag_no_case("SAVEPOINT"), multispace1,
+    ))(input)?;
     let (input, name) = take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)?;
     Ok((input, Statement::RollbackToSavepoint(name.to_string())))
 }
 
 fn parse_release_savepoint(input: &str) -> IResult<&str, Statement> {
-    let (input, _) = tag_no_case("RELEASE SAVEPOINT")(input)?;
-    let (input, _) = multispace1(input)?;
+    let (input, _) = tuple((
+        tag_no_case("RELEASE"), multispace1,
+        tag_no_case("SAVEPOINT"), multispace1,
+    ))(input)?;
     let (input, name) = take_while1(|c: char| c.is_alphanumeric() || c == '_')(input)?;
     Ok((input, Statement::ReleaseSavepoint(name.to_string())))
 }
 
+fn parse_show_tables(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = tuple((tag_no_case("SHOW"), multispace1, tag_no_case("TABLES")))(input)?;
+    Ok((input, Statement::ShowTables))
+}
+
+fn parse_describe(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = alt((tag_no_case("DESCRIBE"), tag_no_case("DESC")))(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, table) = table_name(input)?;
+    Ok((input, Statement::Describe(table.to_string())))
+}
+
+fn parse_reindex(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = tag_no_case("REINDEX TABLE")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, table) = table_name(input)?;
+    let (input, column) = opt(preceded(
+        tuple((multispace1, tag_no_case("COLUMN"), multispace1)),
+        column_name,
+    ))(input)?;
+    Ok((input, Statement::Reindex {
+        table: table.to_string(),
+        column: column.map(|c| c.to_string()),
+    }))
+}
+
+fn parse_vacuum(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = tag_no_case("VACUUM")(input)?;
+    Ok((input, Statement::Vacuum))
+}
+
+fn parse_verify_index(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = tuple((tag_no_case("VERIFY"), multispace1, tag_no_case("INDEX"), multispace1))(input)?;
+    let (input, table) = table_name(input)?;
+    let (input, _) = tuple((multispace0, tag("("), multispace0))(input)?;
+    let (input, column) = column_name(input)?;
+    let (input, _) = tuple((multispace0, tag(")")))(input)?;
+    Ok((input, Statement::VerifyIndex {
+        table: table.to_string(),
+        column: column.to_string(),
+    }))
+}
+
+fn parse_explain(input: &str) -> IResult<&str, Statement> {
+    let (input, _) = tuple((tag_no_case("EXPLAIN"), multispace1))(input)?;
+    let (input, analyze) = opt(tuple((tag_no_case("ANALYZE"), multispace1)))(input)?;
+    let (input, statement) = SelectStatement::parse(input)?;
+    Ok((input, Statement::Explain {
+        analyze: analyze.is_some(),
+        statement: Box::new(statement),
+    }))
+}
+
+/// Wraps `statement` in `Statement::Returning` when it is followed by a
+/// `RETURNING` column list
+pub(crate) fn parse_returning(input: &str, statement: Statement) -> IResult<&str, Statement> {
+    let (input, columns) = opt(preceded(
+        tuple((multispace0, tag_no_case("RETURNING"), multispace1)),
+        select::parse_column_list,
+    ))(input)?;
+    Ok((input, match columns {
+        Some(columns) => Statement::Returning { statement: Box::new(statement), columns },
+        None => statement,
+    }))
+}
+
+/// The optional `LIMIT n` of an UPDATE or DELETE, the most rows it may
+/// change
+pub(crate) fn parse_limit(input: &str) -> IResult<&str, Option<usize>> {
+    opt(preceded(
+        tuple((multispace0, tag_no_case("LIMIT"), multispace1)),
+        map_res(digit1, str::parse),
+    ))(input)
+}
+
+/// Whitespace and `--` or `/* */` comments, which may surround a
+/// statement but not appear inside one
+fn ignorable(input: &str) -> IResult<&str, ()> {
+    value((), many0(alt((
+        multispace1,
+        recognize(tuple((tag("--"), not_line_ending))),
+        recognize(tuple((tag("/*"), take_until("*/"), tag("*/")))),
+    ))))(input)
+}
+
+type StatementParser = fn(&str) -> IResult<&str, Statement>;
+
+/// Statement parsers, tried in order
+const STATEMENT_PARSERS: &[StatementParser] = &[
+    CreateStatement::parse,
+    InsertStatement::parse,
+    UnionStatement::parse,
+    SelectStatement::parse,
+    UpdateStatement::parse,
+    DeleteStatement::parse,
+    AlterStatement::parse,
+    DropStatement::parse,
+    CreateIndexStatement::parse,
+    DropIndexStatement::parse,
+    parse_savepoint,
+    parse_rollback_to_savepoint,
+    parse_release_savepoint,
+    parse_begin_transaction,
+    parse_commit,
+    // After ROLLBACK TO SAVEPOINT so the bare keyword doesn't shadow it
+    parse_rollback,
+    parse_reindex,
+    parse_vacuum,
+    parse_verify_index,
+    parse_show_tables,
+    parse_describe,
+    parse_explain,
+];
+
+/// The first statement that parses. When none does, the error is the one
+/// from the parser that got furthest, which is where the statement most
+/// likely went wrong, rather than whichever parser happened to be last.
+fn parse_any_statement(input: &str) -> IResult<&str, Statement> {
+    let mut furthest: Option<nom::error::Error<&str>> = None;
+    for parse in STATEMENT_PARSERS {
+        match parse(input) {
+            Err(nom::Err::Error(error)) => {
+                if furthest.as_ref().is_none_or(|furthest| error.input.len() < furthest.input.len()) {
+                    furthest = Some(error);
+                }
+            }
+            result => return result,
+        }
+    }
+    Err(nom::Err::Error(furthest.unwrap_or_else(|| {
+        nom::error::Error::new(input, nom::error::ErrorKind::Alt)
+    })))
+}
+
 impl Statement {
     pub fn parse(input: &str) -> IResult<&str, Statement> {
-        let (input, _) = multispace0(input)?;
-        let (input, stmt) = alt((
-            CreateStatement::parse,
-            InsertStatement::parse,
-            SelectStatement::parse,
-            UpdateStatement::parse,
-            DeleteStatement::parse,
-            AlterStatement::parse,
-            DropStatement::parse,
-            CreateIndexStatement::parse,
-            DropIndexStatement::parse,
-            parse_savepoint,
-            parse_rollback_to_savepoint,
-            parse_release_savepoint,
-            parse_begin_transaction,
-            parse_commit,
-        ))(input)?;
-        let (input, _) = multispace0(input)?;
+        let (input, _) = ignorable(input)?;
+        let (input, stmt) = parse_any_statement(input)?;
+        let (input, _) = tuple((ignorable, opt(tag(";")), ignorable))(input)?;
         if !input.is_empty() {
             return Err(nom::Err::Error(nom::error::Error::new(
                 input,
@@ -104,4 +272,74 @@ impl Statement {
         }
         Ok((input, stmt))
     }
+
+    /// Parses a script of statements separated by semicolons, allowing the
+    /// same comments and final semicolon as `parse`. An empty script has no
+    /// statements.
+    pub fn parse_script(input: &str) -> IResult<&str, Vec<Statement>> {
+        let mut statements = Vec::new();
+        let (mut input, _) = ignorable(input)?;
+        while !input.is_empty() {
+            let (rest, stmt) = parse_any_statement(input)?;
+            let (rest, separator) = tuple((ignorable, opt(tag(";")), ignorable))(rest)?;
+            if separator.1.is_none() && !rest.is_empty() {
+                return Err(nom::Err::Error(nom::error::Error::new(
+                    rest,
+                    nom::error::ErrorKind::Eof
+                )));
+            }
+            statements.push(stmt);
+            input = rest;
+        }
+        Ok((input, statements))
+    }
+
+    /// Whether the statement leaves the database unchanged, which is all a
+    /// read-only transaction may run. `SELECT ... FOR UPDATE` counts as a
+    /// write since it locks rows for one.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self,
+            Statement::Select(_)
+            | Statement::Union(_)
+            | Statement::ShowTables
+            | Statement::Describe(_)
+            | Statement::VerifyIndex { .. }
+            | Statement::Explain { .. }
+            | Statement::Savepoint(_)
+            | Statement::RollbackToSavepoint(_)
+            | Statement::ReleaseSavepoint(_)
+            | Statement::BeginTransaction
+            | Statement::Commit
+            | Statement::Rollback)
+    }
+
+    /// Evaluates the scalar subqueries in the statement's WHERE clause with
+    /// `run`, leaving plain comparisons behind
+    pub fn resolve_subqueries<F>(self, run: &mut F) -> Result<Statement, ReefDBError>
+    where
+        F: FnMut(SelectStatement) -> Result<QueryResult, ReefDBError>,
+    {
+        Ok(match self {
+            Statement::Select(select) => Statement::Select(resolve_select_subqueries(select, run)?),
+            Statement::SelectForUpdate(select) => Statement::SelectForUpdate(resolve_select_subqueries(select, run)?),
+            Statement::Union(union) => Statement::Union(UnionStatement {
+                left: resolve_select_subqueries(union.left, run)?,
+                right: resolve_select_subqueries(union.right, run)?,
+                all: union.all,
+            }),
+            Statement::Update(UpdateStatement::UpdateTable(table, updates, where_clause, limit)) => {
+                let where_clause = where_clause.map(|w| w.resolve_subqueries(run)).transpose()?;
+                Statement::Update(UpdateStatement::UpdateTable(table, updates, where_clause, limit))
+            }
+            Statement::Delete(DeleteStatement::FromTable(table, where_clause, limit)) => {
+                let where_clause = where_clause.map(|w| w.resolve_subqueries(run)).transpose()?;
+                Statement::Delete(DeleteStatement::FromTable(table, where_clause, limit))
+            }
+            Statement::Returning { statement, columns } => Statement::Returning {
+                statement: Box::new(statement.resolve_subqueries(run)?),
+                columns,
+            },
+            other => other,
+        })
+    }
 }
@@ -3,26 +3,29 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case},
     character::complete::{alpha1, alphanumeric1, multispace0, multispace1},
-    combinator::{map, opt, recognize},
+    combinator::{consumed, map, opt, recognize},
     multi::{many0, separated_list0, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
 };
 use crate::sql::{
     clauses::{
         join_clause::JoinClause,
-        wheres::where_type::{WhereType, parse_where_clause},
-        order_by::OrderByClause,
+        wheres::where_type::{CaseExpression, WhereType, parse_where_clause},
+        order_by::{OrderByClause, OrderByTarget},
     },
     column::{Column, ColumnType},
+    column_def::quoted_identifier,
     data_value::DataValue,
-    table_reference::TableReference,
+    table_reference::{parse_alias, TableReference},
     operators::op::Op,
 };
 use crate::sql::statements::Statement;
+use std::collections::HashMap;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum SelectStatement {
-    FromTable(TableReference, Vec<Column>, Option<WhereType>, Vec<JoinClause>, Vec<OrderByClause>),
+    /// Table, selected columns, WHERE clause, joins, ORDER BY and GROUP BY
+    FromTable(TableReference, Vec<Column>, Option<WhereType>, Vec<JoinClause>, Vec<OrderByClause>, Vec<Column>),
 }
 
 impl SelectStatement {
@@ -44,28 +47,42 @@ impl SelectStatement {
             parse_where_clause
         ))(input)?;
 
+        let (input, group_by) = opt(preceded(
+            tuple((multispace0, tag_no_case("GROUP"), multispace1, tag_no_case("BY"), multispace1)),
+            separated_list1(delimited(multispace0, tag(","), multispace0), parse_column),
+        ))(input)?;
+
         let (input, order_by_clauses) = opt(preceded(
             multispace0,
             OrderByClause::parse
         ))(input)?;
 
+        let (input, for_update) = opt(tuple((
+            multispace0,
+            tag_no_case("FOR"),
+            multispace1,
+            tag_no_case("UPDATE"),
+        )))(input)?;
+
         let (input, _) = multispace0(input)?;
-        Ok((input, Statement::Select(SelectStatement::FromTable(
+        let select = SelectStatement::FromTable(
             table_ref,
             columns,
             where_clause,
             joins,
             order_by_clauses.unwrap_or_default(),
-        ))))
+            group_by.unwrap_or_default(),
+        );
+        Ok((input, match for_update {
+            Some(_) => Statement::SelectForUpdate(select),
+            None => Statement::Select(select),
+        }))
     }
 }
 
 fn parse_table_reference(input: &str) -> IResult<&str, TableReference> {
     let (input, name) = identifier(input)?;
-    let (input, alias) = opt(preceded(
-        delimited(multispace0, tag_no_case("AS"), multispace1),
-        identifier
-    ))(input)?;
+    let (input, alias) = parse_alias(input)?;
     Ok((input, TableReference {
         name: name.to_string(),
         alias: alias.map(|a| a.to_string()),
@@ -73,15 +90,18 @@ fn parse_table_reference(input: &str) -> IResult<&str, TableReference> {
 }
 
 fn identifier(input: &str) -> IResult<&str, &str> {
-    recognize(
-        tuple((
-            alpha1,
-            many0(alt((alphanumeric1, tag("_"))))
-        ))
-    )(input)
+    alt((
+        quoted_identifier,
+        recognize(
+            tuple((
+                alpha1,
+                many0(alt((alphanumeric1, tag("_"))))
+            ))
+        ),
+    ))(input)
 }
 
-fn parse_column_list(input: &str) -> IResult<&str, Vec<Column>> {
+pub(crate) fn parse_column_list(input: &str) -> IResult<&str, Vec<Column>> {
     alt((
         // Handle SELECT *
         map(
@@ -96,6 +116,40 @@ fn parse_column_list(input: &str) -> IResult<&str, Vec<Column>> {
         separated_list1(
             delimited(multispace0, tag(","), multispace0),
             alt((
+                // Handle CASE expressions with optional alias
+                map(
+                    tuple((
+                        CaseExpression::parse,
+                        opt(preceded(
+                            delimited(multispace0, tag_no_case("as"), multispace1),
+                            identifier
+                        ))
+                    )),
+                    |(case, alias)| Column {
+                        table: None,
+                        name: alias.unwrap_or("case").to_string(),
+                        column_type: ColumnType::Case(case),
+                    }
+                ),
+                // Handle operator expressions with optional alias, named
+                // after their text
+                map(
+                    tuple((
+                        consumed(DataValue::parse_operator_expression),
+                        opt(preceded(
+                            delimited(multispace0, tag_no_case("as"), multispace1),
+                            identifier
+                        ))
+                    )),
+                    |((text, expression), alias)| match expression {
+                        DataValue::Function { name, args } => Column {
+                            table: None,
+                            name: alias.unwrap_or(text.trim()).to_string(),
+                            column_type: ColumnType::Function(name, args),
+                        },
+                        _ => panic!("Expected function"),
+                    }
+                ),
                 // Handle function calls with optional alias
                 map(
                     tuple((
@@ -111,41 +165,129 @@ fn parse_column_list(input: &str) -> IResult<&str, Vec<Column>> {
                             Column {
                                 table: None,
                                 name: alias_name,
-                                column_type: ColumnType::Function(
-                                    name, 
-                                    args.into_iter()
-                                        .map(|arg| match &arg {
-                                            DataValue::Text(s) => DataValue::Text(s.clone()),
-                                            DataValue::Function { name, args } => DataValue::Function {
-                                                name: name.clone(),
-                                                args: args.clone(),
-                                            },
-                                            _ => DataValue::Text(arg.to_string()),
-                                        })
-                                        .collect()
-                                ),
+                                column_type: ColumnType::Function(name, args),
                             }
                         },
                         _ => panic!("Expected function"),
                     }
                 ),
                 // Handle regular columns with optional table prefix
-                map(
-                    tuple((
-                        opt(terminated(identifier, tag("."))),
-                        identifier
-                    )),
-                    |(table, name)| Column {
-                        table: table.map(|t| t.to_string()),
-                        name: name.to_string(),
-                        column_type: ColumnType::Regular(name.to_string()),
-                    }
-                )
+                parse_column,
             ))
         )
     ))(input)
 }
 
+/// A column name with an optional table prefix
+fn parse_column(input: &str) -> IResult<&str, Column> {
+    map(
+        tuple((
+            opt(terminated(identifier, tag("."))),
+            identifier
+        )),
+        |(table, name)| Column {
+            table: table.map(|t| t.to_string()),
+            name: name.to_string(),
+            column_type: ColumnType::Regular(name.to_string()),
+        }
+    )(input)
+}
+
+/// Rewrites the table qualifiers a query writes with an alias, such as the
+/// `u` of `users u`, to the table's name, which is what execution looks
+/// columns up by
+pub(crate) fn resolve_aliases(
+    table_ref: &TableReference,
+    joins: &mut [JoinClause],
+    columns: &mut [Column],
+    where_clause: Option<&mut WhereType>,
+    order_by: &mut [OrderByClause],
+    group_by: &mut [Column],
+) {
+    let aliases: HashMap<String, String> = std::iter::once(table_ref)
+        .chain(joins.iter().map(|join| &join.table_ref))
+        .filter_map(|t| t.alias.clone().map(|alias| (alias, t.name.clone())))
+        .collect();
+    if aliases.is_empty() {
+        return;
+    }
+    let table_of = |qualifier: &str| aliases.get(qualifier).cloned();
+
+    for column in columns.iter_mut().chain(group_by.iter_mut()) {
+        resolve_column(column, &table_of);
+    }
+    for clause in order_by {
+        if let OrderByTarget::Column(column) = &mut clause.target {
+            resolve_column(column, &table_of);
+        }
+    }
+    for join in joins {
+        if let Some((left, right)) = &mut join.on {
+            for pair in [left, right] {
+                if let Some(table) = table_of(&pair.table_name) {
+                    pair.table_name = table;
+                }
+            }
+        }
+    }
+    if let Some(where_clause) = where_clause {
+        resolve_where(where_clause, &table_of);
+    }
+}
+
+fn resolve_column(column: &mut Column, table_of: &impl Fn(&str) -> Option<String>) {
+    if let Some(table) = column.table.as_deref().and_then(table_of) {
+        column.table = Some(table);
+    }
+    match &mut column.column_type {
+        // An aggregate names its column as `table.column` text
+        ColumnType::Function(_, args) => {
+            for arg in args {
+                if let DataValue::Text(text) = arg {
+                    if let Some((qualifier, name)) = text.split_once('.') {
+                        if let Some(table) = table_of(qualifier) {
+                            *text = format!("{}.{}", table, name);
+                        }
+                    }
+                }
+            }
+        }
+        ColumnType::Case(case) => resolve_case(case, table_of),
+        ColumnType::Regular(_) | ColumnType::Wildcard => {}
+    }
+}
+
+fn resolve_case(case: &mut CaseExpression, table_of: &impl Fn(&str) -> Option<String>) {
+    for (predicate, _) in &mut case.branches {
+        resolve_where(predicate, table_of);
+    }
+}
+
+fn resolve_where(where_type: &mut WhereType, table_of: &impl Fn(&str) -> Option<String>) {
+    match where_type {
+        WhereType::Regular(clause) => {
+            if let Some(table) = clause.table.as_deref().and_then(table_of) {
+                clause.table = Some(table);
+            }
+        }
+        WhereType::ColumnComparison(comparison) => {
+            resolve_column(&mut comparison.left, table_of);
+            resolve_column(&mut comparison.right, table_of);
+        }
+        WhereType::Subquery(clause) => resolve_column(&mut clause.column, table_of),
+        WhereType::In(clause) => resolve_column(&mut clause.column, table_of),
+        WhereType::InSubquery(clause) => resolve_column(&mut clause.column, table_of),
+        WhereType::FTS(clause) => resolve_column(&mut clause.column, table_of),
+        WhereType::Case(comparison) => resolve_case(&mut comparison.case, table_of),
+        // Column references inside an expression are text like its literals
+        WhereType::Expression(_) => {}
+        WhereType::And(left, right) | WhereType::Or(left, right) => {
+            resolve_where(left, table_of);
+            resolve_where(right, table_of);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,6 +320,7 @@ mod tests {
                 None,
                 vec![],
                 vec![],
+                vec![],
             ))
         );
     }
@@ -188,16 +331,16 @@ mod tests {
         let result = SelectStatement::parse(input);
         let (_input, statement) = result.unwrap();
         match statement {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by)) => {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, _)) => {
                 assert_eq!(table_ref.name, "users");
                 assert_eq!(columns.len(), 1);
                 assert_eq!(columns[0].name, "name");
                 assert!(where_clause.is_none());
                 assert!(joins.is_empty());
                 assert_eq!(order_by.len(), 2);
-                assert_eq!(order_by[0].column.name, "age");
+                assert_eq!(order_by[0].column().unwrap().name, "age");
                 assert_eq!(order_by[0].direction, OrderDirection::Desc);
-                assert_eq!(order_by[1].column.name, "name");
+                assert_eq!(order_by[1].column().unwrap().name, "name");
                 assert_eq!(order_by[1].direction, OrderDirection::Asc);
             }
             _ => panic!("Expected Select statement with order by"),
@@ -210,7 +353,7 @@ mod tests {
         let result = SelectStatement::parse(input);
         let (_input, statement) = result.unwrap();
         match statement {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, Some(WhereType::Regular(where_clause)), joins, order_by)) => {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, Some(WhereType::Regular(where_clause)), joins, order_by, _)) => {
                 assert_eq!(table_ref.name, "users");
                 assert_eq!(columns.len(), 1);
                 assert_eq!(columns[0].name, "name");
@@ -244,6 +387,7 @@ mod tests {
                 None,
                 vec![],
                 vec![],
+                vec![],
             ))
         );
     }
@@ -268,6 +412,7 @@ mod tests {
                 None,
                 vec![],
                 vec![],
+                vec![],
             ))
         );
     }
@@ -278,7 +423,7 @@ mod tests {
         let result = SelectStatement::parse(input);
         let (_input, statement) = result.unwrap();
         match statement {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by)) => {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, _)) => {
                 assert_eq!(table_ref.name, "articles");
                 assert_eq!(columns.len(), 3);
                 assert_eq!(columns[0].name, "id");
@@ -297,7 +442,7 @@ mod tests {
         let result = SelectStatement::parse(input);
         let (_input, statement) = result.unwrap();
         match statement {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by)) => {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, _)) => {
                 assert_eq!(table_ref.name, "authors");
                 assert_eq!(columns.len(), 3);
                 assert_eq!(columns[0].name, "name");
@@ -306,8 +451,8 @@ mod tests {
                 assert_eq!(joins.len(), 1);
                 assert_eq!(joins[0].join_type, JoinType::Inner);
                 assert_eq!(joins[0].table_ref.name, "books");
-                assert_eq!(joins[0].on.0.column_name, "id");
-                assert_eq!(joins[0].on.1.column_name, "author_id");
+                assert_eq!(joins[0].on.as_ref().unwrap().0.column_name, "id");
+                assert_eq!(joins[0].on.as_ref().unwrap().1.column_name, "author_id");
                 assert!(order_by.is_empty());
                 match where_clause.unwrap() {
                     WhereType::Regular(where_clause) => {
@@ -328,7 +473,7 @@ mod tests {
         let result = SelectStatement::parse(input);
         let (_input, statement) = result.unwrap();
         match statement {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by)) => {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, _)) => {
                 assert_eq!(table_ref.name, "books");
                 assert_eq!(columns.len(), 1);
                 assert_eq!(columns[0].name, "*");
@@ -353,7 +498,7 @@ mod tests {
         let result = SelectStatement::parse(input);
         let (_input, statement) = result.unwrap();
         match statement {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by)) => {
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, _)) => {
                 assert_eq!(table_ref.name, "users");
                 assert_eq!(table_ref.alias, Some("u".to_string()));
                 assert_eq!(columns.len(), 2);
@@ -0,0 +1,69 @@
+use nom::{
+    bytes::complete::tag_no_case,
+    character::complete::{multispace0, multispace1},
+    combinator::opt,
+    sequence::preceded,
+    IResult,
+};
+
+use super::{select::SelectStatement, Statement};
+
+/// `left UNION [ALL] right`. Without `ALL` duplicate rows are removed from
+/// the combined result.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnionStatement {
+    pub left: SelectStatement,
+    pub right: SelectStatement,
+    pub all: bool,
+}
+
+fn parse_select(input: &str) -> IResult<&str, SelectStatement> {
+    let (rest, stmt) = SelectStatement::parse(input)?;
+    match stmt {
+        Statement::Select(select) => Ok((rest, select)),
+        _ => Err(nom::Err::Error(nom::error::Error::new(input, nom::error::ErrorKind::Verify))),
+    }
+}
+
+impl UnionStatement {
+    pub fn parse(input: &str) -> IResult<&str, Statement> {
+        let (input, left) = parse_select(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = tag_no_case("UNION")(input)?;
+        let (input, all) = opt(preceded(multispace1, tag_no_case("ALL")))(input)?;
+        let (input, _) = multispace1(input)?;
+        let (input, right) = parse_select(input)?;
+
+        Ok((input, Statement::Union(UnionStatement {
+            left,
+            right,
+            all: all.is_some(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_union() {
+        let (remaining, stmt) = UnionStatement::parse("SELECT id FROM a UNION SELECT id FROM b").unwrap();
+        assert_eq!(remaining, "");
+        match stmt {
+            Statement::Union(union) => {
+                assert!(!union.all);
+                let SelectStatement::FromTable(left, ..) = union.left;
+                let SelectStatement::FromTable(right, ..) = union.right;
+                assert_eq!(left.name, "a");
+                assert_eq!(right.name, "b");
+            }
+            other => panic!("Expected UNION, got {:?}", other),
+        }
+
+        let (_, stmt) = UnionStatement::parse("SELECT id FROM a WHERE id > 1 UNION ALL SELECT id FROM b").unwrap();
+        assert!(matches!(stmt, Statement::Union(UnionStatement { all: true, .. })));
+
+        assert!(UnionStatement::parse("SELECT id FROM a").is_err());
+    }
+}
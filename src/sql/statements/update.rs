@@ -1,6 +1,6 @@
 use nom::{
     bytes::complete::{tag, tag_no_case},
-    character::complete::{multispace0, multispace1, alphanumeric1},
+    character::complete::{multispace0, multispace1},
     combinator::{map, opt},
     sequence::{delimited, tuple, preceded},
     multi::separated_list1,
@@ -8,14 +8,31 @@ use nom::{
 };
 
 use crate::sql::{
+    column_def::{column_name, table_name},
     clauses::wheres::where_type::{parse_where_clause, WhereType},
     data_value::DataValue,
-    statements::Statement,
+    statements::{parse_limit, parse_returning, Statement},
 };
 
+/// Parses the `col = value, ...` list of a SET clause
+pub(crate) fn parse_assignments(input: &str) -> IResult<&str, Vec<(String, DataValue)>> {
+    separated_list1(
+        delimited(multispace0, tag(","), multispace0),
+        map(
+            tuple((
+                column_name,
+                delimited(multispace0, tag("="), multispace0),
+                DataValue::parse
+            )),
+            |(col, _, val)| (col.to_string(), val)
+        )
+    )(input)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum UpdateStatement {
-    UpdateTable(String, Vec<(String, DataValue)>, Option<WhereType>),
+    /// Table, assignments, WHERE clause and LIMIT
+    UpdateTable(String, Vec<(String, DataValue)>, Option<WhereType>, Option<usize>),
 }
 
 impl UpdateStatement {
@@ -28,7 +45,7 @@ impl UpdateStatement {
 
         let (input, table_name) = delimited(
             multispace0,
-            alphanumeric1,
+            table_name,
             multispace0
         )(input)?;
 
@@ -38,30 +55,23 @@ impl UpdateStatement {
             multispace1
         )(input)?;
 
-        let (input, updates) = separated_list1(
-            delimited(multispace0, tag(","), multispace0),
-            map(
-                tuple((
-                    alphanumeric1,
-                    delimited(multispace0, tag("="), multispace0),
-                    DataValue::parse
-                )),
-                |(col, _, val)| (col.to_string(), val)
-            )
-        )(input)?;
+        let (input, updates) = parse_assignments(input)?;
 
         let (input, where_clause) = opt(preceded(
             multispace1,
             parse_where_clause
         ))(input)?;
 
+        let (input, limit) = parse_limit(input)?;
+
         let (input, _) = multispace0(input)?;
 
-        Ok((input, Statement::Update(UpdateStatement::UpdateTable(
+        parse_returning(input, Statement::Update(UpdateStatement::UpdateTable(
             table_name.to_string(),
             updates,
-            where_clause
-        ))))
+            where_clause,
+            limit,
+        )))
     }
 }
 
@@ -80,7 +90,7 @@ mod tests {
         let (remaining, stmt) = UpdateStatement::parse(input).unwrap();
         assert_eq!(remaining, "");
         match stmt {
-            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, Some(WhereType::Regular(where_clause)))) => {
+            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, Some(WhereType::Regular(where_clause)), None)) => {
                 assert_eq!(table_name, "users");
                 assert_eq!(updates.len(), 1);
                 assert_eq!(updates[0].0, "name");
@@ -99,7 +109,7 @@ mod tests {
         let (remaining, stmt) = UpdateStatement::parse(input).unwrap();
         assert_eq!(remaining, "");
         match stmt {
-            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, Some(WhereType::Regular(where_clause)))) => {
+            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, Some(WhereType::Regular(where_clause)), None)) => {
                 assert_eq!(table_name, "users");
                 assert_eq!(updates.len(), 3);
                 assert_eq!(updates[0].0, "name");
@@ -115,4 +125,35 @@ mod tests {
             _ => panic!("Expected Update statement with where clause"),
         }
     }
+
+    #[test]
+    fn parse_update_returning_test() {
+        let input = "UPDATE users SET age = 31 WHERE id = 1 RETURNING id, age";
+        let (remaining, stmt) = UpdateStatement::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        match stmt {
+            Statement::Returning { statement, columns } => {
+                assert!(matches!(*statement, Statement::Update(UpdateStatement::UpdateTable(_, _, Some(_), None))));
+                let names: Vec<_> = columns.iter().map(|c| c.name.as_str()).collect();
+                assert_eq!(names, vec!["id", "age"]);
+            }
+            _ => panic!("Expected Returning statement"),
+        }
+    }
+
+    #[test]
+    fn parse_update_limit_test() {
+        let input = "UPDATE users SET active = false WHERE active = true LIMIT 10 RETURNING id";
+        let (remaining, stmt) = UpdateStatement::parse(input).unwrap();
+        assert_eq!(remaining, "");
+        match stmt {
+            Statement::Returning { statement, .. } => {
+                assert!(matches!(*statement, Statement::Update(UpdateStatement::UpdateTable(_, _, Some(_), Some(10)))));
+            }
+            _ => panic!("Expected Returning statement"),
+        }
+
+        let (_, stmt) = UpdateStatement::parse("UPDATE users SET active = false LIMIT 1").unwrap();
+        assert!(matches!(stmt, Statement::Update(UpdateStatement::UpdateTable(_, _, None, Some(1)))));
+    }
 }
@@ -1,6 +1,25 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{alpha1, alphanumeric1, multispace1},
+    combinator::{opt, recognize, verify},
+    multi::many0,
+    sequence::{preceded, tuple},
+    IResult,
+};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+use crate::sql::column_def::quoted_identifier;
+
+/// Words that can follow a table name, which an alias written without AS
+/// can't be
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "AS", "CROSS", "EXCEPT", "FOR", "FULL", "GROUP", "HAVING", "INNER", "INTERSECT", "JOIN",
+    "LEFT", "LIMIT", "NATURAL", "OFFSET", "ON", "ORDER", "OUTER", "RIGHT", "SELECT", "UNION",
+    "USING", "WHEN", "WHERE",
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TableReference {
     pub name: String,
@@ -14,4 +33,24 @@ impl fmt::Display for TableReference {
             None => write!(f, "{}", self.name),
         }
     }
-} 
\ No newline at end of file
+} 
+
+/// The alias after a table name, `AS t` or just `t`
+pub(crate) fn parse_alias(input: &str) -> IResult<&str, Option<&str>> {
+    opt(preceded(
+        multispace1,
+        alt((
+            preceded(tuple((tag_no_case("AS"), multispace1)), alias_identifier),
+            verify(alias_identifier, |alias: &str| {
+                !CLAUSE_KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(alias))
+            }),
+        )),
+    ))(input)
+}
+
+fn alias_identifier(input: &str) -> IResult<&str, &str> {
+    alt((
+        quoted_identifier,
+        recognize(tuple((alpha1, many0(alt((alphanumeric1, tag("_"))))))),
+    ))(input)
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+/// What running one statement cost, as reported by `ReefDB::query_with_stats`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryStats {
+    /// Stored rows read, counting each pass over a joined table and the
+    /// rows read by subqueries
+    pub rows_scanned: usize,
+    /// Rows in the result of a query, zero for other statements
+    pub rows_returned: usize,
+    /// Row pairs produced by joins before the WHERE clause filtered them
+    pub join_rows: usize,
+    /// The index rows were looked up through instead of a full scan, as
+    /// `table.column`
+    pub index_used: Option<String>,
+    pub duration: Duration,
+    /// The steps queries went through, in the order they finished
+    pub steps: Vec<PlanStep>,
+}
+
+/// One step of running a query: a scan, join, filter, aggregate or sort
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanStep {
+    pub description: String,
+    /// Rows the step read or was handed
+    pub rows_in: usize,
+    /// Rows the step passed on
+    pub rows_out: usize,
+    pub duration: Duration,
+}
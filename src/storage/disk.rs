@@ -3,12 +3,13 @@ use crate::sql::data_value::DataValue;
 use crate::sql::data_type::DataType;
 use bincode::{deserialize, serialize};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
-use super::Storage;
+use super::{convert_column, delete_rows_at, update_rows_at, Storage};
 use crate::error::ReefDBError;
 use crate::sql::constraints::constraint::Constraint;
 use crate::indexes::{IndexManager, IndexType};
@@ -105,6 +106,12 @@ impl IndexManager for OnDiskIndexManager {
                 }
                 btree.add_entry(new_value, row_id);
             }
+            IndexType::Hash(hash) => {
+                if !old_value.is_empty() {
+                    hash.remove_entry(old_value.clone(), row_id);
+                }
+                hash.add_entry(new_value, row_id);
+            }
             IndexType::GIN(gin) => {
                 if !old_value.is_empty() {
                     gin.remove_document(table, column, row_id);
@@ -169,61 +176,266 @@ impl IndexManager for OnDiskIndexManager {
     }
 }
 
+/// Granularity the data file grows by unless configured otherwise, see
+/// `OnDiskStorage::with_block_size`
+pub const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+/// Start of a paged data file. Files without it hold the whole tables in
+/// one serialized map, the format before records, and are rewritten as
+/// records when opened.
+const MAGIC: &[u8; 8] = b"REEFPAGE";
+
+/// Magic, block size, version and end of the records
+const HEADER_LEN: usize = 8 + 4 + 8 + 8;
+
+fn default_block_size() -> usize {
+    DEFAULT_BLOCK_SIZE
+}
+
+/// The header every paged data file starts with
+struct Header {
+    block_size: usize,
+    /// Random value changed by every write, so that a copy of the storage
+    /// can tell whether the file is still the one it last wrote
+    version: u64,
+    /// Offset just past the last complete record. An append only counts
+    /// once the header points past it.
+    end: usize,
+}
+
+impl Header {
+    fn parse(contents: &[u8]) -> Option<Header> {
+        if contents.len() < HEADER_LEN || &contents[..8] != MAGIC {
+            return None;
+        }
+        let u64_at = |offset: usize| u64::from_le_bytes(contents[offset..offset + 8].try_into().unwrap());
+        Some(Header {
+            block_size: u32::from_le_bytes(contents[8..12].try_into().unwrap()) as usize,
+            version: u64_at(12),
+            end: u64_at(20) as usize,
+        })
+    }
+
+    fn read(file: &mut File) -> Option<Header> {
+        let mut contents = [0; HEADER_LEN];
+        file.seek(SeekFrom::Start(0)).ok()?;
+        file.read_exact(&mut contents).ok()?;
+        Header::parse(&contents)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&(self.block_size as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&(self.end as u64).to_le_bytes());
+        bytes
+    }
+}
+
+/// A change to the tables, appended to the data file as it happens.
+/// Replaying the records in order rebuilds the tables.
+#[derive(Serialize, Deserialize)]
+enum PageRecord<'a> {
+    /// A table's schema and rows, replacing any table of that name
+    Table(Cow<'a, str>, Cow<'a, [ColumnDef]>, Cow<'a, [Vec<DataValue>]>),
+    /// Rows appended to a table
+    Insert(Cow<'a, str>, Cow<'a, [Vec<DataValue>]>),
+    /// New versions of the rows at the given positions
+    Update(Cow<'a, str>, Vec<(usize, Vec<DataValue>)>),
+    /// Positions of deleted rows, the rows after them moving up
+    Delete(Cow<'a, str>, Vec<usize>),
+    DropTable(Cow<'a, str>),
+}
+
+impl PageRecord<'_> {
+    /// The record's length followed by the record
+    fn encode(&self) -> Vec<u8> {
+        let record = serialize(self).unwrap();
+        let mut bytes = (record.len() as u32).to_le_bytes().to_vec();
+        bytes.extend(record);
+        bytes
+    }
+}
+
+/// Keeps every table in memory and logs each change to a data file of
+/// records, so that a single-row insert, update or delete appends one small
+/// record instead of rewriting the file. The file grows a block at a time;
+/// superseded records stay in it until a vacuum rewrites it.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct OnDiskStorage {
     file_path: String,
     tables: HashMap<String, (Vec<ColumnDef>, Vec<Vec<DataValue>>)>,
     index_manager: OnDiskIndexManager,
+    #[serde(skip, default = "default_block_size")]
+    block_size: usize,
+    /// Version of the file as this storage last wrote or read it
+    #[serde(skip)]
+    version: u64,
+    #[serde(skip)]
+    end: usize,
 }
 
 impl OnDiskStorage {
+    /// Opens the data file at `file_path`, keeping the block size an
+    /// existing file was written with.
+    ///
+    /// # Panics
+    ///
+    /// If the file can't be read as a data file; `open` returns the error
+    /// instead.
     pub fn new(file_path: String) -> Self {
-        let tables = if Path::new(&file_path).exists() {
-            println!("Loading existing file: {}", file_path);
-            let mut file = File::open(&file_path).unwrap();
-            let mut contents = Vec::new();
-            file.read_to_end(&mut contents).unwrap();
-            println!("Read {} bytes", contents.len());
-            let tables = deserialize(&contents).unwrap_or_default();
-            println!("Loaded tables: {:?}", tables);
-            tables
-        } else {
-            println!("File does not exist: {}", file_path);
-            HashMap::new()
-        };
+        Self::open(file_path, None).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        OnDiskStorage {
+    /// Opens the data file at `file_path`, growing it `block_size` bytes at
+    /// a time. Panics like `new`.
+    pub fn with_block_size(file_path: String, block_size: usize) -> Self {
+        Self::open(file_path, Some(block_size.max(1))).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Number of bytes the data file grows by at a time
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Opens the data file at `file_path`, creating it on the first write.
+    /// `block_size` overrides the one an existing file was written with. A
+    /// file that is neither a data file nor one in the format before
+    /// records is an error and is left as it is.
+    pub fn open(file_path: String, block_size: Option<usize>) -> Result<Self, ReefDBError> {
+        let mut storage = OnDiskStorage {
             file_path: file_path.clone(),
-            tables,
+            tables: HashMap::new(),
             index_manager: OnDiskIndexManager::new(file_path),
+            block_size: block_size.unwrap_or(DEFAULT_BLOCK_SIZE),
+            version: 0,
+            end: 0,
+        };
+        let contents = match std::fs::read(&storage.file_path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        if contents.is_empty() {
+            return Ok(storage);
+        }
+
+        let Some(header) = Header::parse(&contents) else {
+            // Migrate a file holding the whole tables to records
+            storage.tables = deserialize(&contents).map_err(|e| ReefDBError::IoError(format!(
+                "{} is not a data file: {}", storage.file_path, e
+            )))?;
+            storage.rewrite()?;
+            return Ok(storage);
+        };
+        storage.block_size = block_size.unwrap_or(header.block_size.max(1));
+        storage.version = header.version;
+        storage.end = header.end;
+        let mut offset = HEADER_LEN;
+        while offset < header.end {
+            let record = contents.get(offset..offset + 4)
+                .map(|len| u32::from_le_bytes(len.try_into().unwrap()) as usize)
+                .and_then(|len| Some((len, contents.get(offset + 4..offset + 4 + len)?)))
+                .and_then(|(len, bytes)| Some((len, deserialize::<PageRecord>(bytes).ok()?)));
+            let Some((len, record)) = record else {
+                // Drop whatever follows a damaged record, the WAL has it
+                storage.rewrite()?;
+                break;
+            };
+            storage.apply(record);
+            offset += 4 + len;
+        }
+        Ok(storage)
+    }
+
+    fn apply(&mut self, record: PageRecord) {
+        match record {
+            PageRecord::Table(name, columns, rows) => {
+                self.tables.insert(name.into_owned(), (columns.into_owned(), rows.into_owned()));
+            }
+            PageRecord::Insert(name, rows) => {
+                if let Some((_, existing)) = self.tables.get_mut(name.as_ref()) {
+                    existing.extend(rows.into_owned());
+                }
+            }
+            PageRecord::Update(name, rows) => {
+                if let Some((_, existing)) = self.tables.get_mut(name.as_ref()) {
+                    for (row_id, row) in rows {
+                        if let Some(existing_row) = existing.get_mut(row_id) {
+                            *existing_row = row;
+                        }
+                    }
+                }
+            }
+            PageRecord::Delete(name, row_ids) => {
+                if let Some(table) = self.tables.get_mut(name.as_ref()) {
+                    delete_rows_at(table, &row_ids);
+                }
+            }
+            PageRecord::DropTable(name) => {
+                self.tables.remove(name.as_ref());
+            }
+        }
+    }
+
+    /// The record holding the whole of `table_name`, as it is now
+    fn table_record(&self, table_name: &str) -> Vec<u8> {
+        match self.tables.get(table_name) {
+            Some((columns, rows)) => PageRecord::Table(table_name.into(), columns.into(), rows.into()).encode(),
+            None => PageRecord::DropTable(table_name.into()).encode(),
+        }
+    }
+
+    /// Appends an encoded record to the data file. Another copy of the
+    /// storage may have written the file since this one last did, in which
+    /// case it is rewritten with this copy's tables instead.
+    fn append(&mut self, record: Vec<u8>) -> std::io::Result<()> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&self.file_path)?;
+        match Header::read(&mut file) {
+            Some(header) if header.version == self.version => {}
+            _ => {
+                drop(file);
+                return self.rewrite();
+            }
+        }
+
+        file.seek(SeekFrom::Start(self.end as u64))?;
+        file.write_all(&record)?;
+        self.end += record.len();
+        let blocks = self.end.div_ceil(self.block_size) as u64;
+        if file.metadata()?.len() < blocks * self.block_size as u64 {
+            file.set_len(blocks * self.block_size as u64)?;
         }
+        self.version = rand::random();
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&Header { block_size: self.block_size, version: self.version, end: self.end }.to_bytes())?;
+        Ok(())
     }
 
-    pub fn save(&self) {
-        println!("Saving tables: {:?}", self.tables);
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(&self.file_path)
-            .unwrap();
-        let mut writer = BufWriter::new(file);
-        let serialized = serialize(&self.tables).unwrap();
-        println!("Writing {} bytes", serialized.len());
-        writer.write_all(&serialized).unwrap();
-        writer.flush().unwrap();
-    }
-
-    pub fn sync(&self) -> std::io::Result<()> {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(&self.file_path)?;
-        let mut writer = BufWriter::new(file);
-        let serialized = serialize(&self.tables).unwrap();
-        writer.write_all(&serialized)?;
-        writer.flush()?;
+    /// Writes a fresh file holding one record per table and swaps it in
+    fn rewrite(&mut self) -> std::io::Result<()> {
+        let mut records = Vec::new();
+        for (table_name, (columns, rows)) in &self.tables {
+            records.extend(PageRecord::Table(table_name.into(), columns.into(), rows.into()).encode());
+        }
+        let version = rand::random();
+        let end = HEADER_LEN + records.len();
+        let tmp_path = format!("{}.rewrite", self.file_path);
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(&Header { block_size: self.block_size, version, end }.to_bytes())?;
+        file.write_all(&records)?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, &self.file_path)?;
+        self.version = version;
+        self.end = end;
         Ok(())
     }
+
+    /// Rewrites the data file with only the current tables, dropping the
+    /// records they superseded
+    pub fn save(&mut self) -> Result<(), ReefDBError> {
+        Ok(self.rewrite()?)
+    }
 }
 
 unsafe impl Send for OnDiskStorage {}
@@ -242,9 +454,10 @@ impl Storage for OnDiskStorage {
         columns: Vec<ColumnDef>,
         row: Vec<Vec<DataValue>>,
     ) {
+        let record = PageRecord::Table(table_name.as_str().into(), columns.as_slice().into(), row.as_slice().into()).encode();
         self.tables.insert(table_name, (columns, row));
         // Ensure changes are persisted to disk
-        self.save();
+        let _ = self.append(record);
     }
 
     fn get_table(
@@ -303,10 +516,11 @@ impl Storage for OnDiskStorage {
             let rowid = rows.len() + 1;
 
             // Add the new row to the table
+            let record = PageRecord::Insert(table_name.into(), std::slice::from_ref(&row).into()).encode();
             rows.push(row);
 
             // Save after all modifications are done
-            let _ = self.sync();
+            self.append(record)?;
 
             // Return the rowid (1-based index)
             Ok(rowid)
@@ -316,6 +530,46 @@ impl Storage for OnDiskStorage {
         }
     }
 
+    fn append_rows(&mut self, table_name: &str, rows: Vec<Vec<DataValue>>) -> Result<usize, ReefDBError> {
+        let (_, existing) = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let count = rows.len();
+        let record = PageRecord::Insert(table_name.into(), rows.as_slice().into()).encode();
+        existing.extend(rows);
+
+        // One write for the whole batch
+        self.append(record)?;
+        Ok(count)
+    }
+
+    fn alter_column_type(&mut self, table_name: &str, column_name: &str, data_type: DataType) -> Result<(), ReefDBError> {
+        let table = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        convert_column(table, column_name, data_type)?;
+        self.append(self.table_record(table_name))?;
+        Ok(())
+    }
+
+    fn update_rows(&mut self, table_name: &str, row_ids: &[usize], updates: &[(String, DataValue)]) -> Result<usize, ReefDBError> {
+        let table = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let count = update_rows_at(table, row_ids, updates);
+        let rows: Vec<(usize, Vec<DataValue>)> = row_ids.iter()
+            .filter_map(|&row_id| Some((row_id, table.1.get(row_id)?.clone())))
+            .collect();
+        self.append(PageRecord::Update(table_name.into(), rows).encode())?;
+        Ok(count)
+    }
+
+    fn delete_rows(&mut self, table_name: &str, row_ids: &[usize]) -> Result<usize, ReefDBError> {
+        let table = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let count = delete_rows_at(table, row_ids);
+        // The file keeps the deleted rows until a vacuum
+        self.append(PageRecord::Delete(table_name.into(), row_ids.to_vec()).encode())?;
+        Ok(count)
+    }
+
     fn update_table(
         &mut self,
         table_name: &str,
@@ -324,6 +578,7 @@ impl Storage for OnDiskStorage {
     ) -> usize {
         let (schema, rows) = self.get_table(table_name).unwrap();
         let mut last_updated_row_id = 0;
+        let mut updated_rows = Vec::new();
 
         for (idx, row) in rows.iter_mut().enumerate() {
             let matches_where = if let Some((column, value)) = &where_clause {
@@ -338,10 +593,11 @@ impl Storage for OnDiskStorage {
                     let column_idx = schema.iter().position(|c| c.name == *column).unwrap();
                     row[column_idx] = value.clone();
                 }
+                updated_rows.push((idx, row.clone()));
                 last_updated_row_id = idx + 1; // Convert to 1-based index
             }
         }
-        self.save();
+        let _ = self.append(PageRecord::Update(table_name.into(), updated_rows).encode());
         last_updated_row_id
     }
 
@@ -350,23 +606,51 @@ impl Storage for OnDiskStorage {
         table_name: &str,
         where_clause: Option<(String, DataValue)>,
     ) -> usize {
-        //TODO: Option<usize>
-        // None if table doesn't exist or column not found
-        let (schema, rows) = self.get_table(table_name).unwrap();
-        let mut idx = 0;
-
-        for row in rows.iter_mut() {
-            if let Some((column, value)) = &where_clause {
-                let column_idx = schema.iter().position(|c| c.name == *column).unwrap();
-                if row[column_idx] != *value {
-                    idx += 1;
-                    continue;
+        let (schema, rows) = match self.get_table(table_name) {
+            Some(table) => table,
+            None => return 0,
+        };
+        let initial_len = rows.len();
+        let record = match where_clause {
+            Some((column, value)) => match schema.iter().position(|c| c.name == column) {
+                Some(column_idx) => {
+                    let row_ids: Vec<usize> = (0..rows.len()).filter(|&i| rows[i][column_idx] == value).collect();
+                    rows.retain(|row| row[column_idx] != value);
+                    PageRecord::Delete(table_name.into(), row_ids).encode()
                 }
+                None => return 0,
+            },
+            None => {
+                rows.clear();
+                self.table_record(table_name)
             }
-            idx += 1;
+        };
+        let deleted_count = initial_len - self.tables[table_name].1.len();
+        // The file keeps the deleted rows until a vacuum
+        let _ = self.append(record);
+        deleted_count
+    }
+
+    /// Syncs the data file to disk, writing this copy's tables when the
+    /// file doesn't exist yet
+    fn flush(&mut self) -> Result<(), ReefDBError> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&self.file_path)?;
+        if Header::read(&mut file).is_none() {
+            drop(file);
+            return Ok(self.rewrite()?);
         }
-        self.save();
-        idx
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Rewrites the data file with only the live rows, dropping the records
+    /// of deleted rows and of rows' older versions
+    fn vacuum(&mut self) -> Result<(), ReefDBError> {
+        for (_, rows) in self.tables.values_mut() {
+            rows.shrink_to_fit();
+        }
+        self.rewrite()?;
+        Ok(())
     }
 
     fn get_table_ref(&self, table_name: &str) -> Option<&(Vec<ColumnDef>, Vec<Vec<DataValue>>)> {
@@ -376,7 +660,7 @@ impl Storage for OnDiskStorage {
     fn remove_table(&mut self, table_name: &str) -> bool {
         let exists = self.tables.remove(table_name).is_some();
         if exists {
-            self.save();
+            let _ = self.append(PageRecord::DropTable(table_name.into()).encode());
         }
         exists
     }
@@ -398,7 +682,7 @@ impl Storage for OnDiskStorage {
             for row in data.iter_mut() {
                 row.push(default_value.clone());
             }
-            self.save();
+            self.append(self.table_record(table_name))?;
             Ok(())
         } else {
             Err(ReefDBError::TableNotFound(table_name.to_string()))
@@ -413,7 +697,7 @@ impl Storage for OnDiskStorage {
                 for row in data.iter_mut() {
                     row.remove(idx);
                 }
-                self.save();
+                self.append(self.table_record(table_name))?;
                 Ok(())
             } else {
                 Err(ReefDBError::ColumnNotFound(column_name.to_string()))
@@ -427,7 +711,7 @@ impl Storage for OnDiskStorage {
         if let Some((schema, _)) = self.tables.get_mut(table_name) {
             if let Some(col) = schema.iter_mut().find(|col| col.name == old_name) {
                 col.name = new_name.to_string();
-                self.save();
+                self.append(self.table_record(table_name))?;
                 Ok(())
             } else {
                 Err(ReefDBError::ColumnNotFound(old_name.to_string()))
@@ -439,7 +723,7 @@ impl Storage for OnDiskStorage {
 
     fn drop_table(&mut self, table_name: &str) {
         if self.tables.remove(table_name).is_some() {
-            self.save();
+            let _ = self.append(PageRecord::DropTable(table_name.into()).encode());
         }
     }
 
@@ -449,19 +733,9 @@ impl Storage for OnDiskStorage {
 
     fn clear(&mut self) {
         self.tables.clear();
-        // Also clear the on-disk storage
-        let file_path = &self.file_path;
-        if let Some(parent) = std::path::Path::new(file_path).parent() {
-            if let Ok(entries) = std::fs::read_dir(parent) {
-                for entry in entries.flatten() {
-                    if let Ok(file_type) = entry.file_type() {
-                        if file_type.is_file() {
-                            let _ = std::fs::remove_file(entry.path());
-                        }
-                    }
-                }
-            }
-        }
+        // Also clear the on-disk storage. Only our own file is removed: the WAL
+        // lives next to it and is needed to recover committed transactions.
+        let _ = std::fs::remove_file(&self.file_path);
     }
 
     fn get_all_tables(&self) -> &HashMap<String, (Vec<ColumnDef>, Vec<Vec<DataValue>>)> {
@@ -549,4 +823,85 @@ mod tests {
             assert_eq!(rows[1][2], DataValue::Integer(25));
         }
     }
+
+    fn users(rows: usize) -> (Vec<ColumnDef>, Vec<Vec<DataValue>>) {
+        let columns = vec![
+            ColumnDef::new("id", DataType::Integer, vec![Constraint::PrimaryKey]),
+            ColumnDef::new("name", DataType::Text, vec![]),
+        ];
+        let rows = (0..rows as i64).map(|i| vec![DataValue::Integer(i), DataValue::Text(format!("user{}", i))]).collect();
+        (columns, rows)
+    }
+
+    #[test]
+    fn test_single_insert_appends() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_string_lossy().to_string();
+        let mut storage = OnDiskStorage::with_block_size(file_path.clone(), 512);
+        let (columns, rows) = users(1000);
+        storage.insert_table("users".to_string(), columns, rows);
+        let before = std::fs::read(&file_path).unwrap();
+
+        storage.push_value("users", vec![DataValue::Integer(1000), DataValue::Text("new".to_string())]).unwrap();
+        let after = std::fs::read(&file_path).unwrap();
+        // Only the header changes before the new record
+        assert_eq!(after[HEADER_LEN..before.len()], before[HEADER_LEN..]);
+        assert!(after.len() - before.len() <= 512, "the file grows by one block");
+
+        storage.update_rows("users", &[3], &[("name".to_string(), DataValue::Text("renamed".to_string()))]).unwrap();
+        storage.delete_rows("users", &[0]).unwrap();
+        let reopened = OnDiskStorage::new(file_path);
+        assert_eq!(reopened.block_size, 512);
+        assert_eq!(reopened.get_table_ref("users"), storage.get_table_ref("users"));
+        let (_, rows) = reopened.get_table_ref("users").unwrap();
+        assert_eq!(rows.len(), 1000);
+        assert_eq!(rows[2][1], DataValue::Text("renamed".to_string()));
+    }
+
+    #[test]
+    fn test_migrates_whole_table_files() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_string_lossy().to_string();
+        let tables: HashMap<String, (Vec<ColumnDef>, Vec<Vec<DataValue>>)> = HashMap::from([("users".to_string(), users(3))]);
+        std::fs::write(&file_path, serialize(&tables).unwrap()).unwrap();
+
+        let storage = OnDiskStorage::new(file_path.clone());
+        assert_eq!(storage.get_all_tables(), &tables);
+        assert!(std::fs::read(&file_path).unwrap().starts_with(MAGIC));
+        assert_eq!(OnDiskStorage::new(file_path).get_all_tables(), &tables);
+    }
+
+    #[test]
+    fn test_leaves_unreadable_files_alone() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_string_lossy().to_string();
+        std::fs::write(&file_path, b"not a data file").unwrap();
+
+        assert!(OnDiskStorage::open(file_path.clone(), None).is_err());
+        assert_eq!(std::fs::read(&file_path).unwrap(), b"not a data file");
+    }
+
+    #[test]
+    fn test_copies_rewrite_a_file_changed_under_them() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_string_lossy().to_string();
+        let mut storage = OnDiskStorage::new(file_path.clone());
+        let (columns, rows) = users(2);
+        storage.insert_table("users".to_string(), columns, rows);
+
+        let mut copy = storage.clone();
+        copy.push_value("users", vec![DataValue::Integer(7), DataValue::Text("copy".to_string())]).unwrap();
+        // Appending after the copy's record would mix the two
+        storage.push_value("users", vec![DataValue::Integer(8), DataValue::Text("original".to_string())]).unwrap();
+        assert_eq!(OnDiskStorage::new(file_path.clone()).get_all_tables(), storage.get_all_tables());
+
+        // A file laid out anew by the copy, with the original's end falling
+        // inside its records
+        let mut copy = storage.clone();
+        let (columns, rows) = users(50);
+        copy.insert_table("orders".to_string(), columns, rows);
+        copy.save().unwrap();
+        storage.push_value("users", vec![DataValue::Integer(9), DataValue::Text("again".to_string())]).unwrap();
+        assert_eq!(OnDiskStorage::new(file_path).get_all_tables(), storage.get_all_tables());
+    }
 }
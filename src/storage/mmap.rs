@@ -10,16 +10,33 @@ use std::fs::{File, OpenOptions};
 use std::path::Path;
 use bincode::{serialize, deserialize};
 use std::any::Any;
-use super::Storage;
+use std::time::{Duration, Instant};
+use super::{convert_column, delete_rows_at, update_rows_at, Storage};
 use crate::sql::data_type::DataType;
 
+/// When `MmapStorage` forces its writes to disk. Writes reach the mapping,
+/// and with it the OS page cache, straight away, which survives the process
+/// crashing but not the machine; only a sync makes them durable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    /// Sync whenever a transaction commits
+    #[default]
+    EveryCommit,
+    /// Sync at the first commit once this long has passed since the last
+    /// sync, trading the most recent commits for fewer syncs
+    Periodic(Duration),
+}
+
 #[derive(Debug)]
 pub struct MmapStorage {
     file_path: String,
     tables: HashMap<String, (Vec<ColumnDef>, Vec<Vec<DataValue>>)>,
     index_manager: DefaultIndexManager,
-    #[allow(dead_code)]
     mmap: Option<MmapMut>,
+    sync_policy: SyncPolicy,
+    last_sync: Instant,
+    /// Whether the mapping holds writes that haven't been synced
+    dirty: bool,
 }
 
 impl Clone for MmapStorage {
@@ -29,10 +46,20 @@ impl Clone for MmapStorage {
             tables: self.tables.clone(),
             index_manager: self.index_manager.clone(),
             mmap: None,
+            sync_policy: self.sync_policy,
+            last_sync: Instant::now(),
+            dirty: false,
         }
     }
 }
 
+impl Drop for MmapStorage {
+    fn drop(&mut self) {
+        // Nothing to report to from a destructor
+        let _ = self.flush();
+    }
+}
+
 impl MmapStorage {
     pub fn new(file_path: String) -> Self {
         let tables = if Path::new(&file_path).exists() {
@@ -65,9 +92,25 @@ impl MmapStorage {
             tables,
             index_manager: DefaultIndexManager::new(),
             mmap: None,
+            sync_policy: SyncPolicy::default(),
+            last_sync: Instant::now(),
+            dirty: false,
         }
     }
 
+    pub fn with_sync_policy(mut self, sync_policy: SyncPolicy) -> Self {
+        self.sync_policy = sync_policy;
+        self
+    }
+
+    pub fn set_sync_policy(&mut self, sync_policy: SyncPolicy) {
+        self.sync_policy = sync_policy;
+    }
+
+    pub fn sync_policy(&self) -> SyncPolicy {
+        self.sync_policy
+    }
+
     fn save(&mut self) -> Result<(), ReefDBError> {
         let serialized = serialize(&self.tables)
             .map_err(|e| ReefDBError::Other(format!("Serialization error: {}", e)))?;
@@ -92,14 +135,11 @@ impl MmapStorage {
                 .map_err(|e| ReefDBError::IoError(e.to_string()))?
         };
 
-        // Write data to memory map
+        // Write data to memory map; it reaches the disk on the next flush
         mmap.copy_from_slice(&serialized);
-        
-        // Sync changes to disk
-        mmap.flush()
-            .map_err(|e| ReefDBError::IoError(e.to_string()))?;
 
         self.mmap = Some(mmap);
+        self.dirty = true;
         Ok(())
     }
 
@@ -160,6 +200,40 @@ impl Storage for MmapStorage {
         Ok(len)
     }
 
+    fn append_rows(&mut self, table_name: &str, rows: Vec<Vec<DataValue>>) -> Result<usize, ReefDBError> {
+        let count = rows.len();
+        self.tables.get_mut(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?
+            .1
+            .extend(rows);
+        let _ = self.save();
+        Ok(count)
+    }
+
+    fn alter_column_type(&mut self, table_name: &str, column_name: &str, data_type: DataType) -> Result<(), ReefDBError> {
+        let table = self.tables.get_mut(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        convert_column(table, column_name, data_type)?;
+        let _ = self.save();
+        Ok(())
+    }
+
+    fn update_rows(&mut self, table_name: &str, row_ids: &[usize], updates: &[(String, DataValue)]) -> Result<usize, ReefDBError> {
+        let table = self.tables.get_mut(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let count = update_rows_at(table, row_ids, updates);
+        let _ = self.save();
+        Ok(count)
+    }
+
+    fn delete_rows(&mut self, table_name: &str, row_ids: &[usize]) -> Result<usize, ReefDBError> {
+        let table = self.tables.get_mut(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let count = delete_rows_at(table, row_ids);
+        let _ = self.save();
+        Ok(count)
+    }
+
     fn update_table(
         &mut self,
         table_name: &str,
@@ -285,6 +359,23 @@ impl Storage for MmapStorage {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    /// Syncs the mapping to disk (msync) if it holds unsynced writes
+    fn flush(&mut self) -> Result<(), ReefDBError> {
+        if let (true, Some(mmap)) = (self.dirty, &self.mmap) {
+            mmap.flush()?;
+        }
+        self.dirty = false;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+
+    fn sync_after_commit(&mut self) -> Result<(), ReefDBError> {
+        match self.sync_policy {
+            SyncPolicy::Periodic(interval) if self.last_sync.elapsed() < interval => Ok(()),
+            SyncPolicy::EveryCommit | SyncPolicy::Periodic(_) => self.flush(),
+        }
+    }
 }
 
 impl IndexManager for MmapStorage {
@@ -367,4 +458,25 @@ mod tests {
             assert_eq!(rows[1][2], DataValue::Integer(25));
         }
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_sync_policy() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_string_lossy().to_string();
+        let columns = vec![ColumnDef::new("id", DataType::Integer, vec![])];
+
+        let mut storage = MmapStorage::new(file_path.clone());
+        storage.insert_table("t".to_string(), columns.clone(), vec![]);
+        assert!(storage.dirty);
+        storage.sync_after_commit().unwrap();
+        assert!(!storage.dirty);
+
+        let mut storage = MmapStorage::new(file_path)
+            .with_sync_policy(SyncPolicy::Periodic(Duration::from_secs(3600)));
+        storage.insert_table("t".to_string(), columns, vec![]);
+        storage.sync_after_commit().unwrap();
+        assert!(storage.dirty, "the interval hasn't passed yet");
+        storage.flush().unwrap();
+        assert!(!storage.dirty);
+    }
+}
@@ -1,11 +1,72 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use serde::{Deserialize, Serialize};
 use crate::{sql::column_def::ColumnDef, sql::{data_value::DataValue, data_type::DataType}, error::ReefDBError};
 
 pub mod disk;
 pub mod memory;
 pub mod mmap;
 
-#[derive(Clone, Debug)]
+/// Rows read by `Storage::column_cardinality` on larger tables
+pub const CARDINALITY_SAMPLE_SIZE: usize = 1000;
+
+/// Converts a column of `table` to `data_type`, leaving the table untouched
+/// if any value fails to convert.
+pub(crate) fn convert_column(
+    table: &mut (Vec<ColumnDef>, Vec<Vec<DataValue>>),
+    column_name: &str,
+    data_type: DataType,
+) -> Result<(), ReefDBError> {
+    let (schema, rows) = table;
+    let idx = schema.iter()
+        .position(|c| c.name == column_name)
+        .ok_or_else(|| ReefDBError::ColumnNotFound(column_name.to_string()))?;
+    let converted = rows.iter()
+        .map(|row| row[idx].cast_to(&data_type))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (row, value) in rows.iter_mut().zip(converted) {
+        row[idx] = value;
+    }
+    schema[idx].data_type = data_type;
+    Ok(())
+}
+
+/// Applies `updates` to the rows of `table` at positions `row_ids`,
+/// skipping positions past the end. Returns the number of rows updated.
+pub(crate) fn update_rows_at(
+    table: &mut (Vec<ColumnDef>, Vec<Vec<DataValue>>),
+    row_ids: &[usize],
+    updates: &[(String, DataValue)],
+) -> usize {
+    let (schema, rows) = table;
+    let mut updated_count = 0;
+    for &row_id in row_ids {
+        let Some(row) = rows.get_mut(row_id) else {
+            continue;
+        };
+        for (column, value) in updates {
+            if let Some(idx) = schema.iter().position(|c| c.name == *column) {
+                row[idx] = value.clone();
+            }
+        }
+        updated_count += 1;
+    }
+    updated_count
+}
+
+/// Removes the rows of `table` at positions `row_ids`, the rows after them
+/// moving up. Returns the number of rows removed.
+pub(crate) fn delete_rows_at(table: &mut (Vec<ColumnDef>, Vec<Vec<DataValue>>), row_ids: &[usize]) -> usize {
+    let (_, rows) = table;
+    let initial_len = rows.len();
+    let mut row_id = 0;
+    rows.retain(|_| {
+        row_id += 1;
+        !row_ids.contains(&(row_id - 1))
+    });
+    initial_len - rows.len()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TableStorage {
     pub tables: HashMap<String, (Vec<ColumnDef>, Vec<Vec<DataValue>>)>,
 }
@@ -61,6 +122,34 @@ pub trait Storage: std::any::Any {
     fn get_table_ref(&self, table_name: &str) -> Option<&(Vec<ColumnDef>, Vec<Vec<DataValue>>)>;
     fn push_value(&mut self, table_name: &str, row: Vec<DataValue>) -> Result<usize, ReefDBError>;
 
+    /// Appends already validated rows in one go, without the per-row
+    /// constraint checks of `push_value`. Returns the number of rows added.
+    fn append_rows(&mut self, table_name: &str, rows: Vec<Vec<DataValue>>) -> Result<usize, ReefDBError> {
+        let (_, existing) = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        let count = rows.len();
+        existing.extend(rows);
+        Ok(count)
+    }
+
+    /// Converts every value of a column to `data_type` and updates the
+    /// schema. Nothing changes unless every value converts.
+    fn alter_column_type(&mut self, table_name: &str, column_name: &str, data_type: DataType) -> Result<(), ReefDBError> {
+        let table = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        convert_column(table, column_name, data_type)
+    }
+
+    /// Moves a table's schema and rows to `new_name`
+    fn rename_table(&mut self, table_name: &str, new_name: &str) -> Result<(), ReefDBError> {
+        let (columns, rows) = self.get_table_ref(table_name)
+            .cloned()
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        self.insert_table(new_name.to_string(), columns, rows);
+        self.drop_table(table_name);
+        Ok(())
+    }
+
     fn update_table(
         &mut self,
         table_name: &str,
@@ -74,8 +163,91 @@ pub trait Storage: std::any::Any {
         where_clause: Option<(String, DataValue)>,
     ) -> usize;
 
+    /// Applies `updates` to the rows at positions `row_ids` only, as an
+    /// UPDATE with a LIMIT does. Returns the number of rows updated.
+    fn update_rows(&mut self, table_name: &str, row_ids: &[usize], updates: &[(String, DataValue)]) -> Result<usize, ReefDBError> {
+        let table = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        Ok(update_rows_at(table, row_ids, updates))
+    }
+
+    /// Deletes the rows at positions `row_ids` only, as a DELETE with a
+    /// LIMIT does. Returns the number of rows deleted.
+    fn delete_rows(&mut self, table_name: &str, row_ids: &[usize]) -> Result<usize, ReefDBError> {
+        let table = self.get_table(table_name)
+            .ok_or_else(|| ReefDBError::TableNotFound(table_name.to_string()))?;
+        Ok(delete_rows_at(table, row_ids))
+    }
+
     fn table_exists(&self, table_name: &str) -> bool;
 
+    /// Number of rows in `table_name`, `None` if there is no such table
+    fn row_count(&self, table_name: &str) -> Option<usize> {
+        self.get_table_ref(table_name).map(|(_, rows)| rows.len())
+    }
+
+    /// Estimated number of distinct non-NULL values in a column, for the
+    /// planner. Exact up to `CARDINALITY_SAMPLE_SIZE` rows; larger tables
+    /// are sampled and the count scaled up with the Haas-Stokes estimator,
+    /// so a value seen only once in the sample counts for more than one
+    /// seen repeatedly.
+    fn column_cardinality(&self, table_name: &str, column_name: &str) -> Option<usize> {
+        let (schema, rows) = self.get_table_ref(table_name)?;
+        let idx = schema.iter().position(|c| c.name == column_name)?;
+        let sampled = rows.len() > CARDINALITY_SAMPLE_SIZE;
+        // Striding by a prime that doesn't divide the row count visits
+        // distinct rows, and keeps periodic data from lining up with the
+        // sample
+        let (sample_size, stride) = if sampled {
+            (CARDINALITY_SAMPLE_SIZE, [7919, 7907].into_iter().find(|prime| rows.len() % prime != 0).unwrap_or(1))
+        } else {
+            (rows.len(), 1)
+        };
+
+        let mut counts: BTreeMap<&DataValue, usize> = BTreeMap::new();
+        let mut values = 0;
+        for i in 0..sample_size {
+            let row = &rows[i * stride % rows.len()];
+            if row[idx] != DataValue::Null {
+                *counts.entry(&row[idx]).or_default() += 1;
+                values += 1;
+            }
+        }
+        if !sampled || values == 0 {
+            return Some(counts.len());
+        }
+
+        let (n, d) = (values as f64, counts.len() as f64);
+        let total = n * rows.len() as f64 / CARDINALITY_SAMPLE_SIZE as f64;
+        let seen_once = counts.values().filter(|&&count| count == 1).count() as f64;
+        let estimate = n * d / (n - seen_once + seen_once * n / total);
+        Some(estimate.round().clamp(d, total) as usize)
+    }
+
+    /// Makes every write so far durable. Storage that writes through, or
+    /// keeps nothing on disk, has nothing to do.
+    fn flush(&mut self) -> Result<(), ReefDBError> {
+        Ok(())
+    }
+
+    /// Called once a transaction has committed, to sync as the storage's
+    /// durability settings ask
+    fn sync_after_commit(&mut self) -> Result<(), ReefDBError> {
+        self.flush()
+    }
+
+    /// Releases the space held by deleted rows. Storage without a backing
+    /// file only has spare `Vec` capacity to give back.
+    fn vacuum(&mut self) -> Result<(), ReefDBError> {
+        let table_names: Vec<String> = self.get_all_tables().keys().cloned().collect();
+        for table_name in table_names {
+            if let Some((_, rows)) = self.get_table(&table_name) {
+                rows.shrink_to_fit();
+            }
+        }
+        Ok(())
+    }
+
     fn get_schema(&mut self, table_name: &str) -> Option<&mut Vec<ColumnDef>> {
         self.get_table(table_name).map(|(schema, _)| schema)
     }
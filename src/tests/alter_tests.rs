@@ -45,6 +45,7 @@ fn test_add_column() {
         None,
         vec![],
         vec![],
+        vec![],
     ));
     if let ReefDBResult::Select(rows) = db.execute_statement(stmt).unwrap() {
         assert_eq!(rows.len(), 1);
@@ -97,6 +98,7 @@ fn test_drop_column() {
         None,
         vec![],
         vec![],
+        vec![],
     ));
     if let ReefDBResult::Select(rows) = db.execute_statement(stmt).unwrap() {
         assert_eq!(rows.len(), 1);
@@ -146,6 +148,7 @@ fn test_rename_column() {
         None,
         vec![],
         vec![],
+        vec![],
     ));
     if let ReefDBResult::Select(rows) = db.execute_statement(stmt).unwrap() {
         assert_eq!(rows.len(), 1);
@@ -196,4 +199,96 @@ fn test_alter_errors() {
         alter_type: AlterType::AddColumn(ColumnDef::new("test", DataType::Text, vec![])),
     });
     assert!(db.execute_statement(stmt).is_err());
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_alter_column_type() -> Result<(), ReefDBError> {
+    use crate::storage::Storage;
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE items (id INTEGER PRIMARY KEY, price INTEGER, code TEXT)")?;
+    db.query("INSERT INTO items VALUES (1, 10, '42')")?;
+    db.query("INSERT INTO items VALUES (2, 25, '7')")?;
+
+    let column = |db: &InMemoryReefDB, idx: usize| {
+        let (schema, rows) = db.storage.get_table_ref("items").unwrap();
+        (schema[idx].data_type.clone(), rows.iter().map(|row| row[idx].clone()).collect::<Vec<_>>())
+    };
+
+    // Integer to float is lossless
+    db.query("ALTER TABLE items ALTER COLUMN price TYPE FLOAT")?;
+    assert_eq!(column(&db, 1), (DataType::Float, vec![DataValue::Float(10.0), DataValue::Float(25.0)]));
+
+    // Text is parsed
+    db.query("ALTER TABLE items ALTER COLUMN code TYPE INTEGER")?;
+    assert_eq!(column(&db, 2), (DataType::Integer, vec![DataValue::Integer(42), DataValue::Integer(7)]));
+    match db.query("SELECT id FROM items WHERE code = 7")? {
+        ReefDBResult::Select(result) => assert_eq!(result[0][0], DataValue::Integer(2)),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // A single bad value leaves the column as it was
+    db.query("INSERT INTO items VALUES (3, 1.5, 3)")?;
+    db.query("ALTER TABLE items ALTER COLUMN code TYPE TEXT")?;
+    db.query("UPDATE items SET code = 'n/a' WHERE id = 3")?;
+    assert!(db.query("ALTER TABLE items ALTER COLUMN code TYPE INTEGER").is_err());
+    assert!(db.query("ALTER TABLE items ALTER COLUMN price TYPE INTEGER").is_err());
+    assert_eq!(column(&db, 2).0, DataType::Text);
+    assert_eq!(column(&db, 2).1[0], DataValue::Text("42".to_string()));
+    assert_eq!(column(&db, 1).1[2], DataValue::Float(1.5));
+
+    assert!(matches!(
+        db.query("ALTER TABLE items ALTER COLUMN missing TYPE TEXT"),
+        Err(ReefDBError::ColumnNotFound(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_rename_table() -> Result<(), ReefDBError> {
+    use crate::indexes::index_manager::IndexKind;
+
+    let ids = |db: &mut InMemoryReefDB, sql: &str| -> Result<Vec<DataValue>, ReefDBError> {
+        match db.query(sql)? {
+            ReefDBResult::Select(result) => Ok(result.rows.into_iter().map(|(_, row)| row[0].clone()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE posts (id INTEGER PRIMARY KEY, views INTEGER, body TSVECTOR)")?;
+    db.query("INSERT INTO posts VALUES (1, 10, 'rust databases')")?;
+    db.query("INSERT INTO posts VALUES (2, 20, 'cooking pasta')")?;
+    db.query("CREATE INDEX ON posts(views)")?;
+
+    db.query("ALTER TABLE posts RENAME TO articles")?;
+    assert_eq!(db.list_tables(), vec!["articles".to_string()]);
+    assert!(matches!(db.query("SELECT * FROM posts"), Err(ReefDBError::TableNotFound(_))));
+
+    // Rows, indexes and full-text search all follow the table
+    assert_eq!(ids(&mut db, "SELECT id FROM articles WHERE views = 20")?, vec![DataValue::Integer(2)]);
+    assert_eq!(db.list_indexes("articles"), vec![
+        ("views".to_string(), IndexKind::BTree),
+        ("body".to_string(), IndexKind::GIN),
+    ]);
+    assert!(db.list_indexes("posts").is_empty());
+    assert!(db.verify_index("articles", "views")?.is_consistent);
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM articles WHERE to_tsvector(body) @@ to_tsquery('rust')")?,
+        vec![DataValue::Integer(1)]
+    );
+
+    // New rows are searchable under the new name
+    db.query("INSERT INTO articles VALUES (3, 30, 'rust compilers')")?;
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM articles WHERE to_tsvector(body) @@ to_tsquery('rust')")?,
+        vec![DataValue::Integer(1), DataValue::Integer(3)]
+    );
+
+    // The old name is free again, the new one has to be
+    db.query("CREATE TABLE posts (id INTEGER PRIMARY KEY)")?;
+    assert!(matches!(db.query("ALTER TABLE posts RENAME TO articles"), Err(ReefDBError::TableAlreadyExists(_))));
+    assert!(matches!(db.query("ALTER TABLE missing RENAME TO other"), Err(ReefDBError::TableNotFound(_))));
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::{
+    error::ReefDBError,
+    result::ReefDBResult,
+    InMemoryReefDB,
+    OnDiskReefDB,
+    indexes::index_manager::{IndexManager, IndexType},
+};
+use tempfile::tempdir;
+
+fn populate(db: &mut InMemoryReefDB) -> Result<(), ReefDBError> {
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+    db.query("CREATE TABLE posts (id INTEGER PRIMARY KEY, body TSVECTOR)")?;
+    db.query("CREATE INDEX ON users(age)")?;
+    db.query("INSERT INTO users VALUES (1, 'alice', 30)")?;
+    db.query("INSERT INTO users VALUES (2, 'bob', 25)")?;
+    db.query("INSERT INTO posts VALUES (1, 'Rust makes systems programming fun')")?;
+    db.query("INSERT INTO posts VALUES (2, 'Gardening tips for the spring')")?;
+    Ok(())
+}
+
+const QUERIES: [&str; 3] = [
+    "SELECT * FROM users",
+    "SELECT name FROM users WHERE age = 25",
+    "SELECT id FROM posts WHERE to_tsvector(body) @@ to_tsquery('rust')",
+];
+
+fn run_queries<S, FTS>(db: &mut crate::ReefDB<S, FTS>) -> Result<Vec<ReefDBResult>, ReefDBError>
+where
+    S: crate::storage::Storage + IndexManager + Clone + std::any::Any,
+    FTS: crate::fts::search::Search + Clone,
+    FTS::NewArgs: Clone + Default,
+{
+    QUERIES.iter().map(|sql| db.query(sql)).collect()
+}
+
+#[test]
+fn test_backup_round_trip_in_memory() -> Result<(), ReefDBError> {
+    let dir = tempdir().unwrap();
+    let backup_path = dir.path().join("db.backup");
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    populate(&mut db)?;
+    db.backup_to(&backup_path)?;
+
+    let mut restored = InMemoryReefDB::restore_from(&backup_path)?;
+    assert_eq!(run_queries(&mut restored)?, run_queries(&mut db)?);
+    assert!(matches!(restored.storage.get_index("users", "age"), Ok(IndexType::BTree(_))));
+
+    // Restoring replaces whatever the target database held
+    let mut other = InMemoryReefDB::create_in_memory()?;
+    other.query("CREATE TABLE scratch (id INTEGER)")?;
+    other.restore_backup(&backup_path)?;
+    assert!(other.query("SELECT * FROM scratch").is_err());
+    assert_eq!(run_queries(&mut other)?, run_queries(&mut db)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_backup_restore_on_disk() -> Result<(), ReefDBError> {
+    let dir = tempdir().unwrap();
+    let backup_path = dir.path().join("db.backup");
+    let kv_path = dir.path().join("restored.db").to_str().unwrap().to_string();
+    let index_path = dir.path().join("restored.idx").to_str().unwrap().to_string();
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    populate(&mut db)?;
+    db.backup_to(&backup_path)?;
+    let expected = run_queries(&mut db)?;
+
+    let mut restored = OnDiskReefDB::restore_from(&backup_path, kv_path.clone(), index_path.clone())?;
+    assert_eq!(run_queries(&mut restored)?, expected);
+    drop(restored);
+
+    // The restored tables survive reopening
+    let mut reopened = OnDiskReefDB::create_on_disk(kv_path, index_path)?;
+    assert_eq!(run_queries(&mut reopened)?[0], expected[0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_restore_rejects_unknown_version() -> Result<(), ReefDBError> {
+    let dir = tempdir().unwrap();
+    let backup_path = dir.path().join("db.backup");
+
+    let db = InMemoryReefDB::create_in_memory()?;
+    db.backup_to(&backup_path)?;
+
+    // The version heads the file
+    let mut bytes = std::fs::read(&backup_path).unwrap();
+    bytes[0] = 99;
+    std::fs::write(&backup_path, bytes).unwrap();
+
+    assert!(InMemoryReefDB::restore_from(&backup_path).is_err());
+
+    // A later version is reported as such even when the rest of the file
+    // doesn't decode as this version's layout
+    let mut bytes = bincode::serialize(&2u32).unwrap();
+    bytes.extend_from_slice(b"not a backup of this version");
+    std::fs::write(&backup_path, bytes).unwrap();
+
+    assert_eq!(
+        InMemoryReefDB::restore_from(&backup_path).err(),
+        Some(ReefDBError::Other("Unsupported backup format version 2 (expected 1)".to_string()))
+    );
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+
+use crate::{
+    builder::ReefDBBuilder,
+    error::ReefDBError,
+    functions::{Function, FunctionArg, FunctionArgType, FunctionReturnType},
+    result::ReefDBResult,
+    sql::{column_def::ColumnDef, data_type::DataType, data_value::DataValue},
+    storage::{disk::OnDiskStorage, Storage},
+    transaction::IsolationLevel,
+};
+use tempfile::tempdir;
+
+fn shout(args: Vec<DataValue>) -> Result<DataValue, ReefDBError> {
+    match &args[0] {
+        DataValue::Text(s) => Ok(DataValue::Text(s.to_uppercase())),
+        other => Err(ReefDBError::Other(format!("shout expects text, got {:?}", other))),
+    }
+}
+
+#[test]
+fn test_builder_in_memory_settings() -> Result<(), ReefDBError> {
+    let mut db = ReefDBBuilder::in_memory()
+        .with_autocommit(false)
+        .with_isolation_level(IsolationLevel::Serializable)
+        .with_lock_timeout(Duration::from_millis(50))
+        .with_function(Function {
+            name: "shout".to_string(),
+            args: vec![FunctionArg::new("value".to_string(), FunctionArgType::String)],
+            return_type: FunctionReturnType::String,
+            variadic: None,
+            handler: shout,
+        })
+        .build()?;
+
+    assert!(!db.is_autocommit());
+    assert_eq!(db.get_autocommit_isolation_level(), IsolationLevel::Serializable);
+    assert_eq!(db.transaction_manager.as_ref().unwrap().get_lock_config().lock_timeout, Duration::from_millis(50));
+
+    db.query("BEGIN TRANSACTION")?;
+    db.query("CREATE TABLE numbers (id INTEGER PRIMARY KEY, n INTEGER)")?;
+    db.query("INSERT INTO numbers VALUES (1, 1)")?;
+    match db.query("SELECT shout('hello') FROM numbers")? {
+        ReefDBResult::Select(rows) => assert_eq!(rows[0][0], DataValue::Text("HELLO".to_string())),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    db.query("COMMIT")?;
+
+    // Functions may not shadow builtins
+    let result = ReefDBBuilder::in_memory()
+        .with_function(Function {
+            name: "concat".to_string(),
+            args: vec![],
+            return_type: FunctionReturnType::Integer,
+            variadic: None,
+            handler: shout,
+        })
+        .build();
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_builder_block_size() -> Result<(), ReefDBError> {
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("db");
+
+    {
+        let mut db = ReefDBBuilder::on_disk(&data_dir).with_block_size(1024).build()?;
+        assert_eq!(db.storage.block_size(), 1024);
+        db.storage.insert_table("users".to_string(), vec![ColumnDef::new("id", DataType::Integer, vec![])], vec![]);
+    }
+
+    // The data file remembers the block size it was written with
+    let db = ReefDBBuilder::on_disk(&data_dir).build()?;
+    assert_eq!(db.storage.block_size(), 1024);
+    assert!(db.storage.table_exists("users"));
+    Ok(())
+}
+
+#[test]
+fn test_builder_on_disk_reopens_data() -> Result<(), ReefDBError> {
+    let dir = tempdir().unwrap();
+    let data_dir = dir.path().join("db");
+
+    {
+        let mut db = ReefDBBuilder::on_disk(&data_dir).build()?;
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+        db.query("INSERT INTO users VALUES (1, 'alice')")?;
+    }
+    assert!(data_dir.join("reefdb.db.wal").exists());
+
+    let mut db = ReefDBBuilder::on_disk(&data_dir).build()?;
+    match db.query("SELECT name FROM users")? {
+        ReefDBResult::Select(rows) => assert_eq!(rows[0][0], DataValue::Text("alice".to_string())),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Persistent backends need somewhere to put their files
+    assert!(ReefDBBuilder::<OnDiskStorage>::new().build().is_err());
+
+    Ok(())
+}
@@ -42,6 +42,7 @@ fn test_create_statement() -> Result<(), ReefDBError> {
         None,
         vec![],
         vec![],
+        vec![],
     );
     let result = db.transaction_manager.as_mut().unwrap().execute_statement(transaction_id, Statement::Select(select_stmt));
     assert!(result.is_ok()); // Table should exist and be queryable
@@ -77,7 +78,7 @@ fn test_create_statement() -> Result<(), ReefDBError> {
         ColumnDef::new("name", DataType::Text, vec![]),
     ];
     let result = db.transaction_manager.as_mut().unwrap().execute_statement(transaction_id, Statement::Create(CreateStatement::Table("users".to_string(), columns)));
-    assert!(matches!(result, Err(ReefDBError::Other(_))));
+    assert_eq!(result, Err(ReefDBError::TableAlreadyExists("users".to_string())));
 
     // Test 6: Create table with empty column list (should fail)
     let result = db.transaction_manager.as_mut().unwrap().execute_statement(transaction_id, Statement::Create(CreateStatement::Table("empty".to_string(), vec![])));
@@ -138,6 +139,7 @@ fn test_create_statement() -> Result<(), ReefDBError> {
         Some(where_clause),
         vec![],
         vec![],
+        vec![],
     );
     let result = db.transaction_manager.as_mut().unwrap().execute_statement(transaction_id, Statement::Select(select_stmt))?;
     if let ReefDBResult::Select(rows) = result {
@@ -150,4 +152,42 @@ fn test_create_statement() -> Result<(), ReefDBError> {
     db.transaction_manager.as_mut().unwrap().commit_transaction(transaction_id)?;
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_quoted_identifiers() -> Result<(), ReefDBError> {
+    use crate::storage::Storage;
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE \"Customer Orders\" (id INTEGER PRIMARY KEY, \"Order\" INTEGER, \"Ship To\" TEXT)")?;
+    db.query("INSERT INTO \"Customer Orders\" VALUES (1, 10, 'Lisbon')")?;
+    db.query("INSERT INTO \"Customer Orders\" VALUES (2, 20, 'Porto')")?;
+
+    // The exact names are stored
+    let (schema, _) = db.storage.get_table_ref("Customer Orders").unwrap();
+    let names: Vec<&str> = schema.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "Order", "Ship To"]);
+
+    match db.query("SELECT \"Ship To\" FROM \"Customer Orders\" AS \"co\" WHERE \"Order\" = 20")? {
+        ReefDBResult::Select(result) => {
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0][0], DataValue::Text("Porto".to_string()));
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    db.query("UPDATE \"Customer Orders\" SET \"Ship To\" = 'Faro' WHERE id = 1")?;
+    db.query("DELETE FROM \"Customer Orders\" WHERE \"Order\" = 20")?;
+    match db.query("SELECT id, \"Ship To\" FROM \"Customer Orders\"")? {
+        ReefDBResult::Select(result) => {
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0][1], DataValue::Text("Faro".to_string()));
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Quoting keeps case, so the bare name is another column
+    assert!(db.query("SELECT order FROM \"Customer Orders\"").is_err());
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use crate::{
+    error::ReefDBError,
+    result::ReefDBResult,
+    sql::data_value::DataValue,
+    InMemoryReefDB,
+};
+
+fn select_all(db: &mut InMemoryReefDB, table: &str) -> Result<Vec<Vec<DataValue>>, ReefDBError> {
+    match db.query(&format!("SELECT * FROM {}", table))? {
+        ReefDBResult::Select(rows) => Ok(rows.rows.into_iter().map(|(_, row)| row).collect()),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_csv_round_trip() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, score FLOAT, done BOOLEAN)")?;
+    db.query("INSERT INTO notes VALUES (1, 'plain', 1.5, true)")?;
+    db.query("INSERT INTO notes VALUES (2, 'commas, and \"quotes\"', 2.0, false)")?;
+    db.query("INSERT INTO notes VALUES (3, 'two\nlines', 0.25, true)")?;
+
+    let mut out = Vec::new();
+    assert_eq!(db.export_table_csv("notes", &mut out)?, 3);
+    let csv = String::from_utf8(out).unwrap();
+    assert!(csv.starts_with("id,body,score,done\n1,plain,1.5,true\n"));
+    assert!(csv.contains("\"commas, and \"\"quotes\"\"\""));
+
+    let mut copy = InMemoryReefDB::create_in_memory()?;
+    copy.query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT, score FLOAT, done BOOLEAN)")?;
+    assert_eq!(copy.import_table_csv("notes", csv.as_bytes())?, 3);
+    assert_eq!(select_all(&mut copy, "notes")?, select_all(&mut db, "notes")?);
+
+    Ok(())
+}
+
+#[test]
+fn test_csv_import_maps_header_and_nulls() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+
+    let csv = "age,id\n30,1\n,2\n";
+    assert_eq!(db.import_table_csv("users", csv.as_bytes())?, 2);
+
+    let rows = select_all(&mut db, "users")?;
+    assert_eq!(rows[0], vec![DataValue::Integer(1), DataValue::Null, DataValue::Integer(30)]);
+    assert_eq!(rows[1], vec![DataValue::Integer(2), DataValue::Null, DataValue::Null]);
+
+    Ok(())
+}
+
+#[test]
+fn test_csv_import_reports_line_numbers() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+
+    let err = db.import_table_csv("users", "id,name,age\n1,alice,30\n2,bob,old\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("CSV line 3"), "{}", err);
+
+    let err = db.import_table_csv("users", "id,name,age\n1,\"multi\nline\",30\n2,bob\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("CSV line 4"), "{}", err);
+
+    let err = db.import_table_csv("users", "id,email\n1,a@b.c\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("email"), "{}", err);
+
+    // Nothing was inserted by the failed imports
+    assert!(select_all(&mut db, "users")?.is_empty());
+
+    // Constraint violations carry the line number too
+    db.import_table_csv("users", "id,name\n1,alice\n".as_bytes())?;
+    let err = db.import_table_csv("users", "id,name\n1,again\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("CSV line 2"), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_csv_import_is_all_or_nothing() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'alice')")?;
+
+    // The rows before the duplicate key are rolled back with it
+    let err = db.import_table_csv("users", "id,name\n2,bob\n3,carol\n1,again\n".as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("CSV line 4"), "{}", err);
+    assert_eq!(select_all(&mut db, "users")?, vec![vec![DataValue::Integer(1), DataValue::Text("alice".to_string())]]);
+
+    assert_eq!(db.import_table_csv("users", "id,name\n2,bob\n".as_bytes())?, 1);
+    assert_eq!(select_all(&mut db, "users")?.len(), 2);
+
+    Ok(())
+}
@@ -36,4 +36,44 @@ fn test_data_types() {
     assert!(schema[3].data_type == DataType::Float);
     assert!(schema[4].data_type == DataType::Date);
     assert!(schema[5].data_type == DataType::Timestamp);
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_timestamp_queries() -> Result<(), crate::error::ReefDBError> {
+    use crate::{InMemoryReefDB, result::ReefDBResult};
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE events (id INTEGER PRIMARY KEY, created_at TIMESTAMP)")?;
+    db.query("INSERT INTO events VALUES (1, '2023-12-31T23:30:00Z')")?;
+    db.query("INSERT INTO events VALUES (2, '2024-01-01T01:30:00+02:00')")?;
+    db.query("INSERT INTO events VALUES (3, '2024-02-15 08:00:00')")?;
+
+    let select = |db: &mut InMemoryReefDB, sql: &str| match db.query(sql) {
+        Ok(ReefDBResult::Select(result)) => result,
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+
+    // Literals are normalized to UTC, so the +02:00 event falls in 2023
+    let result = select(&mut db, "SELECT id, created_at FROM events WHERE created_at > '2024-01-01T00:00:00Z'");
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0][0], DataValue::Integer(3));
+    assert_eq!(result[0][1], DataValue::Timestamp("2024-02-15 08:00:00".to_string()));
+
+    let result = select(&mut db, "SELECT id FROM events WHERE created_at < NOW()");
+    assert_eq!(result.len(), 3);
+
+    let result = select(&mut db, "SELECT EXTRACT('month', created_at), DATE_TRUNC('day', created_at) FROM events WHERE id = 2");
+    assert_eq!(result[0][0], DataValue::Integer(12));
+    assert_eq!(result[0][1], DataValue::Timestamp("2023-12-31 00:00:00".to_string()));
+
+    // Timestamp text is only read as a timestamp against a TIMESTAMP
+    db.query("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)")?;
+    db.query("INSERT INTO notes VALUES (1, '2024-01-01T12:00:00Z')")?;
+    let result = select(&mut db, "SELECT body FROM notes WHERE body = '2024-01-01T12:00:00Z'");
+    assert_eq!(result[0][0], DataValue::Text("2024-01-01T12:00:00Z".to_string()));
+    assert_eq!(db.query("UPDATE events SET created_at = '2024-03-01T00:00:00+01:00' WHERE id = 3")?, ReefDBResult::Update(1));
+    let result = select(&mut db, "SELECT created_at FROM events WHERE id = 3");
+    assert_eq!(result[0][0], DataValue::Timestamp("2024-02-29 23:00:00".to_string()));
+
+    Ok(())
+}
@@ -8,6 +8,13 @@ use crate::sql::{
 
 type Result<T> = std::result::Result<T, ReefDBError>;
 
+fn row_count(db: &mut InMemoryReefDB, sql: &str) -> usize {
+    match db.query(sql) {
+        Ok(ReefDBResult::Select(rows)) => rows.len(),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_delete_basic() -> Result<()> {
     let mut db = InMemoryReefDB::create_in_memory()?;
@@ -88,6 +95,7 @@ fn parse_delete_with_where_test() {
             Statement::Delete(DeleteStatement::FromTable(
                 "users".to_string(),
                 Some(where_clause),
+                None,
             ))
         ))
     );
@@ -109,7 +117,182 @@ fn parse_delete_with_where_text_test() {
             Statement::Delete(DeleteStatement::FromTable(
                 "users".to_string(),
                 Some(where_clause),
+                None,
             ))
         ))
     );
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_delete_with_scalar_subquery() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    db.query("INSERT INTO users VALUES (2, 'Bob')")?;
+    db.query("INSERT INTO users VALUES (3, 'Charlie')")?;
+    db.query("CREATE TABLE banned (id INTEGER PRIMARY KEY, user_id INTEGER, reason TEXT)")?;
+    db.query("INSERT INTO banned VALUES (1, 2, 'spam')")?;
+    db.query("INSERT INTO banned VALUES (2, 3, 'abuse')")?;
+
+    if let ReefDBResult::Select(rows) = db.query("SELECT name FROM users WHERE id = (SELECT user_id FROM banned WHERE reason = 'spam')")? {
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], DataValue::Text("Bob".to_string()));
+    } else {
+        panic!("Expected Select result");
+    }
+
+    assert_eq!(
+        db.query("DELETE FROM users WHERE id = (SELECT user_id FROM banned WHERE reason = 'spam')")?,
+        ReefDBResult::Delete(1)
+    );
+    if let ReefDBResult::Select(rows) = db.query("SELECT id FROM users")? {
+        let ids: Vec<_> = rows.rows.iter().map(|(_, row)| row[0].clone()).collect();
+        assert_eq!(ids, vec![DataValue::Integer(1), DataValue::Integer(3)]);
+    } else {
+        panic!("Expected Select result");
+    }
+
+    // The subquery must produce exactly one value
+    assert!(db.query("DELETE FROM users WHERE id = (SELECT user_id FROM banned)").is_err());
+    assert!(db.query("DELETE FROM users WHERE id = (SELECT user_id FROM banned WHERE reason = 'none')").is_err());
+    assert!(db.query("SELECT * FROM users WHERE id = (SELECT id, user_id FROM banned WHERE reason = 'abuse')").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_returning() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    db.query("INSERT INTO users VALUES (2, 'Bob')")?;
+
+    // The returned rows hold the values before the delete
+    match db.query("DELETE FROM users WHERE id = 2 RETURNING *")? {
+        ReefDBResult::Returning(result) => {
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0], vec![DataValue::Integer(2), DataValue::Text("Bob".to_string())]);
+        }
+        other => panic!("Expected Returning result, got {:?}", other),
+    }
+    match db.query("DELETE FROM users WHERE id = 2 RETURNING name")? {
+        ReefDBResult::Returning(result) => assert_eq!(result.len(), 0),
+        other => panic!("Expected Returning result, got {:?}", other),
+    }
+    match db.query("DELETE FROM users RETURNING name")? {
+        ReefDBResult::Returning(result) => assert_eq!(result.get_text(0, "name")?, Some("Alice")),
+        other => panic!("Expected Returning result, got {:?}", other),
+    }
+    if let ReefDBResult::Select(rows) = db.query("SELECT * FROM users")? {
+        assert_eq!(rows.len(), 0);
+    } else {
+        panic!("Expected Select result");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_limit() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, status TEXT)")?;
+    for id in 1..=5 {
+        db.query(&format!("INSERT INTO users VALUES ({}, 'inactive')", id))?;
+    }
+
+    // Five rows match, only the first two go
+    assert_eq!(db.query("DELETE FROM users WHERE status = 'inactive' LIMIT 2")?, ReefDBResult::Delete(2));
+    let ReefDBResult::Select(result) = db.query("SELECT id FROM users")? else {
+        panic!("Expected Select result");
+    };
+    let ids: Vec<_> = result.rows.iter().map(|(_, row)| row[0].clone()).collect();
+    assert_eq!(ids, vec![DataValue::Integer(3), DataValue::Integer(4), DataValue::Integer(5)]);
+    assert_eq!(db.query("DELETE FROM users LIMIT 0")?, ReefDBResult::Delete(0));
+
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    assert_eq!(tx.query("DELETE FROM users LIMIT 1")?, ReefDBResult::Delete(1));
+    tx.commit()?;
+    assert_eq!(row_count(&mut db, "SELECT id FROM users"), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_where_in() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("CREATE TABLE banned (user_id INTEGER)")?;
+    for id in 1..=5 {
+        db.query(&format!("INSERT INTO users VALUES ({}, 'user{}')", id, id))?;
+    }
+    db.query("INSERT INTO banned VALUES (4)")?;
+
+    // Only the listed rows go, not the whole table
+    assert_eq!(db.query("DELETE FROM users WHERE id IN (1, 2)")?, ReefDBResult::Delete(2));
+    assert_eq!(db.query("DELETE FROM users WHERE id IN (SELECT user_id FROM banned)")?, ReefDBResult::Delete(1));
+    let ReefDBResult::Select(result) = db.query("SELECT id FROM users")? else {
+        panic!("Expected Select result");
+    };
+    let ids: Vec<_> = result.rows.iter().map(|(_, row)| row[0].clone()).collect();
+    assert_eq!(ids, vec![DataValue::Integer(3), DataValue::Integer(5)]);
+
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    assert_eq!(tx.query("DELETE FROM users WHERE id IN (5)")?, ReefDBResult::Delete(1));
+    tx.commit()?;
+    assert_eq!(row_count(&mut db, "SELECT id FROM users"), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_where_between() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, age INTEGER)")?;
+    for id in 1..=5 {
+        db.query(&format!("INSERT INTO users VALUES ({}, {})", id, id * 10))?;
+    }
+
+    assert_eq!(db.query("DELETE FROM users WHERE age BETWEEN 20 AND 30")?, ReefDBResult::Delete(2));
+    assert_eq!(row_count(&mut db, "SELECT id FROM users"), 3);
+
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    assert_eq!(tx.query("DELETE FROM users WHERE age BETWEEN 40 AND 45")?, ReefDBResult::Delete(1));
+    tx.commit()?;
+    assert_eq!(row_count(&mut db, "SELECT id FROM users"), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_where_expression() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE items (n INTEGER, flags INTEGER)")?;
+    for n in 1..=6 {
+        db.query(&format!("INSERT INTO items VALUES ({}, {})", n, n))?;
+    }
+
+    // Rows 2, 4 and 6 go, not the whole table
+    assert_eq!(db.query("DELETE FROM items WHERE n % 2 = 0")?, ReefDBResult::Delete(3));
+    assert_eq!(row_count(&mut db, "SELECT n FROM items"), 3);
+    // Of 1, 3 and 5 only 5 has bit 4 set
+    assert_eq!(db.query("DELETE FROM items WHERE flags & 4 = 4")?, ReefDBResult::Delete(1));
+    assert_eq!(row_count(&mut db, "SELECT n FROM items"), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_delete_where_like() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    db.query("INSERT INTO users VALUES (2, 'alfred')")?;
+    db.query("INSERT INTO users VALUES (3, 'Bob')")?;
+
+    assert_eq!(db.query("DELETE FROM users WHERE name ILIKE 'a%'")?, ReefDBResult::Delete(2));
+    assert_eq!(db.query("DELETE FROM users WHERE name LIKE 'B_b'")?, ReefDBResult::Delete(1));
+    assert_eq!(row_count(&mut db, "SELECT id FROM users"), 0);
+
+    Ok(())
+}
@@ -44,6 +44,7 @@ fn test_drop_table() {
         None,
         vec![],
         vec![],
+        vec![],
     ));
     assert!(db.execute_statement(stmt).is_err());
 }
@@ -91,6 +92,7 @@ fn test_operations_after_drop() {
         "users".to_string(),
         vec![("name".to_string(), DataValue::Text("Jane".to_string()))],
         None,
+        None,
     ));
     assert!(db.execute_statement(stmt).is_err());
 
@@ -110,4 +112,4 @@ fn test_operations_after_drop() {
         ],
     ));
     assert!(db.execute_statement(stmt).is_ok());
-} 
\ No newline at end of file
+} 
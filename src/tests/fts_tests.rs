@@ -205,4 +205,322 @@ fn test_full_text_search_e2e() -> Result<(), ReefDBError> {
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_custom_stop_words() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE companies(id INTEGER PRIMARY KEY,name TSVECTOR)")?;
+    db.query("INSERT INTO companies VALUES (1, 'Acme Widgets Inc')")?;
+    db.query("INSERT INTO companies VALUES (2, 'Globex Ltd')")?;
+
+    let search = |db: &mut InMemoryReefDB, term: &str| -> Result<usize, ReefDBError> {
+        match db.query(&format!(
+            "SELECT id FROM companies WHERE to_tsvector(name) @@ to_tsquery('{}')", term
+        ))? {
+            ReefDBResult::Select(results) => Ok(results.len()),
+            _ => panic!("Expected Select result"),
+        }
+    };
+
+    assert_eq!(search(&mut db, "inc")?, 1);
+    assert_eq!(search(&mut db, "ltd")?, 1);
+
+    // Augment the default list; existing rows are reindexed
+    db.set_fts_stop_words("companies", "name", &["inc", "ltd"], true)?;
+    assert_eq!(search(&mut db, "inc")?, 0);
+    assert_eq!(search(&mut db, "ltd")?, 0);
+    assert_eq!(search(&mut db, "widgets")?, 1);
+
+    // New rows use the custom list too
+    db.query("INSERT INTO companies VALUES (3, 'Initech Inc')")?;
+    assert_eq!(search(&mut db, "inc")?, 0);
+    assert_eq!(search(&mut db, "initech")?, 1);
+
+    // Stop words only apply to TSVECTOR columns
+    assert!(db.set_fts_stop_words("companies", "id", &["inc"], true).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_full_text_search_with_and_or() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE articles(id INTEGER PRIMARY KEY,title TEXT,content TSVECTOR)")?;
+    db.query("CREATE GIN INDEX ON articles(content)")?;
+    db.query("INSERT INTO articles VALUES (1, 'Rust Programming', 'Learn Rust programming language basics')")?;
+    db.query("INSERT INTO articles VALUES (2, 'Web Development', 'Building modern web applications with Rust')")?;
+    db.query("INSERT INTO articles VALUES (3, 'Database Design', 'Introduction to database design principles')")?;
+    db.query("INSERT INTO articles VALUES (4, 'Cooking', 'Slow cooked stews for winter evenings')")?;
+
+    let ids = |db: &mut InMemoryReefDB, sql: &str| -> Result<Vec<DataValue>, ReefDBError> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => {
+                let mut ids: Vec<DataValue> = results.rows.iter().map(|(_, row)| row[0].clone()).collect();
+                ids.sort_by(|a, b| a.sort_cmp(b).unwrap());
+                Ok(ids)
+            }
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('rust') AND id > 1")?,
+        vec![DataValue::Integer(2)]
+    );
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('rust') OR id = 4")?,
+        vec![DataValue::Integer(1), DataValue::Integer(2), DataValue::Integer(4)]
+    );
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('database') OR to_tsvector(content) @@ to_tsquery('web')")?,
+        vec![DataValue::Integer(2), DataValue::Integer(3)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_full_text_search_without_integer_id() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE notes(slug TEXT PRIMARY KEY,body TSVECTOR)")?;
+    db.query("CREATE GIN INDEX ON notes(body)")?;
+    db.query("INSERT INTO notes VALUES ('intro', 'Getting started with Rust')")?;
+    db.query("INSERT INTO notes VALUES ('cooking', 'Slow cooked stews for winter evenings')")?;
+    db.query("INSERT INTO notes VALUES ('async', 'Async Rust and futures')")?;
+
+    match db.query("SELECT slug FROM notes WHERE to_tsvector(body) @@ to_tsquery('rust')")? {
+        ReefDBResult::Select(results) => {
+            let slugs: Vec<DataValue> = results.rows.iter().map(|(_, row)| row[0].clone()).collect();
+            assert_eq!(slugs, vec![
+                DataValue::Text("intro".to_string()),
+                DataValue::Text("async".to_string()),
+            ]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Integer keys that don't follow insertion order match by row too
+    db.query("CREATE TABLE posts(id INTEGER PRIMARY KEY,body TSVECTOR)")?;
+    db.query("INSERT INTO posts VALUES (30, 'Nothing to see here')")?;
+    db.query("INSERT INTO posts VALUES (10, 'Rust ownership explained')")?;
+    match db.query("SELECT id FROM posts WHERE to_tsvector(body) @@ to_tsquery('rust')")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.rows.iter().map(|(_, row)| row[0].clone()).collect::<Vec<_>>(), vec![DataValue::Integer(10)]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_full_text_search_after_delete() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE articles(id INTEGER PRIMARY KEY,content TSVECTOR)")?;
+    db.query("CREATE GIN INDEX ON articles(content)")?;
+    db.query("INSERT INTO articles VALUES (1, 'Rust ownership and borrowing')")?;
+    db.query("INSERT INTO articles VALUES (2, 'Rust macros in depth')")?;
+    db.query("INSERT INTO articles VALUES (3, 'Gardening for beginners')")?;
+    db.query("DELETE FROM articles WHERE id = 2")?;
+
+    let ids = |db: &mut InMemoryReefDB, query: &str| -> Result<Vec<DataValue>, ReefDBError> {
+        let sql = format!("SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('{}')", query);
+        match db.query(&sql)? {
+            ReefDBResult::Select(results) => Ok(results.rows.iter().map(|(_, row)| row[0].clone()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+    assert_eq!(ids(&mut db, "rust")?, vec![DataValue::Integer(1)]);
+    assert_eq!(ids(&mut db, "gardening")?, vec![DataValue::Integer(3)]);
+    assert_eq!(ids(&mut db, "macros")?, Vec::<DataValue>::new());
+
+    // Rows inserted after the delete get documents of their own
+    db.query("INSERT INTO articles VALUES (4, 'Rust for gardening robots')")?;
+    assert_eq!(ids(&mut db, "gardening")?, vec![DataValue::Integer(3), DataValue::Integer(4)]);
+    Ok(())
+}
+
+#[test]
+fn test_full_text_search_with_not() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE langs(id INTEGER PRIMARY KEY,body TSVECTOR)")?;
+    db.query("CREATE GIN INDEX ON langs(body)")?;
+    db.query("INSERT INTO langs VALUES (1, 'rust python')")?;
+    db.query("INSERT INTO langs VALUES (2, 'rust go')")?;
+
+    for query in ["rust AND NOT python", "rust -python"] {
+        match db.query(&format!("SELECT id FROM langs WHERE to_tsvector(body) @@ to_tsquery('{}')", query))? {
+            ReefDBResult::Select(results) => {
+                let ids: Vec<DataValue> = results.rows.iter().map(|(_, row)| row[0].clone()).collect();
+                assert_eq!(ids, vec![DataValue::Integer(2)], "query {}", query);
+            }
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_setweight_ranks_weighted_sections() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE posts(id INTEGER PRIMARY KEY,title TSVECTOR,body TSVECTOR)")?;
+    db.query("INSERT INTO posts VALUES (1, 'Rust in production', 'Rust in production')")?;
+
+    let query = "SELECT ts_rank(setweight(to_tsvector(title), 'A'), to_tsquery('rust')) AS title_rank, \
+                 ts_rank(setweight(to_tsvector(body), 'D'), to_tsquery('rust')) AS body_rank FROM posts";
+    match db.query(query)? {
+        ReefDBResult::Select(results) => match (&results[0][0], &results[0][1]) {
+            (DataValue::Float(title_rank), DataValue::Float(body_rank)) => {
+                assert!(*body_rank > 0.0);
+                assert!(title_rank > body_rank);
+            }
+            other => panic!("Expected float ranks, got {:?}", other),
+        },
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Weighted vectors still match, and weights must be A, B, C or D
+    match db.query("SELECT id FROM posts WHERE setweight(to_tsvector(title), 'B') @@ to_tsquery('rust')")? {
+        ReefDBResult::Select(results) => assert_eq!(results.len(), 1),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    assert!(db.query("SELECT ts_rank(setweight(to_tsvector(title), 'X'), to_tsquery('rust')) FROM posts").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_rank_with_collection_stats() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE docs(id INTEGER PRIMARY KEY,body TSVECTOR)")?;
+    db.query("INSERT INTO docs VALUES (1, 'zig')")?;
+    for id in 2..=4 {
+        db.query(&format!("INSERT INTO docs VALUES ({}, 'rust')", id))?;
+    }
+
+    let ranks = |db: &mut InMemoryReefDB| -> Result<(f64, f64), ReefDBError> {
+        let rank = |db: &mut InMemoryReefDB, id: i64, term: &str| -> Result<f64, ReefDBError> {
+            let sql = format!("SELECT ts_rank(to_tsvector(body), to_tsquery('{}')) FROM docs WHERE id = {}", term, id);
+            match db.query(&sql)? {
+                ReefDBResult::Select(results) => match results[0][0] {
+                    DataValue::Float(rank) => Ok(rank),
+                    ref other => panic!("Expected a float rank, got {:?}", other),
+                },
+                other => panic!("Expected Select result, got {:?}", other),
+            }
+        };
+        Ok((rank(db, 1, "zig")?, rank(db, 2, "rust")?))
+    };
+
+    // Without statistics both terms count the same
+    let (zig, rust) = ranks(&mut db)?;
+    assert_eq!(zig, rust);
+
+    // zig is in one document out of four, rust in three
+    db.refresh_fts_stats();
+    let stats = db.inverted_index.collection_stats("docs", "body").unwrap();
+    assert_eq!(stats.total_docs, 4);
+    assert_eq!(stats.avg_doc_length, 1.0);
+    let (zig, rust) = ranks(&mut db)?;
+    assert!(zig > rust);
+
+    Ok(())
+}
+
+#[test]
+fn test_fts_with_date_range_scans_only_matches() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE logs(id INTEGER PRIMARY KEY,body TSVECTOR,created_at TIMESTAMP)")?;
+    let rows = 1000;
+    for i in 0..rows {
+        let body = if i % 25 == 0 { "disk error on node" } else { "request served" };
+        let created_at = format!("2024-{:02}-{:02} 12:00:00", i % 3 + 1, i % 28 + 1);
+        db.query(&format!("INSERT INTO logs VALUES ({}, '{}', '{}')", i, body, created_at))?;
+    }
+
+    let ids = |result: ReefDBResult| match result {
+        ReefDBResult::Select(result) => result.rows.into_iter().map(|(_, row)| row[0].clone()).collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+
+    // The range alone has to look at every row
+    let (in_range, naive) = db.query_with_stats(
+        "SELECT id FROM logs WHERE created_at BETWEEN '2024-01-01' AND '2024-02-01'"
+    )?;
+    assert_eq!(naive.rows_scanned, rows);
+
+    // With the text predicate only the rows it matched are read
+    let (hybrid, stats) = db.query_with_stats(
+        "SELECT id FROM logs WHERE body @@ 'error' AND created_at BETWEEN '2024-01-01' AND '2024-02-01'"
+    )?;
+    assert_eq!(stats.rows_scanned, rows / 25);
+    assert_eq!(stats.index_used.as_deref(), Some("logs.body"));
+
+    let expected: Vec<DataValue> = ids(in_range).into_iter()
+        .filter(|id| matches!(id, DataValue::Integer(i) if i % 25 == 0))
+        .collect();
+    assert!(!expected.is_empty());
+    assert_eq!(ids(hybrid), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_fuzzy_fts_term() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE docs(id INTEGER PRIMARY KEY,body TSVECTOR)")?;
+    db.query("INSERT INTO docs VALUES (1, 'the database is down')")?;
+    db.query("INSERT INTO docs VALUES (2, 'all services healthy')")?;
+
+    let count = |db: &mut InMemoryReefDB, sql: &str| match db.query(sql) {
+        Ok(ReefDBResult::Select(result)) => result.len(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+
+    assert_eq!(count(&mut db, "SELECT id FROM docs WHERE body @@ 'databse~1'"), 1);
+    assert_eq!(count(&mut db, "SELECT id FROM docs WHERE body @@ 'databse~0'"), 0);
+    assert_eq!(count(&mut db, "SELECT id FROM docs WHERE body @@ 'databse'"), 0);
+    assert_eq!(count(&mut db, "SELECT id FROM docs WHERE body @@ to_tsquery('english', 'helthy~1')"), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_custom_tokenizer_column() -> Result<(), ReefDBError> {
+    use crate::fts::tokenizers::ngram::NgramTokenizer;
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.register_tokenizer("bigram", NgramTokenizer::with_size(2))?;
+
+    db.query("CREATE TABLE notes(id INTEGER PRIMARY KEY,plain TSVECTOR,body TSVECTOR TOKENIZER 'bigram')")?;
+    db.query("INSERT INTO notes VALUES (1, '東京タワーに行きました', '東京タワーに行きました')")?;
+    db.query("INSERT INTO notes VALUES (2, '京都の寺', '京都の寺')")?;
+
+    let ids = |db: &mut InMemoryReefDB, sql: &str| -> Result<Vec<DataValue>, ReefDBError> {
+        match db.query(sql)? {
+            ReefDBResult::Select(result) => Ok(result.rows.into_iter().map(|(_, row)| row[0].clone()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    // The default tokenizer keeps the sentence as one token
+    assert!(ids(&mut db, "SELECT id FROM notes WHERE to_tsvector(plain) @@ to_tsquery('東京')")?.is_empty());
+    assert_eq!(ids(&mut db, "SELECT id FROM notes WHERE to_tsvector(body) @@ to_tsquery('東京')")?, vec![DataValue::Integer(1)]);
+    assert_eq!(ids(&mut db, "SELECT id FROM notes WHERE body @@ 'タワー'")?, vec![DataValue::Integer(1)]);
+    assert_eq!(ids(&mut db, "SELECT id FROM notes WHERE body @@ '京都'")?, vec![DataValue::Integer(2)]);
+
+    // Updated rows are tokenized the same way
+    db.query("UPDATE notes SET body = '大阪城' WHERE id = 2")?;
+    assert!(ids(&mut db, "SELECT id FROM notes WHERE body @@ '京都'")?.is_empty());
+    assert_eq!(ids(&mut db, "SELECT id FROM notes WHERE body @@ '大阪'")?, vec![DataValue::Integer(2)]);
+
+    assert!(db.query("CREATE TABLE other(body TSVECTOR TOKENIZER 'missing')").is_err());
+    assert!(db.query("CREATE TABLE other(body TEXT TOKENIZER 'bigram')").is_err());
+    assert!(db.register_tokenizer("bigram", NgramTokenizer::with_size(3)).is_err());
+
+    Ok(())
+}
@@ -13,6 +13,9 @@ use crate::{
         statements::{create::CreateStatement, insert::InsertStatement},
         constraints::constraint::Constraint,
     },
+    indexes::index_manager::{IndexKind, IndexManager, IndexType as IndexManagerType},
+    key_format::encode_value,
+    storage::Storage,
 };
 
 #[test]
@@ -33,6 +36,7 @@ fn test_index_operations() -> Result<(), ReefDBError> {
         table_name: "users".to_string(),
         column_name: "age".to_string(),
         index_type: IndexType::BTree,
+        or_replace: false,
     };
     let result = db.execute_statement(Statement::CreateIndex(create_index_stmt))?;
     assert_eq!(result, ReefDBResult::CreateIndex);
@@ -52,4 +56,349 @@ fn test_index_operations() -> Result<(), ReefDBError> {
     db.execute_statement(Statement::Insert(InsertStatement::IntoTable("users".to_string(), values2)))?;
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_reindex_btree_column() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+    db.query("CREATE INDEX ON users(age)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice', 25)")?;
+    db.query("INSERT INTO users VALUES (2, 'Bob', 30)")?;
+    db.query("INSERT INTO users VALUES (3, 'Carol', 25)")?;
+
+    let result = db.query("REINDEX TABLE users COLUMN age")?;
+    assert_eq!(result, ReefDBResult::Reindex);
+
+    match db.storage.get_index("users", "age")? {
+        IndexManagerType::BTree(btree) => {
+            let key = encode_value(&DataValue::Integer(25))?;
+            let rows = btree.search(key).expect("value 25 should be indexed");
+            assert_eq!(rows.len(), 2);
+            assert!(rows.contains(&1) && rows.contains(&3));
+        }
+        _ => panic!("Expected BTree index"),
+    }
+
+    // Reindexing a column without an index is a no-op, unknown columns are errors
+    db.query("REINDEX TABLE users COLUMN name")?;
+    assert!(db.query("REINDEX TABLE users COLUMN missing").is_err());
+    assert!(db.query("REINDEX TABLE missing").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_btree_range_search_over_negative_integers() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE readings (id INTEGER PRIMARY KEY, delta INTEGER)")?;
+    db.query("CREATE INDEX ON readings(delta)")?;
+    for (id, delta) in [(1, -20), (2, -3), (3, 0), (4, 9), (5, 10), (6, 300)] {
+        db.query(&format!("INSERT INTO readings VALUES ({}, {})", id, delta))?;
+    }
+    db.query("REINDEX TABLE readings")?;
+
+    match db.storage.get_index("readings", "delta")? {
+        IndexManagerType::BTree(btree) => {
+            let start = encode_value(&DataValue::Integer(-5))?;
+            let end = encode_value(&DataValue::Integer(10))?;
+            let mut rows: Vec<usize> = btree.range_search(start, end).into_iter().collect();
+            rows.sort();
+            assert_eq!(rows, vec![2, 3, 4, 5]);
+        }
+        _ => panic!("Expected BTree index"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_hash_index() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'alice@example.com')")?;
+    db.query("INSERT INTO users VALUES (2, 'bob@example.com')")?;
+    db.query("CREATE INDEX ON users(email) USING HASH")?;
+    assert_eq!(db.list_indexes("users"), vec![("email".to_string(), IndexKind::Hash)]);
+
+    db.query("REINDEX TABLE users COLUMN email")?;
+    match db.storage.get_index("users", "email")? {
+        IndexManagerType::Hash(hash) => {
+            let key = encode_value(&DataValue::Text("bob@example.com".to_string()))?;
+            let rows = hash.search(key).expect("bob should be indexed");
+            assert_eq!(rows.iter().collect::<Vec<_>>(), vec![&2]);
+        }
+        _ => panic!("Expected Hash index"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_create_or_replace_index() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, bio TSVECTOR)")?;
+    db.query("INSERT INTO users VALUES (1, 'alice@example.com', 'likes rust')")?;
+    db.query("CREATE INDEX ON users(email)")?;
+
+    assert_eq!(
+        db.query("CREATE INDEX ON users(email) USING HASH"),
+        Err(ReefDBError::IndexAlreadyExists("users.email".to_string()))
+    );
+    let email_index = |db: &InMemoryReefDB| db.list_indexes("users").into_iter()
+        .find(|(column, _)| column == "email")
+        .map(|(_, kind)| kind);
+    assert_eq!(email_index(&db), Some(IndexKind::BTree));
+
+    db.query("INSERT INTO users VALUES (2, 'bob@example.com', 'likes go')")?;
+    db.query("CREATE OR REPLACE INDEX ON users(email) USING HASH")?;
+    db.query("CREATE OR REPLACE INDEX ON users(email) USING HASH")?;
+    assert_eq!(email_index(&db), Some(IndexKind::Hash));
+
+    // The replacement holds the rows already in the table
+    match db.storage.get_index("users", "email")? {
+        IndexManagerType::Hash(hash) => {
+            for (id, email) in [(1, "alice@example.com"), (2, "bob@example.com")] {
+                let key = encode_value(&DataValue::Text(email.to_string()))?;
+                assert_eq!(hash.search(key).map(|rows| rows.contains(&id)), Some(true));
+            }
+        }
+        _ => panic!("Expected Hash index"),
+    }
+
+    // A full-text column always has a GIN index, creating one rebuilds it
+    db.query("CREATE GIN INDEX ON users(bio)")?;
+    db.validate("CREATE OR REPLACE INDEX ON users(email)")?;
+    assert!(db.validate("CREATE INDEX ON users(email)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_verify_index() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, age INTEGER)")?;
+    db.query("CREATE INDEX ON users(age)")?;
+    db.query("INSERT INTO users VALUES (1, 25)")?;
+    db.query("INSERT INTO users VALUES (2, 30)")?;
+    db.query("REINDEX TABLE users COLUMN age")?;
+
+    match db.query("VERIFY INDEX users (age)")? {
+        ReefDBResult::Select(result) => assert_eq!(result.len(), 0),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Writes that bypass the index leave it out of sync
+    db.storage.delete_table("users", Some(("id".to_string(), DataValue::Integer(2))));
+    db.storage.push_value("users", vec![DataValue::Integer(3), DataValue::Integer(40)])?;
+    match db.query("VERIFY INDEX users(age)")? {
+        ReefDBResult::Select(result) => {
+            let mut issues: Vec<_> = result.rows.iter().map(|(_, row)| row.clone()).collect();
+            issues.sort_by(|a, b| a[0].sort_cmp(&b[0]).unwrap());
+            assert_eq!(issues, vec![
+                vec![DataValue::Text("missing_entry".to_string()), DataValue::Integer(2)],
+                vec![DataValue::Text("orphaned_row_id".to_string()), DataValue::Integer(2)],
+            ]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    let results = db.verify_indexes()?;
+    assert_eq!(results.len(), 1);
+    assert_eq!((results[0].0.as_str(), results[0].1.as_str()), ("users", "age"));
+    assert!(!results[0].2.is_consistent);
+
+    db.query("REINDEX TABLE users")?;
+    assert!(db.verify_index("users", "age")?.is_consistent);
+    assert!(db.query("VERIFY INDEX users (id)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_writes_maintain_indexes() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, age INTEGER)")?;
+    db.query("CREATE INDEX ON users(email) USING HASH")?;
+    db.query("CREATE INDEX ON users(age)")?;
+    for id in 1..=10 {
+        db.query(&format!("INSERT INTO users VALUES ({}, 'user{}@example.com', {})", id, id, 20 + id % 3))?;
+    }
+    db.query("UPDATE users SET email = 'renamed@example.com' WHERE id = 4")?;
+    db.query("UPDATE users SET age = 99 WHERE age = 21")?;
+    db.query("DELETE FROM users WHERE id = 2")?;
+    db.bulk_insert("users", vec![vec![DataValue::Integer(11), DataValue::Text("bulk@example.com".to_string()), DataValue::Integer(20)]])?;
+
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    tx.query("DELETE FROM users WHERE id = 1")?;
+    tx.rollback()?;
+
+    for column in ["email", "age"] {
+        let result = db.verify_index("users", column)?;
+        assert!(result.is_consistent, "{} index: {:?}", column, result.issues);
+    }
+
+    // `=` predicates read only the rows the index finds, preferring a hash index
+    let ids = |result: ReefDBResult| match result {
+        ReefDBResult::Select(rows) => rows.rows.iter().map(|(_, row)| row[0].clone()).collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+    let (result, stats) = db.query_with_stats("SELECT id FROM users WHERE age = 99 AND email = 'user7@example.com'")?;
+    assert_eq!(ids(result), vec![DataValue::Integer(7)]);
+    assert_eq!(stats.index_used.as_deref(), Some("users.email"));
+    assert_eq!(stats.rows_scanned, 1);
+
+    let (result, stats) = db.query_with_stats("SELECT id FROM users WHERE age = 99")?;
+    assert_eq!(ids(result), vec![DataValue::Integer(1), DataValue::Integer(4), DataValue::Integer(7), DataValue::Integer(10)]);
+    assert_eq!(stats.index_used.as_deref(), Some("users.age"));
+    assert_eq!(stats.rows_scanned, 4);
+
+    match db.query("EXPLAIN SELECT id FROM users WHERE email = 'renamed@example.com'")? {
+        ReefDBResult::Select(plan) => assert_eq!(plan[0][0], DataValue::Text("HASH Index Scan on users using users.email".to_string())),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_reindex_tsvector_column() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE articles (id INTEGER PRIMARY KEY, content TSVECTOR)")?;
+    db.query("INSERT INTO articles VALUES (1, 'Learn Rust programming')")?;
+    db.query("INSERT INTO articles VALUES (2, 'Database design principles')")?;
+
+    let search = "SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('rust')";
+
+    // Simulate an index that fell out of sync with the table data
+    db.inverted_index.clear_column("articles", "content");
+    if let ReefDBResult::Select(results) = db.query(search)? {
+        assert_eq!(results.len(), 0);
+    } else {
+        panic!("Expected Select result");
+    }
+
+    db.query("REINDEX TABLE articles")?;
+    if let ReefDBResult::Select(results) = db.query(search)? {
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0][0], DataValue::Integer(1));
+    } else {
+        panic!("Expected Select result");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_tsvector_column_gets_gin_index() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE articles (id INTEGER PRIMARY KEY, content TSVECTOR)")?;
+    assert_eq!(db.list_indexes("articles"), vec![("content".to_string(), IndexKind::GIN)]);
+
+    db.query("INSERT INTO articles VALUES (1, 'Learn Rust programming')")?;
+    db.query("INSERT INTO articles VALUES (2, 'Database design principles')")?;
+    db.query("INSERT INTO articles VALUES (3, 'Rust for databases')")?;
+    db.query("UPDATE articles SET content = 'Python programming' WHERE id = 1")?;
+    db.query("DELETE FROM articles WHERE id = 2")?;
+
+    // Row ids follow the rows as the inverted index's do
+    let gin_search = |db: &InMemoryReefDB, query: &str| match db.storage.get_index("articles", "content") {
+        Ok(IndexManagerType::GIN(gin)) => {
+            let mut ids: Vec<usize> = gin.search("articles", "content", query).into_iter().collect();
+            ids.sort();
+            ids
+        }
+        other => panic!("Expected a GIN index, got {:?}", other.map(|index| index.kind())),
+    };
+    assert_eq!(gin_search(&db, "rust"), vec![2]);
+    assert_eq!(gin_search(&db, "programming"), vec![1]);
+    assert!(gin_search(&db, "design").is_empty());
+    assert!(db.verify_index("articles", "content")?.is_consistent);
+
+    Ok(())
+}
+
+#[test]
+fn test_schema_introspection() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, age INTEGER)")?;
+    db.query("CREATE TABLE articles (id INTEGER PRIMARY KEY, content TSVECTOR)")?;
+    db.query("CREATE INDEX ON users(age)")?;
+    db.query("CREATE GIN INDEX ON articles(content)")?;
+
+    assert_eq!(db.list_tables(), vec!["articles".to_string(), "users".to_string()]);
+
+    let columns = db.describe_table("users")?;
+    let names: Vec<_> = columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["id", "name", "age"]);
+    assert_eq!(columns[1].data_type, DataType::Text);
+    assert!(matches!(db.describe_table("missing"), Err(ReefDBError::TableNotFound(_))));
+
+    assert_eq!(db.list_indexes("users"), vec![("age".to_string(), IndexKind::BTree)]);
+    let article_indexes: Vec<_> = db.list_indexes("articles").into_iter().map(|(name, _)| name).collect();
+    assert_eq!(article_indexes, vec!["content".to_string()]);
+    assert!(db.list_indexes("missing").is_empty());
+
+    match db.query("SHOW TABLES")? {
+        ReefDBResult::Select(result) => {
+            assert_eq!(result.columns[0].name, "table_name");
+            assert_eq!(result[0], vec![DataValue::Text("articles".to_string())]);
+            assert_eq!(result[1], vec![DataValue::Text("users".to_string())]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    match db.query("DESCRIBE users")? {
+        ReefDBResult::Select(result) => {
+            assert_eq!(result.len(), 3);
+            assert_eq!(result[0], vec![
+                DataValue::Text("id".to_string()),
+                DataValue::Text("INTEGER".to_string()),
+                DataValue::Boolean(true),
+                DataValue::Boolean(true),
+                DataValue::Null,
+            ]);
+            assert_eq!(result[1][2], DataValue::Boolean(false));
+            assert_eq!(result[2][4], DataValue::Text("BTREE".to_string()));
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    assert!(db.query("DESCRIBE missing").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_scan_table() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    for (id, name) in [(1, "Alice"), (2, "Bob"), (3, "Carol")] {
+        db.query(&format!("INSERT INTO users VALUES ({}, '{}')", id, name))?;
+    }
+
+    let rows: Vec<(usize, &Vec<DataValue>)> = db.scan_table("users")?.collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[1], (1, &vec![DataValue::Integer(2), DataValue::Text("Bob".to_string())]));
+    let names: Vec<&DataValue> = db.scan_table("users")?.map(|(_, row)| &row[1]).collect();
+    assert_eq!(names, vec![
+        &DataValue::Text("Alice".to_string()),
+        &DataValue::Text("Bob".to_string()),
+        &DataValue::Text("Carol".to_string()),
+    ]);
+
+    assert_eq!(db.get_row("users", 2)?, Some(&vec![DataValue::Integer(3), DataValue::Text("Carol".to_string())]));
+    assert_eq!(db.get_row("users", 3)?, None);
+    assert!(matches!(db.scan_table("missing"), Err(ReefDBError::TableNotFound(_))));
+    assert!(matches!(db.get_row("missing", 0), Err(ReefDBError::TableNotFound(_))));
+
+    db.query("CREATE TABLE empty (id INTEGER PRIMARY KEY)")?;
+    assert_eq!(db.scan_table("empty")?.count(), 0);
+    Ok(())
+}
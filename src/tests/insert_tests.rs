@@ -38,6 +38,7 @@ fn test_insert_statement() -> Result<(), ReefDBError> {
         None,
         vec![],
         vec![],
+        vec![],
     );
     let result = db.execute_statement(Statement::Select(select_stmt))?;
     if let ReefDBResult::Select(rows) = result {
@@ -58,7 +59,7 @@ fn test_insert_statement() -> Result<(), ReefDBError> {
         DataValue::Integer(30),
     ];
     let result = db.execute_statement(Statement::Insert(InsertStatement::IntoTable("users".to_string(), values)));
-    assert!(matches!(result, Err(ReefDBError::Other(_))));
+    assert!(matches!(result, Err(ReefDBError::ArityMismatch(_))));
 
     // Test 5: Insert with type mismatch (should fail)
     let values = vec![
@@ -68,7 +69,7 @@ fn test_insert_statement() -> Result<(), ReefDBError> {
         DataValue::Text("charlie@example.com".to_string()),
     ];
     let result = db.execute_statement(Statement::Insert(InsertStatement::IntoTable("users".to_string(), values)));
-    assert!(matches!(result, Err(ReefDBError::Other(_))));
+    assert!(matches!(result, Err(ReefDBError::TypeMismatch(_))));
 
     // Test 6: Insert into non-existent table (should fail)
     let values = vec![DataValue::Integer(1)];
@@ -104,6 +105,7 @@ fn test_insert_statement() -> Result<(), ReefDBError> {
         None,
         vec![],
         vec![],
+        vec![],
     );
     let result = db.execute_statement(Statement::Select(select_stmt))?;
     if let ReefDBResult::Select(rows) = result {
@@ -132,4 +134,153 @@ fn test_insert_statement() -> Result<(), ReefDBError> {
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_bulk_insert() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE readings (id INTEGER PRIMARY KEY, sensor TEXT, value FLOAT)")?;
+    db.query("CREATE TABLE looped (id INTEGER PRIMARY KEY, sensor TEXT, value FLOAT)")?;
+
+    let rows: Vec<Vec<DataValue>> = (1..=5_000)
+        .map(|i| vec![
+            DataValue::Integer(i),
+            DataValue::Text(format!("sensor-{}", i % 7)),
+            DataValue::Float(i as f64 / 10.0),
+        ])
+        .collect();
+    assert_eq!(db.bulk_insert("readings", rows.clone())?, 5_000);
+
+    // The same rows one INSERT at a time end up identical
+    for row in rows.iter().take(200) {
+        db.query(&format!("INSERT INTO looped VALUES ({}, '{}', {:?})", row[0], row[1], row[2]))?;
+    }
+    let count = |db: &mut InMemoryReefDB, sql: &str| match db.query(sql) {
+        Ok(ReefDBResult::Select(result)) => result.len(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+    assert_eq!(count(&mut db, "SELECT * FROM readings"), 5_000);
+    assert_eq!(count(&mut db, "SELECT * FROM looped"), 200);
+    assert_eq!(count(&mut db, "SELECT id FROM readings WHERE sensor = 'sensor-3'"), 714);
+
+    // A conflicting row rejects the whole batch
+    let batch = vec![
+        vec![DataValue::Integer(10_001), DataValue::Text("a".to_string()), DataValue::Float(1.0)],
+        vec![DataValue::Integer(10_001), DataValue::Text("b".to_string()), DataValue::Float(2.0)],
+    ];
+    assert!(db.bulk_insert("readings", batch).is_err());
+    let batch = vec![vec![DataValue::Integer(1), DataValue::Text("dup".to_string()), DataValue::Float(0.0)]];
+    assert!(db.bulk_insert("readings", batch).is_err());
+    assert!(db.bulk_insert("readings", vec![vec![DataValue::Integer(1)]]).is_err());
+    assert_eq!(count(&mut db, "SELECT * FROM readings"), 5_000);
+
+    // Full-text columns are indexed as rows arrive
+    db.query("CREATE TABLE docs (id INTEGER PRIMARY KEY, body TSVECTOR)")?;
+    db.bulk_insert("docs", vec![
+        vec![DataValue::Integer(1), DataValue::Text("rust databases".to_string())],
+        vec![DataValue::Integer(2), DataValue::Text("gardening tips".to_string())],
+    ])?;
+    assert_eq!(count(&mut db, "SELECT id FROM docs WHERE to_tsvector(body) @@ to_tsquery('rust')"), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_insert_on_conflict() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT UNIQUE)")?;
+
+    let names = |db: &mut InMemoryReefDB| match db.query("SELECT id, name FROM users ORDER BY id") {
+        Ok(ReefDBResult::Select(result)) => result.rows.iter()
+            .map(|(_, row)| (row[0].clone(), row[1].clone()))
+            .collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+
+    // No conflict: a plain insert
+    let result = db.query("INSERT INTO users VALUES (1, 'Al', 'al@x') ON CONFLICT (id) DO UPDATE SET name = 'Albert'")?;
+    assert_eq!(result, ReefDBResult::Insert(1));
+    assert_eq!(names(&mut db), vec![(DataValue::Integer(1), DataValue::Text("Al".to_string()))]);
+
+    // Conflict: the existing row is updated instead
+    let result = db.query("INSERT INTO users VALUES (1, 'Al', 'al@x') ON CONFLICT (id) DO UPDATE SET name = 'Albert'")?;
+    assert_eq!(result, ReefDBResult::Update(1));
+    assert_eq!(names(&mut db), vec![(DataValue::Integer(1), DataValue::Text("Albert".to_string()))]);
+
+    // DO NOTHING skips the row; without a target any unique column conflicts
+    assert_eq!(db.query("INSERT INTO users VALUES (2, 'Bo', 'al@x') ON CONFLICT DO NOTHING")?, ReefDBResult::Insert(0));
+    assert_eq!(db.query("INSERT INTO users VALUES (2, 'Bo', 'bo@x') ON CONFLICT DO NOTHING")?, ReefDBResult::Insert(2));
+    assert_eq!(names(&mut db).len(), 2);
+
+    // The target has to be a key column
+    assert!(db.query("INSERT INTO users VALUES (3, 'Cy', 'cy@x') ON CONFLICT (name) DO NOTHING").is_err());
+
+    // Inside a transaction the conflict check sees the transaction's own writes
+    db.query("BEGIN TRANSACTION")?;
+    db.query("INSERT INTO users VALUES (3, 'Cy', 'cy@x')")?;
+    db.query("INSERT INTO users VALUES (3, 'Cy', 'cy@x') ON CONFLICT (id) DO UPDATE SET name = 'Cyd'")?;
+    db.query("COMMIT")?;
+    assert_eq!(names(&mut db)[2], (DataValue::Integer(3), DataValue::Text("Cyd".to_string())));
+
+    // The transaction manager runs the resolved insert or update
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    let tm = db.transaction_manager.as_mut().unwrap();
+    let tx = tm.begin_transaction(crate::transaction::IsolationLevel::ReadCommitted)?;
+    let mut run = |sql: &str| tm.execute_statement(tx, Statement::parse(sql).unwrap().1);
+    run("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    assert_eq!(run("INSERT INTO users VALUES (1, 'Al') ON CONFLICT (id) DO NOTHING")?, ReefDBResult::Insert(1));
+    assert_eq!(run("INSERT INTO users VALUES (1, 'Al') ON CONFLICT (id) DO NOTHING")?, ReefDBResult::Insert(0));
+    assert_eq!(run("INSERT INTO users VALUES (1, 'Al') ON CONFLICT (id) DO UPDATE SET name = 'Albert'")?, ReefDBResult::Update(1));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_constraint() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE people (id INTEGER PRIMARY KEY, age INTEGER CHECK (age >= 0 AND age < 150))")?;
+
+    assert_eq!(db.query("INSERT INTO people VALUES (1, 30)")?, ReefDBResult::Insert(1));
+    assert!(matches!(db.query("INSERT INTO people VALUES (2, -1)"), Err(ReefDBError::CheckViolation(_))));
+    assert!(matches!(db.query("INSERT INTO people VALUES (2, 150)"), Err(ReefDBError::CheckViolation(_))));
+    // NULL is not a violation
+    assert_eq!(db.query("INSERT INTO people VALUES (2, NULL)")?, ReefDBResult::Insert(2));
+
+    // Updates are checked against the row as it would become
+    assert!(matches!(db.query("UPDATE people SET age = -5 WHERE id = 1"), Err(ReefDBError::CheckViolation(_))));
+    assert_eq!(db.query("UPDATE people SET age = 31 WHERE id = 1")?, ReefDBResult::Update(1));
+
+    assert!(matches!(
+        db.bulk_insert("people", vec![vec![DataValue::Integer(3), DataValue::Integer(3)], vec![DataValue::Integer(4), DataValue::Integer(-4)]]),
+        Err(ReefDBError::CheckViolation(_))
+    ));
+
+    match db.query("SELECT age FROM people WHERE id = 1")? {
+        ReefDBResult::Select(result) => assert_eq!(result.rows[0].1[0], DataValue::Integer(31)),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_insert_escaped_strings() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT, bio TSVECTOR)")?;
+    db.query("INSERT INTO people VALUES (1, 'O''Brien', 'it''s a long story')")?;
+    db.query("INSERT INTO people VALUES (2, 'first line\nsecond line', 'quiet')")?;
+    db.query("INSERT INTO people VALUES (3, E'tab\\there', 'c:\\temp')")?;
+
+    let name = |db: &mut InMemoryReefDB, sql: &str| match db.query(sql) {
+        Ok(ReefDBResult::Select(result)) => result.rows.into_iter().map(|(_, row)| row[0].clone()).collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+
+    assert_eq!(name(&mut db, "SELECT name FROM people WHERE id = 1"), vec![DataValue::Text("O'Brien".to_string())]);
+    assert_eq!(name(&mut db, "SELECT id FROM people WHERE name = 'O''Brien'"), vec![DataValue::Integer(1)]);
+    assert_eq!(name(&mut db, "SELECT name FROM people WHERE id = 2"), vec![DataValue::Text("first line\nsecond line".to_string())]);
+    assert_eq!(name(&mut db, "SELECT name FROM people WHERE id = 3"), vec![DataValue::Text("tab\there".to_string())]);
+    assert_eq!(name(&mut db, "SELECT id FROM people WHERE bio @@ 'story'"), vec![DataValue::Integer(1)]);
+
+    Ok(())
+}
@@ -94,6 +94,101 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_group_by_over_join() -> Result<()> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+
+        // Begin a transaction for setup
+        let setup_tx = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::Serializable)?;
+
+        for sql in [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, country TEXT)",
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, amount INTEGER)",
+            "INSERT INTO users VALUES (1, 'FR')",
+            "INSERT INTO users VALUES (2, 'US')",
+            "INSERT INTO users VALUES (3, 'FR')",
+            "INSERT INTO orders VALUES (1, 1, 10)",
+            "INSERT INTO orders VALUES (2, 2, 20)",
+            "INSERT INTO orders VALUES (3, 3, 30)",
+            "INSERT INTO orders VALUES (4, 1, 5)",
+        ] {
+            db.transaction_manager.as_mut().unwrap().execute_statement(setup_tx, Statement::parse(sql).unwrap().1)?;
+        }
+
+        // Commit setup
+        db.transaction_manager.as_mut().unwrap().commit_transaction(setup_tx)?;
+
+        // Begin a transaction for the grouped join query
+        let query_tx = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::Serializable)?;
+
+        let select_stmt = Statement::parse(
+            "SELECT users.country, COUNT(*), SUM(orders.amount) FROM users INNER JOIN orders ON users.id = orders.user_id GROUP BY users.country"
+        ).unwrap().1;
+
+        let result = db.transaction_manager.as_mut().unwrap().execute_statement(query_tx, select_stmt)?;
+
+        if let ReefDBResult::Select(results) = result {
+            assert_eq!(results.columns.len(), 3);
+            assert_eq!(results.columns[0].name, "country");
+
+            // Groups come out in the order they first appear
+            let rows: Vec<_> = results.rows.iter().map(|(_, row)| row.clone()).collect();
+            assert_eq!(rows, vec![
+                vec![DataValue::Text("FR".to_string()), DataValue::Integer(3), DataValue::Integer(45)],
+                vec![DataValue::Text("US".to_string()), DataValue::Integer(1), DataValue::Integer(20)],
+            ]);
+        } else {
+            panic!("Expected Select result");
+        }
+
+        // Plain columns outside GROUP BY have no single value per group
+        let stmt = Statement::parse(
+            "SELECT orders.amount, COUNT(*) FROM users INNER JOIN orders ON users.id = orders.user_id GROUP BY users.country"
+        ).unwrap().1;
+        assert!(db.execute_statement(stmt).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_with_aliases() -> Result<()> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        for sql in [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, country TEXT)",
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, amount INTEGER)",
+            "INSERT INTO users VALUES (1, 'FR')",
+            "INSERT INTO users VALUES (2, 'US')",
+            "INSERT INTO users VALUES (3, 'FR')",
+            "INSERT INTO orders VALUES (1, 1, 10)",
+            "INSERT INTO orders VALUES (2, 2, 20)",
+            "INSERT INTO orders VALUES (3, 3, 30)",
+            "INSERT INTO orders VALUES (4, 1, 5)",
+        ] {
+            db.query(sql)?;
+        }
+        let rows = |result: ReefDBResult| match result {
+            ReefDBResult::Select(results) => results.rows.into_iter().map(|(_, row)| row).collect::<Vec<_>>(),
+            other => panic!("Expected Select result, got {:?}", other),
+        };
+
+        // Aliases qualify grouped and aggregated columns like table names
+        let result = db.query(
+            "SELECT u.country, COUNT(*), SUM(o.amount) FROM users u JOIN orders o ON u.id = o.user_id GROUP BY u.country"
+        )?;
+        assert_eq!(rows(result), vec![
+            vec![DataValue::Text("FR".to_string()), DataValue::Integer(3), DataValue::Integer(45)],
+            vec![DataValue::Text("US".to_string()), DataValue::Integer(1), DataValue::Integer(20)],
+        ]);
+
+        let result = db.query("SELECT u.country, COUNT(*) FROM users AS u GROUP BY u.country")?;
+        assert_eq!(rows(result), vec![
+            vec![DataValue::Text("FR".to_string()), DataValue::Integer(2)],
+            vec![DataValue::Text("US".to_string()), DataValue::Integer(1)],
+        ]);
+
+        Ok(())
+    }
+
     #[test]
     fn test_inner_join_with_where_clause() -> Result<()> {
         let kv_path = "join_where_test_kv.db";
@@ -391,4 +486,277 @@ mod tests {
         cleanup_test_files(kv_path, index_path);
         Ok(())
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_typed_accessors_on_joined_result() -> Result<()> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT, active BOOLEAN)")?;
+        db.query("CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER, title TEXT, price FLOAT)")?;
+        db.query("INSERT INTO authors VALUES (1, 'Alice', true)")?;
+        db.query("INSERT INTO books VALUES (10, 1, NULL, 9.5)")?;
+
+        let results = match db.query("SELECT * FROM authors INNER JOIN books ON authors.id = books.author_id")? {
+            ReefDBResult::Select(results) => results,
+            other => panic!("Expected Select result, got {:?}", other),
+        };
+
+        // Qualified names pick the right table, unqualified names must be unique
+        assert_eq!(results.get_int(0, "authors.id")?, Some(1));
+        assert_eq!(results.get_int(0, "books.id")?, Some(10));
+        assert!(results.get_int(0, "id").is_err());
+        assert_eq!(results.get_text(0, "name")?, Some("Alice"));
+        assert_eq!(results.get_bool(0, "active")?, Some(true));
+        assert_eq!(results.get_float(0, "books.price")?, Some(9.5));
+
+        // NULL is None, mismatches and unknown columns are errors
+        assert_eq!(results.get_text(0, "title")?, None);
+        assert!(results.get_int(0, "name").is_err());
+        assert_eq!(results.get_int(0, "missing"), Err(ReefDBError::ColumnNotFound("missing".to_string())));
+        assert!(results.get_int(1, "authors.id").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_column_names_across_join() -> Result<()> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+        db.query("CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, name TEXT)")?;
+        db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+        db.query("INSERT INTO orders VALUES (100, 1, 'Keyboard')")?;
+        let join = "FROM users INNER JOIN orders ON users.id = orders.user_id";
+
+        // Every column records the table it came from
+        let results = match db.query(&format!("SELECT users.id, orders.id, user_id {}", join))? {
+            ReefDBResult::Select(results) => results,
+            other => panic!("Expected Select result, got {:?}", other),
+        };
+        let tables: Vec<(&str, Option<&str>)> = results.columns.iter()
+            .map(|column| (column.name.as_str(), column.table.as_deref()))
+            .collect();
+        assert_eq!(tables, vec![("id", Some("users")), ("id", Some("orders")), ("user_id", Some("orders"))]);
+        assert_eq!(results[0], vec![DataValue::Integer(1), DataValue::Integer(100), DataValue::Integer(1)]);
+        assert_eq!(results.get_int(0, "orders.id")?, Some(100));
+        assert_eq!(results.get_int(0, "id"), Err(ReefDBError::AmbiguousColumn("id".to_string())));
+
+        // A bare name both tables have is an error rather than the first match
+        let err = db.query(&format!("SELECT id {}", join)).unwrap_err();
+        assert_eq!(err, ReefDBError::AmbiguousColumn("id".to_string()));
+        assert_eq!(err.code(), 1006);
+        assert!(matches!(db.query(&format!("SELECT user_id, name {}", join)), Err(ReefDBError::AmbiguousColumn(_))));
+
+        // Likewise in the WHERE clause
+        let err = db.query(&format!("SELECT users.name {} WHERE name = 'Alice'", join)).unwrap_err();
+        assert_eq!(err, ReefDBError::AmbiguousColumn("name".to_string()));
+        let results = match db.query(&format!("SELECT users.name {} WHERE user_id = 1", join))? {
+            ReefDBResult::Select(results) => results,
+            other => panic!("Expected Select result, got {:?}", other),
+        };
+        assert_eq!(results.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cross_join_and_implicit_join() -> Result<()> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT)")?;
+        db.query("CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER, title TEXT)")?;
+        db.query("INSERT INTO authors VALUES (1, 'Alice')")?;
+        db.query("INSERT INTO authors VALUES (2, 'Bob')")?;
+        db.query("INSERT INTO books VALUES (10, 1, 'Rust')")?;
+        db.query("INSERT INTO books VALUES (11, 2, 'SQL')")?;
+        db.query("INSERT INTO books VALUES (12, 1, 'Nom')")?;
+
+        let select = |db: &mut InMemoryReefDB, sql: &str| -> Result<crate::result::QueryResult> {
+            match db.query(sql)? {
+                ReefDBResult::Select(results) => Ok(results),
+                other => panic!("Expected Select result, got {:?}", other),
+            }
+        };
+
+        // Every combination of rows
+        let product = select(&mut db, "SELECT * FROM authors CROSS JOIN books")?;
+        assert_eq!(product.len(), 6);
+        assert_eq!(product.columns.len(), 5);
+        let product = select(&mut db, "SELECT * FROM authors, books")?;
+        assert_eq!(product.len(), 6);
+
+        // WHERE on the product acts as an inner join
+        let implicit = select(&mut db, "SELECT authors.name, books.title FROM authors, books WHERE authors.id = books.author_id ORDER BY title")?;
+        let explicit = select(&mut db, "SELECT authors.name, books.title FROM authors INNER JOIN books ON authors.id = books.author_id ORDER BY title")?;
+        assert_eq!(implicit.rows, explicit.rows);
+        assert_eq!(implicit.len(), 3);
+        assert_eq!(implicit.get_text(0, "name")?, Some("Alice"));
+        assert_eq!(implicit.get_text(0, "title")?, Some("Nom"));
+
+        // Column comparisons combine with other conditions
+        let filtered = select(&mut db, "SELECT title FROM authors, books WHERE authors.id = books.author_id AND name = 'Bob'")?;
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0][0], DataValue::Text("SQL".to_string()));
+
+        // The transaction manager executes cross joins as well
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let tx = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        for sql in [
+            "CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT)",
+            "CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER, title TEXT)",
+            "INSERT INTO authors VALUES (1, 'Alice')",
+            "INSERT INTO authors VALUES (2, 'Bob')",
+            "INSERT INTO books VALUES (10, 1, 'Rust')",
+            "INSERT INTO books VALUES (11, 2, 'SQL')",
+            "INSERT INTO books VALUES (12, 1, 'Nom')",
+        ] {
+            tm.execute_statement(tx, Statement::parse(sql).unwrap().1)?;
+        }
+        for (sql, expected) in [
+            ("SELECT title FROM authors, books", 6),
+            ("SELECT title FROM authors, books WHERE authors.id = books.author_id", 3),
+        ] {
+            match tm.execute_statement(tx, Statement::parse(sql).unwrap().1)? {
+                ReefDBResult::Select(results) => assert_eq!(results.len(), expected, "{}", sql),
+                other => panic!("Expected Select result, got {:?}", other),
+            }
+        }
+        tm.commit_transaction(tx)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_limits() -> Result<()> {
+        use crate::transaction::QueryLimits;
+
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE a (id INTEGER PRIMARY KEY)")?;
+        db.query("CREATE TABLE b (id INTEGER PRIMARY KEY)")?;
+        for id in 1..=10 {
+            db.query(&format!("INSERT INTO a VALUES ({})", id))?;
+            db.query(&format!("INSERT INTO b VALUES ({})", id))?;
+        }
+
+        // Unlimited by default
+        assert_eq!(db.get_query_limits(), QueryLimits::default());
+        assert!(db.query("SELECT * FROM a CROSS JOIN b").is_ok());
+
+        db.set_query_limits(QueryLimits::default().with_max_join_rows(50));
+        assert!(matches!(db.query("SELECT * FROM a CROSS JOIN b"), Err(ReefDBError::ResourceLimitExceeded(_))));
+        assert!(db.query("SELECT * FROM a INNER JOIN b ON a.id = b.id").is_ok());
+
+        db.set_query_limits(QueryLimits::default().with_max_rows_scanned(5));
+        assert!(matches!(db.query("SELECT * FROM a"), Err(ReefDBError::ResourceLimitExceeded(_))));
+
+        db.set_query_limits(QueryLimits::default().with_max_result_rows(3));
+        assert!(matches!(db.query("SELECT * FROM a"), Err(ReefDBError::ResourceLimitExceeded(_))));
+        assert!(db.query("SELECT * FROM a WHERE id = 4").is_ok());
+
+        // Transactions run by the transaction manager are held to the same limits
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        let tm = db.transaction_manager.as_mut().unwrap();
+        tm.set_query_limits(QueryLimits::default().with_max_join_rows(50));
+        let tx = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        tm.execute_statement(tx, Statement::parse("CREATE TABLE a (id INTEGER PRIMARY KEY)").unwrap().1)?;
+        tm.execute_statement(tx, Statement::parse("CREATE TABLE b (id INTEGER PRIMARY KEY)").unwrap().1)?;
+        for id in 1..=10 {
+            tm.execute_statement(tx, Statement::parse(&format!("INSERT INTO a VALUES ({})", id)).unwrap().1)?;
+            tm.execute_statement(tx, Statement::parse(&format!("INSERT INTO b VALUES ({})", id)).unwrap().1)?;
+        }
+        let result = tm.execute_statement(tx, Statement::parse("SELECT * FROM a CROSS JOIN b").unwrap().1);
+        assert!(matches!(result, Err(ReefDBError::ResourceLimitExceeded(_))));
+        let result = tm.execute_statement(tx, Statement::parse("SELECT * FROM a, b WHERE a.id = b.id").unwrap().1);
+        assert!(matches!(result, Err(ReefDBError::ResourceLimitExceeded(_))));
+        tm.rollback_transaction(tx)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_timeout() -> Result<()> {
+        use std::time::Duration;
+        use crate::transaction::QueryLimits;
+
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE a (id INTEGER PRIMARY KEY)")?;
+        db.query("CREATE TABLE b (id INTEGER PRIMARY KEY)")?;
+        for id in 1..=400 {
+            db.query(&format!("INSERT INTO a VALUES ({})", id))?;
+            db.query(&format!("INSERT INTO b VALUES ({})", id))?;
+        }
+
+        let sql = "SELECT * FROM a CROSS JOIN b";
+        let result = db.query_with_timeout(sql, Duration::from_millis(1));
+        assert!(matches!(result, Err(ReefDBError::QueryTimeout(_))));
+        // The implicit transaction was rolled back and the database still works
+        match db.query("SELECT * FROM a WHERE id = 4")? {
+            ReefDBResult::Select(results) => assert_eq!(results.len(), 1),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+        assert!(db.query_with_timeout("SELECT * FROM a WHERE id = 4", Duration::from_secs(60)).is_ok());
+
+        // A timeout in the query limits applies to every query
+        db.set_query_limits(QueryLimits::default().with_timeout(Duration::from_millis(1)));
+        assert!(matches!(db.query(sql), Err(ReefDBError::QueryTimeout(_))));
+        assert!(db.query_with_timeout(sql, Duration::from_secs(60)).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_order_join_by_both_tables() -> Result<()> {
+        let setup = [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)",
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, amount INTEGER)",
+            "INSERT INTO users VALUES (1, 'Carol')",
+            "INSERT INTO users VALUES (2, 'Alice')",
+            "INSERT INTO users VALUES (3, 'Bob')",
+            "INSERT INTO orders VALUES (1, 1, 20)",
+            "INSERT INTO orders VALUES (2, 2, 10)",
+            "INSERT INTO orders VALUES (3, 3, 20)",
+            "INSERT INTO orders VALUES (4, 2, 20)",
+            "INSERT INTO orders VALUES (5, 2, 20)",
+        ];
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        for sql in setup {
+            db.query(sql)?;
+        }
+        let expected = [("Alice", 4), ("Alice", 5), ("Bob", 3), ("Carol", 1), ("Alice", 2)];
+
+        let rows = |result: ReefDBResult| match result {
+            ReefDBResult::Select(results) => results.rows.into_iter()
+                .map(|(_, row)| match (&row[0], &row[1]) {
+                    (DataValue::Text(name), DataValue::Integer(id)) => (name.clone(), *id),
+                    other => panic!("Unexpected row {:?}", other),
+                })
+                .collect::<Vec<_>>(),
+            other => panic!("Expected Select result, got {:?}", other),
+        };
+        let expected: Vec<(String, i64)> = expected.iter().map(|(name, id)| (name.to_string(), *id)).collect();
+
+        // By the joined table's column, then the base table's; Alice's two
+        // orders of 20 tie and stay in stored order
+        let sql = "SELECT users.name, orders.id FROM users INNER JOIN orders ON users.id = orders.user_id ORDER BY orders.amount DESC, users.name";
+        assert_eq!(rows(db.query(sql)?), expected);
+
+        // The sort columns don't have to be selected
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => {
+                assert_eq!(results.columns.len(), 2);
+                assert!(results.rows.iter().all(|(_, row)| row.len() == 2));
+            }
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+
+        // The transaction manager sorts joined rows the same way
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let tx = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        for sql in setup {
+            tm.execute_statement(tx, Statement::parse(sql).unwrap().1)?;
+        }
+        let result = tm.execute_statement(tx, Statement::parse(sql).unwrap().1)?;
+        tm.commit_transaction(tx)?;
+        assert_eq!(rows(result), expected);
+
+        Ok(())
+    }
+}
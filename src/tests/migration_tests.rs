@@ -0,0 +1,62 @@
+use crate::{error::ReefDBError, result::ReefDBResult, InMemoryReefDB};
+use crate::sql::data_value::DataValue;
+
+fn row_count(db: &mut InMemoryReefDB, sql: &str) -> usize {
+    match db.query(sql) {
+        Ok(ReefDBResult::Select(rows)) => rows.len(),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_apply_migrations() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    assert_eq!(db.current_schema_version(), None);
+
+    assert!(db.apply_migration(1, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?);
+    assert!(db.apply_migration(2, "
+        ALTER TABLE users ADD COLUMN email TEXT;
+        -- seed data
+        INSERT INTO users VALUES (1, 'Alice', 'alice@example.com');
+        INSERT INTO users VALUES (2, 'Bob', 'bob@example.com');
+    ")?);
+    assert_eq!(db.current_schema_version(), Some(2));
+    assert_eq!(db.applied_migrations(), vec![1, 2]);
+    assert_eq!(row_count(&mut db, "SELECT * FROM users"), 2);
+
+    // Applying them again changes nothing
+    assert!(!db.apply_migration(1, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?);
+    assert!(!db.apply_migration(2, "INSERT INTO users VALUES (3, 'Carol', 'carol@example.com')")?);
+    assert_eq!(db.current_schema_version(), Some(2));
+    assert_eq!(row_count(&mut db, "SELECT * FROM users"), 2);
+    assert_eq!(row_count(&mut db, "SELECT * FROM \"_reefdb_migrations\""), 2);
+    Ok(())
+}
+
+#[test]
+fn test_failed_migration_applies_nothing() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.apply_migration(1, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+
+    // The second statement fails, so the first is rolled back too
+    let result = db.apply_migration(2, "
+        INSERT INTO users VALUES (1, 'Alice');
+        INSERT INTO users VALUES (1, 'Alice again');
+    ");
+    assert!(result.is_err());
+    assert_eq!(db.current_schema_version(), Some(1));
+    assert_eq!(row_count(&mut db, "SELECT * FROM users"), 0);
+
+    // A script that doesn't parse doesn't start
+    assert!(matches!(db.apply_migration(2, "INSERT INTO users VALUES (1, 'Alice') INSERT"), Err(ReefDBError::SyntaxError { .. })));
+    assert_eq!(db.current_schema_version(), Some(1));
+
+    // Nor is the migrations table left behind by a failed first migration
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    assert!(db.apply_migration(1, "CREATE TABLE t (id INTEGER PRIMARY KEY); INSERT INTO missing VALUES (1)").is_err());
+    assert!(db.list_tables().is_empty());
+
+    db.apply_migration(1, "CREATE TABLE t (id INTEGER PRIMARY KEY); INSERT INTO t VALUES (7)")?;
+    assert_eq!(db.get_row("t", 0)?, Some(&vec![DataValue::Integer(7)]));
+    Ok(())
+}
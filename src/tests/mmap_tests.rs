@@ -259,4 +259,27 @@ fn test_mmap_concurrent_access() {
     let mut storage = Arc::try_unwrap(storage).unwrap().into_inner().unwrap();
     let (_, rows) = storage.get_table("counters").unwrap();
     assert_eq!(rows.len(), 10);
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_mmap_flush_persists() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let file_path = temp_file.path().to_string_lossy().to_string();
+
+    let mut storage = MmapStorage::new(file_path.clone());
+    let columns = vec![
+        ColumnDef::new("id", DataType::Integer, vec![Constraint::PrimaryKey]),
+        ColumnDef::new("name", DataType::Text, vec![]),
+    ];
+    storage.insert_table("users".to_string(), columns, vec![]);
+    storage.push_value("users", vec![DataValue::Integer(1), DataValue::Text("John".to_string())]).unwrap();
+    storage.push_value("users", vec![DataValue::Integer(2), DataValue::Text("Jane".to_string())]).unwrap();
+    storage.flush().unwrap();
+
+    // Reopen while the first instance is still alive, so nothing relies on its drop
+    let mut reopened = MmapStorage::new(file_path);
+    let (_, rows) = reopened.get_table("users").unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1][1], DataValue::Text("Jane".to_string()));
+    drop(storage);
+}
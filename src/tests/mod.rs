@@ -14,6 +14,13 @@ pub mod join_integration_tests;
 pub mod fts_tests;
 pub mod mmap_tests;
 pub mod data_types;
+pub mod backup_tests;
+pub mod builder_tests;
+pub mod csv_tests;
+pub mod server_tests;
+pub mod replication_tests;
+pub mod validation_tests;
+pub mod migration_tests;
 use crate::sql::{
     column_def::ColumnDef,
     data_type::DataType,
@@ -106,6 +113,7 @@ mod tests {
             None,
             vec![],
             vec![],
+            vec![],
         ));
         let result = db.execute_statement(select_stmt)?;
         
@@ -168,6 +176,7 @@ mod tests {
             None,
             vec![],
             vec![],
+            vec![],
         ));
         let result = db.execute_statement(select_stmt)?;
         
@@ -235,6 +244,7 @@ mod tests {
             None,
             vec![],
             vec![],
+            vec![],
         ));
         
         // Verify data is not visible before commit
@@ -261,4 +271,4 @@ mod tests {
         
         Ok(())
     }
-} 
\ No newline at end of file
+} 
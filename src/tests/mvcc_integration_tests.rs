@@ -5,10 +5,12 @@ mod tests {
             statements::Statement,
             data_value::DataValue,
         },
+        builder::ReefDBBuilder,
         InMemoryReefDB,
         transaction::IsolationLevel,
         result::ReefDBResult,
     };
+    use std::time::{Duration, Instant};
 
     #[test]
     fn test_mvcc_concurrent_transactions() -> Result<(), crate::error::ReefDBError> {
@@ -141,4 +143,344 @@ mod tests {
         
         Ok(())
     }
-} 
\ No newline at end of file
+
+    fn read_balance(db: &mut InMemoryReefDB, tx_id: u64) -> Result<DataValue, crate::error::ReefDBError> {
+        let select_stmt = Statement::parse("SELECT balance FROM accounts WHERE id = 1").unwrap().1;
+        match db.transaction_manager.as_mut().unwrap().execute_statement(tx_id, select_stmt)? {
+            ReefDBResult::Select(rows) => Ok(rows[0][0].clone()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    }
+
+    fn setup_accounts(db: &mut InMemoryReefDB) -> Result<(), crate::error::ReefDBError> {
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let setup_tx = tm.begin_transaction(IsolationLevel::Serializable)?;
+        tm.execute_statement(setup_tx, Statement::parse("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)").unwrap().1)?;
+        tm.execute_statement(setup_tx, Statement::parse("INSERT INTO accounts VALUES (1, 100)").unwrap().1)?;
+        tm.commit_transaction(setup_tx)
+    }
+
+    #[test]
+    fn test_repeatable_read_prevents_non_repeatable_reads() -> Result<(), crate::error::ReefDBError> {
+        for (isolation_level, expected_second_read) in [
+            (IsolationLevel::ReadCommitted, DataValue::Integer(200)),
+            (IsolationLevel::RepeatableRead, DataValue::Integer(100)),
+        ] {
+            let mut db = InMemoryReefDB::create_in_memory()?;
+            setup_accounts(&mut db)?;
+
+            let reader = db.transaction_manager.as_mut().unwrap().begin_transaction(isolation_level)?;
+            let writer = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+
+            assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(100));
+
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            db.transaction_manager.as_mut().unwrap().execute_statement(writer,
+                Statement::parse("UPDATE accounts SET balance = 200 WHERE id = 1").unwrap().1)?;
+            db.transaction_manager.as_mut().unwrap().commit_transaction(writer)?;
+
+            // ReadCommitted sees the new value, RepeatableRead keeps its snapshot
+            assert_eq!(read_balance(&mut db, reader)?, expected_second_read, "{:?}", isolation_level);
+            db.transaction_manager.as_mut().unwrap().commit_transaction(reader)?;
+        }
+
+        Ok(())
+    }
+
+    fn update_balance(db: &mut InMemoryReefDB, balance: i64) -> Result<(), crate::error::ReefDBError> {
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let tx_id = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        let sql = format!("UPDATE accounts SET balance = {} WHERE id = 1", balance);
+        tm.execute_statement(tx_id, Statement::parse(&sql).unwrap().1)?;
+        tm.commit_transaction(tx_id)
+    }
+
+    #[test]
+    fn test_gc_reclaims_versions_after_commits() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        setup_accounts(&mut db)?;
+        db.transaction_manager.as_mut().unwrap().set_gc_interval(1000);
+
+        for balance in 1..=10 {
+            update_balance(&mut db, balance)?;
+        }
+        let tm = db.transaction_manager.as_mut().unwrap();
+        assert_eq!(tm.version_count()?, 10);
+
+        // Only the latest committed version is still visible to anyone
+        assert_eq!(tm.collect_garbage()?, 9);
+        assert_eq!(tm.version_count()?, 1);
+
+        // Collection also runs on its own every `gc_interval` commits
+        tm.set_gc_interval(5);
+        for balance in 11..=15 {
+            update_balance(&mut db, balance)?;
+        }
+        assert_eq!(db.transaction_manager.as_mut().unwrap().version_count()?, 1);
+
+        let reader = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+        assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(15));
+        db.transaction_manager.as_mut().unwrap().commit_transaction(reader)
+    }
+
+    #[test]
+    fn test_autovacuum_reclaims_versions_in_background() -> Result<(), crate::error::ReefDBError> {
+        let mut db = ReefDBBuilder::in_memory()
+            .with_autovacuum(Duration::from_millis(10))
+            .build()?;
+        setup_accounts(&mut db)?;
+        db.transaction_manager.as_mut().unwrap().set_gc_interval(1000);
+
+        for balance in 1..=10 {
+            update_balance(&mut db, balance)?;
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while db.transaction_manager.as_ref().unwrap().version_count()? > 1 {
+            assert!(Instant::now() < deadline, "autovacuum never collected the old versions");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let reader = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+        assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(10));
+        db.transaction_manager.as_mut().unwrap().commit_transaction(reader)
+    }
+
+    #[test]
+    fn test_serializable_write_write_conflict() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        setup_accounts(&mut db)?;
+
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let first = tm.begin_transaction(IsolationLevel::Serializable)?;
+        let second = tm.begin_transaction(IsolationLevel::Serializable)?;
+        tm.execute_statement(first, Statement::parse("UPDATE accounts SET balance = 200 WHERE id = 1").unwrap().1)?;
+        tm.execute_statement(second, Statement::parse("UPDATE accounts SET balance = 300 WHERE id = 1").unwrap().1)?;
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        tm.commit_transaction(first)?;
+
+        // The second writer started before the first committed, so it must retry
+        let err = tm.commit_transaction(second).unwrap_err();
+        assert!(matches!(err, crate::error::ReefDBError::SerializationFailure(_)), "{:?}", err);
+        assert!(err.is_retryable());
+        assert!(tm.commit_transaction(second).is_err());
+
+        let retry = tm.begin_transaction(IsolationLevel::Serializable)?;
+        tm.execute_statement(retry, Statement::parse("UPDATE accounts SET balance = 300 WHERE id = 1").unwrap().1)?;
+        tm.commit_transaction(retry)?;
+
+        let reader = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(300));
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_for_update_blocks_writers() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        setup_accounts(&mut db)?;
+
+        let select_for_update = Statement::parse("SELECT balance FROM accounts WHERE id = 1 FOR UPDATE").unwrap().1;
+        assert!(matches!(select_for_update, Statement::SelectForUpdate(_)));
+        let update = |balance: i64| Statement::parse(&format!("UPDATE accounts SET balance = {} WHERE id = 1", balance)).unwrap().1;
+
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let locker = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        let writer = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        let reader = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+
+        match tm.execute_statement(locker, select_for_update)? {
+            ReefDBResult::Select(rows) => assert_eq!(rows[0][0], DataValue::Integer(100)),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+
+        // Writers are locked out, plain readers are not
+        let err = tm.execute_statement(writer, update(500)).unwrap_err();
+        assert!(matches!(err, crate::error::ReefDBError::LockConflict(_)), "{:?}", err);
+        assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(100));
+
+        let tm = db.transaction_manager.as_mut().unwrap();
+        tm.execute_statement(locker, update(150))?;
+        tm.commit_transaction(locker)?;
+
+        // Once the lock is released the writer gets through
+        tm.execute_statement(writer, update(500))?;
+        tm.commit_transaction(writer)?;
+        assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_only_transaction_rejects_writes() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        setup_accounts(&mut db)?;
+
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let report = tm.begin_read_only_transaction(IsolationLevel::RepeatableRead)?;
+        let versions = tm.version_count()?;
+        for sql in [
+            "INSERT INTO accounts VALUES (2, 50)",
+            "UPDATE accounts SET balance = 0 WHERE id = 1",
+            "DELETE FROM accounts WHERE id = 1",
+            "CREATE TABLE audit (id INTEGER PRIMARY KEY)",
+            "DROP TABLE accounts",
+            "SELECT balance FROM accounts WHERE id = 1 FOR UPDATE",
+        ] {
+            let result = tm.execute_statement(report, Statement::parse(sql).unwrap().1);
+            assert_eq!(result.unwrap_err(), crate::error::ReefDBError::ReadOnlyTransaction, "{}", sql);
+        }
+        assert_eq!(tm.version_count()?, versions);
+        assert_eq!(read_balance(&mut db, report)?, DataValue::Integer(100));
+
+        // It holds no locks, so writers are not held up by it
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let writer = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+        tm.execute_statement(writer, Statement::parse("UPDATE accounts SET balance = 200 WHERE id = 1").unwrap().1)?;
+        tm.commit_transaction(writer)?;
+        db.transaction_manager.as_mut().unwrap().commit_transaction(report)?;
+
+        let reader = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+        assert_eq!(read_balance(&mut db, reader)?, DataValue::Integer(200));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_uncommitted_sees_dirty_writes() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        setup_accounts(&mut db)?;
+
+        let writer = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+        let dirty_reader = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadUncommitted)?;
+        let committed_reader = db.transaction_manager.as_mut().unwrap().begin_transaction(IsolationLevel::ReadCommitted)?;
+
+        db.transaction_manager.as_mut().unwrap().execute_statement(writer,
+            Statement::parse("UPDATE accounts SET balance = 300 WHERE id = 1").unwrap().1)?;
+
+        assert_eq!(read_balance(&mut db, dirty_reader)?, DataValue::Integer(300));
+        assert_eq!(read_balance(&mut db, committed_reader)?, DataValue::Integer(100));
+
+        // Once rolled back, the dirty version disappears
+        db.transaction_manager.as_mut().unwrap().rollback_transaction(writer)?;
+        assert_eq!(read_balance(&mut db, dirty_reader)?, DataValue::Integer(100));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_handle() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")?;
+
+        let row_count = |db: &mut InMemoryReefDB| -> Result<usize, crate::error::ReefDBError> {
+            match db.query("SELECT id FROM accounts")? {
+                ReefDBResult::Select(rows) => Ok(rows.len()),
+                other => panic!("Expected Select result, got {:?}", other),
+            }
+        };
+
+        // Committed changes are kept
+        let mut tx = db.begin(IsolationLevel::ReadCommitted)?;
+        tx.query("INSERT INTO accounts VALUES (1, 100)")?;
+        tx.commit()?;
+        assert_eq!(row_count(&mut db)?, 1);
+
+        // An explicit rollback discards changes
+        let mut tx = db.begin(IsolationLevel::ReadCommitted)?;
+        tx.query("INSERT INTO accounts VALUES (2, 200)")?;
+        tx.rollback()?;
+        assert_eq!(row_count(&mut db)?, 1);
+
+        // Dropping the handle without committing rolls back
+        {
+            let mut tx = db.begin(IsolationLevel::Serializable)?;
+            tx.query("INSERT INTO accounts VALUES (3, 300)")?;
+            assert!(tx.query("COMMIT").is_err());
+        }
+        assert_eq!(row_count(&mut db)?, 1);
+        assert!(db.current_transaction_id.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transaction_closure() -> Result<(), crate::error::ReefDBError> {
+        use crate::error::ReefDBError;
+
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")?;
+
+        let row_count = |db: &mut InMemoryReefDB| -> Result<usize, ReefDBError> {
+            match db.query("SELECT id FROM accounts")? {
+                ReefDBResult::Select(rows) => Ok(rows.len()),
+                other => panic!("Expected Select result, got {:?}", other),
+            }
+        };
+
+        // Ok commits every statement of the closure
+        let results = db.transaction(|tx| {
+            tx.query("INSERT INTO accounts VALUES (1, 100)")?;
+            tx.query_script("INSERT INTO accounts VALUES (2, 200); UPDATE accounts SET balance = 150 WHERE id = 1")
+        })?;
+        assert_eq!(results.len(), 2);
+        assert_eq!(row_count(&mut db)?, 2);
+
+        // An error rolls all of them back and is returned as is
+        let result: Result<(), ReefDBError> = db.transaction(|tx| {
+            tx.query("INSERT INTO accounts VALUES (3, 300)")?;
+            tx.query("DELETE FROM accounts WHERE id = 1")?;
+            Err(ReefDBError::Other("insufficient funds".to_string()))
+        });
+        assert_eq!(result, Err(ReefDBError::Other("insufficient funds".to_string())));
+        assert_eq!(row_count(&mut db)?, 2);
+        assert!(db.current_transaction_id.is_none());
+
+        // A statement failing halfway through a script does too
+        let result = db.transaction(|tx| tx.query_script("INSERT INTO accounts VALUES (4, 400); INSERT INTO missing VALUES (1)"));
+        assert!(matches!(result, Err(ReefDBError::TableNotFound(_))));
+        assert_eq!(row_count(&mut db)?, 2);
+
+        // So does a panic
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = db.transaction(|tx| -> Result<(), ReefDBError> {
+                tx.query("INSERT INTO accounts VALUES (5, 500)")?;
+                panic!("closure panicked");
+            });
+        }));
+        assert!(panicked.is_err());
+        assert_eq!(row_count(&mut db)?, 2);
+        assert!(db.current_transaction_id.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback_statement() -> Result<(), crate::error::ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER)")?;
+        db.query("INSERT INTO accounts VALUES (1, 100)")?;
+
+        assert!(matches!(
+            db.query("ROLLBACK"),
+            Err(crate::error::ReefDBError::TransactionNotActive)
+        ));
+
+        db.query("BEGIN TRANSACTION")?;
+        db.query("INSERT INTO accounts VALUES (2, 200)")?;
+        db.query("UPDATE accounts SET balance = 0 WHERE id = 1")?;
+        assert!(matches!(db.query("ROLLBACK")?, ReefDBResult::Rollback));
+        assert!(db.current_transaction_id.is_none());
+
+        match db.query("SELECT id, balance FROM accounts")? {
+            ReefDBResult::Select(rows) => {
+                assert_eq!(rows.len(), 1);
+                assert_eq!(rows.rows[0].1, vec![DataValue::Integer(1), DataValue::Integer(100)]);
+            }
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+
+        // ROLLBACK TO SAVEPOINT still parses as a savepoint rollback
+        let (_, stmt) = Statement::parse("ROLLBACK TO SAVEPOINT sp1").unwrap();
+        assert_eq!(stmt, Statement::RollbackToSavepoint("sp1".to_string()));
+
+        Ok(())
+    }
+}
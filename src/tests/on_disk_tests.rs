@@ -35,7 +35,7 @@ mod tests {
         
         // Insert table
         storage.insert_table("test_table".to_string(), columns, rows);
-        storage.save();
+        storage.save().unwrap();
         
         // Create GIN index
         let mut gin = GinIndex::<DefaultTokenizer>::new();
@@ -67,4 +67,326 @@ mod tests {
         assert!(result.is_consistent, "Loaded index should be consistent");
         assert!(result.issues.is_empty(), "Loaded index should have no issues");
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_vacuum_shrinks_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("vacuum.db").to_str().unwrap().to_string();
+        let file_size = || std::fs::metadata(&db_path).unwrap().len();
+
+        let mut storage = OnDiskStorage::new(db_path.clone());
+        let columns = vec![
+            ColumnDef::new("id", DataType::Integer, vec![]),
+            ColumnDef::new("name", DataType::Text, vec![]),
+        ];
+        let rows = (0..100).map(|i| vec![DataValue::Integer(i % 2), DataValue::Text(format!("user{}", i))]).collect();
+        storage.insert_table("users".to_string(), columns, rows);
+        let full_size = file_size();
+
+        assert_eq!(storage.delete_table("users", Some(("id".to_string(), DataValue::Integer(1)))), 50);
+        assert!(file_size() >= full_size, "deleted rows stay in the file until a vacuum");
+
+        storage.vacuum().unwrap();
+        assert!(file_size() < full_size);
+        drop(storage);
+
+        let storage = OnDiskStorage::new(db_path.clone());
+        let (_, rows) = storage.get_table_ref("users").unwrap();
+        assert_eq!(rows.len(), 50);
+        assert!(rows.iter().enumerate().all(|(i, row)| row[1] == DataValue::Text(format!("user{}", i * 2))));
+    }
+}
+
+#[cfg(test)]
+mod recovery_tests {
+    use crate::{OnDiskReefDB, builder::ReefDBBuilder, result::ReefDBResult, sql::data_value::DataValue, transaction::IsolationLevel, wal::CheckpointPolicy};
+    use std::time::{Duration, Instant};
+    use tempfile::tempdir;
+
+    fn select_ids(db: &mut OnDiskReefDB) -> Vec<DataValue> {
+        match db.query("SELECT id FROM users").unwrap() {
+            ReefDBResult::Select(rows) => rows.rows.iter().map(|(_, row)| row[0].clone()).collect(),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_vacuum_statement() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("vacuum.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("vacuum.idx").to_str().unwrap().to_string();
+
+        let mut db = OnDiskReefDB::create_on_disk(kv_path, index_path).unwrap();
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+        for id in 1..=20 {
+            db.query(&format!("INSERT INTO users VALUES ({}, 'user{}')", id, id)).unwrap();
+        }
+        db.query("CREATE INDEX ON users(id)").unwrap();
+        for id in (2..=20).step_by(2) {
+            db.query(&format!("DELETE FROM users WHERE id = {}", id)).unwrap();
+        }
+
+        assert_eq!(db.query("VACUUM").unwrap(), ReefDBResult::Vacuum);
+        let expected: Vec<DataValue> = (1..=20).step_by(2).map(DataValue::Integer).collect();
+        assert_eq!(select_ids(&mut db), expected);
+        assert!(db.verify_index("users", "id").unwrap().is_consistent);
+
+        // Rewriting indexes can't be undone by a rollback
+        let mut tx = db.begin(IsolationLevel::ReadCommitted).unwrap();
+        assert!(tx.query("VACUUM").is_err());
+        tx.rollback().unwrap();
+    }
+
+    #[test]
+    fn test_wal_recovery_after_crash() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("recovery.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("recovery.idx").to_str().unwrap().to_string();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            db.query("INSERT INTO users VALUES (1, 'alice')").unwrap();
+            db.query("INSERT INTO users VALUES (2, 'bob')").unwrap();
+
+            // Left open when the process "crashes"
+            db.query("BEGIN TRANSACTION").unwrap();
+            db.query("INSERT INTO users VALUES (3, 'mallory')").unwrap();
+        }
+
+        // Lose the storage file entirely: only the WAL survives
+        std::fs::remove_file(&kv_path).unwrap();
+
+        let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+        assert_eq!(select_ids(&mut db), vec![DataValue::Integer(1), DataValue::Integer(2)]);
+        drop(db);
+
+        // Recovery is repeatable and discards the flushed but uncommitted row
+        let mut db = OnDiskReefDB::create_on_disk(kv_path, index_path).unwrap();
+        assert_eq!(select_ids(&mut db), vec![DataValue::Integer(1), DataValue::Integer(2)]);
+    }
+
+    #[test]
+    fn test_wal_recovery_undoes_writes_to_older_tables() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("undo.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("undo.idx").to_str().unwrap().to_string();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            db.query("CREATE TABLE archive (id INTEGER PRIMARY KEY)").unwrap();
+            for id in 1..=3 {
+                db.query(&format!("INSERT INTO users VALUES ({}, 'user{}')", id, id)).unwrap();
+            }
+            // The tables now only live in the data file
+            db.transaction_manager.as_mut().unwrap().checkpoint().unwrap();
+
+            // Left open when the process "crashes", with every write already in storage
+            db.query("BEGIN TRANSACTION").unwrap();
+            db.query("INSERT INTO users VALUES (4, 'mallory')").unwrap();
+            db.query("UPDATE users SET name = 'changed' WHERE id = 1").unwrap();
+            db.query("DELETE FROM users WHERE id = 2").unwrap();
+            db.query("ALTER TABLE archive ADD COLUMN note TEXT").unwrap();
+            db.query("DROP TABLE archive").unwrap();
+            db.query("CREATE TABLE scratch (id INTEGER)").unwrap();
+        }
+
+        for _ in 0..2 {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            let ids: Vec<DataValue> = (1..=3).map(DataValue::Integer).collect();
+            assert_eq!(select_ids(&mut db), ids);
+            match db.query("SELECT name FROM users WHERE id = 1").unwrap() {
+                ReefDBResult::Select(result) => assert_eq!(result.rows[0].1, vec![DataValue::Text("user1".to_string())]),
+                other => panic!("Expected Select result, got {:?}", other),
+            }
+            assert_eq!(db.get_table_schema("archive").unwrap().0.len(), 1);
+            assert!(db.query("SELECT id FROM scratch").is_err());
+        }
+    }
+
+    #[test]
+    fn test_wal_recovery_keeps_rolled_back_state() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("rolled_back.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("rolled_back.idx").to_str().unwrap().to_string();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            db.query("INSERT INTO users VALUES (1, 'alice')").unwrap();
+            db.transaction_manager.as_mut().unwrap().checkpoint().unwrap();
+
+            db.query("BEGIN TRANSACTION").unwrap();
+            db.query("INSERT INTO users VALUES (2, 'bob')").unwrap();
+            db.query("ROLLBACK").unwrap();
+        }
+
+        // The rollback already took the insert back, so nothing else goes
+        let mut db = OnDiskReefDB::create_on_disk(kv_path, index_path).unwrap();
+        assert_eq!(select_ids(&mut db), vec![DataValue::Integer(1)]);
+    }
+
+    #[test]
+    fn test_wal_recovery_after_rename() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("rename.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("rename.idx").to_str().unwrap().to_string();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            db.query("INSERT INTO people VALUES (1, 'alice')").unwrap();
+            db.query("ALTER TABLE people RENAME TO users").unwrap();
+            db.query("INSERT INTO users VALUES (2, 'bob')").unwrap();
+        }
+
+        // Rebuilt from the WAL, writes after the rename land in the new table
+        let _ = std::fs::remove_file(&kv_path);
+        let mut db = OnDiskReefDB::create_on_disk(kv_path, index_path).unwrap();
+        assert_eq!(select_ids(&mut db), vec![DataValue::Integer(1), DataValue::Integer(2)]);
+        assert!(db.query("SELECT id FROM people").is_err());
+    }
+
+    #[test]
+    fn test_wal_recovery_after_limited_writes() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("limit.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("limit.idx").to_str().unwrap().to_string();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            for id in 1..=5 {
+                db.query(&format!("INSERT INTO users VALUES ({}, 'user')", id)).unwrap();
+            }
+            db.query("DELETE FROM users WHERE name = 'user' LIMIT 2").unwrap();
+            db.query("UPDATE users SET name = 'renamed' WHERE name = 'user' LIMIT 1").unwrap();
+        }
+
+        // Replay touches the same rows, not every row that matches
+        let _ = std::fs::remove_file(&kv_path);
+        let mut db = OnDiskReefDB::create_on_disk(kv_path, index_path).unwrap();
+        let ids: Vec<DataValue> = (3..=5).map(DataValue::Integer).collect();
+        assert_eq!(select_ids(&mut db), ids);
+        match db.query("SELECT id FROM users WHERE name = 'renamed'").unwrap() {
+            ReefDBResult::Select(result) => assert_eq!(result.len(), 1),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_shrinks_wal_and_keeps_data() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("checkpoint.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("checkpoint.idx").to_str().unwrap().to_string();
+        let wal_size = || std::fs::metadata(format!("{}.wal", kv_path)).unwrap().len();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            for id in 1..=20 {
+                db.query(&format!("INSERT INTO users VALUES ({}, 'user{}')", id, id)).unwrap();
+            }
+            db.query("UPDATE users SET name = 'renamed' WHERE id = 1").unwrap();
+            db.query("DELETE FROM users WHERE id = 2").unwrap();
+
+            let size_before = wal_size();
+            db.transaction_manager.as_mut().unwrap().checkpoint().unwrap();
+            assert!(wal_size() < size_before, "checkpoint should shrink the WAL");
+
+            // Only the marker is left, the data file holds the rest
+            let mut wal = crate::wal::WriteAheadLog::new(format!("{}.wal", kv_path)).unwrap();
+            let entries = wal.read_entries().unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].operation, crate::wal::WALOperation::Checkpoint);
+
+            db.query("INSERT INTO users VALUES (21, 'after')").unwrap();
+        }
+
+        // Recover from the data file and what the WAL logged since
+        let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path).unwrap();
+        let ids = select_ids(&mut db);
+        assert_eq!(ids.len(), 20);
+        assert!(ids.contains(&DataValue::Integer(21)));
+        assert!(!ids.contains(&DataValue::Integer(2)));
+    }
+
+    #[test]
+    fn test_insert_after_checkpoint_and_reopen() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("reopen.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("reopen.idx").to_str().unwrap().to_string();
+
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+            db.query("INSERT INTO users VALUES (1, 'alice')").unwrap();
+            db.transaction_manager.as_mut().unwrap().checkpoint().unwrap();
+        }
+
+        // The WAL no longer mentions the table, only the data file does
+        {
+            let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path.clone()).unwrap();
+            db.query("INSERT INTO users VALUES (2, 'bob')").unwrap();
+            db.query("UPDATE users SET name = 'carol' WHERE id = 2").unwrap();
+            assert!(db.query("INSERT INTO missing VALUES (3, 'dave')").is_err());
+            assert_eq!(select_ids(&mut db), vec![DataValue::Integer(1), DataValue::Integer(2)]);
+        }
+
+        let mut db = OnDiskReefDB::create_on_disk(kv_path, index_path).unwrap();
+        assert_eq!(select_ids(&mut db), vec![DataValue::Integer(1), DataValue::Integer(2)]);
+        match db.query("SELECT name FROM users WHERE id = 2").unwrap() {
+            ReefDBResult::Select(result) => assert_eq!(result.rows[0].1, vec![DataValue::Text("carol".to_string())]),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_automatic_checkpoint() {
+        let temp_dir = tempdir().unwrap();
+        let kv_path = temp_dir.path().join("auto.db").to_str().unwrap().to_string();
+        let index_path = temp_dir.path().join("auto.idx").to_str().unwrap().to_string();
+
+        let mut db = OnDiskReefDB::create_on_disk(kv_path.clone(), index_path).unwrap();
+        db.transaction_manager.as_mut().unwrap()
+            .set_checkpoint_policy(CheckpointPolicy::default().with_max_transactions(5));
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+        for id in 1..=50 {
+            db.query(&format!("INSERT INTO users VALUES ({}, 'user{}')", id, id)).unwrap();
+        }
+
+        // Without checkpoints the log holds an insert and a commit per row
+        let mut wal = crate::wal::WriteAheadLog::new(format!("{}.wal", kv_path)).unwrap();
+        assert!(wal.read_entries().unwrap().len() < 20);
+    }
+
+    #[test]
+    fn test_autovacuum_checkpoints_wal() {
+        let temp_dir = tempdir().unwrap();
+        let wal_entries = || crate::wal::WriteAheadLog::new(temp_dir.path().join("reefdb.db.wal"))
+            .unwrap()
+            .read_entries()
+            .unwrap()
+            .len();
+
+        let mut db = ReefDBBuilder::on_disk(temp_dir.path())
+            .with_autovacuum(Duration::from_millis(10))
+            .build()
+            .unwrap();
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+        for id in 1..=50 {
+            db.query(&format!("INSERT INTO users VALUES ({}, 'user{}')", id, id)).unwrap();
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while wal_entries() >= 20 {
+            assert!(Instant::now() < deadline, "autovacuum never checkpointed the WAL");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        drop(db);
+
+        let mut reopened = ReefDBBuilder::on_disk(temp_dir.path()).build().unwrap();
+        assert_eq!(select_ids(&mut reopened).len(), 50);
+    }
+}
@@ -0,0 +1,86 @@
+use std::thread;
+
+use crate::{error::ReefDBError, replication::Follower, result::ReefDBResult, wal::{WALEntry, WALOperation}, InMemoryReefDB};
+
+fn select_all(result: ReefDBResult) -> Vec<Vec<crate::sql::data_value::DataValue>> {
+    match result {
+        ReefDBResult::Select(rows) => rows.rows.into_iter().map(|(_, row)| row).collect(),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_follower_converges() -> Result<(), ReefDBError> {
+    let mut leader = InMemoryReefDB::create_in_memory()?;
+    let receiver = leader.replicate()?;
+    let mut follower = Follower::new(InMemoryReefDB::create_in_memory()?);
+
+    leader.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    leader.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    leader.query("INSERT INTO users VALUES (2, 'Bob')")?;
+    follower.catch_up(&receiver)?;
+    assert_eq!(follower.applied_transactions(), 3);
+    assert_eq!(select_all(follower.query("SELECT * FROM users")?), select_all(leader.query("SELECT * FROM users")?));
+
+    // A rolled back transaction is never shipped
+    {
+        let mut tx = leader.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+        tx.query("INSERT INTO users VALUES (3, 'Carol')")?;
+        tx.rollback()?;
+    }
+    leader.query("UPDATE users SET name = 'Bobby' WHERE id = 2")?;
+    leader.query("DELETE FROM users WHERE id = 1")?;
+    follower.catch_up(&receiver)?;
+    assert_eq!(select_all(follower.query("SELECT * FROM users")?), select_all(leader.query("SELECT * FROM users")?));
+
+    // Followers only read
+    for sql in [
+        "INSERT INTO users VALUES (4, 'Dave')",
+        "UPDATE users SET name = 'x' WHERE id = 2",
+        "DELETE FROM users WHERE id = 2",
+        "DROP TABLE users",
+    ] {
+        assert_eq!(follower.query(sql).unwrap_err(), ReefDBError::ReadOnlyReplica, "{}", sql);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_follower_apply_loop() -> Result<(), ReefDBError> {
+    let mut leader = InMemoryReefDB::create_in_memory()?;
+    let receiver = leader.replicate()?;
+    let following = thread::spawn(move || -> Result<_, ReefDBError> {
+        let mut follower = Follower::new(InMemoryReefDB::create_in_memory()?);
+        follower.run(receiver)?;
+        Ok(follower)
+    });
+
+    leader.query("CREATE TABLE items (id INTEGER PRIMARY KEY, qty INTEGER)")?;
+    for id in 1..=5 {
+        leader.query(&format!("INSERT INTO items VALUES ({}, {})", id, id * 10))?;
+    }
+    let expected = select_all(leader.query("SELECT * FROM items")?);
+    // Dropping the leader closes the channel and ends the loop
+    drop(leader);
+
+    let mut follower = following.join().unwrap()?;
+    assert_eq!(select_all(follower.query("SELECT * FROM items")?), expected);
+    Ok(())
+}
+
+#[test]
+fn test_leader_ships_each_commit_without_undo_images() -> Result<(), ReefDBError> {
+    let mut leader = InMemoryReefDB::create_in_memory()?;
+    leader.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    leader.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    let receiver = leader.replicate()?;
+
+    leader.query("UPDATE users SET name = 'Alicia' WHERE id = 1")?;
+    let entries: Vec<WALEntry> = receiver.try_iter()
+        .map(|payload| bincode::deserialize(&payload))
+        .collect::<Result<_, _>>()?;
+    let operations: Vec<&WALOperation> = entries.iter().map(|entry| &entry.operation).collect();
+    assert_eq!(operations, [&WALOperation::Update, &WALOperation::Commit]);
+    assert!(entries.iter().all(|entry| entry.transaction_id == entries[0].transaction_id));
+    Ok(())
+}
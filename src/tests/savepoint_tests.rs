@@ -178,6 +178,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_rollback_to_savepoint_releases_inner_savepoints() -> Result<(), ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+
+        db.query("BEGIN TRANSACTION")?;
+        db.query("SAVEPOINT sp1")?;
+        db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+        db.query("SAVEPOINT sp2")?;
+        db.query("INSERT INTO users VALUES (2, 'Bob')")?;
+        db.query("SAVEPOINT sp3")?;
+        db.query("ROLLBACK TO SAVEPOINT sp1")?;
+
+        assert!(matches!(db.query("ROLLBACK TO SAVEPOINT sp3"), Err(ReefDBError::SavepointNotFound(_))));
+        assert!(matches!(db.query("RELEASE SAVEPOINT sp2"), Err(ReefDBError::SavepointNotFound(_))));
+        // The target itself stays usable
+        db.query("ROLLBACK TO SAVEPOINT sp1")?;
+        db.query("RELEASE SAVEPOINT sp1")?;
+        db.query("COMMIT")?;
+
+        match db.query("SELECT * FROM users")? {
+            ReefDBResult::Select(rows) => assert_eq!(rows.len(), 0),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_savepoint_depth_limit() -> Result<(), ReefDBError> {
+        let mut db = InMemoryReefDB::create_in_memory()?;
+        db.set_max_savepoint_depth(2);
+
+        db.query("BEGIN TRANSACTION")?;
+        db.query("SAVEPOINT sp1")?;
+        db.query("SAVEPOINT sp2")?;
+        assert!(matches!(db.query("SAVEPOINT sp3"), Err(ReefDBError::ResourceLimitExceeded(_))));
+
+        // Releasing makes room again
+        db.query("RELEASE SAVEPOINT sp2")?;
+        db.query("SAVEPOINT sp3")?;
+        db.query("COMMIT")?;
+
+        // Transactions run through the transaction manager share the limit
+        let tm = db.transaction_manager.as_mut().unwrap();
+        let transaction_id = tm.begin_transaction(IsolationLevel::Serializable)?;
+        for name in ["sp1", "sp2"] {
+            tm.execute_statement(transaction_id, Statement::parse(&format!("SAVEPOINT {}", name)).unwrap().1)?;
+        }
+        let result = tm.execute_statement(transaction_id, Statement::parse("SAVEPOINT sp3").unwrap().1);
+        assert!(matches!(result, Err(ReefDBError::ResourceLimitExceeded(_))));
+        tm.rollback_transaction(transaction_id)
+    }
+
     #[test]
     fn test_savepoint_with_delete() -> Result<(), ReefDBError> {
         let mut db = InMemoryReefDB::create_in_memory()?;
@@ -233,4 +286,24 @@ mod tests {
 
         Ok(())
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_savepoint_parse_whitespace() {
+        let cases = [
+            ("SAVEPOINT   sp1", Statement::Savepoint(SavepointStatement { name: "sp1".to_string() })),
+            ("savepoint\n\tsp1", Statement::Savepoint(SavepointStatement { name: "sp1".to_string() })),
+            ("ROLLBACK  TO\n  SAVEPOINT sp1", Statement::RollbackToSavepoint("sp1".to_string())),
+            ("rollback\tto savepoint\n\nsp_2", Statement::RollbackToSavepoint("sp_2".to_string())),
+            ("RELEASE   SAVEPOINT sp1", Statement::ReleaseSavepoint("sp1".to_string())),
+            ("  release\nsavepoint\n sp1  ", Statement::ReleaseSavepoint("sp1".to_string())),
+        ];
+        for (sql, expected) in cases {
+            let (remaining, stmt) = Statement::parse(sql).unwrap();
+            assert_eq!(remaining, "");
+            assert_eq!(stmt, expected, "parsing {:?}", sql);
+        }
+
+        assert!(Statement::parse("ROLLBACK TOSAVEPOINT sp1").is_err());
+        assert!(Statement::parse("RELEASESAVEPOINT sp1").is_err());
+    }
+}
@@ -69,6 +69,7 @@ fn test_fts_search_with_select() -> Result<(), ReefDBError> {
         Some(where_clause),
         vec![],
         vec![],
+        vec![],
     );
 
     let result = db.execute_statement(Statement::Select(select_stmt))?;
@@ -85,4 +86,4 @@ fn test_fts_search_with_select() -> Result<(), ReefDBError> {
     }
 
     Ok(())
-} 
\ No newline at end of file
+} 
@@ -75,4 +75,1016 @@ fn test_select_all() -> Result<()> {
     }
 
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_select_to_json() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    db.query("INSERT INTO users VALUES (2, NULL)")?;
+
+    if let ReefDBResult::Select(results) = db.query("SELECT * FROM users")? {
+        assert_eq!(results.to_json(), serde_json::json!([
+            {"id": 1, "name": "Alice"},
+            {"id": 2, "name": null},
+        ]));
+    } else {
+        panic!("Expected Select result");
+    }
+
+    assert_eq!(DataValue::Float(1.5).to_json(), serde_json::json!(1.5));
+    assert_eq!(DataValue::Boolean(true).to_json(), serde_json::json!(true));
+
+    Ok(())
+}
+
+#[test]
+fn test_select_result_serde_round_trip() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, score FLOAT, body TSVECTOR)")?;
+    db.query("INSERT INTO docs VALUES (1, 'Rust', 0.5, 'systems programming in rust')")?;
+    db.query("INSERT INTO docs VALUES (2, NULL, 1.0, 'gardening')")?;
+
+    let result = db.query("SELECT id, title, score, to_tsvector(body) FROM docs")?;
+
+    let json = serde_json::to_string(&result).unwrap();
+    let decoded: ReefDBResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, result);
+
+    let bytes = bincode::serialize(&result).unwrap();
+    let decoded: ReefDBResult = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(decoded, result);
+
+    // Values are externally tagged by variant name
+    assert_eq!(serde_json::to_value(DataValue::Integer(7)).unwrap(), serde_json::json!({"Integer": 7}));
+    assert_eq!(serde_json::to_value(ReefDBResult::Update(2)).unwrap(), serde_json::json!({"Update": 2}));
+
+    Ok(())
+}
+
+#[test]
+fn test_query_iter_streams_rows() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE numbers (id INTEGER PRIMARY KEY, n INTEGER)")?;
+    for i in 1..=100 {
+        db.query(&format!("INSERT INTO numbers VALUES ({}, {})", i, 101 - i))?;
+    }
+
+    let sql = "SELECT id FROM numbers WHERE n > 50";
+    let streamed = db.query_iter(sql)?.collect::<Result<Vec<_>>>()?;
+    if let ReefDBResult::Select(results) = db.query(sql)? {
+        let expected: Vec<_> = results.rows.into_iter().map(|(_, row)| row).collect();
+        assert_eq!(streamed, expected);
+    } else {
+        panic!("Expected Select result");
+    }
+
+    let mut rows = db.query_iter("SELECT * FROM numbers")?;
+    assert_eq!(rows.columns().len(), 2);
+    assert_eq!(rows.next().unwrap()?, vec![DataValue::Integer(1), DataValue::Integer(100)]);
+    assert_eq!(rows.by_ref().count(), 99);
+    assert!(rows.next().is_none());
+
+    // ORDER BY is materialized but iterates the same way
+    let first = db.query_iter("SELECT * FROM numbers ORDER BY n")?.next().unwrap()?;
+    assert_eq!(first, vec![DataValue::Integer(100), DataValue::Integer(1)]);
+
+    // So are aggregates and groups
+    let counts = db.query_iter("SELECT COUNT(*) FROM numbers WHERE n > 50")?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(counts, vec![vec![DataValue::Integer(50)]]);
+    let groups = db.query_iter("SELECT n, COUNT(*) FROM numbers WHERE n <= 3 GROUP BY n")?.collect::<Result<Vec<_>>>()?;
+    assert_eq!(groups.len(), 3);
+    assert!(groups.iter().all(|row| row[1] == DataValue::Integer(1)));
+
+    assert!(db.query_iter("SELECT * FROM missing").is_err());
+    assert!(db.query_iter("DELETE FROM numbers").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_order_by_output_columns() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+    db.query("INSERT INTO users VALUES (1, 'Carol', 35)")?;
+    db.query("INSERT INTO users VALUES (2, 'Alice', 25)")?;
+    db.query("INSERT INTO users VALUES (3, 'Bob', 30)")?;
+
+    let names = |result: ReefDBResult| match result {
+        ReefDBResult::Select(results) => results.rows.into_iter().map(|(_, row)| row[0].clone()).collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+    let text = |names: &[&str]| names.iter().map(|n| DataValue::Text(n.to_string())).collect::<Vec<_>>();
+
+    // Output column at a different position than in the table
+    assert_eq!(names(db.query("SELECT name, id FROM users ORDER BY id DESC")?), text(&["Bob", "Alice", "Carol"]));
+    // Column that is not projected falls back to the table row
+    assert_eq!(names(db.query("SELECT name FROM users ORDER BY age")?), text(&["Alice", "Bob", "Carol"]));
+    // Qualified column
+    assert_eq!(names(db.query("SELECT name FROM users ORDER BY users.name")?), text(&["Alice", "Bob", "Carol"]));
+
+    assert!(matches!(db.query("SELECT name FROM users ORDER BY missing"), Err(ReefDBError::ColumnNotFound(_))));
+
+    // Positions in the SELECT list, counted from 1
+    assert_eq!(names(db.query("SELECT name, age FROM users ORDER BY 2 DESC")?), text(&["Carol", "Bob", "Alice"]));
+    assert_eq!(names(db.query("SELECT name, ABS(age) AS years FROM users ORDER BY 1")?), text(&["Alice", "Bob", "Carol"]));
+    assert!(db.query("SELECT name, age FROM users ORDER BY 3").is_err());
+    assert!(db.query("SELECT name, age FROM users ORDER BY 0").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_order_by_aliased_function() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE articles (id INTEGER PRIMARY KEY, content TSVECTOR)")?;
+    db.query("INSERT INTO articles VALUES (1, 'Rust and more Rust')")?;
+    db.query("INSERT INTO articles VALUES (2, 'Learning Rust')")?;
+
+    let query = "SELECT id, ts_rank(to_tsvector(content), to_tsquery('rust')) as rank FROM articles \
+                 WHERE to_tsvector(content) @@ to_tsquery('rust') ORDER BY rank DESC";
+    if let ReefDBResult::Select(results) = db.query(query)? {
+        assert_eq!(results.len(), 2);
+        let ranks: Vec<_> = results.rows.iter().map(|(_, row)| row[1].clone()).collect();
+        assert!(ranks.windows(2).all(|w| w[0] >= w[1]));
+    } else {
+        panic!("Expected Select result");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_order_by_nulls() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE scores (id INTEGER PRIMARY KEY, score INTEGER)")?;
+    db.query("INSERT INTO scores VALUES (1, 20)")?;
+    db.query("INSERT INTO scores VALUES (2, NULL)")?;
+    db.query("INSERT INTO scores VALUES (3, 10)")?;
+    db.query("INSERT INTO scores VALUES (4, NULL)")?;
+    db.query("INSERT INTO scores VALUES (5, 30)")?;
+
+    let mut ids = |sql: &str| -> Result<Vec<i64>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok((0..results.len()).map(|i| results.get_int(i, "id").unwrap().unwrap()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    // NULL is the smallest value by default
+    assert_eq!(ids("SELECT id FROM scores ORDER BY score, id")?, vec![2, 4, 3, 1, 5]);
+    assert_eq!(ids("SELECT id FROM scores ORDER BY score DESC, id")?, vec![5, 1, 3, 2, 4]);
+
+    // Explicit placement holds regardless of direction
+    assert_eq!(ids("SELECT id FROM scores ORDER BY score NULLS LAST, id")?, vec![3, 1, 5, 2, 4]);
+    assert_eq!(ids("SELECT id FROM scores ORDER BY score DESC NULLS FIRST, id DESC")?, vec![4, 2, 5, 1, 3]);
+
+    Ok(())
+}
+
+#[test]
+fn test_order_by_collate_nocase() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO people VALUES (1, 'alice')")?;
+    db.query("INSERT INTO people VALUES (2, 'Bob')")?;
+    db.query("INSERT INTO people VALUES (3, 'carol')")?;
+    db.query("INSERT INTO people VALUES (4, 'Alice')")?;
+
+    let mut ids = |sql: &str| -> Result<Vec<i64>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok((0..results.len()).map(|i| results.get_int(i, "id").unwrap().unwrap()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    // Byte order puts every uppercase name first
+    assert_eq!(ids("SELECT id FROM people ORDER BY name")?, vec![4, 2, 1, 3]);
+    assert_eq!(ids("SELECT id FROM people ORDER BY name COLLATE NOCASE")?, vec![4, 1, 2, 3]);
+    assert_eq!(ids("SELECT id FROM people ORDER BY name COLLATE nocase DESC")?, vec![3, 2, 1, 4]);
+
+    Ok(())
+}
+
+#[test]
+fn test_select_in_list_and_subquery() -> Result<()> {
+    use crate::sql::statements::Statement;
+    use crate::transaction::IsolationLevel;
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER)")?;
+    db.query("CREATE TABLE active_users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    for (id, user_id) in [(1, 10), (2, 20), (3, 10), (4, 30)] {
+        db.query(&format!("INSERT INTO orders VALUES ({}, {})", id, user_id))?;
+    }
+    db.query("INSERT INTO active_users VALUES (10, 'alice')")?;
+    db.query("INSERT INTO active_users VALUES (30, 'carol')")?;
+
+    let ids = |db: &mut InMemoryReefDB, sql: &str| -> Result<Vec<i64>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok((0..results.len()).map(|i| results.get_int(i, "id").unwrap().unwrap()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    assert_eq!(ids(&mut db, "SELECT id FROM orders WHERE user_id IN (20, 30) ORDER BY id")?, vec![2, 4]);
+    assert_eq!(ids(&mut db, "SELECT id FROM orders WHERE user_id IN (SELECT id FROM active_users) ORDER BY id")?, vec![1, 3, 4]);
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM orders WHERE user_id IN (SELECT id FROM active_users WHERE name = 'nobody')")?,
+        Vec::<i64>::new()
+    );
+
+    // The subquery has to project a single column
+    assert!(db.query("SELECT id FROM orders WHERE user_id IN (SELECT * FROM active_users)").is_err());
+
+    // Inside a transaction the subquery sees the transaction's own writes
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    let tm = db.transaction_manager.as_mut().unwrap();
+    let tx = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+    for sql in [
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER)",
+        "CREATE TABLE active_users (id INTEGER PRIMARY KEY, name TEXT)",
+        "INSERT INTO orders VALUES (1, 10)",
+        "INSERT INTO orders VALUES (2, 20)",
+        "INSERT INTO active_users VALUES (20, 'bob')",
+    ] {
+        tm.execute_statement(tx, Statement::parse(sql).unwrap().1)?;
+    }
+    let sql = "SELECT id FROM orders WHERE user_id IN (SELECT id FROM active_users)";
+    match tm.execute_statement(tx, Statement::parse(sql).unwrap().1)? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0][0], DataValue::Integer(2));
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_union_and_union_all() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE customers (id INTEGER PRIMARY KEY, city TEXT)")?;
+    db.query("CREATE TABLE suppliers (id INTEGER PRIMARY KEY, city TEXT, rating FLOAT)")?;
+    db.query("INSERT INTO customers VALUES (1, 'Paris')")?;
+    db.query("INSERT INTO customers VALUES (2, 'Berlin')")?;
+    db.query("INSERT INTO customers VALUES (3, 'Paris')")?;
+    db.query("INSERT INTO suppliers VALUES (1, 'Berlin', 4.5)")?;
+    db.query("INSERT INTO suppliers VALUES (2, 'Madrid', 3.0)")?;
+
+    let select = |db: &mut InMemoryReefDB, sql: &str| -> Result<crate::result::QueryResult> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok(results),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    let all = select(&mut db, "SELECT city FROM customers UNION ALL SELECT city FROM suppliers")?;
+    assert_eq!(all.len(), 5);
+
+    let distinct = select(&mut db, "SELECT city FROM customers UNION SELECT city FROM suppliers")?;
+    assert_eq!(distinct.len(), 3);
+    let cities: Vec<_> = (0..distinct.len()).map(|i| distinct.get_text(i, "city").unwrap().unwrap()).collect();
+    assert_eq!(cities, vec!["Paris", "Berlin", "Madrid"]);
+    assert_eq!(distinct.columns[0].table, Some("customers".to_string()));
+
+    // Whole rows are compared when deduplicating
+    let rows = select(&mut db, "SELECT id, city FROM customers UNION SELECT id, city FROM suppliers")?;
+    assert_eq!(rows.len(), 5);
+
+    // Both sides must agree on arity and types
+    assert!(db.query("SELECT id, city FROM customers UNION SELECT city FROM suppliers").is_err());
+    assert!(db.query("SELECT city FROM customers UNION SELECT rating FROM suppliers").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_transactional_select_with_selective_where() -> Result<()> {
+    use crate::sql::statements::Statement;
+    use crate::transaction::IsolationLevel;
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    let tm = db.transaction_manager.as_mut().unwrap();
+    let tx = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+    tm.execute_statement(tx, Statement::parse("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT)").unwrap().1)?;
+    for id in 1..=500 {
+        let kind = if id % 100 == 0 { "rare" } else { "common" };
+        let sql = format!("INSERT INTO events VALUES ({}, '{}')", id, kind);
+        tm.execute_statement(tx, Statement::parse(&sql).unwrap().1)?;
+    }
+
+    let sql = "SELECT id FROM events WHERE kind = 'rare' ORDER BY id DESC";
+    match tm.execute_statement(tx, Statement::parse(sql).unwrap().1)? {
+        ReefDBResult::Select(results) => {
+            let ids: Vec<_> = (0..results.len()).map(|i| results[i][0].clone()).collect();
+            assert_eq!(ids, [500, 400, 300, 200, 100].map(DataValue::Integer));
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_aggregates_over_no_rows() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, age INTEGER)")?;
+    db.query("INSERT INTO users VALUES (1, 30)")?;
+    db.query("INSERT INTO users VALUES (2, 40)")?;
+
+    match db.query("SELECT COUNT(*) FROM users WHERE id = 999999")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0], vec![DataValue::Integer(0)]);
+            assert_eq!(results.columns.len(), 1);
+            assert_eq!(results.columns[0].name, "COUNT");
+            assert_eq!(results.columns[0].data_type, DataType::Integer);
+            assert!(!results.columns[0].nullable);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    match db.query("SELECT SUM(age), AVG(age), MIN(age), MAX(age) FROM users WHERE id = 999999")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0], vec![DataValue::Null; 4]);
+            assert_eq!(results.columns[0].data_type, DataType::Integer);
+            assert_eq!(results.columns[1].data_type, DataType::Float);
+            assert!(results.columns.iter().all(|c| c.nullable));
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    match db.query("SELECT COUNT(*), SUM(age), AVG(age), MIN(age), MAX(age) FROM users")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0], vec![
+                DataValue::Integer(2),
+                DataValue::Integer(70),
+                DataValue::Float(35.0),
+                DataValue::Integer(30),
+                DataValue::Integer(40),
+            ]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Without GROUP BY, plain columns can't be mixed with aggregates
+    assert!(db.query("SELECT id, COUNT(*) FROM users").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_distinct_aggregates() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, country TEXT, age INTEGER)")?;
+    db.query("INSERT INTO users VALUES (1, 'FR', 30)")?;
+    db.query("INSERT INTO users VALUES (2, 'US', 30)")?;
+    db.query("INSERT INTO users VALUES (3, 'FR', 40)")?;
+    db.query("INSERT INTO users VALUES (4, NULL, 40)")?;
+
+    match db.query("SELECT COUNT(country), COUNT(DISTINCT country), SUM(DISTINCT age), AVG(DISTINCT age) FROM users")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results[0], vec![
+                DataValue::Integer(3),
+                DataValue::Integer(2),
+                DataValue::Integer(70),
+                DataValue::Float(35.0),
+            ]);
+            assert_eq!(results.columns[2].data_type, DataType::Integer);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    assert!(db.query("SELECT COUNT(DISTINCT *) FROM users").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_case_expression() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, age INTEGER)")?;
+    db.query("INSERT INTO users VALUES (1, 12)")?;
+    db.query("INSERT INTO users VALUES (2, 30)")?;
+    db.query("INSERT INTO users VALUES (3, 70)")?;
+
+    match db.query("SELECT id, CASE WHEN age >= 65 THEN 'senior' WHEN age >= 18 THEN 'adult' ELSE 'minor' END AS category, CASE WHEN age < 18 THEN 1 END FROM users ORDER BY id")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.columns[1].name, "category");
+            assert_eq!(results.columns[1].data_type, DataType::Text);
+            assert!(!results.columns[1].nullable);
+            assert_eq!(results.columns[2].name, "case");
+            assert!(results.columns[2].nullable);
+
+            // The first matching branch wins, then ELSE, then NULL
+            assert_eq!(results[0][1..], [DataValue::Text("minor".to_string()), DataValue::Integer(1)]);
+            assert_eq!(results[1][1..], [DataValue::Text("adult".to_string()), DataValue::Null]);
+            assert_eq!(results[2][1..], [DataValue::Text("senior".to_string()), DataValue::Null]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    match db.query("SELECT id FROM users WHERE CASE WHEN age >= 18 THEN 'adult' ELSE 'minor' END = 'adult' ORDER BY id")? {
+        ReefDBResult::Select(results) => {
+            let ids: Vec<_> = results.rows.iter().map(|(_, row)| row[0].clone()).collect();
+            assert_eq!(ids, vec![DataValue::Integer(2), DataValue::Integer(3)]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    assert!(db.query("SELECT CASE WHEN missing = 1 THEN 'x' END FROM users").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_comparison_functions() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE scores (id INTEGER PRIMARY KEY, home INTEGER, away FLOAT)")?;
+    db.query("INSERT INTO scores VALUES (1, 3, 1.5)")?;
+    db.query("INSERT INTO scores VALUES (2, NULL, 2.0)")?;
+    db.query("INSERT INTO scores VALUES (3, 2, 2.0)")?;
+
+    let rows = match db.query("SELECT greatest(home, away), least(home, away), nullif(home, away) FROM scores ORDER BY id")? {
+        ReefDBResult::Select(results) => results.rows.into_iter().map(|(_, row)| row).collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+    assert_eq!(rows, vec![
+        vec![DataValue::Integer(3), DataValue::Float(1.5), DataValue::Integer(3)],
+        // NULL is skipped rather than winning or losing
+        vec![DataValue::Float(2.0), DataValue::Float(2.0), DataValue::Null],
+        // 2 and 2.0 are equal
+        vec![DataValue::Integer(2), DataValue::Integer(2), DataValue::Null],
+    ]);
+    Ok(())
+}
+
+#[test]
+fn test_modulo_and_bitwise_operators() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE files (id INTEGER PRIMARY KEY, flags INTEGER)")?;
+    db.query("INSERT INTO files VALUES (1, 5)")?;
+    db.query("INSERT INTO files VALUES (2, 6)")?;
+    db.query("INSERT INTO files VALUES (3, 3)")?;
+    db.query("INSERT INTO files VALUES (4, 12)")?;
+
+    match db.query("SELECT id % 3, flags & 4 AS readable, flags | 1, flags ^ 1, flags % 4 & 1 FROM files WHERE id = 2")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.columns[0].name, "id % 3");
+            assert_eq!(results.columns[1].name, "readable");
+            // `%` binds tighter than `&`
+            assert_eq!(results[0], vec![
+                DataValue::Integer(2),
+                DataValue::Integer(4),
+                DataValue::Integer(7),
+                DataValue::Integer(7),
+                DataValue::Integer(0),
+            ]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    let ids = |db: &mut InMemoryReefDB, sql: &str| -> Result<Vec<DataValue>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok(results.rows.iter().map(|(_, row)| row[0].clone()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+    assert_eq!(ids(&mut db, "SELECT id FROM files WHERE flags & 4 = 4 ORDER BY id")?,
+        vec![DataValue::Integer(1), DataValue::Integer(2), DataValue::Integer(4)]);
+    assert_eq!(ids(&mut db, "SELECT id FROM files WHERE id % 2 = 0 AND flags & 8 = 8")?,
+        vec![DataValue::Integer(4)]);
+
+    // Division by zero is an error rather than a value
+    assert!(db.query("SELECT flags % 0 FROM files").is_err());
+    assert!(db.query("SELECT id FROM files WHERE flags % 0 = 1").is_err());
+    assert!(db.query("SELECT id FROM files WHERE missing & 1 = 1").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_concatenation_operator() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE people (id INTEGER PRIMARY KEY, first_name TEXT, last_name TEXT)")?;
+    db.query("INSERT INTO people VALUES (1, 'Ada', 'Lovelace')")?;
+    db.query("INSERT INTO people VALUES (2, 'Alan', NULL)")?;
+
+    match db.query("SELECT first_name || ' ' || last_name AS name, id || ':' || first_name FROM people ORDER BY id")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.columns[0].name, "name");
+            assert_eq!(results[0], vec![
+                DataValue::Text("Ada Lovelace".to_string()),
+                DataValue::Text("1:Ada".to_string()),
+            ]);
+            // Unlike CONCAT, a NULL operand makes the result NULL
+            assert_eq!(results[1][0], DataValue::Null);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    match db.query("SELECT id FROM people WHERE first_name || last_name = 'AdaLovelace'")? {
+        ReefDBResult::Select(results) => assert_eq!(results.len(), 1),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_where_compares_integers_and_floats() -> Result<()> {
+    use crate::transaction::IsolationLevel;
+
+    let setup = [
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, price FLOAT, qty INTEGER)",
+        "INSERT INTO items VALUES (1, 9.5, 3)",
+        "INSERT INTO items VALUES (2, 10.0, 10)",
+        "INSERT INTO items VALUES (3, 12.25, 12)",
+        // Never matches a comparison
+        "INSERT INTO items VALUES (4, NULL, NULL)",
+    ];
+    let cases = [
+        // A float column against integer literals
+        ("SELECT id FROM items WHERE price > 10", vec![3]),
+        ("SELECT id FROM items WHERE price = 10", vec![2]),
+        ("SELECT id FROM items WHERE price != 10", vec![1, 3]),
+        ("SELECT id FROM items WHERE price >= 10 AND price < 13", vec![2, 3]),
+        ("SELECT id FROM items WHERE price IN (10, 12)", vec![2]),
+        // An integer column against float literals
+        ("SELECT id FROM items WHERE qty > 9.5", vec![2, 3]),
+        ("SELECT id FROM items WHERE qty = 10.0", vec![2]),
+        ("SELECT id FROM items WHERE qty <= 3.0 OR qty > 11.5", vec![1, 3]),
+    ];
+    let ids = |result: ReefDBResult| match result {
+        ReefDBResult::Select(results) => results.rows.into_iter()
+            .map(|(_, row)| match row[0] {
+                DataValue::Integer(id) => id,
+                ref other => panic!("Expected an integer id, got {:?}", other),
+            })
+            .collect::<Vec<_>>(),
+        other => panic!("Expected Select result, got {:?}", other),
+    };
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    for sql in setup {
+        db.query(sql)?;
+    }
+    for (sql, expected) in &cases {
+        assert_eq!(&ids(db.query(sql)?), expected, "{}", sql);
+    }
+
+    // Writes find their rows the same way
+    assert!(matches!(db.query("UPDATE items SET qty = 11 WHERE price = 10")?, ReefDBResult::Update(1)));
+    assert!(matches!(db.query("DELETE FROM items WHERE qty = 3.0")?, ReefDBResult::Delete(1)));
+    assert_eq!(ids(db.query("SELECT id FROM items WHERE qty = 11")?), vec![2]);
+
+    // So does the transaction manager
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    let tm = db.transaction_manager.as_mut().unwrap();
+    let tx = tm.begin_transaction(IsolationLevel::ReadCommitted)?;
+    for sql in setup {
+        tm.execute_statement(tx, crate::sql::parser::Parser::parse_sql(sql)?)?;
+    }
+    for (sql, expected) in &cases {
+        assert_eq!(&ids(tm.execute_statement(tx, crate::sql::parser::Parser::parse_sql(sql)?)?), expected, "{}", sql);
+    }
+    tm.commit_transaction(tx)?;
+    Ok(())
+}
+
+#[test]
+fn test_select_with_ilike() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    db.query("INSERT INTO users VALUES (2, 'alice')")?;
+    db.query("INSERT INTO users VALUES (3, 'Bob')")?;
+
+    let ids = |db: &mut InMemoryReefDB, sql: &str| -> Result<Vec<DataValue>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok(results.rows.into_iter().map(|(_, row)| row[0].clone()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM users WHERE name ILIKE 'al%' ORDER BY id")?,
+        vec![DataValue::Integer(1), DataValue::Integer(2)]
+    );
+    assert_eq!(
+        ids(&mut db, "SELECT id FROM users WHERE name LIKE 'al%'")?,
+        vec![DataValue::Integer(2)]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_math_functions_in_select_and_where() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE items (id INTEGER PRIMARY KEY, delta INTEGER, price FLOAT)")?;
+    db.query("INSERT INTO items VALUES (1, -4, 2.345)")?;
+    db.query("INSERT INTO items VALUES (3, 5, 10.5)")?;
+
+    match db.query("SELECT ABS(delta) AS magnitude, ROUND(price, 1) AS rounded, FLOOR(price) AS floored, POWER(delta, 2) AS squared FROM items ORDER BY id")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results[0], vec![DataValue::Integer(4), DataValue::Float(2.3), DataValue::Float(2.0), DataValue::Integer(16)]);
+            assert_eq!(results[1], vec![DataValue::Integer(5), DataValue::Float(10.5), DataValue::Float(10.0), DataValue::Integer(25)]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    match db.query("SELECT id FROM items WHERE id = MOD(7, 4)")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0], vec![DataValue::Integer(3)]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    // Calls work on the column side too
+    match db.query("SELECT id FROM items WHERE ABS(delta) > 4")? {
+        ReefDBResult::Select(results) => {
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0], vec![DataValue::Integer(3)]);
+        }
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    assert_eq!(db.query("DELETE FROM items WHERE FLOOR(price) = 2")?, ReefDBResult::Delete(1));
+
+    assert!(db.query("SELECT MOD(delta, 0) AS m FROM items").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_query_with_stats() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE articles (id INTEGER PRIMARY KEY, views INTEGER, content TSVECTOR)")?;
+    for (id, content) in ["rust databases", "cooking pasta", "rust compilers", "gardening", "travel"].iter().enumerate() {
+        db.query(&format!("INSERT INTO articles VALUES ({}, {}, '{}')", id + 1, id * 10, content))?;
+    }
+
+    // A plain predicate reads every row
+    let (result, stats) = db.query_with_stats("SELECT id FROM articles WHERE views > 15")?;
+    assert!(matches!(result, ReefDBResult::Select(ref rows) if rows.len() == 3));
+    assert_eq!(stats.rows_scanned, 5);
+    assert_eq!(stats.rows_returned, 3);
+    assert_eq!(stats.index_used, None);
+
+    // A full-text predicate reads only the rows the inverted index found
+    let (result, stats) = db.query_with_stats(
+        "SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('rust') AND views > 15"
+    )?;
+    assert!(matches!(result, ReefDBResult::Select(ref rows) if rows.len() == 1));
+    assert_eq!(stats.rows_scanned, 2);
+    assert_eq!(stats.rows_returned, 1);
+    assert_eq!(stats.index_used.as_deref(), Some("articles.content"));
+
+    let (_, stats) = db.query_with_stats("INSERT INTO articles VALUES (6, 60, 'rust')")?;
+    assert_eq!((stats.rows_scanned, stats.rows_returned), (0, 0));
+
+    // Plain queries collect nothing
+    db.query("SELECT * FROM articles")?;
+    assert!(db.query_stats.borrow().is_none());
+
+    // A full-text predicate most rows match is cheaper to check in a scan
+    db.query("INSERT INTO articles VALUES (7, 70, 'rust again')")?;
+    let (result, stats) = db.query_with_stats("SELECT id FROM articles WHERE to_tsvector(content) @@ to_tsquery('rust')")?;
+    assert!(matches!(result, ReefDBResult::Select(ref rows) if rows.len() == 4));
+    assert_eq!(stats.rows_scanned, 7);
+    assert_eq!(stats.index_used, None);
+    Ok(())
+}
+
+#[test]
+fn test_table_statistics() -> Result<()> {
+    use crate::sql::{column_def::ColumnDef, constraints::constraint::Constraint};
+    use crate::storage::{Storage, TableStorage};
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, country TEXT)")?;
+    for (id, country) in [(1, "'FR'"), (2, "'US'"), (3, "'FR'"), (4, "NULL"), (5, "'DE'")] {
+        db.query(&format!("INSERT INTO users VALUES ({}, {})", id, country))?;
+    }
+    assert_eq!(db.storage.row_count("users"), Some(5));
+    // NULL is not a value of its own
+    assert_eq!(db.storage.column_cardinality("users", "country"), Some(3));
+    assert_eq!(db.storage.column_cardinality("users", "id"), Some(5));
+
+    db.query("DELETE FROM users WHERE country = 'FR'")?;
+    assert_eq!(db.storage.row_count("users"), Some(3));
+    assert_eq!(db.storage.column_cardinality("users", "country"), Some(2));
+
+    assert_eq!(db.storage.row_count("missing"), None);
+    assert_eq!(db.storage.column_cardinality("users", "missing"), None);
+
+    // Larger tables are sampled
+    let mut storage = TableStorage::new();
+    let rows = (0..20_000).map(|id| vec![DataValue::Integer(id), DataValue::Integer(id % 10)]).collect();
+    storage.insert_table("events".to_string(), vec![
+        ColumnDef::new("id", DataType::Integer, vec![Constraint::PrimaryKey]),
+        ColumnDef::new("kind", DataType::Integer, vec![]),
+    ], rows);
+    assert_eq!(storage.row_count("events"), Some(20_000));
+    assert_eq!(storage.column_cardinality("events", "kind"), Some(10));
+    assert_eq!(storage.column_cardinality("events", "id"), Some(20_000));
+    Ok(())
+}
+
+fn explain_rows(result: ReefDBResult) -> Vec<Vec<DataValue>> {
+    match result {
+        ReefDBResult::Select(result) => result.rows.into_iter().map(|(_, row)| row).collect(),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_explain_analyze() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT)")?;
+    db.query("CREATE TABLE books (id INTEGER PRIMARY KEY, author_id INTEGER, title TEXT)")?;
+    for id in 1..=3 {
+        db.query(&format!("INSERT INTO authors VALUES ({}, 'author{}')", id, id))?;
+    }
+    for (id, author_id) in [(1, 1), (2, 1), (3, 2), (4, 3)] {
+        db.query(&format!("INSERT INTO books VALUES ({}, {}, 'book{}')", id, author_id, id))?;
+    }
+    let sql = "SELECT authors.name, books.title FROM authors INNER JOIN books ON authors.id = books.author_id \
+               WHERE authors.id = 1 ORDER BY books.title";
+
+    // Without ANALYZE nothing runs: row counts are estimated from the
+    // table statistics and nothing is timed
+    let plan = explain_rows(db.query(&format!("EXPLAIN {}", sql))?);
+    let steps: Vec<&DataValue> = plan.iter().map(|row| &row[0]).collect();
+    assert_eq!(steps, vec![
+        &DataValue::Text("Seq Scan on authors".to_string()),
+        &DataValue::Text("Nested Loop Inner Join books on authors.id = books.author_id".to_string()),
+        &DataValue::Text("Filter".to_string()),
+        &DataValue::Text("Sort".to_string()),
+    ]);
+    let estimates: Vec<(&DataValue, &DataValue)> = plan.iter().map(|row| (&row[1], &row[2])).collect();
+    assert_eq!(estimates, vec![
+        (&DataValue::Integer(3), &DataValue::Integer(3)),
+        // 3 authors and 4 books over 3 distinct author ids
+        (&DataValue::Integer(15), &DataValue::Integer(4)),
+        // One of 3 distinct ids
+        (&DataValue::Integer(4), &DataValue::Integer(1)),
+        (&DataValue::Integer(1), &DataValue::Integer(1)),
+    ]);
+    assert!(plan.iter().all(|row| row[3] == DataValue::Null));
+
+    let analyzed = explain_rows(db.query(&format!("EXPLAIN ANALYZE {}", sql))?);
+    assert_eq!(analyzed.len(), 5);
+    let counts: Vec<(&DataValue, &DataValue, &DataValue)> = analyzed.iter().map(|row| (&row[0], &row[1], &row[2])).collect();
+    assert_eq!(counts[0], (&DataValue::Text("Seq Scan on authors".to_string()), &DataValue::Integer(3), &DataValue::Integer(3)));
+    // Each of the 3 authors is read along with a pass over the 4 books
+    assert_eq!((counts[1].1, counts[1].2), (&DataValue::Integer(15), &DataValue::Integer(4)));
+    assert_eq!((counts[2].1, counts[2].2), (&DataValue::Integer(4), &DataValue::Integer(2)));
+    assert_eq!(counts[3], (&DataValue::Text("Sort".to_string()), &DataValue::Integer(2), &DataValue::Integer(2)));
+    assert_eq!(counts[4], (&DataValue::Text("Total".to_string()), &DataValue::Integer(15), &DataValue::Integer(2)));
+    assert!(analyzed.iter().all(|row| matches!(row[3], DataValue::Float(ms) if ms >= 0.0)));
+
+    // A full-text predicate shows up as an index scan
+    db.query("CREATE TABLE posts (id INTEGER PRIMARY KEY, body TSVECTOR)")?;
+    db.query("INSERT INTO posts VALUES (1, 'rust rocks')")?;
+    db.query("INSERT INTO posts VALUES (2, 'go gophers')")?;
+    let analyzed = explain_rows(db.query(
+        "EXPLAIN ANALYZE SELECT id FROM posts WHERE to_tsvector(body) @@ to_tsquery('rust')"
+    )?);
+    assert_eq!(analyzed[0][..3], [
+        DataValue::Text("Full-Text Index Scan on posts using posts.body".to_string()),
+        DataValue::Integer(1),
+        DataValue::Integer(1),
+    ]);
+
+    assert!(db.query("EXPLAIN DELETE FROM posts").is_err());
+    assert_eq!(explain_rows(db.query("SELECT * FROM posts")?).len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_negative_literals() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE accounts (id INTEGER PRIMARY KEY, balance INTEGER, rate FLOAT)")?;
+    db.query("INSERT INTO accounts VALUES (-1, -50, -0.5)")?;
+    db.query("INSERT INTO accounts VALUES (2, 10, 1.5)")?;
+    db.query("INSERT INTO accounts VALUES (3, -9223372036854775808, - 2.0)")?;
+
+    let mut ids = |sql: &str| -> Result<Vec<i64>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok((0..results.len()).map(|i| results.get_int(i, "id").unwrap().unwrap()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    assert_eq!(ids("SELECT id FROM accounts WHERE balance = -50")?, vec![-1]);
+    assert_eq!(ids("SELECT id FROM accounts WHERE balance < - 10 ORDER BY id")?, vec![-1, 3]);
+    assert_eq!(ids("SELECT id FROM accounts WHERE rate = -0.5")?, vec![-1]);
+    assert_eq!(ids("SELECT id FROM accounts WHERE id = -1")?, vec![-1]);
+    assert_eq!(ids("SELECT id FROM accounts WHERE balance BETWEEN -60 AND 0")?, vec![-1]);
+
+    // An integer too large for i64 is read as a float, which the column rejects
+    assert!(db.query("INSERT INTO accounts VALUES (4, 99999999999999999999, 0.0)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_float_literals() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE prices (id INTEGER PRIMARY KEY, amount FLOAT)")?;
+    db.query("INSERT INTO prices VALUES (1, 9.99)")?;
+    db.query("INSERT INTO prices VALUES (2, .5)")?;
+    db.query("INSERT INTO prices VALUES (3, 1.5e3)")?;
+    db.query("INSERT INTO prices VALUES (4, 1e-2)")?;
+
+    let mut ids = |sql: &str| -> Result<Vec<i64>> {
+        match db.query(sql)? {
+            ReefDBResult::Select(results) => Ok((0..results.len()).map(|i| results.get_int(i, "id").unwrap().unwrap()).collect()),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+    };
+
+    assert_eq!(ids("SELECT id FROM prices WHERE amount = 9.99")?, vec![1]);
+    assert_eq!(ids("SELECT id FROM prices WHERE amount = .5")?, vec![2]);
+    assert_eq!(ids("SELECT id FROM prices WHERE amount = 1500.0")?, vec![3]);
+    assert_eq!(ids("SELECT id FROM prices WHERE amount = 1E-2")?, vec![4]);
+    assert_eq!(ids("SELECT id FROM prices WHERE amount > 1e1 ORDER BY id")?, vec![3]);
+
+    // Without a dot or exponent a literal stays an integer
+    assert!(db.query("INSERT INTO prices VALUES (5, 7)").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_select_rejects_trailing_input() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+
+    db.query("CREATE TABLE t (id INTEGER PRIMARY KEY)")?;
+    db.query("INSERT INTO t VALUES (1)")?;
+    db.query("INSERT INTO t VALUES (2)")?;
+
+    // A typo in WHERE must not turn into a select of every row
+    assert!(matches!(db.query("SELECT * FROM t WHEN id=1"), Err(ReefDBError::SyntaxError { .. })));
+
+    match db.query("SELECT * FROM t WHERE id = 1; -- just the first")? {
+        ReefDBResult::Select(result) => assert_eq!(result.len(), 1),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_count_star_fast_path() -> Result<()> {
+    use crate::storage::Storage;
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT)")?;
+    for id in 1..=41 {
+        db.query(&format!("INSERT INTO events VALUES ({}, '{}')", id, if id % 5 == 0 { "error" } else { "info" }))?;
+    }
+    db.query("DELETE FROM events WHERE id = 41")?;
+
+    // The row count comes straight from storage without reading a row
+    let (result, stats) = db.query_with_stats("SELECT COUNT(*) FROM events")?;
+    assert_eq!(explain_rows(result), vec![vec![DataValue::Integer(40)]]);
+    assert_eq!(db.storage.row_count("events"), Some(40));
+    assert_eq!(stats.rows_scanned, 0);
+    assert_eq!(stats.steps.len(), 1);
+    assert_eq!(stats.steps[0].description, "Count Fast Path on events");
+
+    // A WHERE clause still needs every row read
+    let (result, stats) = db.query_with_stats("SELECT COUNT(*) FROM events WHERE kind = 'error'")?;
+    assert_eq!(explain_rows(result), vec![vec![DataValue::Integer(8)]]);
+    assert_eq!(stats.rows_scanned, 40);
+
+    // So does counting the values of a column, which skips NULLs
+    let (_, stats) = db.query_with_stats("SELECT COUNT(kind) FROM events")?;
+    assert_eq!(stats.rows_scanned, 40);
+
+    let plan = explain_rows(db.query("EXPLAIN SELECT COUNT(*) FROM events")?);
+    assert_eq!(plan, vec![vec![
+        DataValue::Text("Count Fast Path on events".to_string()),
+        DataValue::Integer(0),
+        DataValue::Integer(1),
+        DataValue::Null,
+    ]]);
+
+    db.query("CREATE TABLE empty (id INTEGER PRIMARY KEY)")?;
+    assert_eq!(explain_rows(db.query("SELECT COUNT(*) FROM empty")?), vec![vec![DataValue::Integer(0)]]);
+    Ok(())
+}
+
+#[test]
+fn test_default_row_order_is_stable() -> Result<()> {
+    use crate::transaction::IsolationLevel;
+
+    let ids = |result: ReefDBResult| -> Vec<DataValue> {
+        explain_rows(result).into_iter().map(|row| row[0].clone()).collect()
+    };
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE posts (id INTEGER PRIMARY KEY, author INTEGER, body TSVECTOR)")?;
+    db.query("CREATE TABLE authors (id INTEGER PRIMARY KEY, name TEXT)")?;
+    // Ids out of order, so row order is not also id order
+    let post_ids = [7, 3, 9, 1, 12, 5, 10, 2, 8, 4, 11, 6];
+    for (n, id) in post_ids.iter().enumerate() {
+        let body = if n % 3 == 0 { "rust notes" } else { "other notes" };
+        db.query(&format!("INSERT INTO posts VALUES ({}, {}, '{}')", id, n % 2, body))?;
+    }
+    db.query("INSERT INTO authors VALUES (1, 'Alice')")?;
+    db.query("INSERT INTO authors VALUES (0, 'Bob')")?;
+    db.query("UPDATE posts SET author = 1 WHERE id = 9")?;
+
+    // Without ORDER BY rows come back in the order they were inserted
+    let expected: Vec<DataValue> = post_ids.iter().map(|&id| DataValue::Integer(id)).collect();
+    let queries = [
+        "SELECT * FROM posts",
+        "SELECT id FROM posts WHERE author = 1",
+        "SELECT id FROM posts WHERE to_tsvector(body) @@ to_tsquery('rust')",
+        "SELECT posts.id, authors.name FROM posts INNER JOIN authors ON posts.author = authors.id",
+        // Ties keep that order too
+        "SELECT id FROM posts ORDER BY author",
+    ];
+    assert_eq!(ids(db.query(queries[0])?), expected);
+    let by_author: Vec<DataValue> = [7, 12, 10, 8, 11, 3, 9, 1, 5, 2, 4, 6].iter()
+        .map(|&id| DataValue::Integer(id))
+        .collect();
+    assert_eq!(ids(db.query(queries[4])?), by_author);
+    for sql in queries {
+        let first = ids(db.query(sql)?);
+        for _ in 0..5 {
+            assert_eq!(ids(db.query(sql)?), first, "{}", sql);
+        }
+        // A transaction reads through MVCC in the same order
+        let mut tx = db.begin(IsolationLevel::RepeatableRead)?;
+        assert_eq!(ids(tx.query(sql)?), first, "{}", sql);
+        assert_eq!(ids(tx.query(sql)?), first, "{}", sql);
+        tx.rollback()?;
+    }
+    Ok(())
+}
+
+/// Keeps the messages logged while the tests run
+struct CapturingLogger(std::sync::Mutex<Vec<String>>);
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger(std::sync::Mutex::new(Vec::new()));
+
+#[test]
+fn test_warn_on_full_scan() -> Result<()> {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(log::LevelFilter::Warn);
+    // Other tests may log too, so only warnings about this table count
+    let warnings = || LOGGER.0.lock().unwrap().iter().filter(|message| message.contains("scan_events")).count();
+
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE scan_events (id INTEGER PRIMARY KEY, kind TEXT)")?;
+    for id in 1..=20 {
+        db.query(&format!("INSERT INTO scan_events VALUES ({}, 'click')", id))?;
+    }
+
+    // Off by default
+    db.query("SELECT id FROM scan_events WHERE kind = 'view'")?;
+    assert_eq!(warnings(), 0);
+
+    db.set_warn_on_full_scan(true);
+    db.query("SELECT id FROM scan_events WHERE kind = 'view'")?;
+    assert_eq!(warnings(), 0, "20 rows is below the default threshold");
+
+    db.set_full_scan_warning_rows(10);
+    db.query("SELECT id FROM scan_events WHERE kind = 'view'")?;
+    assert_eq!(warnings(), 1);
+    let message = LOGGER.0.lock().unwrap().iter().rev().find(|message| message.contains("scan_events")).cloned().unwrap();
+    assert!(message.contains("kind"), "{}", message);
+
+    // Reading the whole table is what a query without a predicate asks for
+    db.query("SELECT id FROM scan_events")?;
+    assert_eq!(warnings(), 1);
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::{json, Value};
+
+use crate::{error::ReefDBError, server::TextServer, InMemoryReefDB};
+
+/// Sends one line and reads the one-line JSON response
+fn request(stream: &mut TcpStream, reader: &mut BufReader<TcpStream>, sql: &str) -> Value {
+    writeln!(stream, "{}", sql).unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+#[test]
+fn test_text_server() -> Result<(), ReefDBError> {
+    let db = Arc::new(Mutex::new(InMemoryReefDB::create_in_memory()?));
+    let server = TextServer::bind("127.0.0.1:0")?;
+    let addr = server.local_addr()?;
+    let shutdown = server.shutdown_handle()?;
+    let serving = {
+        let db = db.clone();
+        thread::spawn(move || server.serve(db))
+    };
+
+    let mut stream = TcpStream::connect(addr)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    assert_eq!(request(&mut stream, &mut reader, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)"),
+        json!({"ok": true, "result": "CREATE TABLE"}));
+    assert_eq!(request(&mut stream, &mut reader, "INSERT INTO users VALUES (1, 'Alice')"),
+        json!({"ok": true, "count": 1}));
+
+    // Errors are reported without closing the connection
+    let error = request(&mut stream, &mut reader, "SELECT * FROM missing");
+    assert_eq!(error["ok"], json!(false));
+    assert!(error["error"].as_str().unwrap().contains("missing"));
+
+    // A second client sees the same database
+    let mut other = TcpStream::connect(addr)?;
+    let mut other_reader = BufReader::new(other.try_clone()?);
+    assert_eq!(request(&mut other, &mut other_reader, "SELECT name FROM users"),
+        json!({"ok": true, "rows": [{"name": "Alice"}]}));
+
+    shutdown.shutdown();
+    serving.join().unwrap()?;
+    assert!(db.lock().unwrap().list_tables().contains(&"users".to_string()));
+    Ok(())
+}
+
+#[test]
+fn test_text_server_refuses_transactions() -> Result<(), ReefDBError> {
+    let db = Arc::new(Mutex::new(InMemoryReefDB::create_in_memory()?));
+    let server = TextServer::bind("127.0.0.1:0")?;
+    let addr = server.local_addr()?;
+    let shutdown = server.shutdown_handle()?;
+    let serving = {
+        let db = db.clone();
+        thread::spawn(move || server.serve(db))
+    };
+
+    let mut first = TcpStream::connect(addr)?;
+    let mut first_reader = BufReader::new(first.try_clone()?);
+    let mut second = TcpStream::connect(addr)?;
+    let mut second_reader = BufReader::new(second.try_clone()?);
+    request(&mut first, &mut first_reader, "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)");
+
+    // One client's transaction would otherwise swallow the other's statements
+    for sql in ["BEGIN TRANSACTION", "SAVEPOINT sp", "COMMIT", "ROLLBACK"] {
+        let refused = request(&mut first, &mut first_reader, sql);
+        assert_eq!(refused["ok"], json!(false), "{} should be refused", sql);
+        assert!(refused["error"].as_str().unwrap().contains("Transactions are not supported"));
+    }
+    assert_eq!(request(&mut first, &mut first_reader, "INSERT INTO users VALUES (1, 'Alice')"),
+        json!({"ok": true, "count": 1}));
+    assert_eq!(request(&mut second, &mut second_reader, "INSERT INTO users VALUES (2, 'Bob')"),
+        json!({"ok": true, "count": 2}));
+
+    // Both inserts committed, and stay after the first client goes away
+    drop(first_reader);
+    drop(first);
+    assert_eq!(request(&mut second, &mut second_reader, "SELECT name FROM users"),
+        json!({"ok": true, "rows": [{"name": "Alice"}, {"name": "Bob"}]}));
+    assert!(db.lock().unwrap().current_transaction_id.is_none());
+
+    shutdown.shutdown();
+    serving.join().unwrap()?;
+    Ok(())
+}
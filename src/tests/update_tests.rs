@@ -8,6 +8,13 @@ use crate::sql::{
 
 type Result<T> = std::result::Result<T, ReefDBError>;
 
+fn row_count(db: &mut InMemoryReefDB, sql: &str) -> usize {
+    match db.query(sql) {
+        Ok(ReefDBResult::Select(rows)) => rows.len(),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_update_basic() -> Result<()> {
     let mut db = InMemoryReefDB::create_in_memory()?;
@@ -89,6 +96,7 @@ fn parse_update_with_where_test() {
                 "users".to_string(),
                 vec![("name".to_string(), DataValue::Text("John".to_string()))],
                 Some(where_clause),
+                None,
             ))
         ))
     );
@@ -117,7 +125,143 @@ fn parse_update_multiple_columns_test() {
                     ("status".to_string(), DataValue::Text("active".to_string())),
                 ],
                 Some(where_clause),
+                None,
             ))
         ))
     );
-} 
\ No newline at end of file
+}
+
+#[test]
+fn test_update_returning() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice', 30)")?;
+    db.query("INSERT INTO users VALUES (2, 'Bob', 25)")?;
+
+    // The returned rows hold the values after the update
+    match db.query("UPDATE users SET age = 31 WHERE name = 'Alice' RETURNING id, age")? {
+        ReefDBResult::Returning(result) => {
+            assert_eq!(result.len(), 1);
+            assert_eq!(result.get_int(0, "id")?, Some(1));
+            assert_eq!(result.get_int(0, "age")?, Some(31));
+        }
+        other => panic!("Expected Returning result, got {:?}", other),
+    }
+
+    match db.query("UPDATE users SET name = 'Anon' RETURNING *")? {
+        ReefDBResult::Returning(result) => {
+            assert_eq!(result.columns.len(), 3);
+            let names: Vec<_> = result.rows.iter().map(|(_, row)| row[1].clone()).collect();
+            assert_eq!(names, vec![DataValue::Text("Anon".to_string()); 2]);
+        }
+        other => panic!("Expected Returning result, got {:?}", other),
+    }
+
+    // Without RETURNING the count is still returned
+    assert_eq!(db.query("UPDATE users SET age = 40 WHERE id = 2")?, ReefDBResult::Update(1));
+    assert!(db.query("UPDATE users SET age = 1 RETURNING missing").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_update_limit() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, status TEXT)")?;
+    for id in 1..=5 {
+        db.query(&format!("INSERT INTO users VALUES ({}, 'user{}', 'active')", id, id))?;
+    }
+
+    // Five rows match, only the first two change
+    assert_eq!(db.query("UPDATE users SET status = 'inactive' WHERE status = 'active' LIMIT 2")?, ReefDBResult::Update(2));
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'inactive'"), 2);
+    assert_eq!(db.query("UPDATE users SET status = 'gone' LIMIT 0")?, ReefDBResult::Update(0));
+
+    match db.query("UPDATE users SET status = 'inactive' WHERE status = 'active' LIMIT 1 RETURNING id")? {
+        ReefDBResult::Returning(result) => assert_eq!(result.len(), 1),
+        other => panic!("Expected Returning result, got {:?}", other),
+    }
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 2);
+
+    // Transactions stop at the limit as well
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    assert_eq!(tx.query("UPDATE users SET status = 'inactive' WHERE status = 'active' LIMIT 1")?, ReefDBResult::Update(1));
+    tx.commit()?;
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_update_where_in() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, status TEXT)")?;
+    db.query("CREATE TABLE banned (user_id INTEGER)")?;
+    for id in 1..=5 {
+        db.query(&format!("INSERT INTO users VALUES ({}, 'active')", id))?;
+    }
+    db.query("INSERT INTO banned VALUES (4)")?;
+
+    // Only the listed rows change, not the whole table
+    assert_eq!(db.query("UPDATE users SET status = 'inactive' WHERE id IN (1, 2)")?, ReefDBResult::Update(2));
+    assert_eq!(db.query("UPDATE users SET status = 'banned' WHERE id IN (SELECT user_id FROM banned)")?, ReefDBResult::Update(1));
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 2);
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'banned'"), 1);
+
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    assert_eq!(tx.query("UPDATE users SET status = 'inactive' WHERE id IN (3)")?, ReefDBResult::Update(1));
+    tx.commit()?;
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_update_where_between() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, age INTEGER, status TEXT)")?;
+    for id in 1..=5 {
+        db.query(&format!("INSERT INTO users VALUES ({}, {}, 'active')", id, id * 10))?;
+    }
+
+    assert_eq!(db.query("UPDATE users SET status = 'inactive' WHERE age BETWEEN 20 AND 30")?, ReefDBResult::Update(2));
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 3);
+
+    let mut tx = db.begin(crate::transaction::IsolationLevel::ReadCommitted)?;
+    assert_eq!(tx.query("UPDATE users SET status = 'inactive' WHERE age BETWEEN 40 AND 45")?, ReefDBResult::Update(1));
+    tx.commit()?;
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_update_where_expression() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE items (n INTEGER, flags INTEGER, status TEXT)")?;
+    for n in 1..=6 {
+        db.query(&format!("INSERT INTO items VALUES ({}, {}, 'new')", n, n))?;
+    }
+
+    assert_eq!(db.query("UPDATE items SET status = 'even' WHERE n % 2 = 0")?, ReefDBResult::Update(3));
+    assert_eq!(db.query("UPDATE items SET status = 'flagged' WHERE flags & 4 = 4")?, ReefDBResult::Update(3));
+    assert_eq!(row_count(&mut db, "SELECT n FROM items WHERE status = 'new'"), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_update_where_like() -> Result<()> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, status TEXT)")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice', 'active')")?;
+    db.query("INSERT INTO users VALUES (2, 'alfred', 'active')")?;
+    db.query("INSERT INTO users VALUES (3, 'Bob', 'active')")?;
+
+    // The pattern is matched, not compared as a literal
+    assert_eq!(db.query("UPDATE users SET status = 'a' WHERE name LIKE 'A%'")?, ReefDBResult::Update(1));
+    assert_eq!(db.query("UPDATE users SET status = 'a' WHERE name ILIKE 'A%'")?, ReefDBResult::Update(2));
+    assert_eq!(row_count(&mut db, "SELECT id FROM users WHERE status = 'active'"), 1);
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::{error::ReefDBError, result::ReefDBResult, InMemoryReefDB};
+
+#[test]
+fn test_validate() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, age INTEGER CHECK (age >= 0))")?;
+    db.query("INSERT INTO users VALUES (1, 'Alice', 30)")?;
+
+    db.validate("INSERT INTO users VALUES (2, 'Bob', 25)")?;
+    db.validate("UPDATE users SET age = 31 WHERE id = 1")?;
+    db.validate("DELETE FROM users WHERE name = 'Alice'")?;
+    db.validate("SELECT name FROM users WHERE age > 20")?;
+    db.validate("ALTER TABLE users ADD COLUMN email TEXT")?;
+    db.validate("CREATE TABLE posts (id INTEGER PRIMARY KEY)")?;
+    db.validate("DROP TABLE users")?;
+
+    assert!(matches!(db.validate("INSERT INTO missing VALUES (1)"), Err(ReefDBError::TableNotFound(_))));
+    assert!(matches!(db.validate("INSERT INTO users VALUES (2, 'Bob')"), Err(ReefDBError::ArityMismatch(_))));
+    assert!(matches!(db.validate("INSERT INTO users VALUES ('two', 'Bob', 25)"), Err(ReefDBError::TypeMismatch(_))));
+    assert!(matches!(db.validate("INSERT INTO users VALUES (2, 'Bob', -1)"), Err(ReefDBError::CheckViolation(_))));
+    assert!(matches!(db.validate("UPDATE users SET age = -1 WHERE id = 1"), Err(ReefDBError::CheckViolation(_))));
+    assert!(matches!(db.validate("UPDATE users SET email = 'a' WHERE id = 1"), Err(ReefDBError::ColumnNotFound(_))));
+    assert!(matches!(db.validate("DELETE FROM users WHERE email = 'a'"), Err(ReefDBError::ColumnNotFound(_))));
+    assert!(matches!(db.validate("SELECT email FROM users"), Err(ReefDBError::ColumnNotFound(_))));
+    assert!(matches!(db.validate("ALTER TABLE users DROP COLUMN email"), Err(ReefDBError::ColumnNotFound(_))));
+    assert_eq!(db.validate("ALTER TABLE users ADD COLUMN name TEXT"), Err(ReefDBError::DuplicateColumn("name".to_string())));
+    assert_eq!(db.validate("CREATE TABLE users (id INTEGER)"), Err(ReefDBError::TableAlreadyExists("users".to_string())));
+    assert!(matches!(db.validate("SELEKT * FROM users"), Err(ReefDBError::SyntaxError { .. })));
+
+    // Nothing was written along the way
+    match db.query("SELECT * FROM users")? {
+        ReefDBResult::Select(result) => assert_eq!(result.rows.len(), 1),
+        other => panic!("Expected Select result, got {:?}", other),
+    }
+    assert_eq!(db.list_tables(), vec!["users".to_string()]);
+    assert_eq!(db.describe_table("users")?.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_error_variants_and_codes() -> Result<(), ReefDBError> {
+    let mut db = InMemoryReefDB::create_in_memory()?;
+    db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)")?;
+
+    let error = |db: &mut InMemoryReefDB, sql: &str| db.query(sql).unwrap_err();
+
+    let err = error(&mut db, "CREATE TABLE users (id INTEGER)");
+    assert_eq!(err, ReefDBError::TableAlreadyExists("users".to_string()));
+    assert_eq!(err.code(), 1003);
+
+    let err = error(&mut db, "CREATE TABLE posts (id INTEGER, id TEXT)");
+    assert_eq!(err, ReefDBError::DuplicateColumn("id".to_string()));
+    assert_eq!(err.code(), 1004);
+    assert!(!db.list_tables().contains(&"posts".to_string()));
+
+    let err = error(&mut db, "ALTER TABLE users RENAME COLUMN name TO id");
+    assert_eq!(err, ReefDBError::DuplicateColumn("id".to_string()));
+
+    let err = error(&mut db, "INSERT INTO users VALUES ('one', 'Alice')");
+    assert!(matches!(err, ReefDBError::TypeMismatch(_)));
+    assert_eq!(err.code(), 2001);
+
+    let err = error(&mut db, "INSERT INTO users VALUES (1)");
+    assert!(matches!(err, ReefDBError::ArityMismatch(_)));
+    assert_eq!(err.code(), 2002);
+
+    let err = error(&mut db, "SELECT * FROM users UNION SELECT id FROM users");
+    assert!(matches!(err, ReefDBError::ArityMismatch(_)));
+
+    db.query("INSERT INTO users VALUES (1, 'Alice')")?;
+    let err = error(&mut db, "SELECT ABS(name) FROM users");
+    assert!(matches!(err, ReefDBError::TypeMismatch(_)));
+
+    let err = error(&mut db, "SELEKT * FROM users");
+    assert_eq!(err, ReefDBError::SyntaxError { message: "unexpected \"SELEKT\"".to_string(), position: 0 });
+    assert_eq!(err.code(), 1000);
+
+    assert_eq!(error(&mut db, "SELECT * FROM missing").code(), 1001);
+    assert_eq!(ReefDBError::Other(String::new()).code(), 9000);
+    Ok(())
+}
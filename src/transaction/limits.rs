@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use crate::error::ReefDBError;
+
+/// Caps on the work a single query may do before it is aborted with
+/// `ResourceLimitExceeded`, or `QueryTimeout` for the timeout. All limits
+/// are disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct QueryLimits {
+    /// Maximum number of stored rows a query may read, counting each pass
+    /// over a joined table
+    pub max_rows_scanned: Option<usize>,
+    /// Maximum number of rows a query may return
+    pub max_result_rows: Option<usize>,
+    /// Maximum number of joined rows a query may produce before filtering
+    pub max_join_rows: Option<usize>,
+    /// How long a statement may run. SELECT scans check it between rows,
+    /// so they stop shortly after it passes; writes don't check it.
+    pub timeout: Option<Duration>,
+}
+
+impl QueryLimits {
+    pub fn with_max_rows_scanned(mut self, max_rows_scanned: usize) -> Self {
+        self.max_rows_scanned = Some(max_rows_scanned);
+        self
+    }
+
+    pub fn with_max_result_rows(mut self, max_result_rows: usize) -> Self {
+        self.max_result_rows = Some(max_result_rows);
+        self
+    }
+
+    pub fn with_max_join_rows(mut self, max_join_rows: usize) -> Self {
+        self.max_join_rows = Some(max_join_rows);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub(crate) fn check_rows_scanned(&self, count: usize) -> Result<(), ReefDBError> {
+        Self::check(self.max_rows_scanned, count, "rows scanned")
+    }
+
+    pub(crate) fn check_result_rows(&self, count: usize) -> Result<(), ReefDBError> {
+        Self::check(self.max_result_rows, count, "result rows")
+    }
+
+    pub(crate) fn check_join_rows(&self, count: usize) -> Result<(), ReefDBError> {
+        Self::check(self.max_join_rows, count, "joined rows")
+    }
+
+    fn check(limit: Option<usize>, count: usize, what: &str) -> Result<(), ReefDBError> {
+        match limit {
+            Some(max) if count > max => Err(ReefDBError::ResourceLimitExceeded(format!(
+                "{} {} exceeds the limit of {}", count, what, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,6 +1,8 @@
+mod limits;
 mod savepoint_handler;
 mod state_handler;
 
+pub use limits::QueryLimits;
 pub use savepoint_handler::SavepointHandler;
 pub use state_handler::{TransactionState, TransactionStateHandler, IsolationLevel};
 use crate::fts::search::Search;
@@ -38,6 +40,10 @@ where
     pub(crate) savepoint_handler: SavepointHandler,
     pub(crate) reef_db: ReefDB<S, FTS>,
     pub(crate) acid_manager: AcidManager,
+    pub(crate) read_only: bool,
+    /// Whether the statements run on the database that began the
+    /// transaction instead of on `reef_db`
+    pub(crate) external: bool,
 }
 
 impl<S: Storage + IndexManager + Clone, FTS: Search + Clone> Transaction<S, FTS>
@@ -55,6 +61,8 @@ where
             savepoint_handler,
             reef_db: reef_db.clone(),
             acid_manager,
+            read_only: false,
+            external: false,
         };
 
         // Take initial snapshot
@@ -106,6 +114,8 @@ where
                 self.reef_db.storage.insert_table(table_name.clone(), columns.clone(), rows.clone());
             }
         }
+        let table_names: Vec<String> = snapshot.tables.keys().cloned().collect();
+        self.reef_db.rebuild_value_indexes(&table_names)?;
         
         // Update the ACID manager's snapshot
         let mut current_state = self.acid_manager.get_committed_snapshot();
@@ -153,6 +163,8 @@ where
             reef_db.tables.restore_from(&final_state);
             reef_db.storage.restore_from(&final_state);
         }
+        let table_names: Vec<String> = reef_db.storage.get_all_tables().keys().cloned().collect();
+        reef_db.rebuild_value_indexes(&table_names)?;
 
         // Update transaction state
         self.state_handler.commit()?;
@@ -186,6 +198,10 @@ where
         self.state_handler.get_isolation_level()
     }
 
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
     pub fn get_start_timestamp(&self) -> std::time::SystemTime {
         self.state_handler.get_start_timestamp()
     }
@@ -194,22 +210,36 @@ where
         if *self.state_handler.get_state() != TransactionState::Active {
             return Err(ReefDBError::TransactionNotActive);
         }
-        
+
+        let stmt = self.reef_db.resolve_subqueries(stmt)?;
         match stmt {
             Statement::Create(CreateStatement::Table(name, columns)) => {
                 self.reef_db.handle_create(name, columns)
             },
-            Statement::Select(SelectStatement::FromTable(table_name, columns, where_clause, joins, order_by)) => {
-                self.reef_db.handle_select(table_name, columns, where_clause, joins, order_by)
+            Statement::Select(SelectStatement::FromTable(table_name, columns, where_clause, joins, order_by, group_by))
+            | Statement::SelectForUpdate(SelectStatement::FromTable(table_name, columns, where_clause, joins, order_by, group_by)) => {
+                self.reef_db.handle_select(table_name, columns, where_clause, joins, order_by, group_by)
+            },
+            Statement::Union(union_stmt) => {
+                self.reef_db.handle_union(union_stmt)
+            },
+            Statement::Returning { statement, columns } => {
+                self.reef_db.handle_returning(*statement, columns)
             },
             Statement::Insert(InsertStatement::IntoTable(table_name, values)) => {
                 self.reef_db.handle_insert(table_name, values)
             },
-            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause)) => {
-                self.reef_db.handle_update(table_name, updates, where_clause)
+            Statement::Insert(InsertStatement::Upsert(table_name, values, on_conflict)) => {
+                match self.reef_db.resolve_upsert(table_name, values, on_conflict)? {
+                    Some(stmt) => self.execute_statement(stmt),
+                    None => Ok(ReefDBResult::Insert(0)),
+                }
+            },
+            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause, limit)) => {
+                self.reef_db.handle_update(table_name, updates, where_clause, limit)
             },
-            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause)) => {
-                self.reef_db.handle_delete(table_name, where_clause)
+            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause, limit)) => {
+                self.reef_db.handle_delete(table_name, where_clause, limit)
             },
             Statement::Alter(AlterStatement { table_name, alter_type }) => {
                 self.reef_db.handle_alter(table_name, alter_type)
@@ -241,6 +271,27 @@ where
             Statement::Commit => {
                 Ok(ReefDBResult::Commit)
             },
+            Statement::Rollback => {
+                Ok(ReefDBResult::Rollback)
+            },
+            Statement::Reindex { table, column } => {
+                self.reef_db.handle_reindex(table, column)
+            },
+            Statement::Vacuum => {
+                self.reef_db.handle_vacuum()
+            },
+            Statement::ShowTables => {
+                self.reef_db.handle_show_tables()
+            },
+            Statement::Describe(table) => {
+                self.reef_db.handle_describe(&table)
+            },
+            Statement::VerifyIndex { table, column } => {
+                self.reef_db.handle_verify_index(&table, &column)
+            },
+            Statement::Explain { analyze, statement } => {
+                self.reef_db.handle_explain(analyze, *statement)
+            },
         }
     }
 
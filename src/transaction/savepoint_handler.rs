@@ -3,25 +3,41 @@ use crate::{
     error::ReefDBError,
     TableStorage,
     transaction::TransactionState,
-    savepoint::{Savepoint, SavepointState},
+    savepoint::{Savepoint, SavepointState, DEFAULT_MAX_SAVEPOINT_DEPTH},
 };
 
 #[derive(Clone)]
 pub struct SavepointHandler {
     savepoints: HashMap<String, Savepoint>,
+    /// Names of the savepoints, oldest first
+    order: Vec<String>,
+    max_depth: usize,
 }
 
 impl SavepointHandler {
     pub fn new() -> Self {
         Self {
             savepoints: HashMap::new(),
+            order: Vec::new(),
+            max_depth: DEFAULT_MAX_SAVEPOINT_DEPTH,
         }
     }
 
+    /// Sets how many savepoints may be held at once
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
     pub fn create_savepoint(&mut self, name: String, tables: TableStorage) -> Result<(), ReefDBError> {
         if self.savepoints.contains_key(&name) {
             return Err(ReefDBError::Other(format!("Savepoint {} already exists", name)));
         }
+        if self.savepoints.len() >= self.max_depth {
+            return Err(ReefDBError::ResourceLimitExceeded(format!(
+                "cannot create savepoint {}: a transaction may hold at most {} savepoints",
+                name, self.max_depth
+            )));
+        }
         
         let savepoint = Savepoint {
             name: name.clone(),
@@ -30,10 +46,14 @@ impl SavepointHandler {
             state: SavepointState::Active,
         };
         
+        self.order.push(name.clone());
         self.savepoints.insert(name, savepoint);
         Ok(())
     }
 
+    /// Returns the tables as they were at savepoint `name`, which stays
+    /// usable, and the names of the savepoints created after it, which are
+    /// released
     pub fn rollback_to_savepoint(&mut self, name: &str) -> Result<(TableStorage, Vec<String>), ReefDBError> {
         let savepoint = self.savepoints.get(name)
             .ok_or_else(|| ReefDBError::SavepointNotFound(name.to_string()))?;
@@ -44,21 +64,11 @@ impl SavepointHandler {
         
         let snapshot = savepoint.table_snapshot.clone();
         
-        // Get all savepoint names in order
-        let mut savepoint_names: Vec<_> = self.savepoints.keys().cloned().collect();
-        savepoint_names.sort();
-        
-        let savepoint_time = savepoint_names.iter()
+        // Remove all savepoints created after this one
+        let position = self.order.iter()
             .position(|sp_name| sp_name == name)
             .unwrap();
-            
-        // Get names of savepoints to be removed
-        let removed_savepoints: Vec<String> = savepoint_names.iter()
-            .skip(savepoint_time + 1)
-            .cloned()
-            .collect();
-            
-        // Remove all savepoints created after this one
+        let removed_savepoints = self.order.split_off(position + 1);
         for sp_name in &removed_savepoints {
             self.savepoints.remove(sp_name);
         }
@@ -79,6 +89,7 @@ impl SavepointHandler {
         }
         
         self.savepoints.remove(name);
+        self.order.retain(|sp_name| sp_name != name);
         Ok(())
     }
 
@@ -124,6 +135,35 @@ mod tests {
         assert!(handler.rollback_to_savepoint("sp4").is_err());
     }
 
+    #[test]
+    fn test_rollback_follows_creation_order() {
+        let mut handler = SavepointHandler::new();
+        let tables = TableStorage::new();
+
+        // Names sorting before the target were still created after it
+        for name in ["sp3", "sp2", "sp1"] {
+            handler.create_savepoint(name.to_string(), tables.clone()).unwrap();
+        }
+        let (_, removed) = handler.rollback_to_savepoint("sp3").unwrap();
+        assert_eq!(removed, vec!["sp2".to_string(), "sp1".to_string()]);
+        assert_eq!(handler.get_savepoints().len(), 1);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut handler = SavepointHandler::new();
+        let tables = TableStorage::new();
+        handler.set_max_depth(1);
+
+        handler.create_savepoint("sp1".to_string(), tables.clone()).unwrap();
+        assert!(matches!(
+            handler.create_savepoint("sp2".to_string(), tables.clone()),
+            Err(ReefDBError::ResourceLimitExceeded(_))
+        ));
+        handler.release_savepoint("sp1").unwrap();
+        assert!(handler.create_savepoint("sp2".to_string(), tables).is_ok());
+    }
+
     #[test]
     fn test_release_savepoint() {
         let mut handler = SavepointHandler::new();
@@ -0,0 +1,109 @@
+use std::any::Any;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::result::ReefDBResult;
+use crate::sql::statements::Statement;
+use crate::storage::Storage;
+use crate::transaction::IsolationLevel;
+use crate::ReefDB;
+
+/// An explicit transaction on a `ReefDB`, started with `ReefDB::begin`.
+/// Dropping the handle without calling `commit` rolls the transaction back.
+pub struct TransactionHandle<'a, S: Storage + IndexManager + Clone + Any, FTS: Search + Clone>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    db: &'a mut ReefDB<S, FTS>,
+    id: u64,
+    finished: bool,
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Begins a transaction at `isolation_level`. Statements run through the
+    /// returned handle until it is committed, rolled back or dropped.
+    pub fn begin(&mut self, isolation_level: IsolationLevel) -> Result<TransactionHandle<'_, S, FTS>, ReefDBError> {
+        let id = self.begin_explicit_transaction(isolation_level)?;
+        Ok(TransactionHandle { db: self, id, finished: false })
+    }
+
+    /// Runs `f` in a transaction at READ COMMITTED, like BEGIN, and commits
+    /// it if `f` returns `Ok`. If `f` returns an error or panics the
+    /// transaction is rolled back instead.
+    pub fn transaction<T, F>(&mut self, f: F) -> Result<T, ReefDBError>
+    where
+        F: FnOnce(&mut TransactionHandle<'_, S, FTS>) -> Result<T, ReefDBError>,
+    {
+        let mut tx = self.begin(IsolationLevel::ReadCommitted)?;
+        // Returning early or unwinding drops the handle, which rolls back
+        let value = f(&mut tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> TransactionHandle<'_, S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Executes a statement inside the transaction. The transaction is ended
+    /// with `commit` or `rollback`, not with BEGIN, COMMIT or ROLLBACK statements.
+    pub fn execute(&mut self, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
+        match stmt {
+            Statement::BeginTransaction | Statement::Commit | Statement::Rollback => Err(ReefDBError::Other(
+                "Use commit or rollback on the transaction handle to end the transaction".to_string()
+            )),
+            stmt => self.db.execute_statement_in_transaction(stmt),
+        }
+    }
+
+    pub fn query(&mut self, sql: &str) -> Result<ReefDBResult, ReefDBError> {
+        use crate::sql::parser::Parser;
+        let stmt = Parser::parse_sql(sql)?;
+        self.execute(stmt)
+    }
+
+    /// Runs the statements in `sql`, separated by semicolons, one after the
+    /// other and returns their results. Nothing runs if the script does not
+    /// parse, and it stops at the first statement that fails.
+    pub fn query_script(&mut self, sql: &str) -> Result<Vec<ReefDBResult>, ReefDBError> {
+        use crate::sql::parser::Parser;
+        Parser::parse_script(sql)?
+            .into_iter()
+            .map(|stmt| self.execute(stmt))
+            .collect()
+    }
+
+    /// Commits the transaction. If the commit fails the transaction is
+    /// rolled back.
+    pub fn commit(mut self) -> Result<(), ReefDBError> {
+        self.db.handle_commit()?;
+        self.finished = true;
+        Ok(())
+    }
+
+    pub fn rollback(mut self) -> Result<(), ReefDBError> {
+        self.finished = true;
+        self.db.rollback_explicit_transaction()
+    }
+}
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> Drop for TransactionHandle<'_, S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            // Nothing to report to from a destructor
+            let _ = self.db.rollback_explicit_transaction();
+        }
+    }
+}
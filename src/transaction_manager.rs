@@ -1,18 +1,24 @@
 use std::any::Any;
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::cmp::Ordering;
+use std::time::Duration;
 use crate::fts::search::Search;
 
 use crate::result::{ColumnInfo, QueryResult};
 use crate::{
+    autovacuum::Autovacuum,
     deadlock::DeadlockDetector,
     error::ReefDBError,
+    functions::BUILTINS,
     indexes::{
         index_manager::IndexManager,
        
     },
     key_format::KeyFormat,
+    locks::LockConfig,
     locks::LockManager,
     locks::LockType,
     mvcc::MVCCManager,
@@ -52,12 +58,34 @@ use crate::{
     transaction::{
         Transaction,
         IsolationLevel,
+        QueryLimits,
         TransactionState,
     },
-    wal::{WriteAheadLog, WALEntry, WALOperation},
+    wal::{checkpoint, replay, CheckpointPolicy, WriteAheadLog, WALEntry, WALOperation},
     ReefDB,
 };
 
+/// Commits between two MVCC garbage collections
+const DEFAULT_GC_INTERVAL: usize = 64;
+
+/// A joined table's join clause with its borrowed schema and rows.
+type JoinedTable<'a> = (JoinClause, (&'a [ColumnDef], &'a [Vec<DataValue>]));
+
+/// Locks one of the manager's bookkeeping structures (WAL, MVCC, lock,
+/// deadlock and savepoint managers). Their methods finish each update before
+/// anything can panic, so one poisoned by a panicking statement is still
+/// consistent and its guard is taken back rather than wedging every later
+/// transaction.
+fn lock_recovering<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Locks the committed database state. A panic while it was held may have
+/// left it half-updated, so poisoning is reported instead of recovered.
+fn lock_database<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, ReefDBError> {
+    mutex.lock().map_err(|_| ReefDBError::LockPoisoned("database"))
+}
+
 #[derive(Clone)]
 pub struct TransactionManager<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone>
 where
@@ -70,6 +98,19 @@ where
     mvcc_manager: Arc<Mutex<MVCCManager>>,
     deadlock_detector: Arc<Mutex<DeadlockDetector>>,
     savepoint_manager: Arc<Mutex<SavepointManager>>,
+    lock_config: LockConfig,
+    checkpoint_policy: CheckpointPolicy,
+    commits_since_checkpoint: usize,
+    gc_interval: usize,
+    commits_since_gc: usize,
+    query_limits: QueryLimits,
+    replicas: Vec<Sender<Vec<u8>>>,
+    /// Entries logged by each open transaction while there are followers,
+    /// shipped to them when it commits
+    unshipped: Arc<Mutex<HashMap<u64, Vec<WALEntry>>>>,
+    /// The background GC and checkpoint thread, stopped when the last
+    /// copy of the manager is dropped
+    autovacuum: Option<Arc<Autovacuum>>,
 }
 
 // Helper structs
@@ -88,6 +129,10 @@ where
     FTS::NewArgs: Clone,
 {
     pub fn create(reef_db: ReefDB<S, FTS>, wal: WriteAheadLog) -> Self {
+        Self::create_with_config(reef_db, wal, LockConfig::default())
+    }
+
+    pub fn create_with_config(reef_db: ReefDB<S, FTS>, wal: WriteAheadLog, lock_config: LockConfig) -> Self {
         TransactionManager {
             active_transactions: HashMap::new(),
             lock_manager: Arc::new(Mutex::new(LockManager::new())),
@@ -96,20 +141,245 @@ where
             mvcc_manager: reef_db.mvcc_manager.clone(),
             deadlock_detector: Arc::new(Mutex::new(DeadlockDetector::new())),
             savepoint_manager: Arc::new(Mutex::new(SavepointManager::new())),
+            lock_config,
+            checkpoint_policy: CheckpointPolicy::default(),
+            commits_since_checkpoint: 0,
+            gc_interval: DEFAULT_GC_INTERVAL,
+            commits_since_gc: 0,
+            query_limits: QueryLimits::default(),
+            replicas: Vec::new(),
+            unshipped: Arc::new(Mutex::new(HashMap::new())),
+            autovacuum: None,
+        }
+    }
+
+    pub fn set_checkpoint_policy(&mut self, checkpoint_policy: CheckpointPolicy) {
+        self.checkpoint_policy = checkpoint_policy;
+    }
+
+    /// Collects old MVCC versions after every `gc_interval` commits
+    pub fn set_gc_interval(&mut self, gc_interval: usize) {
+        self.gc_interval = gc_interval.max(1);
+    }
+
+    /// Removes the MVCC versions no active transaction can see anymore,
+    /// returning how many were removed
+    pub fn collect_garbage(&mut self) -> Result<usize, ReefDBError> {
+        self.commits_since_gc = 0;
+        let mut mvcc_manager = lock_recovering(&self.mvcc_manager);
+        let oldest_active = mvcc_manager.oldest_active_transaction();
+        Ok(mvcc_manager.gc(oldest_active))
+    }
+
+    /// Starts collecting old MVCC versions and checkpointing the WAL on a
+    /// background thread every `interval`, replacing any thread started
+    /// before; see `Autovacuum`
+    pub fn start_autovacuum(&mut self, interval: Duration)
+    where
+        S: Send,
+        FTS: Send + 'static,
+    {
+        let reef_db = self.reef_db.clone();
+        self.autovacuum = Some(Arc::new(Autovacuum::spawn(
+            interval,
+            self.mvcc_manager.clone(),
+            self.wal.clone(),
+            move || lock_database(&reef_db).and_then(|mut reef_db| reef_db.storage.flush()),
+        )));
+    }
+
+    /// Number of MVCC row versions currently held
+    pub fn version_count(&self) -> Result<usize, ReefDBError> {
+        Ok(lock_recovering(&self.mvcc_manager).version_count())
+    }
+
+    /// Ids of the transactions begun and not yet committed or rolled
+    /// back, in ascending order
+    pub fn active_transaction_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.active_transactions.keys().copied().collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Isolation level of an active transaction, `None` once it has ended
+    pub fn transaction_isolation(&self, transaction_id: u64) -> Option<IsolationLevel> {
+        self.active_transactions.get(&transaction_id)
+            .map(|transaction| transaction.get_isolation_level())
+    }
+
+    /// Transactions holding a lock on `table_name`, in ascending order
+    pub fn lock_holders(&self, table_name: &str) -> Vec<u64> {
+        let mut holders = lock_recovering(&self.lock_manager).get_lock_holders(table_name);
+        holders.sort_unstable();
+        holders
+    }
+
+    /// Sets how many savepoints a transaction may hold at once. Creating
+    /// one more fails with `ResourceLimitExceeded`.
+    pub fn set_max_savepoint_depth(&mut self, max_depth: usize) {
+        lock_recovering(&self.savepoint_manager).set_max_depth(max_depth);
+        for transaction in self.active_transactions.values_mut() {
+            transaction.savepoint_handler.set_max_depth(max_depth);
+        }
+    }
+
+    pub fn set_lock_config(&mut self, lock_config: LockConfig) {
+        self.lock_config = lock_config;
+    }
+
+    pub fn get_lock_config(&self) -> LockConfig {
+        self.lock_config
+    }
+
+    pub fn set_query_limits(&mut self, query_limits: QueryLimits) {
+        self.query_limits = query_limits;
+    }
+
+    pub fn get_query_limits(&self) -> QueryLimits {
+        self.query_limits
+    }
+
+    /// Registers a follower. Every later commit sends the transaction's
+    /// WAL entries other than undo images, bincode-serialized and ending
+    /// with its commit record,
+    /// down the returned channel; see `replication::Follower`. A follower
+    /// is forgotten once its receiver is dropped.
+    pub fn replicate(&mut self) -> Receiver<Vec<u8>> {
+        let (sender, receiver) = mpsc::channel();
+        self.replicas.push(sender);
+        receiver
+    }
+
+    /// Sends the entries the transaction of `commit_record` logged, then
+    /// the record itself, to every follower
+    fn ship_to_replicas(&mut self, commit_record: WALEntry) -> Result<(), ReefDBError> {
+        let entries = lock_recovering(&self.unshipped).remove(&commit_record.transaction_id).unwrap_or_default();
+        if self.replicas.is_empty() {
+            return Ok(());
+        }
+        let payloads = entries.iter()
+            .chain(std::iter::once(&commit_record))
+            .map(bincode::serialize)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.replicas.retain(|replica| payloads.iter().all(|payload| replica.send(payload.clone()).is_ok()));
+        Ok(())
+    }
+
+    /// Appends a mutation record for `transaction_id` to the WAL.
+    pub(crate) fn log_operation(&self, transaction_id: u64, operation: WALOperation, table_name: &str, data: Vec<u8>) -> Result<(), ReefDBError> {
+        let wal_entry = WALEntry {
+            transaction_id,
+            timestamp: std::time::SystemTime::now(),
+            operation,
+            table_name: table_name.to_string(),
+            data,
+        };
+
+        // Followers apply only committed writes, so they never need the
+        // images that undo a write
+        if !self.replicas.is_empty() {
+            let mut unshipped = lock_recovering(&self.unshipped);
+            match wal_entry.operation {
+                // A commit logged here, as a restore does, is never shipped
+                WALOperation::Commit | WALOperation::Rollback => {
+                    unshipped.remove(&transaction_id);
+                }
+                WALOperation::Undo => {}
+                _ => unshipped.entry(transaction_id).or_default().push(wal_entry.clone()),
+            }
+        }
+
+        lock_recovering(&self.wal).append_entry(wal_entry)
+    }
+
+    /// Replays the operations of every committed transaction in the WAL and
+    /// undoes those of transactions that never finished. Returns the number
+    /// of operations that were reapplied or undone.
+    pub fn recover(&mut self) -> Result<usize, ReefDBError> {
+        let entries = lock_recovering(&self.wal).read_entries()?;
+        let unfinished_entries = replay::unfinished_entries(&entries);
+        let committed_entries = replay::committed_entries(entries);
+
+        if committed_entries.is_empty() && unfinished_entries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut reef_db = lock_database(&self.reef_db)?;
+        reef_db.undo_wal_entries(&unfinished_entries)?;
+        reef_db.replay_wal_entries(&committed_entries)?;
+
+        // Once storage holds the undone state, end the transactions so a
+        // later recovery doesn't undo them again
+        reef_db.storage.flush()?;
+        let unfinished: HashSet<u64> = unfinished_entries.iter().map(|entry| entry.transaction_id).collect();
+        for transaction_id in unfinished {
+            self.log_operation(transaction_id, WALOperation::Rollback, "", vec![])?;
+        }
+
+        Ok(committed_entries.len() + unfinished_entries.len())
+    }
+
+    /// Flushes the storage, which then holds every committed transaction,
+    /// and drops their entries from the WAL, keeping a checkpoint marker and
+    /// the entries of transactions still in flight
+    pub fn checkpoint(&mut self) -> Result<(), ReefDBError> {
+        // Held until the WAL is rewritten so no commit lands in between
+        let mut reef_db = lock_database(&self.reef_db)?;
+        reef_db.storage.flush()?;
+        let mut wal = lock_recovering(&self.wal);
+        checkpoint::compact(&mut wal, |transaction_id| self.active_transactions.contains_key(&transaction_id))?;
+        self.commits_since_checkpoint = 0;
+        Ok(())
+    }
+
+    fn maybe_checkpoint(&mut self) -> Result<(), ReefDBError> {
+        let wal_bytes = lock_recovering(&self.wal).size();
+        if self.checkpoint_policy.should_checkpoint(self.commits_since_checkpoint, wal_bytes) {
+            self.checkpoint()?;
         }
+        Ok(())
+    }
+
+    /// Returns a copy of the database state owned by the transaction manager.
+    pub(crate) fn database(&self) -> Result<ReefDB<S, FTS>, ReefDBError> {
+        let reef_db = lock_database(&self.reef_db)?;
+        Ok(reef_db.clone())
     }
 
     pub fn begin_transaction(&mut self, isolation_level: IsolationLevel) -> Result<u64, ReefDBError> {
-        let reef_db = self.reef_db.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire database lock".to_string()))?;
+        self.begin_transaction_with(isolation_level, false)
+    }
+
+    /// Begins a transaction that may only read. Writes fail with
+    /// `ReadOnlyTransaction`, and its statements take no locks.
+    pub fn begin_read_only_transaction(&mut self, isolation_level: IsolationLevel) -> Result<u64, ReefDBError> {
+        self.begin_transaction_with(isolation_level, true)
+    }
+
+    /// Begins a transaction for a database that runs the statements on its
+    /// own copy, using the transaction for locks, MVCC and the WAL only.
+    /// Committing or rolling it back leaves the manager's copy alone.
+    pub(crate) fn begin_external_transaction(&mut self, isolation_level: IsolationLevel) -> Result<u64, ReefDBError> {
+        let id = self.begin_transaction_with(isolation_level, false)?;
+        if let Some(transaction) = self.active_transactions.get_mut(&id) {
+            transaction.external = true;
+        }
+        Ok(id)
+    }
+
+    fn begin_transaction_with(&mut self, isolation_level: IsolationLevel, read_only: bool) -> Result<u64, ReefDBError> {
+        let reef_db = lock_database(&self.reef_db)?;
         
-        let transaction = Transaction::create((*reef_db).clone(), isolation_level);
+        let mut transaction = Transaction::create((*reef_db).clone(), isolation_level);
+        transaction.read_only = read_only;
+        transaction.savepoint_handler.set_max_depth(lock_recovering(&self.savepoint_manager).max_depth());
         let id = transaction.get_id();
         
         // Initialize MVCC timestamp for the transaction
-        self.mvcc_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?
-            .begin_transaction(id);
+        let mut mvcc_manager = lock_recovering(&self.mvcc_manager);
+        mvcc_manager.begin_transaction(id);
+        mvcc_manager.set_isolation_level(id, isolation_level);
+        drop(mvcc_manager);
         
         self.active_transactions.insert(id, transaction);
         Ok(id)
@@ -126,6 +396,19 @@ where
         // Get the final transaction state before commit
         let final_state = transaction.get_table_state();
 
+        // A Serializable transaction that lost a write race is aborted before
+        // its commit reaches the WAL
+        let conflict = lock_recovering(&self.mvcc_manager).check_write_conflicts(id);
+        if let Err(e) = conflict {
+            self.active_transactions.insert(id, transaction);
+            self.rollback_transaction(id)?;
+            return Err(e);
+        }
+
+        // Held from the commit record until the storage has the changes, so
+        // a checkpoint never drops the entries of a commit it can't flush
+        let mut reef_db = lock_database(&self.reef_db)?;
+
         // Write to WAL before committing
         let wal_entry = WALEntry {
             transaction_id: id,
@@ -135,39 +418,43 @@ where
             data: vec![],
         };
 
-        self.wal.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire WAL lock".to_string()))?
-            .append_entry(wal_entry)?;
+        lock_recovering(&self.wal).append_entry(wal_entry.clone())?;
 
         // Commit MVCC changes first
-        let commit_result = self.mvcc_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?
-            .commit(id);
+        let commit_result = lock_recovering(&self.mvcc_manager).commit(id);
 
         if let Err(e) = commit_result {
             // If MVCC commit fails, rollback the transaction
+            drop(reef_db);
+            self.active_transactions.insert(id, transaction);
             self.rollback_transaction(id)?;
             return Err(e);
         }
 
-        // Only update the database state after MVCC commit succeeds
-        let mut reef_db = self.reef_db.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire database lock".to_string()))?;
-        
-        // Update database state with final transaction state
-        reef_db.tables.restore_from(&final_state);
-        
-        // Commit the transaction
-        transaction.commit(&mut reef_db)?;
+        // A transaction whose statements ran elsewhere has nothing to apply
+        if !transaction.external {
+            // Update database state with final transaction state
+            reef_db.tables.restore_from(&final_state);
+
+            // Commit the transaction
+            transaction.commit(&mut reef_db)?;
+        }
 
         // Release locks and remove from deadlock detector
-        self.lock_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire lock manager".to_string()))?
-            .release_transaction_locks(id);
+        lock_recovering(&self.lock_manager).release_transaction_locks(id);
         
-        self.deadlock_detector.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire deadlock detector".to_string()))?
-            .remove_transaction(id);
+        lock_recovering(&self.deadlock_detector).remove_transaction(id);
+
+        drop(reef_db);
+        // Before a checkpoint folds the transaction's entries away
+        self.ship_to_replicas(wal_entry)?;
+        self.commits_since_checkpoint += 1;
+        self.maybe_checkpoint()?;
+
+        self.commits_since_gc += 1;
+        if self.commits_since_gc >= self.gc_interval {
+            self.collect_garbage()?;
+        }
 
         Ok(())
     }
@@ -176,40 +463,50 @@ where
         let mut transaction = self.active_transactions.remove(&id)
             .ok_or_else(|| ReefDBError::Other("Transaction not found".to_string()))?;
 
-        let mut reef_db = self.reef_db.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire database lock".to_string()))?;
-        
-        transaction.rollback(&mut reef_db)?;
+        if !transaction.external {
+            let mut reef_db = lock_database(&self.reef_db)?;
+            transaction.rollback(&mut reef_db)?;
+        }
 
         // Rollback MVCC changes
-        self.mvcc_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?
-            .rollback(id);
+        let _ = lock_recovering(&self.mvcc_manager).rollback(id);
 
         // Release locks and remove from deadlock detector
-        self.lock_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire lock manager".to_string()))?
-            .release_transaction_locks(id);
+        lock_recovering(&self.lock_manager).release_transaction_locks(id);
         
-        self.deadlock_detector.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire deadlock detector".to_string()))?
-            .remove_transaction(id);
+        lock_recovering(&self.deadlock_detector).remove_transaction(id);
 
         // Clear savepoints for this transaction
-        let mut savepoint_manager = self.savepoint_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire savepoint manager lock".to_string()))?;
+        let mut savepoint_manager = lock_recovering(&self.savepoint_manager);
         savepoint_manager.clear_transaction_savepoints(id);
 
-        Ok(())
+        // Its writes are taken back, so recovery must not undo them again
+        self.log_operation(id, WALOperation::Rollback, "", vec![])
     }
 
     pub fn acquire_lock(&self, transaction_id: u64, table_name: &str, lock_type: LockType) -> Result<(), ReefDBError> {
-        let mut lock_manager = self.lock_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire lock manager".to_string()))?;
+        let started = std::time::Instant::now();
+        loop {
+            match self.try_acquire_lock(transaction_id, table_name, lock_type.clone()) {
+                Err(ReefDBError::LockConflict(msg)) if !self.lock_config.lock_timeout.is_zero() => {
+                    if started.elapsed() >= self.lock_config.lock_timeout {
+                        return Err(ReefDBError::LockTimeout(format!(
+                            "{} (waited {:?})", msg, self.lock_config.lock_timeout
+                        )));
+                    }
+                    // Wait without holding the lock manager so the holder can release
+                    std::thread::sleep(self.lock_config.poll_interval);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn try_acquire_lock(&self, transaction_id: u64, table_name: &str, lock_type: LockType) -> Result<(), ReefDBError> {
+        let mut lock_manager = lock_recovering(&self.lock_manager);
         
         // Check for deadlocks before acquiring lock
-        let mut deadlock_detector = self.deadlock_detector.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire deadlock detector".to_string()))?;
+        let mut deadlock_detector = lock_recovering(&self.deadlock_detector);
         
         // Get current lock holders for this table
         let lock_holders = lock_manager.get_lock_holders(table_name);
@@ -249,22 +546,24 @@ where
     }
 
     pub fn create_savepoint(&mut self, transaction_id: u64, name: String) -> Result<(), ReefDBError> {
+        // Get the transaction's current state
+        let table_state = self.active_transactions.get(&transaction_id)
+            .map(|transaction| transaction.get_table_state())
+            .ok_or(ReefDBError::TransactionNotFound(transaction_id))?;
+        self.create_savepoint_of(transaction_id, name, table_state)
+    }
+
+    /// Creates a savepoint of `transaction_id` that rolls back to `tables`
+    pub(crate) fn create_savepoint_of(&mut self, transaction_id: u64, name: String, tables: TableStorage) -> Result<(), ReefDBError> {
         let transaction = self.active_transactions.get(&transaction_id)
-            .ok_or_else(|| ReefDBError::TransactionNotFound(transaction_id))?;
+            .ok_or(ReefDBError::TransactionNotFound(transaction_id))?;
         
         if transaction.get_state() != &TransactionState::Active {
             return Err(ReefDBError::TransactionNotActive);
         }
         
-        // Get the transaction's current state
-        let table_state = transaction.get_table_state();
-        
         // Create the savepoint with this state
-        self.savepoint_manager.lock()
-            .map_err(|_| ReefDBError::LockAcquisitionFailed("Failed to acquire savepoint manager lock".to_string()))?
-            .create_savepoint(transaction_id, name, table_state)?;
-        
-        Ok(())
+        lock_recovering(&self.savepoint_manager).create_savepoint(transaction_id, name, tables)
     }
 
     pub fn rollback_to_savepoint(&mut self, transaction_id: u64, name: &str) -> Result<TableStorage, ReefDBError> {
@@ -276,35 +575,26 @@ where
         }
         
         // Get the savepoint state
-        let restored_state = self.savepoint_manager.lock()
-            .map_err(|_| ReefDBError::LockAcquisitionFailed("Failed to acquire savepoint manager lock".to_string()))?
+        let restored_state = lock_recovering(&self.savepoint_manager)
             .rollback_to_savepoint(transaction_id, name)?;
         
         // Update transaction's state
         transaction.restore_table_state(&restored_state);
         
-        // Update database state
-        let mut reef_db = self.reef_db.lock()
-            .map_err(|_| ReefDBError::LockAcquisitionFailed("Failed to acquire database lock".to_string()))?;
-        reef_db.tables.restore_from(&restored_state);
-        
-        // Update storage state
-        for (table_name, (columns, rows)) in restored_state.tables.iter() {
-            reef_db.storage.insert_table(table_name.clone(), columns.clone(), rows.clone());
+        if !transaction.external {
+            // Update database state
+            let mut reef_db = lock_database(&self.reef_db)?;
+            reef_db.tables.restore_from(&restored_state);
+
+            // Update storage state
+            for (table_name, (columns, rows)) in restored_state.tables.iter() {
+                reef_db.storage.insert_table(table_name.clone(), columns.clone(), rows.clone());
+            }
+            reef_db.rebuild_value_indexes(&restored_state.tables.keys().cloned().collect::<Vec<_>>())?;
         }
         
         // Write WAL entry for rollback
-        let wal_entry = WALEntry {
-            transaction_id,
-            timestamp: std::time::SystemTime::now(),
-            operation: WALOperation::Rollback,
-            table_name: String::new(),
-            data: vec![],
-        };
-        
-        self.wal.lock()
-            .map_err(|_| ReefDBError::LockAcquisitionFailed("Failed to acquire WAL lock".to_string()))?
-            .append_entry(wal_entry)?;
+        self.log_operation(transaction_id, WALOperation::RollbackToSavepoint, "", vec![])?;
         
         Ok(restored_state)
     }
@@ -317,8 +607,7 @@ where
             return Err(ReefDBError::TransactionNotActive);
         }
         
-        let mut savepoint_manager = self.savepoint_manager.lock()
-            .map_err(|_| ReefDBError::LockAcquisitionFailed("Failed to acquire savepoint manager lock".to_string()))?;
+        let mut savepoint_manager = lock_recovering(&self.savepoint_manager);
         
         savepoint_manager.release_savepoint(transaction_id, name)
     }
@@ -361,10 +650,46 @@ where
                     false
                 }
             },
+            WhereType::ColumnComparison(comparison) => {
+                // Joined columns follow the main table's, so a column qualified
+                // with another table is looked up from the end
+                let find = |column: &crate::sql::column::Column| match &column.table {
+                    Some(table) if table != table_name => schema.iter().rposition(|c| c.name == column.name),
+                    _ => schema.iter().position(|c| c.name == column.name),
+                };
+                match (find(&comparison.left), find(&comparison.right)) {
+                    (Some(left), Some(right)) => comparison.operator.evaluate(&row_data[left], &row_data[right]),
+                    _ => false,
+                }
+            },
             WhereType::FTS(_) => {
                 // FTS search is handled separately by the FTS index
                 false
             },
+            WhereType::In(clause) => {
+                let col_idx = match &clause.column.table {
+                    Some(table) if table != table_name => schema.iter().rposition(|c| c.name == clause.column.name),
+                    _ => schema.iter().position(|c| c.name == clause.column.name),
+                };
+                col_idx.is_some_and(|idx| row_data[idx] != DataValue::Null && clause.values.contains(&row_data[idx]))
+            },
+            WhereType::Case(clause) => {
+                let case = &clause.case;
+                let value = case.branches.iter()
+                    .find(|(predicate, _)| Self::evaluate_where_clause(predicate, row_data, schema, table_name))
+                    .map(|(_, value)| value)
+                    .or(case.else_value.as_ref())
+                    .unwrap_or(&DataValue::Null);
+                clause.operator.evaluate(value, &clause.value)
+            },
+            WhereType::Expression(clause) => {
+                BUILTINS.evaluate_arg(&clause.expression, row_data, schema)
+                    .is_ok_and(|value| clause.operator.evaluate(&value, &clause.value))
+            },
+            WhereType::Subquery(_) | WhereType::InSubquery(_) => {
+                // Subqueries are resolved before the statement runs
+                false
+            },
             WhereType::And(left, right) => {
                 Self::evaluate_where_clause(left, row_data, schema, table_name) &&
                 Self::evaluate_where_clause(right, row_data, schema, table_name)
@@ -428,68 +753,189 @@ where
         }
     }
 
+    /// Sorts rows read in stored order by the ORDER BY clauses, breaking
+    /// ties by row id like the regular query path. Joined rows hold the
+    /// base table's columns followed by each joined table's in turn.
     fn sort_results(
-        &self,
         mut results: Vec<(usize, Vec<DataValue>)>,
         order_by: &[OrderByClause],
         schema: &[ColumnDef],
         table_name: &str,
-        joined_tables: &[(JoinClause, (Vec<ColumnDef>, Vec<Vec<DataValue>>))],
+        joined_tables: &[JoinedTable],
     ) -> Vec<(usize, Vec<DataValue>)> {
         if order_by.is_empty() || results.is_empty() {
             return results;
         }
 
-        results.sort_by(|a, b| {
-            for order_clause in order_by {
-                let col_name = &order_clause.column.name;
-                
-                // Find the column index in the result values
-                let col_idx = match &order_clause.column.table {
-                    Some(table) => {
-                        // For columns with explicit table references
-                        if table == table_name {
-                            // Column is from the main table
-                            schema.iter().position(|c| c.name == *col_name)
-                        } else {
-                            // Column is from a joined table
-                            joined_tables.iter()
-                                .find(|(join, _)| join.table_ref.name == *table)
-                                .and_then(|(_, (schema, _))| schema.iter().position(|c| c.name == *col_name))
-                                .map(|pos| pos + schema.len())
-                        }
-                    },
-                    None => {
-                        // For columns without table references, find the first matching column
-                        schema.iter().position(|c| c.name == *col_name).or_else(|| {
-                            joined_tables.iter()
-                                .find_map(|(_, (schema, _))| {
-                                    schema.iter().position(|c| c.name == *col_name)
-                                        .map(|pos| pos + schema.len())
-                                })
-                        })
-                    }
-                };
+        // Every table of the row with the position its columns start at
+        let mut tables = vec![(table_name, schema, 0)];
+        let mut offset = schema.len();
+        for (join, (joined_schema, _)) in joined_tables {
+            tables.push((join.table_ref.name.as_str(), joined_schema, offset));
+            offset += joined_schema.len();
+        }
 
-                if let Some(idx) = col_idx {
-                    if idx < a.1.len() && idx < b.1.len() {
-                        let cmp = a.1[idx].cmp(&b.1[idx]);
-                        if cmp != Ordering::Equal {
-                            return match order_clause.direction {
-                                OrderDirection::Desc => cmp.reverse(),
-                                OrderDirection::Asc => cmp,
-                            };
-                        }
+        // Rows are sorted before projection, so positions in the SELECT
+        // list are left to the regular query path. An unqualified column
+        // is the first one with that name.
+        let keys: Vec<(usize, &OrderByClause)> = order_by.iter()
+            .filter_map(|clause| {
+                let column = clause.column()?;
+                tables.iter()
+                    .filter(|(table, _, _)| column.table.as_deref().is_none_or(|t| t == *table))
+                    .find_map(|(_, schema, offset)| {
+                        schema.iter().position(|c| c.name == column.name).map(|pos| pos + offset)
+                    })
+                    .map(|idx| (idx, clause))
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            for (idx, order_clause) in &keys {
+                if *idx < a.1.len() && *idx < b.1.len() {
+                    let cmp = a.1[*idx].cmp(&b.1[*idx]);
+                    if cmp != Ordering::Equal {
+                        return match order_clause.direction {
+                            OrderDirection::Desc => cmp.reverse(),
+                            OrderDirection::Asc => cmp,
+                        };
                     }
                 }
             }
-            Ordering::Equal
+            a.0.cmp(&b.0)
         });
 
         results
     }
 
+    /// Builds the WAL records for a statement executed through its transaction.
+    /// Updates are logged per row while they execute and return nothing here.
+    /// A LIMIT, or a filter the storage layer can't apply itself, is logged
+    /// as one record per affected row, keyed like those updates, because row
+    /// positions differ between transactions.
+    fn wal_records(reef_db: &ReefDB<S, FTS>, stmt: &Statement) -> Result<Vec<(WALOperation, String, Vec<u8>)>, ReefDBError> {
+        let record = match stmt {
+            Statement::Create(CreateStatement::Table(table_name, columns)) => {
+                let table: (&Vec<ColumnDef>, Vec<Vec<DataValue>>) = (columns, vec![]);
+                (WALOperation::CreateTable, table_name.clone(), bincode::serialize(&table)?)
+            }
+            Statement::Insert(InsertStatement::IntoTable(table_name, values)) => {
+                (WALOperation::Insert, table_name.clone(), bincode::serialize(values)?)
+            }
+            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause, None))
+                if ReefDB::<S, FTS>::storage_filter_is_exact(where_clause.as_ref()) => {
+                let storage_where = ReefDB::<S, FTS>::storage_where_clause(where_clause.clone());
+                (WALOperation::Delete, table_name.clone(), bincode::serialize(&storage_where)?)
+            }
+            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause, limit)) => {
+                return Self::matched_row_keys(reef_db, table_name, where_clause, *limit)?
+                    .into_iter()
+                    .map(|row_key| Ok((WALOperation::Delete, table_name.clone(), bincode::serialize(&row_key)?)))
+                    .collect();
+            }
+            Statement::Drop(drop_stmt) => {
+                (WALOperation::DropTable, drop_stmt.table_name.clone(), bincode::serialize(&())?)
+            }
+            Statement::Alter(alter_stmt) => {
+                (WALOperation::AlterTable, alter_stmt.table_name.clone(), bincode::serialize(&alter_stmt.alter_type)?)
+            }
+            Statement::Returning { statement, .. } => match statement.as_ref() {
+                // Runs in the transaction's handler rather than row by row
+                Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause, None))
+                    if ReefDB::<S, FTS>::storage_filter_is_exact(where_clause.as_ref()) => {
+                    let storage_where = ReefDB::<S, FTS>::storage_where_clause(where_clause.clone());
+                    (WALOperation::Update, table_name.clone(), bincode::serialize(&(updates, &storage_where))?)
+                }
+                Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause, limit)) => {
+                    return Self::matched_row_keys(reef_db, table_name, where_clause, *limit)?
+                        .into_iter()
+                        .map(|row_key| Ok((WALOperation::Update, table_name.clone(), bincode::serialize(&(updates, row_key))?)))
+                        .collect();
+                }
+                other => return Self::wal_records(reef_db, other),
+            },
+            _ => return Ok(Vec::new()),
+        };
+        Ok(vec![record])
+    }
+
+    /// Keys of the rows a DELETE or UPDATE matches, the first `limit` of
+    /// them if it has one
+    fn matched_row_keys(
+        reef_db: &ReefDB<S, FTS>,
+        table_name: &str,
+        where_clause: &Option<WhereType>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Option<(String, DataValue)>>, ReefDBError> {
+        let row_ids = reef_db.matching_rows(table_name, where_clause.as_ref(), limit)?;
+        let (schema, rows) = reef_db.get_table_schema(table_name)?;
+        Ok(row_ids.into_iter()
+            .map(|row_id| Some((schema[0].name.clone(), rows[row_id][0].clone())))
+            .collect())
+    }
+
+    /// Takes the locks `SELECT ... FOR UPDATE` relies on. Locks are per
+    /// table: FOR UPDATE holds its tables exclusively until the transaction
+    /// ends, while writers share theirs with each other, leaving row
+    /// conflicts to MVCC, but wait for any FOR UPDATE holder.
+    fn acquire_statement_locks(&self, transaction_id: u64, stmt: &Statement) -> Result<(), ReefDBError> {
+        match stmt {
+            Statement::SelectForUpdate(SelectStatement::FromTable(table_ref, _, _, joins, _, _)) => {
+                self.acquire_lock(transaction_id, &table_ref.name, LockType::Exclusive)?;
+                for join in joins {
+                    self.acquire_lock(transaction_id, &join.table_ref.name, LockType::Exclusive)?;
+                }
+            }
+            Statement::Insert(InsertStatement::IntoTable(table_name, _))
+            | Statement::Update(UpdateStatement::UpdateTable(table_name, _, _, _))
+            | Statement::Delete(DeleteStatement::FromTable(table_name, _, _)) => {
+                self.acquire_lock(transaction_id, table_name, LockType::Shared)?;
+            }
+            Statement::Returning { statement, .. } => self.acquire_statement_locks(transaction_id, statement)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
     pub fn execute_statement(&mut self, transaction_id: u64, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
+        if self.get_transaction(transaction_id)?.is_read_only() {
+            if !stmt.is_read_only() {
+                return Err(ReefDBError::ReadOnlyTransaction);
+            }
+            return self.execute_statement_unlogged(transaction_id, stmt);
+        }
+
+        // An upsert is logged and executed as the insert or update it resolves to
+        let stmt = match stmt {
+            Statement::Insert(InsertStatement::Upsert(table_name, values, on_conflict)) => {
+                let transaction = self.get_transaction(transaction_id)?;
+                match transaction.reef_db.resolve_upsert(table_name, values, on_conflict)? {
+                    Some(stmt) => stmt,
+                    None => return Ok(ReefDBResult::Insert(0)),
+                }
+            }
+            stmt => stmt,
+        };
+        self.acquire_statement_locks(transaction_id, &stmt)?;
+        let wal_records = Self::wal_records(&self.get_transaction(transaction_id)?.reef_db, &stmt)?;
+        let result = self.execute_statement_unlogged(transaction_id, stmt)?;
+
+        // Only statements that succeeded are replayed on recovery
+        for (operation, table_name, data) in wal_records {
+            self.log_operation(transaction_id, operation, &table_name, data)?;
+        }
+        Ok(result)
+    }
+
+    fn execute_statement_unlogged(&mut self, transaction_id: u64, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
+        // Subselects run through this same path so they read the
+        // transaction's snapshot
+        let stmt = stmt.resolve_subqueries(&mut |select| {
+            match self.execute_statement_unlogged(transaction_id, Statement::Select(select))? {
+                ReefDBResult::Select(result) => Ok(result),
+                _ => unreachable!("a SELECT always returns a Select result"),
+            }
+        })?;
         match stmt {
             Statement::Create(create_stmt) => {
                 let transaction = self.get_transaction(transaction_id)?;
@@ -499,7 +945,7 @@ where
                 let transaction = self.get_transaction(transaction_id)?;
                 transaction.execute_statement(Statement::Insert(insert_stmt))
             }
-            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause)) => {
+            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause, limit)) => {
                 // First get the transaction guard
                 let mut guard = self.get_transaction_guard(transaction_id)?;
                 
@@ -515,18 +961,22 @@ where
                 let table_data = guard.transaction.reef_db.storage.get_table_ref(&table_name)
                     .ok_or_else(|| ReefDBError::TableNotFound(table_name.clone()))?;
                 let (schema, rows) = table_data.clone(); // Clone to avoid lifetime issues
+                let updates = guard.transaction.reef_db.coerce_updates(&table_name, updates);
                 
                 // Drop the guard before getting the MVCC manager
                 drop(guard);
 
                 // Now get the MVCC manager
-                let mut mvcc_manager = self.mvcc_manager.lock()
-                    .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?;
+                let mut mvcc_manager = lock_recovering(&self.mvcc_manager);
                 
                 let mut updated_count = 0;
+                let mut wal_records = Vec::new();
 
                 // Process each row
                 for row in rows {
+                    if limit.is_some_and(|limit| updated_count >= limit) {
+                        break;
+                    }
                     // Get the ID from the first column (primary key)
                     let id = match &row[0] {
                         DataValue::Integer(n) => n.to_string(),
@@ -558,8 +1008,17 @@ where
                         // Write the new version using MVCC
                         mvcc_manager.write(transaction_id, key, new_data)?;
                         updated_count += 1;
+
+                        // Log the update against the row's key so replay touches only this row
+                        let row_key = Some((schema[0].name.clone(), row[0].clone()));
+                        wal_records.push(bincode::serialize(&(&updates, row_key))?);
                     }
                 }
+                drop(mvcc_manager);
+
+                for data in wal_records {
+                    self.log_operation(transaction_id, WALOperation::Update, &table_name, data)?;
+                }
 
                 Ok(ReefDBResult::Update(updated_count))
             }
@@ -571,7 +1030,14 @@ where
                 let transaction = self.get_transaction(transaction_id)?;
                 transaction.execute_statement(Statement::Drop(drop_stmt))
             }
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by)) => {
+            // Grouping and ordering by SELECT list position are left to the
+            // regular query path
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by))
+                if group_by.is_empty() && order_by.iter().all(|clause| clause.column().is_some()) => {
+                // The MVCC manager is locked while the guard borrows the table data
+                let mvcc_manager = Arc::clone(&self.mvcc_manager);
+                let limits = self.query_limits;
+
                 // First get the transaction guard and storage data
                 let guard = self.get_transaction_guard(transaction_id)?;
 
@@ -581,11 +1047,11 @@ where
                     guard.transaction.reef_db.tables.restore_from(&snapshot);
                 }
 
-                // Get table data and clone what we need
+                // Borrow the table data; only rows that end up in the result are cloned
                 let table_data = guard.transaction.reef_db.storage.get_table_ref(&table_ref.name)
                     .ok_or_else(|| ReefDBError::TableNotFound(table_ref.name.clone()))?;
-                let schema = table_data.0.to_vec();
-                let rows = table_data.1.to_vec();
+                let schema = table_data.0.as_slice();
+                let rows = table_data.1.as_slice();
                 let current_isolation_level = guard.isolation_level.clone();
 
                 // Get all joined table data upfront
@@ -595,7 +1061,15 @@ where
                     let joined_table = guard.transaction.reef_db.storage.get_table_ref(&join.table_ref.name)
                         .ok_or_else(|| ReefDBError::TableNotFound(join.table_ref.name.clone()))?;
                     joined_schemas.push((join.table_ref.name.as_str(), joined_table.0.as_slice()));
-                    joined_tables.push((join.clone(), (joined_table.0.to_vec(), joined_table.1.to_vec())));
+                    joined_tables.push((join.clone(), (joined_table.0.as_slice(), joined_table.1.as_slice())));
+                }
+
+                // The combined schema after each join, shared by every row
+                let mut level_schemas = vec![schema.to_vec()];
+                for (_, (joined_schema, _)) in &joined_tables {
+                    let mut combined_schema = level_schemas[level_schemas.len() - 1].clone();
+                    combined_schema.extend_from_slice(joined_schema);
+                    level_schemas.push(combined_schema);
                 }
 
                 // Create column info for all tables
@@ -606,13 +1080,23 @@ where
                 };
 
                 // Get the MVCC manager
-                let mut mvcc_manager = self.mvcc_manager.lock()
-                    .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?;
+                let mut mvcc_manager = lock_recovering(&mvcc_manager);
                 
                 let mut results = Vec::new();
+                let mut rows_scanned = 0;
+                let mut join_rows = 0;
+                let where_context = match &where_clause {
+                    Some(where_clause) if joined_tables.is_empty() => Some(
+                        guard.transaction.reef_db.where_context(where_clause, schema, &[], &table_ref.name)
+                    ),
+                    _ => None,
+                };
 
                 // Process each row
                 for (i, row) in rows.iter().enumerate() {
+                    rows_scanned += 1;
+                    limits.check_rows_scanned(rows_scanned)?;
+
                     // Get the ID from the first column (primary key)
                     let id = match &row[0] {
                         DataValue::Integer(n) => n.to_string(),
@@ -620,49 +1104,88 @@ where
                     };
                     let key = KeyFormat::row(&table_ref.name, 0, &id);
                     
-                    // Read MVCC data - use read_committed to ensure we see committed changes
-                    let data = if current_isolation_level == IsolationLevel::ReadCommitted {
-                        match mvcc_manager.read_committed(transaction_id, &key)? {
-                            Some(data) => data,
-                            None => {
-                                // If no committed version exists, check for uncommitted changes
-                                match mvcc_manager.read_uncommitted(&key)? {
-                                    Some(_) => row.clone(), // If there are uncommitted changes, use original row
-                                    None => row.clone()     // If no changes at all, use original row
+                    // Read MVCC data according to the transaction's isolation level
+                    let data: Cow<[DataValue]> = match current_isolation_level {
+                        IsolationLevel::ReadUncommitted => {
+                            // Dirty reads are allowed, surface the latest version regardless of state
+                            match mvcc_manager.read_uncommitted(&key)? {
+                                Some(data) => Cow::Owned(data),
+                                None => Cow::Borrowed(row)
+                            }
+                        }
+                        IsolationLevel::ReadCommitted => {
+                            match mvcc_manager.read_committed(transaction_id, &key)? {
+                                Some(data) => Cow::Owned(data),
+                                None => {
+                                    // If no committed version exists, check for uncommitted changes
+                                    match mvcc_manager.read_uncommitted(&key)? {
+                                        Some(_) => Cow::Borrowed(row), // If there are uncommitted changes, use original row
+                                        None => Cow::Borrowed(row)      // If no changes at all, use original row
+                                    }
                                 }
                             }
                         }
-                    } else {
-                        match mvcc_manager.read_committed(transaction_id, &key)? {
-                            Some(data) => data,
-                            None => row.clone()
+                        IsolationLevel::RepeatableRead => {
+                            // Read from the snapshot taken at the first read of this transaction
+                            match mvcc_manager.read_repeatable(transaction_id, &key)? {
+                                Some(data) => Cow::Owned(data),
+                                None => Cow::Borrowed(row)
+                            }
+                        }
+                        IsolationLevel::Serializable => {
+                            match mvcc_manager.read_committed(transaction_id, &key)? {
+                                Some(data) => Cow::Owned(data),
+                                None => Cow::Borrowed(row)
+                            }
                         }
                     };
 
+                    // Without joins the WHERE clause is applied to the row itself,
+                    // with FTS conditions answered by the transaction's index
+                    if joined_tables.is_empty() {
+                        let include = match (&where_clause, &where_context) {
+                            (Some(where_clause), Some(context)) => guard.transaction.reef_db.evaluate_where(
+                                where_clause, i, &data, 0, &[], context,
+                            )?,
+                            _ => true,
+                        };
+                        if include {
+                            results.push((i, data.into_owned()));
+                            limits.check_result_rows(results.len())?;
+                        }
+                        continue;
+                    }
+
                     // Handle joins if present
-                    let mut matched_rows = vec![(data.clone(), schema.clone())];
+                    let mut matched_rows = vec![data.into_owned()];
                     
-                    for (join, (joined_schema, joined_rows)) in &joined_tables {
+                    for (level, (join, (joined_schema, joined_rows))) in joined_tables.iter().enumerate() {
+                        let curr_schema = &level_schemas[level];
+                        let combined_schema = &level_schemas[level + 1];
                         let mut new_matched_rows = Vec::new();
                         
-                        for (curr_row, curr_schema) in matched_rows {
-                            for joined_row in joined_rows {
-                                let should_join = Self::evaluate_join_condition(
-                                    &join.on,
+                        for curr_row in matched_rows {
+                            for joined_row in joined_rows.iter() {
+                                rows_scanned += 1;
+                                limits.check_rows_scanned(rows_scanned)?;
+
+                                // Cross joins have no condition
+                                let should_join = join.on.as_ref().is_none_or(|on| Self::evaluate_join_condition(
+                                    on,
                                     &curr_row,
-                                    &curr_schema,
+                                    curr_schema,
                                     joined_row,
                                     joined_schema,
                                     &table_ref.name,
                                     &join.table_ref.name,
-                                );
+                                ));
                                 
                                 if should_join {
+                                    join_rows += 1;
+                                    limits.check_join_rows(join_rows)?;
+
                                     let mut combined_row = curr_row.clone();
-                                    combined_row.extend(joined_row.clone());
-                                    
-                                    let mut combined_schema = curr_schema.clone();
-                                    combined_schema.extend(joined_schema.clone());
+                                    combined_row.extend_from_slice(joined_row);
                                     
                                     // Check where clause on the complete joined data
                                     let should_include = if let Some(ref where_clause) = where_clause {
@@ -709,12 +1232,17 @@ where
                                                 }
                                             }
                                             WhereType::And(left, right) => {
-                                                result = Self::evaluate_where_clause(left, &combined_row, &combined_schema, &table_ref.name) &&
-                                                        Self::evaluate_where_clause(right, &combined_row, &combined_schema, &table_ref.name);
+                                                result = Self::evaluate_where_clause(left, &combined_row, combined_schema, &table_ref.name) &&
+                                                        Self::evaluate_where_clause(right, &combined_row, combined_schema, &table_ref.name);
                                             }
                                             WhereType::Or(left, right) => {
-                                                result = Self::evaluate_where_clause(left, &combined_row, &combined_schema, &table_ref.name) ||
-                                                        Self::evaluate_where_clause(right, &combined_row, &combined_schema, &table_ref.name);
+                                                result = Self::evaluate_where_clause(left, &combined_row, combined_schema, &table_ref.name) ||
+                                                        Self::evaluate_where_clause(right, &combined_row, combined_schema, &table_ref.name);
+                                            }
+                                            WhereType::ColumnComparison(_) | WhereType::Subquery(_)
+                                            | WhereType::In(_) | WhereType::InSubquery(_) | WhereType::Case(_)
+                                            | WhereType::Expression(_) => {
+                                                result = Self::evaluate_where_clause(where_clause, &combined_row, combined_schema, &table_ref.name);
                                             }
                                             WhereType::FTS(_) => {
                                                 result = false;
@@ -726,7 +1254,7 @@ where
                                     };
 
                                     if should_include {
-                                        new_matched_rows.push((combined_row, combined_schema));
+                                        new_matched_rows.push(combined_row);
                                     }
                                 }
                             }
@@ -735,13 +1263,16 @@ where
                     }
 
                     // Process each matched row
-                    for (joined_data, _) in matched_rows {
+                    for joined_data in matched_rows {
                         results.push((i, joined_data));
+                        limits.check_result_rows(results.len())?;
                     }
                 }
 
+                drop(mvcc_manager);
+
                 // Sort results if order by clauses are present
-                results = self.sort_results(results, &order_by, &schema, &table_ref.name, &joined_tables);
+                results = Self::sort_results(results, &order_by, schema, &table_ref.name, &joined_tables);
 
                 // Project columns after sorting
                 let mut projected_results = Vec::new();
@@ -843,6 +1374,16 @@ where
                 let transaction = self.get_transaction(transaction_id)?;
                 transaction.execute_statement(Statement::Alter(alter_stmt))
             }
+            Statement::Union(union_stmt) => {
+                // Each side reads through the transaction's snapshot like a plain SELECT
+                let mut select = |select| match self.execute_statement_unlogged(transaction_id, Statement::Select(select))? {
+                    ReefDBResult::Select(result) => Ok::<_, ReefDBError>(result),
+                    _ => unreachable!("a SELECT always returns a Select result"),
+                };
+                let left = select(union_stmt.left)?;
+                let right = select(union_stmt.right)?;
+                Ok(ReefDBResult::Select(left.union(right, union_stmt.all)?))
+            }
             _ => {
                 let transaction = self.get_transaction(transaction_id)?;
                 transaction.execute_statement(stmt)
@@ -851,13 +1392,11 @@ where
     }
 
     pub fn execute_statement_committed(&mut self, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
-        let reef_db = self.reef_db.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire database lock".to_string()))?;
+        let reef_db = lock_database(&self.reef_db)?;
 
         match stmt {
-            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, _joins, order_by)) => {
-                let mvcc_manager = self.mvcc_manager.lock()
-                    .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?;
+            Statement::Select(SelectStatement::FromTable(table_ref, columns, where_clause, _joins, order_by, _)) => {
+                let mvcc_manager = lock_recovering(&self.mvcc_manager);
 
                 // Get the table data
                 let (schema, rows) = reef_db.storage.get_table_ref(&table_ref.name)
@@ -884,10 +1423,9 @@ where
                             println!("MVCC Debug - Schema: {:?}", schema);
                             reef_db.evaluate_where_clause(
                                 where_clause,
+                                i,
                                 &data,  // Use the full row data for where clause evaluation
-                                &[],    // No join row for simple select
                                 schema,
-                                &[],    // No join schema for simple select
                                 &table_ref.name,
                             ).unwrap_or(false)
                         } else {
@@ -923,7 +1461,7 @@ where
                 }
 
                 // Sort results if order by clauses are present
-                results = self.sort_results(results, &order_by, schema, &table_ref.name, &[]);
+                results = Self::sort_results(results, &order_by, schema, &table_ref.name, &[]);
 
                 println!("MVCC Debug - Final results count: {}", results.len());
                 let column_infos = ColumnInfo::from_schema_and_columns(&schema, &columns, &table_ref.name)?;
@@ -933,10 +1471,8 @@ where
         }
     }
 
-    fn try_execute_with_retry(&mut self, transaction_id: u64, stmt: Statement, max_retries: u32) -> Result<ReefDBResult, ReefDBError> {
-        if !self.mvcc_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?
-            .is_active(transaction_id)
+    fn try_execute_with_retry(&mut self, transaction_id: u64, stmt: Statement) -> Result<ReefDBResult, ReefDBError> {
+        if !lock_recovering(&self.mvcc_manager).is_active(transaction_id)
         {
             return Err(ReefDBError::TransactionNotActive);
         }
@@ -945,9 +1481,9 @@ where
         loop {
             match self.execute_statement_internal(transaction_id, stmt.clone()) {
                 Ok(result) => return Ok(result),
-                Err(ReefDBError::Deadlock) if retries < max_retries => {
+                Err(ReefDBError::Deadlock) if retries < self.lock_config.max_retries => {
                     // On deadlock, wait briefly with exponential backoff and retry
-                    std::thread::sleep(std::time::Duration::from_millis(10 * (1 << retries)));
+                    std::thread::sleep(self.lock_config.retry_backoff * (1 << retries));
                     retries += 1;
                     continue;
                 }
@@ -970,27 +1506,26 @@ where
 
         // First acquire any needed locks based on the statement type
         match &stmt {
-            Statement::Insert(InsertStatement::IntoTable(table_name, _)) => {
+            Statement::Insert(InsertStatement::IntoTable(table_name, _) | InsertStatement::Upsert(table_name, _, _)) => {
                 self.acquire_lock(transaction_id, table_name, LockType::Exclusive)?;
             }
-            Statement::Update(UpdateStatement::UpdateTable(table_name, _, _)) => {
+            Statement::Update(UpdateStatement::UpdateTable(table_name, _, _, _)) => {
                 self.acquire_lock(transaction_id, table_name, LockType::Exclusive)?;
             }
-            Statement::Delete(DeleteStatement::FromTable(table_name, _)) => {
+            Statement::Delete(DeleteStatement::FromTable(table_name, _, _)) => {
                 self.acquire_lock(transaction_id, table_name, LockType::Exclusive)?;
             }
             Statement::Create(CreateStatement::Table(table_name, _)) => {
                 self.acquire_lock(transaction_id, table_name, LockType::Exclusive)?;
             }
-            Statement::Select(SelectStatement::FromTable(table_ref, _, _, _,_)) => {
-                // For serializable isolation, we need shared locks to prevent phantom reads
-                // But with MVCC, we don't need to acquire locks for reads since each transaction
-                // sees its own snapshot of the data
-                if isolation_level == IsolationLevel::Serializable && !self.mvcc_manager.lock()
-                    .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?
-                    .is_active(transaction_id) {
-                    self.acquire_lock(transaction_id, &table_ref.name, LockType::Shared)?;
-                }
+            // For serializable isolation, we need shared locks to prevent phantom reads
+            // But with MVCC, we don't need to acquire locks for reads since each transaction
+            // sees its own snapshot of the data
+            Statement::Select(SelectStatement::FromTable(table_ref, _, _, _,_, _))
+                if isolation_level == IsolationLevel::Serializable
+                    && !lock_recovering(&self.mvcc_manager).is_active(transaction_id) =>
+            {
+                self.acquire_lock(transaction_id, &table_ref.name, LockType::Shared)?;
             }
             _ => {}
         }
@@ -1007,7 +1542,7 @@ where
             
             // For SELECT statements, we want to see the snapshot from when the transaction started
             match &stmt {
-                Statement::Select(SelectStatement::FromTable(_, _, _, _,_)) => {
+                Statement::Select(SelectStatement::FromTable(_, _, _, _,_, _)) => {
                     transaction.reef_db.tables.restore_from(&snapshot);
                 }
                 _ => {
@@ -1031,7 +1566,7 @@ where
 
     pub fn update_database_state(&mut self, state: TableStorage) {
         // Update the database state
-        if let Ok(mut reef_db) = self.reef_db.lock() {
+        if let Ok(mut reef_db) = lock_database(&self.reef_db) {
             reef_db.tables.restore_from(&state);
             
             // Get the updated state to propagate to transactions
@@ -1056,14 +1591,13 @@ where
 
     // Helper methods for MVCC operations
     fn read_mvcc_data(&self, key: &str) -> Result<Option<Vec<DataValue>>, ReefDBError> {
-        let mvcc_manager = self.mvcc_manager.lock().unwrap();
+        let mvcc_manager = lock_recovering(&self.mvcc_manager);
         // Use a special system transaction ID (0) for direct reads
         mvcc_manager.read_committed(0, key)
     }
 
     fn write_mvcc_data(&self, transaction_id: u64, key: String, data: Vec<DataValue>) -> Result<(), ReefDBError> {
-        let mut mvcc_manager = self.mvcc_manager.lock()
-            .map_err(|_| ReefDBError::Other("Failed to acquire MVCC manager lock".to_string()))?;
+        let mut mvcc_manager = lock_recovering(&self.mvcc_manager);
         mvcc_manager.write(transaction_id, key, data)
     }
 
@@ -1080,8 +1614,13 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
     use crate::InMemoryReefDB;
+    use crate::sql::clauses::order_by::OrderByTarget;
     use crate::sql::data_type::DataType;
 
+    /// The payload of an Update entry: the new values and the key of the
+    /// updated rows
+    type UpdatePayload = (Vec<(String, DataValue)>, Option<(String, DataValue)>);
+
     #[test]
     fn test_transaction_manager() {
         let dir = tempdir().unwrap();
@@ -1108,6 +1647,93 @@ mod tests {
         assert!(tm.acquire_lock(tx_id2, "users", LockType::Shared).is_ok());
     }
 
+    #[test]
+    fn test_lock_wait_timeout() {
+        let db = InMemoryReefDB::create_in_memory().unwrap();
+        let config = LockConfig::default().with_lock_timeout(std::time::Duration::from_millis(30));
+        let mut tm = TransactionManager::create_with_config(db, WriteAheadLog::new_in_memory().unwrap(), config);
+
+        let tx_id = tm.begin_transaction(IsolationLevel::Serializable).unwrap();
+        let tx_id2 = tm.begin_transaction(IsolationLevel::Serializable).unwrap();
+        tm.acquire_lock(tx_id, "users", LockType::Exclusive).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = tm.acquire_lock(tx_id2, "users", LockType::Shared);
+        assert!(matches!(result, Err(ReefDBError::LockTimeout(_))));
+        assert!(started.elapsed() >= std::time::Duration::from_millis(30));
+
+        // Without a timeout the conflict is reported immediately
+        tm.set_lock_config(LockConfig::default());
+        assert!(matches!(
+            tm.acquire_lock(tx_id2, "users", LockType::Shared),
+            Err(ReefDBError::LockConflict(_))
+        ));
+    }
+
+    #[test]
+    fn test_inspect_active_transactions() {
+        let db = InMemoryReefDB::create_in_memory().unwrap();
+        let mut tm = TransactionManager::create(db, WriteAheadLog::new_in_memory().unwrap());
+        assert!(tm.active_transaction_ids().is_empty());
+
+        let tx_id = tm.begin_transaction(IsolationLevel::Serializable).unwrap();
+        let tx_id2 = tm.begin_transaction(IsolationLevel::ReadCommitted).unwrap();
+        let mut expected = vec![tx_id, tx_id2];
+        expected.sort_unstable();
+        assert_eq!(tm.active_transaction_ids(), expected);
+        assert_eq!(tm.transaction_isolation(tx_id), Some(IsolationLevel::Serializable));
+        assert_eq!(tm.transaction_isolation(tx_id2), Some(IsolationLevel::ReadCommitted));
+
+        tm.acquire_lock(tx_id, "users", LockType::Shared).unwrap();
+        tm.acquire_lock(tx_id2, "users", LockType::Shared).unwrap();
+        assert_eq!(tm.lock_holders("users"), expected);
+        assert!(tm.lock_holders("orders").is_empty());
+
+        tm.commit_transaction(tx_id).unwrap();
+        assert_eq!(tm.active_transaction_ids(), vec![tx_id2]);
+        assert_eq!(tm.transaction_isolation(tx_id), None);
+        assert_eq!(tm.lock_holders("users"), vec![tx_id2]);
+    }
+
+    #[test]
+    fn test_wal_records_mutation_data() {
+        let db = InMemoryReefDB::create_in_memory().unwrap();
+        let mut tm = TransactionManager::create(db, WriteAheadLog::new_in_memory().unwrap());
+
+        let tx_id = tm.begin_transaction(IsolationLevel::ReadCommitted).unwrap();
+        for sql in [
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)",
+            "INSERT INTO users VALUES (1, 'alice')",
+            "UPDATE users SET name = 'bob' WHERE id = 1",
+            "DELETE FROM users WHERE id = 1",
+        ] {
+            tm.execute_statement(tx_id, Statement::parse(sql).unwrap().1).unwrap();
+        }
+        tm.commit_transaction(tx_id).unwrap();
+
+        let entries = tm.wal.lock().unwrap().read_entries().unwrap();
+        let operations: Vec<_> = entries.iter().map(|e| e.operation.clone()).collect();
+        assert_eq!(operations, vec![
+            WALOperation::CreateTable,
+            WALOperation::Insert,
+            WALOperation::Update,
+            WALOperation::Delete,
+            WALOperation::Commit,
+        ]);
+        assert!(entries.iter().all(|e| e.transaction_id == tx_id));
+        assert!(entries[..4].iter().all(|e| e.table_name == "users"));
+
+        let row: Vec<DataValue> = bincode::deserialize(&entries[1].data).unwrap();
+        assert_eq!(row, vec![DataValue::Integer(1), DataValue::Text("alice".to_string())]);
+
+        let (updates, row_key): UpdatePayload = bincode::deserialize(&entries[2].data).unwrap();
+        assert_eq!(updates, vec![("name".to_string(), DataValue::Text("bob".to_string()))]);
+        assert_eq!(row_key, Some(("id".to_string(), DataValue::Integer(1))));
+
+        let where_clause: Option<(String, DataValue)> = bincode::deserialize(&entries[3].data).unwrap();
+        assert_eq!(where_clause, Some(("id".to_string(), DataValue::Integer(1))));
+    }
+
     #[test]
     fn test_order_by() {
         let dir = tempdir().unwrap();
@@ -1195,13 +1821,16 @@ mod tests {
             None,
             vec![],
             vec![OrderByClause {
-                column: Column {
+                target: OrderByTarget::Column(Column {
                     table: None,
                     name: "age".to_string(),
                     column_type: crate::sql::column::ColumnType::Regular("age".to_string()),
-                },
+                }),
                 direction: OrderDirection::Desc,
+                nulls: None,
+                collation: None,
             }],
+            vec![],
         ));
 
         let result = tm.execute_statement(tx_id, select_stmt).unwrap();
@@ -1242,22 +1871,27 @@ mod tests {
             vec![],
             vec![
                 OrderByClause {
-                    column: Column {
+                    target: OrderByTarget::Column(Column {
                         table: None,
                         name: "age".to_string(),
                         column_type: crate::sql::column::ColumnType::Regular("age".to_string()),
-                    },
+                    }),
                     direction: OrderDirection::Asc,
+                    nulls: None,
+                    collation: None,
                 },
                 OrderByClause {
-                    column: Column {
+                    target: OrderByTarget::Column(Column {
                         table: None,
                         name: "name".to_string(),
                         column_type: crate::sql::column::ColumnType::Regular("name".to_string()),
-                    },
+                    }),
                     direction: OrderDirection::Desc,
+                    nulls: None,
+                    collation: None,
                 },
             ],
+            vec![],
         ));
 
         let result = tm.execute_statement(tx_id, select_stmt).unwrap();
@@ -1420,13 +2054,16 @@ mod tests {
             None,
             vec![],
             vec![OrderByClause {
-                column: Column {
+                target: OrderByTarget::Column(Column {
                     table: None,
                     name: "age".to_string(),
                     column_type: crate::sql::column::ColumnType::Regular("age".to_string()),
-                },
+                }),
                 direction: OrderDirection::Desc,
+                nulls: None,
+                collation: None,
             }],
+            vec![],
         ));
 
         let result = tm.execute_statement(tx_id, select_stmt).unwrap();
@@ -1451,7 +2088,7 @@ mod tests {
                 name: "orders".to_string(),
                 alias: None,
             },
-            on: (
+            on: Some((
                 ColumnValuePair {
                     table_name: "users".to_string(),
                     column_name: "id".to_string(),
@@ -1460,7 +2097,7 @@ mod tests {
                     table_name: "orders".to_string(),
                     column_name: "user_id".to_string(),
                 },
-            ),
+            )),
             join_type: crate::sql::clauses::join_clause::JoinType::Inner,
         };
 
@@ -1490,22 +2127,27 @@ mod tests {
             vec![join_clause],
             vec![
                 OrderByClause {
-                    column: Column {
+                    target: OrderByTarget::Column(Column {
                         table: Some("orders".to_string()),
                         name: "amount".to_string(),
                         column_type: crate::sql::column::ColumnType::Regular("amount".to_string()),
-                    },
+                    }),
                     direction: OrderDirection::Desc,
+                    nulls: None,
+                    collation: None,
                 },
                 OrderByClause {
-                    column: Column {
+                    target: OrderByTarget::Column(Column {
                         table: None,
                         name: "name".to_string(),
                         column_type: crate::sql::column::ColumnType::Regular("name".to_string()),
-                    },
+                    }),
                     direction: OrderDirection::Asc,
+                    nulls: None,
+                    collation: None,
                 },
             ],
+            vec![],
         ));
 
         let result = tm.execute_statement(tx_id, select_stmt).unwrap();
@@ -1526,4 +2168,35 @@ mod tests {
 
         tm.commit_transaction(tx_id).unwrap();
     }
-}
\ No newline at end of file
+
+    /// Panics on another thread while holding `mutex`, poisoning it
+    fn poison<T: Send + 'static>(mutex: Arc<Mutex<T>>) {
+        let held = mutex.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = held.lock().unwrap();
+            panic!("poisoning the lock");
+        }).join();
+        assert!(mutex.is_poisoned());
+    }
+
+    #[test]
+    fn test_poisoned_locks() {
+        let mut db = InMemoryReefDB::create_in_memory().unwrap();
+        db.query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)").unwrap();
+
+        // Bookkeeping poisoned by a panic is recovered
+        let tm = db.transaction_manager.as_ref().unwrap();
+        poison(tm.mvcc_manager.clone());
+        poison(tm.lock_manager.clone());
+        poison(tm.wal.clone());
+        db.query("INSERT INTO users VALUES (1, 'Alice')").unwrap();
+        match db.query("SELECT * FROM users").unwrap() {
+            ReefDBResult::Select(rows) => assert_eq!(rows.len(), 1),
+            other => panic!("Expected Select result, got {:?}", other),
+        }
+
+        // The database state may be half-updated, so that fails cleanly
+        poison(db.transaction_manager.as_ref().unwrap().reef_db.clone());
+        assert_eq!(db.query("SELECT * FROM users").unwrap_err(), ReefDBError::LockPoisoned("database"));
+    }
+}
@@ -0,0 +1,288 @@
+//! Dry runs: the checks a statement's handler makes before it writes
+//! anything, without the write.
+
+use std::any::Any;
+
+use crate::error::ReefDBError;
+use crate::fts::search::Search;
+use crate::indexes::index_manager::IndexManager;
+use crate::sql::column_def::ColumnDef;
+use crate::sql::data_type::DataType;
+use crate::sql::data_value::DataValue;
+use crate::sql::clauses::wheres::where_type::WhereType;
+use crate::sql::parser::Parser;
+use crate::sql::statements::{
+    alter::AlterType,
+    create::CreateStatement,
+    create_index::{CreateIndexStatement, IndexType},
+    delete::DeleteStatement,
+    insert::InsertStatement,
+    select::SelectStatement,
+    update::UpdateStatement,
+    Statement,
+};
+use crate::storage::Storage;
+use crate::{column_tokenizer, ReefDB};
+
+impl<S: Storage + IndexManager + Clone + Any, FTS: Search + Clone> ReefDB<S, FTS>
+where
+    FTS::NewArgs: Clone + Default,
+{
+    /// Parses `sql` and checks it against the current schema and rows
+    /// without running it: tables and columns must exist, values must
+    /// match their column types and written rows must pass their CHECK
+    /// constraints. `Ok` means the statement would get past those checks
+    /// if it ran now.
+    pub fn validate(&self, sql: &str) -> Result<(), ReefDBError> {
+        let stmt = Parser::parse_sql(sql)?;
+        self.validate_statement(stmt)
+    }
+
+    pub fn validate_statement(&self, stmt: Statement) -> Result<(), ReefDBError> {
+        match self.resolve_subqueries(stmt)? {
+            Statement::Create(CreateStatement::Table(name, columns)) => self.validate_create(&name, &columns),
+            Statement::Insert(InsertStatement::IntoTable(table_name, values)) => {
+                let values = self.coerce_insert(&table_name, values);
+                self.validate_insert(&table_name, &values).map(|_| ())
+            }
+            Statement::Insert(InsertStatement::Upsert(table_name, values, on_conflict)) => {
+                match self.resolve_upsert(table_name, values, on_conflict)? {
+                    Some(stmt) => self.validate_statement(stmt),
+                    None => Ok(()),
+                }
+            }
+            Statement::Update(UpdateStatement::UpdateTable(table_name, updates, where_clause, _)) => {
+                let updates = self.coerce_updates(&table_name, updates);
+                self.validate_update(&table_name, &updates, where_clause.as_ref())
+            }
+            Statement::Delete(DeleteStatement::FromTable(table_name, where_clause, _)) => {
+                self.validate_delete(&table_name, where_clause.as_ref())
+            }
+            Statement::Returning { statement, .. } => self.validate_statement(*statement),
+            // Reads change nothing, so running them is the most thorough check
+            Statement::Select(select) | Statement::SelectForUpdate(select) => {
+                let SelectStatement::FromTable(table_ref, columns, where_clause, joins, order_by, group_by) = select;
+                self.handle_select(table_ref, columns, where_clause, joins, order_by, group_by).map(|_| ())
+            }
+            Statement::Union(union) => self.handle_union(union).map(|_| ()),
+            Statement::Alter(alter) => self.validate_alter(&alter.table_name, &alter.alter_type),
+            Statement::Drop(drop) => self.verify_table_exists(&drop.table_name),
+            Statement::CreateIndex(stmt) => self.validate_create_index(&stmt),
+            Statement::DropIndex(stmt) => self.verify_column_exists(&stmt.table_name, &stmt.column_name),
+            Statement::Reindex { table, column: Some(column) } => self.verify_column_exists(&table, &column),
+            Statement::Reindex { table, column: None } | Statement::Describe(table) => self.verify_table_exists(&table),
+            Statement::VerifyIndex { table, column } => self.verify_index(&table, &column).map(|_| ()),
+            Statement::Explain { statement, .. } => self.validate_statement(*statement),
+            Statement::Vacuum
+            | Statement::ShowTables
+            | Statement::Savepoint(_)
+            | Statement::RollbackToSavepoint(_)
+            | Statement::ReleaseSavepoint(_)
+            | Statement::BeginTransaction
+            | Statement::Commit
+            | Statement::Rollback => Ok(()),
+        }
+    }
+
+    fn verify_column_exists(&self, table_name: &str, column_name: &str) -> Result<(), ReefDBError> {
+        let (schema, _) = self.get_table_schema(table_name)?;
+        if !schema.iter().any(|c| c.name == column_name) {
+            return Err(ReefDBError::ColumnNotFound(column_name.to_string()));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_create(&self, name: &str, columns: &[ColumnDef]) -> Result<(), ReefDBError> {
+        if columns.is_empty() {
+            return Err(ReefDBError::Other("Cannot create table with empty column list".to_string()));
+        }
+        if self.storage.table_exists(name) || self.tables.table_exists(name) {
+            return Err(ReefDBError::TableAlreadyExists(name.to_string()));
+        }
+        for (i, column) in columns.iter().enumerate() {
+            if columns[..i].iter().any(|c| c.name == column.name) {
+                return Err(ReefDBError::DuplicateColumn(column.name.clone()));
+            }
+            let Some(tokenizer) = column_tokenizer(column) else {
+                continue;
+            };
+            if column.data_type != DataType::TSVector {
+                return Err(ReefDBError::Other(format!(
+                    "Column {} is not a TSVECTOR column and cannot have a tokenizer", column.name
+                )));
+            }
+            if self.tokenizers.get(tokenizer).is_none() {
+                return Err(ReefDBError::Other(format!("Tokenizer {} is not registered", tokenizer)));
+            }
+        }
+        Ok(())
+    }
+
+    /// The column must exist and, unless the statement replaces it, have no
+    /// index yet. A TSVECTOR column always has a GIN index, which creating
+    /// another one rebuilds.
+    pub(crate) fn validate_create_index(&self, stmt: &CreateIndexStatement) -> Result<(), ReefDBError> {
+        let (schema, _) = self.get_table_schema(&stmt.table_name)?;
+        let column = schema.iter()
+            .find(|c| c.name == stmt.column_name)
+            .ok_or_else(|| ReefDBError::ColumnNotFound(stmt.column_name.clone()))?;
+        let rebuilds_gin = column.data_type == DataType::TSVector && stmt.index_type == IndexType::GIN;
+        if !stmt.or_replace && !rebuilds_gin && self.storage.get_index(&stmt.table_name, &stmt.column_name).is_ok() {
+            return Err(ReefDBError::IndexAlreadyExists(format!("{}.{}", stmt.table_name, stmt.column_name)));
+        }
+        Ok(())
+    }
+
+    /// Reads `values` as the types of the columns of `table_name` they are
+    /// inserted into, leaving them as they are if the table doesn't exist
+    pub(crate) fn coerce_insert(&self, table_name: &str, values: Vec<DataValue>) -> Vec<DataValue> {
+        match self.get_table_schema(table_name) {
+            Ok((schema, _)) if schema.len() == values.len() => values.into_iter()
+                .zip(schema.iter())
+                .map(|(value, column)| value.coerce_to(&column.data_type))
+                .collect(),
+            _ => values,
+        }
+    }
+
+    /// Reads the values of `updates` as the types of the columns they set
+    pub(crate) fn coerce_updates(&self, table_name: &str, updates: Vec<(String, DataValue)>) -> Vec<(String, DataValue)> {
+        let Ok((schema, _)) = self.get_table_schema(table_name) else {
+            return updates;
+        };
+        updates.into_iter()
+            .map(|(name, value)| match schema.iter().find(|c| c.name == name) {
+                Some(column) => (name, value.coerce_to(&column.data_type)),
+                None => (name, value),
+            })
+            .collect()
+    }
+
+    /// Checks a row about to be appended to `table_name`, returning the
+    /// table's schema
+    pub(crate) fn validate_insert(&self, table_name: &str, values: &[DataValue]) -> Result<Vec<ColumnDef>, ReefDBError> {
+        let (schema, rows) = self.get_table_schema(table_name)?;
+
+        // Validate number of values matches number of columns
+        if values.len() != schema.len() {
+            return Err(ReefDBError::ArityMismatch(format!(
+                "{} values for {} columns",
+                values.len(),
+                schema.len()
+            )));
+        }
+
+        // Validate value types match column types
+        for (value, column) in values.iter().zip(schema.iter()) {
+            if !value.matches_type(&column.data_type) {
+                return Err(ReefDBError::TypeMismatch(format!(
+                    "column {} expects {:?}, got {:?}",
+                    column.name,
+                    column.data_type,
+                    value
+                )));
+            }
+        }
+
+        let checks = self.row_checks(table_name, schema)?;
+        self.check_row(table_name, &checks, rows.len(), values)?;
+        Ok(schema.clone())
+    }
+
+    pub(crate) fn validate_update(
+        &self,
+        table_name: &str,
+        updates: &[(String, DataValue)],
+        where_clause: Option<&WhereType>,
+    ) -> Result<(), ReefDBError> {
+        self.verify_table_exists(table_name)?;
+        let (schema, rows) = self.get_table_schema(table_name)?;
+
+        // Validate update columns exist and value types match
+        for (col_name, value) in updates {
+            let column = schema.iter()
+                .find(|c| &c.name == col_name)
+                .ok_or_else(|| ReefDBError::ColumnNotFound(col_name.clone()))?;
+
+            if !value.matches_type(&column.data_type) {
+                return Err(ReefDBError::TypeMismatch(format!(
+                    "column {} expects {:?}, got {:?}",
+                    col_name,
+                    column.data_type,
+                    value
+                )));
+            }
+        }
+
+        // Validate where clause column exists if present
+        if let Some(where_clause) = where_clause {
+            self.validate_where_clause(where_clause, schema)?;
+        }
+
+        // Every updated row has to pass the CHECK constraints as it will be
+        let checks = self.row_checks(table_name, schema)?;
+        if !checks.is_empty() {
+            for row_index in self.matching_rows(table_name, where_clause, None)? {
+                let mut row = rows[row_index].clone();
+                for (col_name, value) in updates {
+                    let col_idx = schema.iter().position(|c| &c.name == col_name).unwrap();
+                    row[col_idx] = value.clone();
+                }
+                self.check_row(table_name, &checks, row_index, &row)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_delete(&self, table_name: &str, where_clause: Option<&WhereType>) -> Result<(), ReefDBError> {
+        self.verify_table_exists(table_name)?;
+        let (schema, _) = self.get_table_schema(table_name)?;
+
+        // Validate where clause column exists if present
+        if let Some(where_clause) = where_clause {
+            self.validate_where_clause(where_clause, schema)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn validate_alter(&self, table_name: &str, alter_type: &AlterType) -> Result<(), ReefDBError> {
+        self.verify_table_exists(table_name)?;
+        let (schema, _) = self.get_table_schema(table_name)?;
+
+        match alter_type {
+            AlterType::AddColumn(column_def) => {
+                // Verify column doesn't already exist
+                if schema.iter().any(|c| c.name == column_def.name) {
+                    return Err(ReefDBError::DuplicateColumn(column_def.name.clone()));
+                }
+            }
+            AlterType::DropColumn(column_name) => {
+                self.verify_column_exists(table_name, column_name)?;
+            }
+            AlterType::RenameColumn(old_name, new_name) => {
+                self.verify_column_exists(table_name, old_name)?;
+                // Verify new name doesn't already exist
+                if schema.iter().any(|c| &c.name == new_name) {
+                    return Err(ReefDBError::DuplicateColumn(new_name.clone()));
+                }
+            }
+            AlterType::AlterColumnType { column, new_type } => {
+                let current = schema.iter()
+                    .find(|c| &c.name == column)
+                    .ok_or_else(|| ReefDBError::ColumnNotFound(column.clone()))?;
+                // Full-text columns keep their index, so they cannot change type
+                if current.data_type == DataType::TSVector || *new_type == DataType::TSVector {
+                    return Err(ReefDBError::Other(format!(
+                        "Cannot change the type of column {} to or from TSVECTOR", column
+                    )));
+                }
+            }
+            AlterType::RenameTable(new_name) => {
+                if self.storage.table_exists(new_name) || self.tables.table_exists(new_name) {
+                    return Err(ReefDBError::TableAlreadyExists(new_name.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
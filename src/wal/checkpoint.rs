@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use crate::error::ReefDBError;
+use crate::wal::{WALEntry, WALOperation, WriteAheadLog};
+
+/// When the transaction manager checkpoints the WAL on its own.
+/// Both thresholds are disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CheckpointPolicy {
+    /// Checkpoint after this many committed transactions
+    pub max_transactions: Option<usize>,
+    /// Checkpoint once the WAL grows past this many bytes
+    pub max_wal_bytes: Option<u64>,
+}
+
+impl CheckpointPolicy {
+    pub fn with_max_transactions(mut self, max_transactions: usize) -> Self {
+        self.max_transactions = Some(max_transactions);
+        self
+    }
+
+    pub fn with_max_wal_bytes(mut self, max_wal_bytes: u64) -> Self {
+        self.max_wal_bytes = Some(max_wal_bytes);
+        self
+    }
+
+    pub(crate) fn should_checkpoint(&self, committed_transactions: usize, wal_bytes: u64) -> bool {
+        self.max_transactions.is_some_and(|max| committed_transactions >= max)
+            || self.max_wal_bytes.is_some_and(|max| wal_bytes >= max)
+    }
+}
+
+/// Drops the entries of every committed transaction, whose changes the
+/// storage holds once the caller has flushed it, leaving a checkpoint
+/// marker followed by the entries of the transactions `in_flight` reports
+/// as still running. Recovery then starts from the storage.
+pub(crate) fn compact(wal: &mut WriteAheadLog, in_flight: impl Fn(u64) -> bool) -> Result<(), ReefDBError> {
+    let entries = wal.read_entries()?;
+
+    // A transaction can still look in flight just after its commit record
+    // was written, but the storage already holds its changes
+    let committed: HashSet<u64> = entries.iter()
+        .filter(|entry| entry.operation == WALOperation::Commit)
+        .map(|entry| entry.transaction_id)
+        .collect();
+    let in_flight: Vec<WALEntry> = entries.into_iter()
+        .filter(|entry| !committed.contains(&entry.transaction_id) && in_flight(entry.transaction_id))
+        .collect();
+
+    let mut compacted = Vec::with_capacity(in_flight.len() + 1);
+    compacted.push(WALEntry {
+        transaction_id: rand::random::<u64>(),
+        timestamp: std::time::SystemTime::now(),
+        operation: WALOperation::Checkpoint,
+        table_name: String::new(),
+        data: vec![],
+    });
+    compacted.extend(in_flight);
+
+    wal.rewrite(&compacted)
+}
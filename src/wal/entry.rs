@@ -11,6 +11,17 @@ pub enum WALOperation {
     AlterTable,
     Commit,
     Rollback,
+    Checkpoint,
+    BulkInsert,
+    /// An UPDATE with a LIMIT, logged with the positions of the rows it changed
+    UpdateRows,
+    /// A DELETE with a LIMIT, logged with the positions of the rows it removed
+    DeleteRows,
+    /// What the transaction's next entry changes, as it was before, so that
+    /// recovery can undo a transaction that never finished
+    Undo,
+    /// A rollback to a savepoint, after which the transaction goes on
+    RollbackToSavepoint,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -1,6 +1,6 @@
 use std::fs::{File, OpenOptions};
 use std::io::{self, Write, Read, Seek, SeekFrom};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use bincode;
 
 use crate::error::ReefDBError;
@@ -8,6 +8,7 @@ use super::entry::WALEntry;
 
 pub struct WriteAheadLog {
     file: File,
+    path: Option<PathBuf>,
     current_position: u64,
     sync_on_append: bool,
 }
@@ -19,12 +20,13 @@ impl WriteAheadLog {
             .read(true)
             .write(true)
             .append(true)
-            .open(path)?;
+            .open(path.as_ref())?;
         
         let current_position = file.metadata()?.len();
         
         Ok(WriteAheadLog {
             file,
+            path: Some(path.as_ref().to_path_buf()),
             current_position,
             sync_on_append: true,
         })
@@ -34,6 +36,7 @@ impl WriteAheadLog {
         let file = tempfile::tempfile()?;
         Ok(WriteAheadLog {
             file,
+            path: None,
             current_position: 0,
             sync_on_append: true,
         })
@@ -107,6 +110,49 @@ impl WriteAheadLog {
         Ok(())
     }
 
+    /// Whether the log is kept in a file that outlives the process
+    pub fn is_persistent(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Size of the log in bytes
+    pub fn size(&self) -> u64 {
+        self.current_position
+    }
+
+    /// Replaces the contents of the log with `entries`. File-backed logs are
+    /// written to a temporary file and renamed into place, so a crash leaves
+    /// either the old or the new log intact.
+    pub fn rewrite(&mut self, entries: &[WALEntry]) -> Result<(), ReefDBError> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => {
+                self.truncate()?;
+                for entry in entries {
+                    self.append_entry(entry.clone())?;
+                }
+                return Ok(());
+            }
+        };
+
+        let tmp_path = PathBuf::from(format!("{}.tmp", path.display()));
+        let mut tmp = WriteAheadLog::new(&tmp_path)
+            .map_err(|e| ReefDBError::WALError(format!("Failed to create WAL checkpoint file: {}", e)))?;
+        tmp.truncate()?;
+        tmp.set_sync_on_append(false);
+        for entry in entries {
+            tmp.append_entry(entry.clone())?;
+        }
+        tmp.sync()?;
+
+        std::fs::rename(&tmp_path, &path)
+            .map_err(|e| ReefDBError::WALError(format!("Failed to replace WAL: {}", e)))?;
+
+        self.file = tmp.file;
+        self.current_position = tmp.current_position;
+        Ok(())
+    }
+
     pub fn sync(&mut self) -> Result<(), ReefDBError> {
         self.file.sync_all()
             .map_err(|e| ReefDBError::WALError(format!("Failed to sync WAL to disk: {}", e)))?;
@@ -274,6 +320,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rewrite_replaces_file_contents() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("rewrite.wal");
+
+        let mut wal = WriteAheadLog::new(&file_path).unwrap();
+        for i in 1..=5 {
+            wal.append_entry(create_test_entry(i, WALOperation::Insert)).unwrap();
+        }
+        let size_before = wal.size();
+
+        wal.rewrite(&[create_test_entry(9, WALOperation::Checkpoint)]).unwrap();
+        assert!(wal.size() < size_before);
+        assert!(!dir.path().join("rewrite.wal.tmp").exists());
+
+        // Appends go to the new file and survive reopening
+        wal.append_entry(create_test_entry(10, WALOperation::Commit)).unwrap();
+        drop(wal);
+
+        let mut wal = WriteAheadLog::new(&file_path).unwrap();
+        let entries = wal.read_entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].operation, WALOperation::Checkpoint);
+        assert_eq!(entries[1].transaction_id, 10);
+    }
+
     #[test]
     fn test_invalid_file_path() {
         let result = WriteAheadLog::new("/nonexistent/directory/test.wal");
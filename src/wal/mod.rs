@@ -1,5 +1,8 @@
+pub(crate) mod checkpoint;
 mod entry;
 mod log;
+pub(crate) mod replay;
 
+pub use checkpoint::CheckpointPolicy;
 pub use entry::{WALEntry, WALOperation};
-pub use log::WriteAheadLog; 
\ No newline at end of file
+pub use log::WriteAheadLog;
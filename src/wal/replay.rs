@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ReefDBError;
+use crate::sql::column_def::ColumnDef;
+use crate::sql::data_value::DataValue;
+use crate::sql::statements::alter::AlterType;
+use crate::storage::Storage;
+use super::entry::{WALEntry, WALOperation};
+
+/// Keeps the mutations of transactions that reached a commit (or checkpoint)
+/// record, in log order. Entries of incomplete transactions are discarded.
+pub(crate) fn committed_entries(entries: Vec<WALEntry>) -> Vec<WALEntry> {
+    let committed: HashSet<u64> = entries.iter()
+        .filter(|entry| matches!(entry.operation, WALOperation::Commit | WALOperation::Checkpoint))
+        .map(|entry| entry.transaction_id)
+        .collect();
+
+    entries.into_iter()
+        .filter(|entry| committed.contains(&entry.transaction_id))
+        .filter(|entry| !matches!(
+            entry.operation,
+            WALOperation::Commit | WALOperation::Rollback | WALOperation::Checkpoint
+                | WALOperation::Undo | WALOperation::RollbackToSavepoint
+        ))
+        .collect()
+}
+
+/// Entries of transactions that neither committed nor rolled back, in log
+/// order. Their writes may have reached storage before the crash.
+pub(crate) fn unfinished_entries(entries: &[WALEntry]) -> Vec<WALEntry> {
+    let finished: HashSet<u64> = entries.iter()
+        .filter(|entry| matches!(
+            entry.operation,
+            WALOperation::Commit | WALOperation::Rollback | WALOperation::Checkpoint
+        ))
+        .map(|entry| entry.transaction_id)
+        .collect();
+
+    entries.iter()
+        .filter(|entry| !finished.contains(&entry.transaction_id))
+        .cloned()
+        .collect()
+}
+
+/// A table's rows or whole contents as they were before a write, logged as
+/// an `Undo` entry ahead of it
+#[derive(Serialize, Deserialize)]
+pub(crate) enum UndoImage {
+    /// Positions and old values of the rows an update changes
+    Updated(Vec<(usize, Vec<DataValue>)>),
+    /// Row count of the table before a delete, and the positions and values
+    /// of the rows it removes
+    Deleted(usize, Vec<(usize, Vec<DataValue>)>),
+    /// Schema and rows before an ALTER or DROP
+    Table(Vec<ColumnDef>, Vec<Vec<DataValue>>),
+}
+
+/// Takes back the writes of unfinished transactions, last first. A write
+/// the crash came before is left alone: an insert is only removed while the
+/// table still ends with its rows, and deleted rows only come back while
+/// they are missing.
+pub(crate) fn undo_entries<S: Storage>(storage: &mut S, entries: &[WALEntry]) -> Result<(), ReefDBError> {
+    for entry in entries.iter().rev() {
+        let table_name = &entry.table_name;
+        let table = storage.get_table_ref(table_name).cloned();
+        match (&entry.operation, table) {
+            (WALOperation::Undo, table) => match (bincode::deserialize(&entry.data)?, table) {
+                (UndoImage::Table(columns, rows), _) => storage.insert_table(table_name.clone(), columns, rows),
+                (UndoImage::Updated(old_rows), Some((columns, mut rows))) => {
+                    for (row_id, old_row) in old_rows {
+                        if let Some(row) = rows.get_mut(row_id) {
+                            *row = old_row;
+                        }
+                    }
+                    storage.insert_table(table_name.clone(), columns, rows);
+                }
+                (UndoImage::Deleted(row_count, deleted), Some((columns, mut rows)))
+                    if rows.len() + deleted.len() == row_count =>
+                {
+                    for (row_id, row) in deleted {
+                        rows.insert(row_id.min(rows.len()), row);
+                    }
+                    storage.insert_table(table_name.clone(), columns, rows);
+                }
+                _ => {}
+            },
+            (WALOperation::Insert, Some((columns, mut rows))) => {
+                let inserted: Vec<DataValue> = bincode::deserialize(&entry.data)?;
+                if rows.last() == Some(&inserted) {
+                    rows.pop();
+                    storage.insert_table(table_name.clone(), columns, rows);
+                }
+            }
+            (WALOperation::BulkInsert, Some((columns, mut rows))) => {
+                let inserted: Vec<Vec<DataValue>> = bincode::deserialize(&entry.data)?;
+                if rows.ends_with(&inserted) {
+                    rows.truncate(rows.len() - inserted.len());
+                    storage.insert_table(table_name.clone(), columns, rows);
+                }
+            }
+            // The table the transaction created, or restored at a savepoint
+            // after an `Undo` entry of its own
+            (WALOperation::CreateTable, Some(_)) => storage.drop_table(table_name),
+            (WALOperation::AlterTable, _) => {
+                if let Ok(AlterType::RenameTable(new_name)) = bincode::deserialize(&entry.data) {
+                    storage.drop_table(&new_name);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Tables whose whole history is in the log, so they can be rebuilt from it.
+/// A table created in the log keeps its history there when renamed.
+pub(crate) fn logged_tables(entries: &[WALEntry]) -> HashSet<String> {
+    let mut tables = HashSet::new();
+    for entry in entries {
+        match entry.operation {
+            WALOperation::CreateTable => {
+                tables.insert(entry.table_name.clone());
+            }
+            WALOperation::AlterTable if tables.contains(&entry.table_name) => {
+                if let Ok(AlterType::RenameTable(new_name)) = bincode::deserialize(&entry.data) {
+                    tables.insert(new_name);
+                }
+            }
+            _ => {}
+        }
+    }
+    tables
+}
+
+/// Applies a single logged mutation to `storage`.
+pub(crate) fn apply_entry<S: Storage>(storage: &mut S, entry: &WALEntry) -> Result<(), ReefDBError> {
+    let table_name = &entry.table_name;
+    match entry.operation {
+        WALOperation::CreateTable => {
+            let (columns, rows): (Vec<ColumnDef>, Vec<Vec<DataValue>>) = bincode::deserialize(&entry.data)?;
+            storage.insert_table(table_name.clone(), columns, rows);
+        }
+        WALOperation::Insert => {
+            storage.push_value(table_name, bincode::deserialize(&entry.data)?)?;
+        }
+        WALOperation::BulkInsert => {
+            storage.append_rows(table_name, bincode::deserialize(&entry.data)?)?;
+        }
+        WALOperation::Update => {
+            let (updates, where_clause) = bincode::deserialize(&entry.data)?;
+            storage.update_table(table_name, updates, where_clause);
+        }
+        WALOperation::Delete => {
+            storage.delete_table(table_name, bincode::deserialize(&entry.data)?);
+        }
+        WALOperation::UpdateRows => {
+            let (updates, row_ids): (Vec<(String, DataValue)>, Vec<usize>) = bincode::deserialize(&entry.data)?;
+            storage.update_rows(table_name, &row_ids, &updates)?;
+        }
+        WALOperation::DeleteRows => {
+            let row_ids: Vec<usize> = bincode::deserialize(&entry.data)?;
+            storage.delete_rows(table_name, &row_ids)?;
+        }
+        WALOperation::AlterTable => {
+            match bincode::deserialize(&entry.data)? {
+                AlterType::AddColumn(column_def) => storage.add_column(table_name, column_def)?,
+                AlterType::DropColumn(column_name) => storage.drop_column(table_name, &column_name)?,
+                AlterType::RenameColumn(old_name, new_name) => {
+                    storage.rename_column(table_name, &old_name, &new_name)?
+                }
+                AlterType::AlterColumnType { column, new_type } => {
+                    storage.alter_column_type(table_name, &column, new_type)?
+                }
+                AlterType::RenameTable(new_name) => storage.rename_table(table_name, &new_name)?,
+            }
+        }
+        WALOperation::DropTable => {
+            storage.drop_table(table_name);
+        }
+        WALOperation::Commit | WALOperation::Rollback | WALOperation::Checkpoint
+        | WALOperation::Undo | WALOperation::RollbackToSavepoint => {}
+    }
+    Ok(())
+}